@@ -0,0 +1,5 @@
+drop table if exists substitute_speakers_of_debate;
+
+alter table agg_speaker_results_of_debate drop column counts_for_speaker_tab;
+
+alter table tournaments drop column iron_speech_rule;
@@ -0,0 +1,24 @@
+-- How the speaker tab treats a speaker who gives more than one substantive
+-- speech in the same debate (an "iron-person" speech):
+-- - 'both': every speech counts towards the speaker tab
+-- - 'highest': only the highest scoring speech counts
+-- - 'first': only the earliest speech counts
+alter table tournaments add column iron_speech_rule text not null default 'both'
+    check (iron_speech_rule in ('both', 'highest', 'first'));
+
+-- Speeches which are excluded by `iron_speech_rule` still count towards the
+-- team's total, but are not included in the speaker tab.
+alter table agg_speaker_results_of_debate
+    add column counts_for_speaker_tab boolean not null default true;
+
+-- Speakers who substitute for a member of a team in a single debate. The
+-- substitute appears on the ballot for that debate, but is not added to
+-- `speakers_of_team`.
+create table if not exists substitute_speakers_of_debate (
+    id text primary key not null,
+    tournament_id text not null references tournaments (id),
+    debate_id text not null references debates (id),
+    team_id text not null references teams (id),
+    speaker_id text not null references speakers (id),
+    unique (debate_id, speaker_id)
+);
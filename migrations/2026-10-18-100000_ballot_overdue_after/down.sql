@@ -0,0 +1 @@
+alter table tournaments drop column ballot_overdue_after_mins;
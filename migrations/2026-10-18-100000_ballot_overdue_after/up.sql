@@ -0,0 +1,5 @@
+-- The number of minutes after the start of a round (i.e. the release of the
+-- draw) after which a judge who has not yet submitted their ballot is shown
+-- as overdue on the ballot overview.
+alter table tournaments add column ballot_overdue_after_mins integer not null
+    default 90 check (ballot_overdue_after_mins >= 0);
@@ -0,0 +1,7 @@
+alter table break_categories drop column team_category_id;
+
+drop table if exists team_categories;
+
+drop table if exists speakers_of_category;
+
+drop table if exists speaker_categories;
@@ -0,0 +1,34 @@
+-- Categories which speakers can be placed into (e.g. ESL, EFL, Novice). A
+-- speaker can be in any number of categories.
+create table if not exists speaker_categories (
+    id text primary key not null,
+    tournament_id text not null references tournaments (id),
+    name text not null,
+    unique (tournament_id, name)
+);
+
+create table if not exists speakers_of_category (
+    id text primary key not null,
+    tournament_id text not null references tournaments (id),
+    category_id text not null references speaker_categories (id),
+    speaker_id text not null references speakers (id),
+    unique (category_id, speaker_id)
+);
+
+-- Team categories are not assigned directly. Instead, a team is in the
+-- category if its speakers satisfy `rule` with respect to the given speaker
+-- category:
+-- - 'all': every speaker on the team is in the speaker category
+-- - 'any': at least one speaker on the team is in the speaker category
+create table if not exists team_categories (
+    id text primary key not null,
+    tournament_id text not null references tournaments (id),
+    name text not null,
+    speaker_category_id text not null references speaker_categories (id),
+    rule text not null check (rule in ('all', 'any')),
+    unique (tournament_id, name)
+);
+
+-- If set, only teams in this team category may break in this break category.
+alter table break_categories
+    add column team_category_id text references team_categories (id);
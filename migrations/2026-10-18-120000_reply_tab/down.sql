@@ -0,0 +1,2 @@
+alter table tournaments drop column reply_tab_min_replies;
+alter table tournaments drop column reply_tab_public;
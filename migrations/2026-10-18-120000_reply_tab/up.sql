@@ -0,0 +1,6 @@
+alter table tournaments add column reply_tab_public boolean not null
+    default false;
+-- Speakers who have given fewer reply speeches than this are omitted from the
+-- reply tab.
+alter table tournaments add column reply_tab_min_replies integer not null
+    default 1 check (reply_tab_min_replies >= 0);
@@ -0,0 +1 @@
+alter table tournaments drop column speaks_standardisation;
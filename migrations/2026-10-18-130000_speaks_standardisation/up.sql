@@ -0,0 +1,4 @@
+-- How each adjudicator's speaker scores are standardised for the
+-- "standardised average" speaker and team metrics.
+alter table tournaments add column speaks_standardisation text not null
+    default 'mean_shift' check (speaks_standardisation in ('mean_shift', 'z_score'));
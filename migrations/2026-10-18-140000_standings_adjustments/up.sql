@@ -0,0 +1,19 @@
+-- Manual changes to a single metric of a team or a speaker (e.g. a point
+-- deduction for a late team). These are added to the computed metrics when
+-- the standings are computed. Adjustments are never deleted, so that the
+-- table doubles as an audit trail; to undo an adjustment, add another with
+-- the opposite delta.
+create table if not exists standings_adjustments (
+    id text primary key not null,
+    tournament_id text not null references tournaments (id),
+    team_id text references teams (id),
+    speaker_id text references speakers (id),
+    -- the serialized metric (as stored in `team_metrics.metric_kind` or
+    -- `speaker_metrics.metric_kind`)
+    metric text not null,
+    delta real not null,
+    reason text not null,
+    author_id text not null references users (id),
+    created_at timestamp not null default CURRENT_TIMESTAMP,
+    check ((team_id is null) <> (speaker_id is null))
+);
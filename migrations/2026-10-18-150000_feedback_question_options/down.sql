@@ -0,0 +1,2 @@
+alter table feedback_questions drop column paths;
+alter table feedback_questions drop column required;
@@ -0,0 +1,8 @@
+-- Questions which are not required may be left blank.
+alter table feedback_questions add column required boolean not null
+    default true;
+-- If set, a JSON list of the feedback paths (e.g. "chair_to_panellist") on
+-- which the question is asked. Otherwise the question is asked on every path
+-- (subject to `for_judges` and `for_teams`).
+alter table feedback_questions add column paths text
+    check (paths is null or (json_valid(paths) = 1 and json_type(paths) = 'array'));
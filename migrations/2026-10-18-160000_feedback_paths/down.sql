@@ -0,0 +1,2 @@
+alter table tournaments drop column elim_feedback_paths;
+alter table tournaments drop column prelim_feedback_paths;
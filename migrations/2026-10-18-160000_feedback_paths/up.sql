@@ -0,0 +1,8 @@
+-- Which feedback paths (see `FeedbackPath`) are open in preliminary and
+-- elimination rounds. These determine who may give feedback on whom.
+alter table tournaments add column prelim_feedback_paths text not null
+    default '["team_to_chair","chair_to_panellist","panellist_to_chair","trainee_to_chair"]'
+    check (json_valid(prelim_feedback_paths) = 1 and json_type(prelim_feedback_paths) = 'array');
+alter table tournaments add column elim_feedback_paths text not null
+    default '["team_to_chair","chair_to_panellist","panellist_to_chair","trainee_to_chair"]'
+    check (json_valid(elim_feedback_paths) = 1 and json_type(elim_feedback_paths) = 'array');
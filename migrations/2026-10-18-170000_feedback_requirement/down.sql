@@ -0,0 +1,3 @@
+drop table if exists feedback_exemptions;
+
+alter table tournaments drop column feedback_requirement;
@@ -0,0 +1,20 @@
+-- Whether teams and judges must submit feedback for completed rounds:
+-- - 'off': feedback is never required
+-- - 'warn': private URL pages show a warning while feedback is outstanding
+-- - 'block': as 'warn', but judges also cannot submit ballots until their
+--   outstanding feedback has been submitted
+alter table tournaments add column feedback_requirement text not null default 'off'
+    check (feedback_requirement in ('off', 'warn', 'block'));
+
+-- Teams and judges who are not required to submit feedback (regardless of
+-- `feedback_requirement`). Exactly one of `team_id` and `judge_id` is set.
+create table if not exists feedback_exemptions (
+    id text primary key not null,
+    tournament_id text not null references tournaments (id),
+    team_id text references teams (id),
+    judge_id text references judges (id),
+    created_at timestamp not null,
+    check ((team_id is null) != (judge_id is null)),
+    unique (team_id),
+    unique (judge_id)
+);
@@ -0,0 +1,3 @@
+drop table if exists hidden_feedback_comments;
+
+alter table tournaments drop column release_feedback_to_judges;
@@ -0,0 +1,14 @@
+-- Whether judges can see the (anonymised, aggregated) feedback given on them
+-- from their private URL pages.
+alter table tournaments add column release_feedback_to_judges boolean not null default false;
+
+-- Free-text answers which are not shown to the judge the feedback is about
+-- when feedback is released. `feedback_id` is the id of a row of either
+-- `feedback_of_judges` or `feedback_of_teams`.
+create table if not exists hidden_feedback_comments (
+    id text primary key not null,
+    tournament_id text not null references tournaments (id),
+    feedback_id text not null,
+    question_id text not null references feedback_questions (id),
+    unique (feedback_id, question_id)
+);
@@ -0,0 +1,7 @@
+alter table tournaments drop column show_team_emoji;
+alter table tournaments drop column tab_team_names;
+alter table tournaments drop column draw_team_names;
+
+alter table teams drop column use_institution_prefix;
+alter table teams drop column emoji;
+alter table teams drop column code_name;
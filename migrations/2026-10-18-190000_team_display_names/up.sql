@@ -0,0 +1,13 @@
+-- Alternative names for teams. The code name is shown instead of the team
+-- name wherever the tournament is configured to hide team identities, and the
+-- institution code is only prepended to the name if `use_institution_prefix`
+-- is set (existing teams keep the previous behaviour of always prefixing).
+alter table teams add column code_name text;
+alter table teams add column emoji text;
+alter table teams add column use_institution_prefix boolean not null default true;
+
+-- Which name of each team is shown on the public draw and the public tabs.
+alter table tournaments add column draw_team_names text not null default 'full' check (draw_team_names in ('full', 'code'));
+alter table tournaments add column tab_team_names text not null default 'full' check (tab_team_names in ('full', 'code'));
+-- Whether team emoji are shown next to team names on the public draw.
+alter table tournaments add column show_team_emoji boolean not null default false;
@@ -0,0 +1 @@
+alter table standings_adjustments drop column from_seq;
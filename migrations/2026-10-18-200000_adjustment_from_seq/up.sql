@@ -0,0 +1,4 @@
+-- The sequence number of the round after which an adjustment takes effect,
+-- so that the standings as they stood after earlier rounds are unaffected by
+-- it. Existing adjustments apply from the start of the tournament.
+alter table standings_adjustments add column from_seq bigint not null default 0 check (from_seq >= 0);
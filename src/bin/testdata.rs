@@ -3,7 +3,8 @@ use std::{fs::File, path::PathBuf};
 use abacus::MIGRATIONS;
 use abacus::schema::{
     break_categories, institutions, judges, motions_of_round, org, rooms,
-    rounds, speakers, speakers_of_team, teams, tournaments, users,
+    rounds, speakers, speakers_of_category, speakers_of_team, teams,
+    tournaments, users,
 };
 use abacus::tournaments::config::{
     PullupMetric, RankableTeamMetric, SpeakerMetric,
@@ -20,7 +21,10 @@ use diesel::{Connection, QueryDsl, RunQueryDsl};
 use diesel_migrations::MigrationHarness;
 use uuid::Uuid;
 
-use crate::tabbycat_cli_copied::{JudgeRow, RoomRow, TeamRow};
+use abacus::tournaments::participants::manage::import::{
+    get_or_create_speaker_category,
+    rows::{JudgeRow, RoomRow, TeamRow},
+};
 
 #[derive(Parser)]
 pub struct Import {
@@ -174,6 +178,8 @@ fn main() {
                         &team.institution,
                     )),
                     teams::number.eq(i as i64),
+                    teams::use_institution_prefix
+                        .eq(team.use_institution_prefix),
                 ))
                 .execute(&mut conn)
                 .unwrap();
@@ -200,6 +206,30 @@ fn main() {
                     ))
                     .execute(&mut conn)
                     .unwrap();
+
+                // Tabbycat team categories (e.g. "ESL") apply to every
+                // speaker on the team
+                for category in
+                    speaker.categories.iter().chain(team.categories.iter())
+                {
+                    let category_id = get_or_create_speaker_category(
+                        &mut conn,
+                        &tournament_id,
+                        category.trim(),
+                    )
+                    .unwrap();
+                    diesel::insert_or_ignore_into(speakers_of_category::table)
+                        .values((
+                            speakers_of_category::id
+                                .eq(Uuid::now_v7().to_string()),
+                            speakers_of_category::tournament_id
+                                .eq(&tournament_id),
+                            speakers_of_category::category_id.eq(&category_id),
+                            speakers_of_category::speaker_id.eq(&speaker_id),
+                        ))
+                        .execute(&mut conn)
+                        .unwrap();
+                }
             }
         }
     }
@@ -409,203 +439,3 @@ fn get_or_create_institution(
         None
     }
 }
-
-#[allow(warnings)]
-mod tabbycat_cli_copied {
-    use std::collections::HashMap;
-
-    use itertools::Itertools;
-    use serde::{
-        Deserialize, Deserializer,
-        de::{self, Unexpected},
-    };
-
-    #[derive(Deserialize, Debug, Clone)]
-    pub struct InstitutionRow {
-        pub region: Option<String>,
-        // TODO: warn when this is >20 characters (Tabbycat currently applies
-        // this restriction) to aid with debugging
-        pub short_code: String,
-        pub full_name: String,
-    }
-
-    #[derive(Deserialize, Debug, Clone)]
-    pub struct RoomRow {
-        pub name: String,
-        pub priority: i64,
-    }
-
-    fn ret_false() -> bool {
-        false
-    }
-
-    fn tags_deserialize<'de, D>(
-        deserializer: D,
-    ) -> Result<Vec<String>, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        let str_sequence = String::deserialize(deserializer)?;
-        Ok(str_sequence
-            .split(',')
-            .map(|item| item.to_owned())
-            .filter(|item| !item.is_empty())
-            .collect())
-    }
-
-    fn bool_from_str<'de, D>(deserializer: D) -> Result<bool, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        match String::deserialize(deserializer)?.to_lowercase().trim() {
-            "t" | "true" | "1" | "on" | "y" | "yes" => Ok(true),
-            "f" | "false" | "0" | "off" | "n" | "no" | "" => Ok(false),
-            other => Err(de::Error::invalid_value(
-                Unexpected::Str(other),
-                &"Must be truthy (t, true, 1, on, y, yes) or falsey (f, false, 0, off, n, no)",
-            )),
-        }
-    }
-
-    fn not_true() -> bool {
-        false
-    }
-
-    // todo: team institution clashes
-    #[derive(Deserialize, Debug, Clone)]
-    pub struct TeamRow {
-        pub full_name: String,
-        /// If not supplied, we truncate the full name.
-        pub short_name: Option<String>,
-        #[serde(deserialize_with = "tags_deserialize", default = "Vec::new")]
-        pub categories: Vec<String>,
-        pub code_name: Option<String>,
-        pub institution: Option<String>,
-        pub seed: Option<u32>,
-        pub emoji: Option<String>,
-        #[serde(deserialize_with = "bool_from_str", default = "not_true")]
-        pub use_institution_prefix: bool,
-        #[serde(flatten, deserialize_with = "deserialize_fields_to_vec")]
-        pub speakers: Vec<Speaker>,
-    }
-
-    #[derive(Deserialize, Debug, Clone)]
-    pub struct Clash {
-        pub object_1: String,
-        pub object_2: String,
-    }
-
-    fn deserialize_fields_to_vec<'de, D>(
-        deserializer: D,
-    ) -> Result<Vec<Speaker>, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        let map: HashMap<String, String> = HashMap::deserialize(deserializer)?;
-        let speaker_buckets = {
-            let mut buckets: HashMap<u8, HashMap<String, String>> =
-                HashMap::new();
-            for (key, value) in map.iter() {
-                if let Some(iter) = key.strip_prefix("speaker") {
-                    // todo: good error messages
-                    let mut iter = iter.split('_');
-                    let number =
-                        iter.next().unwrap().trim().parse::<u8>().unwrap();
-                    let field_name = iter.next().unwrap();
-                    buckets
-                        .entry(number)
-                        .and_modify(|map| {
-                            map.insert(field_name.to_string(), value.clone());
-                        })
-                        .or_insert({
-                            let mut t = HashMap::new();
-
-                            t.insert(field_name.to_string(), value.clone());
-                            t
-                        });
-                }
-            }
-            buckets
-        };
-
-        Ok(speaker_buckets
-            .into_iter()
-            .sorted_by_key(|(t, _)| *t)
-            .filter_map(|(_, map)| {
-                if map.values().all(|key| key.trim().is_empty()) {
-                    None
-                } else {
-                    Some(Speaker {
-                        name: map
-                            .get("name")
-                            .cloned()
-                            .expect("error: missing name!"),
-                        categories: map
-                            .get("categories")
-                            .cloned()
-                            .map(|t| {
-                                t.split(',')
-                                    .map(|x| x.to_string())
-                                    .filter(|t| !t.trim().is_empty())
-                                    .collect::<Vec<_>>()
-                            })
-                            .unwrap_or(vec![]),
-                        email: map.get("email").cloned(),
-                        phone: map.get("phone").cloned(),
-                        anonymous: map
-                            .get("anonymous")
-                            .cloned()
-                            .map(|t| t.eq_ignore_ascii_case("true"))
-                            .unwrap_or(false),
-                        code_name: map.get("code_name").cloned(),
-                        url_key: map.get("url_key").cloned(),
-                        gender: map.get("gender").map(|gender| {
-                            if gender.to_lowercase() == "male" {
-                                "M"
-                            } else if gender.to_lowercase() == "female" {
-                                "F"
-                            } else if gender.to_lowercase() == "other" {
-                                "O"
-                            } else {
-                                gender
-                            }
-                            .to_string()
-                        }),
-                        pronoun: map.get("pronoun").cloned(),
-                    })
-                }
-            })
-            .collect::<Vec<_>>())
-    }
-
-    #[derive(Deserialize, Debug, Clone)]
-    pub struct Speaker {
-        pub name: String,
-        pub categories: Vec<String>,
-        pub email: Option<String>,
-        pub phone: Option<String>,
-        pub anonymous: bool,
-        pub code_name: Option<String>,
-        pub url_key: Option<String>,
-        // todo: validate correct
-        pub gender: Option<String>,
-        // todo: validate length
-        pub pronoun: Option<String>,
-    }
-
-    #[derive(Deserialize, Debug, Clone)]
-    pub struct JudgeRow {
-        pub name: String,
-        pub institution: Option<String>,
-        #[serde(deserialize_with = "tags_deserialize", default = "Vec::new")]
-        pub institution_clashes: Vec<String>,
-        pub email: Option<String>,
-        #[serde(deserialize_with = "bool_from_str", default = "ret_false")]
-        pub is_ca: bool,
-        #[serde(deserialize_with = "bool_from_str", default = "ret_false")]
-        pub is_ia: bool,
-        pub base_score: Option<f64>,
-        #[serde(deserialize_with = "tags_deserialize", default = "Vec::new")]
-        pub availability: Vec<String>,
-    }
-}
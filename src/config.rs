@@ -83,6 +83,9 @@ async fn home(
                         a href="/tournaments/create" class="btn btn-primary" {
                             "+ Create tournament"
                         }
+                        a href="/tournaments/import" class="btn btn-outline-primary ms-2" {
+                            "Import archive"
+                        }
                         p class="text-secondary small mt-3 mb-0" {
                             "Start a new tournament to begin tabulating."
                         }
@@ -238,11 +241,15 @@ pub fn create_app(pool: DbPool) -> Router {
         .route("/logout", post(crate::auth::login::do_logout))
         .route("/register", get(crate::auth::register::register_page).post(crate::auth::register::do_register))
         .route("/tournaments/create", get(crate::tournaments::create::create_tournament_page).post(crate::tournaments::create::do_create_tournament))
+        .route("/tournaments/import", get(crate::tournaments::archive::import::import_archive_page).post(crate::tournaments::archive::import::do_import_archive))
         .route("/tournaments/:id", get(crate::tournaments::view::view_tournament_page))
 
         // Participants
         .route("/tournaments/:id/participants", get(crate::tournaments::participants::manage::manage_tournament_participants))
         .route("/tournaments/:id/participants/ws", get(crate::tournaments::participants::manage::tournament_participant_updates))
+        .route("/tournaments/:id/participants/import", get(crate::tournaments::participants::manage::import::import_participants_page))
+        .route("/tournaments/:id/participants/import/preview", post(crate::tournaments::participants::manage::import::do_preview_import))
+        .route("/tournaments/:id/participants/import/commit", post(crate::tournaments::participants::manage::import::do_commit_import))
         .route("/tournaments/:id/participants/privateurls", get(crate::tournaments::participants::manage::manage_private_urls::view_private_urls))
 
         // Teams
@@ -324,7 +331,19 @@ pub fn create_app(pool: DbPool) -> Router {
         .route("/tournaments/:id/feedback/manage/up", post(crate::tournaments::feedback::manage::config::move_feedback_question_up))
         .route("/tournaments/:id/feedback/manage/down", post(crate::tournaments::feedback::manage::config::move_feedback_question_down))
         .route("/tournaments/:id/feedback/manage/:question_id/edit", get(crate::tournaments::feedback::manage::config::edit_feedback_question_page).post(crate::tournaments::feedback::manage::config::edit_feedback_question))
+        .route("/tournaments/:id/feedback/exemptions", get(crate::tournaments::feedback::manage::exemptions::feedback_exemptions_page))
+        .route("/tournaments/:id/feedback/exemptions/add", post(crate::tournaments::feedback::manage::exemptions::do_add_feedback_exemption))
+        .route("/tournaments/:id/feedback/exemptions/:exemption_id/delete", post(crate::tournaments::feedback::manage::exemptions::do_delete_feedback_exemption))
         .route("/tournaments/:id/feedback/table", get(crate::tournaments::feedback::manage::table::feedback_table_page))
+        .route("/tournaments/:id/export", get(crate::tournaments::export::export_page))
+        .route("/tournaments/:id/export/feedback", get(crate::tournaments::export::export_feedback))
+        .route("/tournaments/:id/export/ballots", get(crate::tournaments::export::export_ballots))
+        .route("/tournaments/:id/export/results", get(crate::tournaments::export::export_results))
+        .route("/tournaments/:id/export/archive", get(crate::tournaments::archive::export::export_archive))
+        .route("/tournaments/:id/judges/:judge_id/feedback", get(crate::tournaments::feedback::manage::judge::judge_feedback_page))
+        .route("/tournaments/:id/judges/:judge_id/feedback/comments", post(crate::tournaments::feedback::manage::judge::do_toggle_hidden_comment))
+        .route("/tournaments/:id/rounds/:round_seq/feedback", get(crate::tournaments::feedback::manage::progress::feedback_progress_page))
+        .route("/tournaments/:id/rounds/:round_seq/feedback/missing.csv", get(crate::tournaments::feedback::manage::progress::missing_feedback_csv))
         .route("/tournaments/:id/privateurls/:private_url/rounds/:round_id/feedback/submit", get(crate::tournaments::feedback::public::submit::submit_feedback_page).post(crate::tournaments::feedback::public::submit::do_submit_feedback))
 
         // Rounds
@@ -336,6 +355,8 @@ pub fn create_app(pool: DbPool) -> Router {
         .route("/tournaments/:id/rounds/:round_seq/draw", get(crate::tournaments::rounds::draws::public::view::view_active_draw_page))
         .route("/tournaments/:id/rounds/:round_seq/draw/manage", get(crate::tournaments::rounds::manage::draw_view::view_draws_page))
         .route("/tournaments/:id/rounds/:round_id/edit", get(crate::tournaments::rounds::manage::edit::edit_round_page).post(crate::tournaments::rounds::manage::edit::do_edit_round))
+        .route("/tournaments/:id/rounds/:round_seq/print/ballots", get(crate::tournaments::rounds::manage::print::print_ballots_page))
+        .route("/tournaments/:id/rounds/:round_seq/print/feedback", get(crate::tournaments::rounds::manage::print::print_feedback_page))
         .route("/tournaments/:id/rounds/:round_seq/briefing", get(crate::tournaments::rounds::manage::briefing::get_briefing_room))
         .route("/tournaments/:id/rounds/:id/draws/setreleased", post(crate::tournaments::rounds::manage::briefing::set_draw_published))
         .route("/tournaments/:id/rounds/:round_seq/results", get(crate::tournaments::rounds::results::view_results_page))
@@ -369,6 +390,29 @@ pub fn create_app(pool: DbPool) -> Router {
         // Standings
         .route("/tournaments/:id/standings/teams", get(crate::tournaments::standings::manage::admin_team_standings::admin_view_team_standings))
         .route("/tournaments/:id/tab/team", get(crate::tournaments::standings::public::public_team_tab_page))
+        .route("/tournaments/:id/standings/speakers", get(crate::tournaments::standings::manage::admin_speaker_standings::admin_view_speaker_standings))
+        .route("/tournaments/:id/tab/speaker", get(crate::tournaments::standings::public::public_speaker_tab_page))
+        .route("/tournaments/:id/standings/history", get(crate::tournaments::standings::manage::history::admin_standings_history_page))
+        .route("/tournaments/:id/standings/history/rounds/:seq", get(crate::tournaments::standings::manage::history::admin_standings_after_round_page))
+        .route("/tournaments/:id/standings/history/teams/:team_id", get(crate::tournaments::standings::manage::history::admin_team_rank_history_page))
+        .route("/tournaments/:id/standings/adjustments", get(crate::tournaments::standings::manage::adjustments::admin_standings_adjustments_page))
+        .route("/tournaments/:id/standings/adjustments/team", post(crate::tournaments::standings::manage::adjustments::do_create_team_adjustment))
+        .route("/tournaments/:id/standings/adjustments/speaker", post(crate::tournaments::standings::manage::adjustments::do_create_speaker_adjustment))
+        .route("/tournaments/:id/standings/replies", get(crate::tournaments::standings::manage::admin_reply_standings::admin_view_reply_standings))
+        .route("/tournaments/:id/tab/replies", get(crate::tournaments::standings::public::public_reply_tab_page))
+        .route("/tournaments/:id/tab/speaker/:category_id", get(crate::tournaments::standings::public::public_speaker_category_tab_page))
+        .route("/tournaments/:id/tab/team/:category_id", get(crate::tournaments::standings::public::public_team_category_tab_page))
+        .route("/tournaments/:id/categories", get(crate::tournaments::categories::manage::manage_categories_page))
+        .route("/tournaments/:id/categories/speakers", get(crate::tournaments::categories::manage::assign_speaker_categories_page))
+        .route("/tournaments/:id/categories/speakers/:speaker_id/:category_id/toggle", post(crate::tournaments::categories::manage::do_toggle_speaker_category))
+        .route("/tournaments/:id/categories/speaker", post(crate::tournaments::categories::manage::do_create_speaker_category))
+        .route("/tournaments/:id/categories/speaker/standard", post(crate::tournaments::categories::manage::do_create_standard_speaker_categories))
+        .route("/tournaments/:id/categories/speaker/:category_id/delete", post(crate::tournaments::categories::manage::do_delete_speaker_category))
+        .route("/tournaments/:id/categories/team", post(crate::tournaments::categories::manage::do_create_team_category))
+        .route("/tournaments/:id/categories/team/:category_id/delete", post(crate::tournaments::categories::manage::do_delete_team_category))
+        .route("/tournaments/:id/categories/break", post(crate::tournaments::categories::manage::do_create_break_category))
+        .route("/tournaments/:id/categories/break/:category_id", get(crate::tournaments::categories::manage::view_break_category_page))
+        .route("/tournaments/:id/categories/break/:category_id/eligibility", post(crate::tournaments::categories::manage::do_set_break_category_eligibility))
 
         // Public Draw
 
@@ -378,12 +422,21 @@ pub fn create_app(pool: DbPool) -> Router {
 
         // Public Motions
         .route("/tournaments/:id/motions", get(crate::tournaments::motions::public_motions_page))
+        .route("/tournaments/:id/motions/statistics", get(crate::tournaments::motions::stats::motion_statistics_page))
 
         // Ballots
         .route("/tournaments/:id/rounds/:round_seq/ballots", get(crate::tournaments::rounds::ballots::manage::overview::admin_ballot_of_seq_overview))
+        .route("/tournaments/:id/rounds/:round_seq/ballots/ws", get(crate::tournaments::rounds::ballots::manage::overview::ballot_overview_updates))
         .route("/tournaments/:id/debates/:debate_id/ballots", get(crate::tournaments::rounds::ballots::manage::view_ballot_set::view_ballot_set_page))
         .route("/tournaments/:id/debates/:debate_id/judges/:judge_id/edit", get(crate::tournaments::rounds::ballots::manage::edit::edit_ballot_page).post(crate::tournaments::rounds::ballots::manage::edit::do_edit_ballot))
         .route("/tournaments/:id/debates/:debate_id/ballots/:ballot_id/view", get(crate::tournaments::rounds::ballots::manage::view_ballot_set::view_single_ballot_page))
+        .route("/tournaments/:id/debates/:debate_id/substitutes", get(crate::tournaments::rounds::ballots::manage::substitutes::manage_substitutes_page).post(crate::tournaments::rounds::ballots::manage::substitutes::do_add_substitute))
+        .route("/tournaments/:id/debates/:debate_id/substitutes/:speaker_id/delete", post(crate::tournaments::rounds::ballots::manage::substitutes::do_remove_substitute))
+        .route("/tournaments/:id/debates/:debate_id/ballots/history", get(crate::tournaments::rounds::ballots::manage::history::ballot_history_page))
+        .route("/tournaments/:id/debates/:debate_id/ballots/:ballot_id/revert", post(crate::tournaments::rounds::ballots::manage::history::do_revert_ballot))
+        .route("/tournaments/:id/debates/:debate_id/ballots/resolve", get(crate::tournaments::rounds::ballots::manage::resolve::resolve_ballot_conflict_page))
+        .route("/tournaments/:id/debates/:debate_id/ballots/:ballot_id/accept", post(crate::tournaments::rounds::ballots::manage::resolve::do_accept_ballot))
+        .route("/tournaments/:id/debates/:debate_id/ballots/:ballot_id/merge", get(crate::tournaments::rounds::ballots::manage::resolve::merge_ballots_page).post(crate::tournaments::rounds::ballots::manage::resolve::do_merge_ballots))
         .route("/tournaments/:id/privateurls/:private_url", get(crate::tournaments::privateurls::view::private_url_page))
 
         .route(
@@ -19,4 +19,11 @@ pub enum MsgContents {
     TeamAvailabilityUpdate,
     JudgeAvailabilityUpdate,
     DrawUpdated(String),
+    /// A ballot was submitted for (or edited in) the debate with the given
+    /// ID, and the debate is still waiting on ballots from other judges.
+    BallotSubmitted(String),
+    /// The ballots of the debate with the given ID disagree with each other.
+    BallotConflict(String),
+    /// All the ballots of the debate with the given ID are in, and agree.
+    BallotConfirmed(String),
 }
@@ -17,6 +17,7 @@ diesel::table! {
         team_id -> Text,
         position -> BigInt,
         score -> Nullable<Float>,
+        counts_for_speaker_tab -> Bool,
     }
 }
 
@@ -70,6 +71,7 @@ diesel::table! {
         tournament_id -> Text,
         name -> Text,
         priority -> BigInt,
+        team_category_id -> Nullable<Text>,
     }
 }
 
@@ -84,6 +86,16 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    feedback_exemptions (id) {
+        id -> Text,
+        tournament_id -> Text,
+        team_id -> Nullable<Text>,
+        judge_id -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     feedback_of_judges (id) {
         id -> Text,
@@ -113,6 +125,8 @@ diesel::table! {
         seq -> BigInt,
         for_judges -> Bool,
         for_teams -> Bool,
+        required -> Bool,
+        paths -> Nullable<Text>,
     }
 }
 
@@ -124,6 +138,15 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    hidden_feedback_comments (id) {
+        id -> Text,
+        tournament_id -> Text,
+        feedback_id -> Text,
+        question_id -> Text,
+    }
+}
+
 diesel::table! {
     institutions (id) {
         id -> Text,
@@ -288,6 +311,14 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    speaker_categories (id) {
+        id -> Text,
+        tournament_id -> Text,
+        name -> Text,
+    }
+}
+
 diesel::table! {
     speaker_metrics (id) {
         id -> Text,
@@ -339,6 +370,15 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    speakers_of_category (id) {
+        id -> Text,
+        tournament_id -> Text,
+        category_id -> Text,
+        speaker_id -> Text,
+    }
+}
+
 diesel::table! {
     speakers_of_team (id) {
         id -> Text,
@@ -347,6 +387,31 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    standings_adjustments (id) {
+        id -> Text,
+        tournament_id -> Text,
+        team_id -> Nullable<Text>,
+        speaker_id -> Nullable<Text>,
+        metric -> Text,
+        delta -> Float,
+        reason -> Text,
+        author_id -> Text,
+        created_at -> Timestamp,
+        from_seq -> BigInt,
+    }
+}
+
+diesel::table! {
+    substitute_speakers_of_debate (id) {
+        id -> Text,
+        tournament_id -> Text,
+        debate_id -> Text,
+        team_id -> Text,
+        speaker_id -> Text,
+    }
+}
+
 diesel::table! {
     team_availability (id) {
         id -> Text,
@@ -357,6 +422,16 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    team_categories (id) {
+        id -> Text,
+        tournament_id -> Text,
+        name -> Text,
+        speaker_category_id -> Text,
+        rule -> Text,
+    }
+}
+
 diesel::table! {
     team_clashes_of_judge (id) {
         id -> Text,
@@ -402,6 +477,9 @@ diesel::table! {
         name -> Text,
         institution_id -> Nullable<Text>,
         number -> BigInt,
+        code_name -> Nullable<Text>,
+        emoji -> Nullable<Text>,
+        use_institution_prefix -> Bool,
     }
 }
 
@@ -473,6 +551,18 @@ diesel::table! {
         team_standings_metrics -> Text,
         speaker_standings_metrics -> Text,
         exclude_from_speaker_standings_after -> Nullable<BigInt>,
+        iron_speech_rule -> Text,
+        ballot_overdue_after_mins -> BigInt,
+        reply_tab_public -> Bool,
+        reply_tab_min_replies -> BigInt,
+        speaks_standardisation -> Text,
+        prelim_feedback_paths -> Text,
+        elim_feedback_paths -> Text,
+        feedback_requirement -> Text,
+        release_feedback_to_judges -> Bool,
+        draw_team_names -> Text,
+        tab_team_names -> Text,
+        show_team_emoji -> Bool,
     }
 }
 
@@ -503,10 +593,14 @@ diesel::joinable!(ballots -> judges (judge_id));
 diesel::joinable!(ballots -> motions_of_round (motion_id));
 diesel::joinable!(ballots -> tournaments (tournament_id));
 diesel::joinable!(ballots -> users (editor_id));
+diesel::joinable!(break_categories -> team_categories (team_category_id));
 diesel::joinable!(break_categories -> tournaments (tournament_id));
 diesel::joinable!(debates -> rooms (room_id));
 diesel::joinable!(debates -> rounds (round_id));
 diesel::joinable!(debates -> tournaments (tournament_id));
+diesel::joinable!(feedback_exemptions -> judges (judge_id));
+diesel::joinable!(feedback_exemptions -> teams (team_id));
+diesel::joinable!(feedback_exemptions -> tournaments (tournament_id));
 diesel::joinable!(feedback_of_judges -> debates (debate_id));
 diesel::joinable!(feedback_of_judges -> judges (judge_id));
 diesel::joinable!(feedback_of_judges -> tournaments (tournament_id));
@@ -514,6 +608,8 @@ diesel::joinable!(feedback_of_teams -> debates (debate_id));
 diesel::joinable!(feedback_of_teams -> tournaments (tournament_id));
 diesel::joinable!(feedback_questions -> tournaments (tournament_id));
 diesel::joinable!(groups -> tournaments (tournament_id));
+diesel::joinable!(hidden_feedback_comments -> feedback_questions (question_id));
+diesel::joinable!(hidden_feedback_comments -> tournaments (tournament_id));
 diesel::joinable!(institutions -> tournaments (tournament_id));
 diesel::joinable!(judge_availability -> judges (judge_id));
 diesel::joinable!(judge_availability -> rounds (round_id));
@@ -545,6 +641,7 @@ diesel::joinable!(rooms_of_category -> rooms (room_id));
 diesel::joinable!(rounds -> break_categories (break_category));
 diesel::joinable!(rounds -> tournaments (tournament_id));
 diesel::joinable!(snapshots -> tournaments (tournament_id));
+diesel::joinable!(speaker_categories -> tournaments (tournament_id));
 diesel::joinable!(speaker_metrics -> speakers (speaker_id));
 diesel::joinable!(speaker_metrics -> tournaments (tournament_id));
 diesel::joinable!(speaker_room_constraints -> rooms_of_category (category_id));
@@ -557,11 +654,24 @@ diesel::joinable!(speaker_scores_of_ballot -> tournaments (tournament_id));
 diesel::joinable!(speaker_standings -> speakers (speaker_id));
 diesel::joinable!(speaker_standings -> tournaments (tournament_id));
 diesel::joinable!(speakers -> tournaments (tournament_id));
+diesel::joinable!(speakers_of_category -> speaker_categories (category_id));
+diesel::joinable!(speakers_of_category -> speakers (speaker_id));
+diesel::joinable!(speakers_of_category -> tournaments (tournament_id));
 diesel::joinable!(speakers_of_team -> speakers (speaker_id));
 diesel::joinable!(speakers_of_team -> teams (team_id));
+diesel::joinable!(standings_adjustments -> speakers (speaker_id));
+diesel::joinable!(standings_adjustments -> teams (team_id));
+diesel::joinable!(standings_adjustments -> tournaments (tournament_id));
+diesel::joinable!(standings_adjustments -> users (author_id));
+diesel::joinable!(substitute_speakers_of_debate -> debates (debate_id));
+diesel::joinable!(substitute_speakers_of_debate -> speakers (speaker_id));
+diesel::joinable!(substitute_speakers_of_debate -> teams (team_id));
+diesel::joinable!(substitute_speakers_of_debate -> tournaments (tournament_id));
 diesel::joinable!(team_availability -> rounds (round_id));
 diesel::joinable!(team_availability -> teams (team_id));
 diesel::joinable!(team_availability -> tournaments (tournament_id));
+diesel::joinable!(team_categories -> speaker_categories (speaker_category_id));
+diesel::joinable!(team_categories -> tournaments (tournament_id));
 diesel::joinable!(team_clashes_of_judge -> judges (judge_id));
 diesel::joinable!(team_clashes_of_judge -> teams (team_id));
 diesel::joinable!(team_clashes_of_judge -> tournaments (tournament_id));
@@ -588,10 +698,12 @@ diesel::allow_tables_to_appear_in_same_query!(
     ballots,
     break_categories,
     debates,
+    feedback_exemptions,
     feedback_of_judges,
     feedback_of_teams,
     feedback_questions,
     groups,
+    hidden_feedback_comments,
     institutions,
     judge_availability,
     judge_clashes_of_judge,
@@ -608,13 +720,18 @@ diesel::allow_tables_to_appear_in_same_query!(
     rooms_of_category,
     rounds,
     snapshots,
+    speaker_categories,
     speaker_metrics,
     speaker_room_constraints,
     speaker_scores_of_ballot,
     speaker_standings,
     speakers,
+    speakers_of_category,
     speakers_of_team,
+    standings_adjustments,
+    substitute_speakers_of_debate,
     team_availability,
+    team_categories,
     team_clashes_of_judge,
     team_metrics,
     team_ranks_of_ballot,
@@ -0,0 +1,727 @@
+//! Writes a tournament as a Debate XML archive.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use axum::extract::Path;
+use diesel::{connection::LoadConnection, prelude::*, sqlite::Sqlite};
+use itertools::Itertools;
+
+use crate::{
+    auth::User,
+    schema::{
+        answers_of_feedback_from_judges, answers_of_feedback_from_teams,
+        break_categories, debates, feedback_of_judges, feedback_of_teams,
+        feedback_questions, institutions, judges, motions_of_round, rooms,
+        rounds, speakers, speakers_of_team, substitute_speakers_of_debate,
+        teams,
+    },
+    state::Conn,
+    tournaments::{
+        Tournament,
+        archive::xml::Element,
+        categories::{
+            speaker::{SpeakerCategory, categories_of_speakers},
+            team::BreakCategory,
+        },
+        feedback::{
+            FeedbackOfJudge, FeedbackOfTeam, FeedbackQuestion,
+            manage::config::FeedbackQuestionKind,
+        },
+        participants::Judge,
+        rooms::Room,
+        rounds::{Motion, Round, draws::DebateRepr},
+        teams::Team,
+    },
+    util_resp::{StandardResponse, download},
+};
+
+/// Assigns sequential archive ids (e.g. "T1", "T2") to database ids, in the
+/// order given.
+fn archive_ids<'a>(
+    prefix: &str,
+    ids: impl Iterator<Item = &'a String>,
+) -> HashMap<String, String> {
+    ids.enumerate()
+        .map(|(i, id)| (id.clone(), format!("{prefix}{}", i + 1)))
+        .collect()
+}
+
+fn style_of(tournament: &Tournament) -> &'static str {
+    if tournament.teams_per_side == 2 {
+        "bp"
+    } else if tournament.reply_speakers {
+        "wsdc"
+    } else {
+        "australs"
+    }
+}
+
+/// The answer to a feedback question, as written in the archive. Multiple
+/// choice answers are separated with `//` (as Tabbycat does).
+fn archive_answer(kind: &FeedbackQuestionKind, answer: &str) -> String {
+    match kind {
+        FeedbackQuestionKind::Boolean {} => (answer == "yes").to_string(),
+        FeedbackQuestionKind::MultipleChoice { .. } => {
+            serde_json::from_str::<Vec<String>>(answer)
+                .map(|options| options.join("//"))
+                .unwrap_or_else(|_| answer.to_string())
+        }
+        _ => answer.to_string(),
+    }
+}
+
+pub fn tournament_archive(
+    tournament: &Tournament,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> Element {
+    let tid = tournament.id.as_str();
+
+    let institutions = institutions::table
+        .filter(institutions::tournament_id.eq(tid))
+        .order_by(institutions::name.asc())
+        .select((institutions::id, institutions::name, institutions::code))
+        .load::<(String, String, String)>(conn)
+        .unwrap();
+    let institution_ids =
+        archive_ids("I", institutions.iter().map(|(id, ..)| id));
+
+    let teams = teams::table
+        .filter(teams::tournament_id.eq(tid))
+        .order_by(teams::number.asc())
+        .load::<Team>(conn)
+        .unwrap();
+    let team_ids = archive_ids("T", teams.iter().map(|team| &team.id));
+    // (team id, speaker id, speaker name)
+    let speakers_of_teams = speakers_of_team::table
+        .inner_join(
+            speakers::table.on(speakers::id.eq(speakers_of_team::speaker_id)),
+        )
+        .filter(speakers::tournament_id.eq(tid))
+        .order_by(speakers::name.asc())
+        .select((speakers_of_team::team_id, speakers::id, speakers::name))
+        .load::<(String, String, String)>(conn)
+        .unwrap()
+        .into_iter()
+        .into_group_map_by(|(team_id, ..)| team_id.clone());
+    // Substitutes are not members of any team, so are listed (as
+    // `substitute="true"`) with the first team they spoke for.
+    let members = speakers_of_teams
+        .values()
+        .flatten()
+        .map(|(_, id, _)| id)
+        .collect::<HashSet<_>>();
+    let substitutes_of_teams = substitute_speakers_of_debate::table
+        .inner_join(
+            speakers::table
+                .on(speakers::id.eq(substitute_speakers_of_debate::speaker_id)),
+        )
+        .filter(substitute_speakers_of_debate::tournament_id.eq(tid))
+        .order_by(substitute_speakers_of_debate::id.asc())
+        .select((
+            substitute_speakers_of_debate::team_id,
+            speakers::id,
+            speakers::name,
+        ))
+        .load::<(String, String, String)>(conn)
+        .unwrap()
+        .into_iter()
+        .filter(|(_, id, _)| !members.contains(&id))
+        .unique_by(|(_, id, _)| id.clone())
+        .into_group_map_by(|(team_id, ..)| team_id.clone());
+    // (speaker, whether they are a substitute) for each team
+    let speakers_of_archive_team = |team_id: &String| {
+        speakers_of_teams
+            .get(team_id)
+            .into_iter()
+            .flatten()
+            .map(|speaker| (speaker, false))
+            .chain(
+                substitutes_of_teams
+                    .get(team_id)
+                    .into_iter()
+                    .flatten()
+                    .map(|speaker| (speaker, true)),
+            )
+    };
+    let speaker_ids = archive_ids(
+        "S",
+        teams
+            .iter()
+            .flat_map(|team| speakers_of_archive_team(&team.id))
+            .map(|((_, id, _), _)| id),
+    );
+
+    let judges = judges::table
+        .filter(judges::tournament_id.eq(tid))
+        .order_by(judges::number.asc())
+        .load::<Judge>(conn)
+        .unwrap();
+    let judge_ids = archive_ids("A", judges.iter().map(|judge| &judge.id));
+
+    let rooms = rooms::table
+        .filter(rooms::tournament_id.eq(tid))
+        .order_by(rooms::number.asc())
+        .load::<Room>(conn)
+        .unwrap();
+    let room_ids = archive_ids("V", rooms.iter().map(|room| &room.id));
+
+    let speaker_categories = SpeakerCategory::of_tournament(tid, conn);
+    let speaker_category_ids = archive_ids(
+        "SC",
+        speaker_categories.iter().map(|category| &category.id),
+    );
+    let categories_of_speakers = categories_of_speakers(tid, conn);
+
+    let break_categories = break_categories::table
+        .filter(break_categories::tournament_id.eq(tid))
+        .order_by(break_categories::priority.asc())
+        .load::<BreakCategory>(conn)
+        .unwrap();
+    let break_category_ids =
+        archive_ids("BC", break_categories.iter().map(|category| &category.id));
+    let eligible_teams: Vec<_> = break_categories
+        .iter()
+        .map(|category| (category, category.eligible_team_ids(conn)))
+        .collect();
+
+    let rounds = rounds::table
+        .filter(rounds::tournament_id.eq(tid))
+        .order_by(rounds::seq.asc())
+        .load::<Round>(conn)
+        .unwrap();
+    let motions = motions_of_round::table
+        .filter(motions_of_round::tournament_id.eq(tid))
+        .order_by(motions_of_round::id.asc())
+        .load::<Motion>(conn)
+        .unwrap();
+    let motion_ids = archive_ids("M", motions.iter().map(|motion| &motion.id));
+
+    let questions = feedback_questions::table
+        .filter(feedback_questions::tournament_id.eq(tid))
+        .order_by(feedback_questions::seq.asc())
+        .load::<FeedbackQuestion>(conn)
+        .unwrap();
+    let question_ids =
+        archive_ids("Q", questions.iter().map(|question| &question.id));
+    let kinds: HashMap<&str, FeedbackQuestionKind> = questions
+        .iter()
+        .map(|question| {
+            (
+                question.id.as_str(),
+                serde_json::from_str(&question.kind).unwrap(),
+            )
+        })
+        .collect();
+
+    let mut root = Element::new("tournament")
+        .with_attr("name", &tournament.name)
+        .with_attr("short", &tournament.abbrv)
+        .with_attr("style", style_of(tournament));
+
+    let mut debate_ids = HashMap::new();
+    for round in &rounds {
+        let mut round_element = Element::new("round")
+            .with_attr("name", &round.name)
+            .with_attr("elimination", round.is_elim());
+        if let Some(category) = round
+            .break_category()
+            .and_then(|id| break_category_ids.get(id))
+        {
+            round_element = round_element.with_attr("break-category", category);
+        }
+
+        let motions_of_round: Vec<&Motion> = motions
+            .iter()
+            .filter(|motion| motion.round_id == round.id)
+            .collect();
+
+        let debates_of_round = debates::table
+            .filter(debates::round_id.eq(&round.id))
+            .order_by(debates::number.asc())
+            .select(debates::id)
+            .load::<String>(conn)
+            .unwrap();
+        for debate_id in debates_of_round {
+            let debate = DebateRepr::fetch(&debate_id, conn);
+            let archive_id = format!("D{}", debate_ids.len() + 1);
+            debate_ids.insert(debate_id.clone(), archive_id.clone());
+
+            let ballots = debate
+                .latest_ballots(conn)
+                .into_iter()
+                .filter(|ballot| {
+                    judge_ids.contains_key(&ballot.metadata.judge_id)
+                })
+                .sorted_by_key(|ballot| {
+                    judge_ids[&ballot.metadata.judge_id].clone()
+                })
+                .collect::<Vec<_>>();
+
+            let mut element =
+                Element::new("debate").with_attr("id", &archive_id);
+            if let Some(room) = debate
+                .debate
+                .room_id
+                .as_ref()
+                .and_then(|id| room_ids.get(id))
+            {
+                element = element.with_attr("venue", room);
+            }
+            let motion_id = ballots
+                .first()
+                .map(|ballot| ballot.metadata.motion_id.as_str())
+                .or(match motions_of_round.as_slice() {
+                    [motion] => Some(motion.id.as_str()),
+                    _ => None,
+                });
+            if let Some(motion) = motion_id.and_then(|id| motion_ids.get(id)) {
+                element = element.with_attr("motion", motion);
+            }
+
+            let judges_with_status = |status: &str| {
+                debate
+                    .judges_of_debate
+                    .iter()
+                    .filter(|judge| judge.status == status)
+                    .filter_map(|judge| judge_ids.get(&judge.judge_id))
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+            };
+            let chair = judges_with_status("C");
+            let panel = chair
+                .iter()
+                .chain(judges_with_status("P").iter())
+                .copied()
+                .collect::<Vec<_>>();
+            element = element.with_attr("adjudicators", panel.join(" "));
+            if let Some(chair) = chair.first() {
+                element = element.with_attr("chair", chair);
+            }
+            let trainees = judges_with_status("T");
+            if !trainees.is_empty() {
+                element = element.with_attr("trainees", trainees.join(" "));
+            }
+
+            let n_teams = debate.teams_of_debate.len() as i64;
+            // The archive lists sides in speaking order of the first speakers
+            // (e.g. OG, OO, CG, CO)
+            for team in debate
+                .teams_of_debate
+                .iter()
+                .sorted_by_key(|team| (team.seq, team.side))
+            {
+                let mut side = Element::new("side")
+                    .with_attr("team", &team_ids[&team.team_id]);
+
+                for ballot in &ballots {
+                    let points = ballot
+                        .team_ranks
+                        .iter()
+                        .find(|rank| rank.team_id == team.team_id)
+                        .map(|rank| rank.points)
+                        .unwrap_or(0);
+                    let rank = if round.is_elim() {
+                        if points == 1 { 1 } else { n_teams }
+                    } else {
+                        n_teams - points
+                    };
+                    let mut ballot_element = Element::new("ballot")
+                        .with_attr(
+                            "adjudicators",
+                            &judge_ids[&ballot.metadata.judge_id],
+                        )
+                        .with_attr("rank", rank);
+                    let scores = ballot
+                        .scores_of_team(&team.team_id)
+                        .into_iter()
+                        .filter_map(|score| score.score)
+                        .collect::<Vec<_>>();
+                    if !scores.is_empty() {
+                        ballot_element = ballot_element
+                            .with_text(&scores.iter().sum::<f32>().to_string());
+                    }
+                    side.push(ballot_element);
+                }
+
+                // (position, speaker) -> (judge, score)
+                let mut speeches: BTreeMap<(i64, &str), Vec<(&str, f32)>> =
+                    BTreeMap::new();
+                for ballot in &ballots {
+                    for score in ballot.scores.iter().filter(|score| {
+                        score.team_id == team.team_id
+                            && speaker_ids.contains_key(&score.speaker_id)
+                    }) {
+                        let entry = speeches
+                            .entry((
+                                score.speaker_position,
+                                score.speaker_id.as_str(),
+                            ))
+                            .or_default();
+                        if let Some(value) = score.score {
+                            entry.push((
+                                ballot.metadata.judge_id.as_str(),
+                                value,
+                            ));
+                        }
+                    }
+                }
+                for ((position, speaker_id), scores) in speeches {
+                    let mut speech = Element::new("speech")
+                        .with_attr("speaker", &speaker_ids[speaker_id])
+                        .with_attr(
+                            "reply",
+                            position >= tournament.substantive_speakers,
+                        );
+                    for (judge_id, score) in scores {
+                        speech.push(
+                            Element::new("ballot")
+                                .with_attr("adjudicators", &judge_ids[judge_id])
+                                .with_text(&score.to_string()),
+                        );
+                    }
+                    side.push(speech);
+                }
+
+                element.push(side);
+            }
+
+            round_element.push(element);
+        }
+
+        root.push(round_element);
+    }
+
+    let mut participants = Element::new("participants");
+    for team in &teams {
+        let mut element = Element::new("team")
+            .with_attr("id", &team_ids[&team.id])
+            .with_attr("name", &team.name);
+        if let Some(institution) = team
+            .institution_id
+            .as_ref()
+            .and_then(|id| institution_ids.get(id))
+        {
+            element = element.with_attr("institution", institution);
+        }
+        if let Some(code_name) = &team.code_name {
+            element = element.with_attr("code", code_name);
+        }
+        let eligibilities = eligible_teams
+            .iter()
+            .filter(|(_, eligible)| {
+                eligible.as_ref().is_none_or(|ids| ids.contains(&team.id))
+            })
+            .map(|(category, _)| break_category_ids[&category.id].as_str())
+            .collect::<Vec<_>>();
+        element =
+            element.with_attr("break-eligibilities", eligibilities.join(" "));
+
+        for ((_, speaker_id, name), is_substitute) in
+            speakers_of_archive_team(&team.id)
+        {
+            let categories = categories_of_speakers
+                .get(speaker_id)
+                .into_iter()
+                .flatten()
+                .filter_map(|id| speaker_category_ids.get(id))
+                .map(String::as_str)
+                .sorted()
+                .collect::<Vec<_>>();
+            let mut speaker = Element::new("speaker")
+                .with_attr("id", &speaker_ids[speaker_id])
+                .with_attr("categories", categories.join(" "))
+                .with_text(name);
+            if is_substitute {
+                speaker = speaker.with_attr("substitute", true);
+            }
+            element.push(speaker);
+        }
+        participants.push(element);
+    }
+
+    let feedback_from_judges = feedback_of_judges::table
+        .filter(feedback_of_judges::tournament_id.eq(tid))
+        .order_by(feedback_of_judges::id.asc())
+        .load::<FeedbackOfJudge>(conn)
+        .unwrap();
+    let feedback_from_teams = feedback_of_teams::table
+        .filter(feedback_of_teams::tournament_id.eq(tid))
+        .order_by(feedback_of_teams::id.asc())
+        .load::<FeedbackOfTeam>(conn)
+        .unwrap();
+    // feedback id -> (question id, answer)
+    let answers = answers_of_feedback_from_judges::table
+        .filter(answers_of_feedback_from_judges::tournament_id.eq(tid))
+        .select((
+            answers_of_feedback_from_judges::feedback_id,
+            answers_of_feedback_from_judges::question_id,
+            answers_of_feedback_from_judges::answer,
+        ))
+        .load::<(String, String, String)>(conn)
+        .unwrap()
+        .into_iter()
+        .chain(
+            answers_of_feedback_from_teams::table
+                .filter(answers_of_feedback_from_teams::tournament_id.eq(tid))
+                .select((
+                    answers_of_feedback_from_teams::feedback_id,
+                    answers_of_feedback_from_teams::question_id,
+                    answers_of_feedback_from_teams::answer,
+                ))
+                .load::<(String, String, String)>(conn)
+                .unwrap(),
+        )
+        .into_group_map_by(|(feedback_id, ..)| feedback_id.clone());
+
+    // (target judge id, feedback element)
+    let feedback = feedback_from_judges
+        .iter()
+        .filter_map(|feedback| {
+            Some((
+                feedback.target_judge_id.as_str(),
+                feedback.id.as_str(),
+                Element::new("feedback")
+                    .with_attr(
+                        "source-adjudicator",
+                        judge_ids.get(&feedback.judge_id)?,
+                    )
+                    .with_attr("debate", debate_ids.get(&feedback.debate_id)?),
+            ))
+        })
+        .chain(feedback_from_teams.iter().filter_map(|feedback| {
+            Some((
+                feedback.target_judge_id.as_str(),
+                feedback.id.as_str(),
+                Element::new("feedback")
+                    .with_attr("source-team", team_ids.get(&feedback.team_id)?)
+                    .with_attr("debate", debate_ids.get(&feedback.debate_id)?),
+            ))
+        }))
+        .map(|(target, feedback_id, mut element)| {
+            for (_, question_id, answer) in
+                answers.get(feedback_id).into_iter().flatten()
+            {
+                element.push(
+                    Element::new("answer")
+                        .with_attr("question", &question_ids[question_id])
+                        .with_text(&archive_answer(
+                            &kinds[question_id.as_str()],
+                            answer,
+                        )),
+                );
+            }
+            (target, element)
+        })
+        .into_group_map();
+
+    for judge in &judges {
+        let mut element = Element::new("adjudicator")
+            .with_attr("id", &judge_ids[&judge.id])
+            .with_attr("name", &judge.name);
+        if let Some(institution) = judge
+            .institution_id
+            .as_ref()
+            .and_then(|id| institution_ids.get(id))
+        {
+            element = element.with_attr("institution", institution);
+        }
+        for feedback in feedback.get(judge.id.as_str()).into_iter().flatten() {
+            element.push(feedback.clone());
+        }
+        participants.push(element);
+    }
+    root.push(participants);
+
+    for (id, name, code) in &institutions {
+        root.push(
+            Element::new("institution")
+                .with_attr("id", &institution_ids[id])
+                .with_attr("reference", code)
+                .with_text(name),
+        );
+    }
+
+    for motion in &motions {
+        let round = rounds.iter().find(|round| round.id == motion.round_id);
+        let mut element = Element::new("motion")
+            .with_attr("id", &motion_ids[&motion.id])
+            .with_attr(
+                "reference",
+                round.map(|round| round.name.as_str()).unwrap_or_default(),
+            )
+            .with_text(&motion.motion);
+        if let Some(infoslide) = &motion.infoslide {
+            element = element
+                .with_child(Element::new("info-slide").with_text(infoslide));
+        }
+        root.push(element);
+    }
+
+    for room in &rooms {
+        root.push(
+            Element::new("venue")
+                .with_attr("id", &room_ids[&room.id])
+                .with_attr("priority", room.priority)
+                .with_text(&room.name),
+        );
+    }
+
+    for question in &questions {
+        let kind = &kinds[question.id.as_str()];
+        let mut element = Element::new("question")
+            .with_attr("id", &question_ids[&question.id])
+            .with_attr("name", &question.question)
+            .with_attr(
+                "type",
+                match kind {
+                    FeedbackQuestionKind::IntegerScale { .. } => "is",
+                    FeedbackQuestionKind::Text {} => "tl",
+                    FeedbackQuestionKind::Boolean {} => "bs",
+                    FeedbackQuestionKind::SingleChoice { .. } => "ss",
+                    FeedbackQuestionKind::MultipleChoice { .. } => "ms",
+                },
+            )
+            .with_attr("required", question.required)
+            .with_attr("from-adj", question.for_judges)
+            .with_attr("from-team", question.for_teams)
+            .with_text(&question.question);
+        if let FeedbackQuestionKind::IntegerScale { min, max } = kind {
+            element = element
+                .with_attr("min-value", min)
+                .with_attr("max-value", max);
+        }
+        if !kind.options().is_empty() {
+            element = element.with_attr("choices", kind.options().join("//"));
+        }
+        root.push(element);
+    }
+
+    for category in &speaker_categories {
+        root.push(
+            Element::new("speaker-category")
+                .with_attr("id", &speaker_category_ids[&category.id])
+                .with_text(&category.name),
+        );
+    }
+
+    for category in &break_categories {
+        root.push(
+            Element::new("break-category")
+                .with_attr("id", &break_category_ids[&category.id])
+                .with_text(&category.name),
+        );
+    }
+
+    root
+}
+
+pub async fn export_archive(
+    Path(tournament_id): Path<String>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+
+    download(
+        "application/xml",
+        format!("{}.xml", tournament.slug),
+        tournament_archive(&tournament, &mut *conn)
+            .to_document()
+            .into_bytes(),
+    )
+}
+
+#[cfg(test)]
+#[test]
+fn test_substitutes_survive_an_export_and_import() {
+    use diesel::sqlite::SqliteConnection;
+    use diesel_migrations::MigrationHarness;
+
+    use crate::{
+        schema::{agg_speaker_results_of_debate, tournaments},
+        tournaments::{
+            archive::{import::import_archive, xml},
+            create::insert_tournament,
+        },
+    };
+
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+    conn.run_pending_migrations(crate::MIGRATIONS).unwrap();
+    let fetch = |tid: &str, conn: &mut SqliteConnection| {
+        tournaments::table
+            .filter(tournaments::id.eq(tid))
+            .first::<Tournament>(conn)
+            .unwrap()
+    };
+
+    // Sam substitutes for Ann
+    insert_tournament("t1", "Sub Open", "SO", "sub-open", "u", &mut conn)
+        .unwrap();
+    let root = xml::parse(
+        "<tournament name=\"Sub Open\" style=\"ap\">\
+         <round name=\"Round 1\">\
+         <debate id=\"D1\" adjudicators=\"A1\" motion=\"M1\">\
+         <side team=\"T1\"><ballot adjudicators=\"A1\" rank=\"1\"/>\
+         <speech speaker=\"S3\"><ballot adjudicators=\"A1\">75</ballot></speech>\
+         </side>\
+         <side team=\"T2\"><ballot adjudicators=\"A1\" rank=\"2\"/>\
+         <speech speaker=\"S2\"><ballot adjudicators=\"A1\">74</ballot></speech>\
+         </side>\
+         </debate>\
+         </round>\
+         <participants>\
+         <team id=\"T1\" name=\"Alpha\"><speaker id=\"S1\">Ann</speaker>\
+         <speaker id=\"S3\" substitute=\"true\">Sam</speaker></team>\
+         <team id=\"T2\" name=\"Beta\"><speaker id=\"S2\">Ben</speaker></team>\
+         <adjudicator id=\"A1\" name=\"Cat\"/>\
+         </participants>\
+         <motion id=\"M1\">This House would substitute</motion>\
+         </tournament>",
+    )
+    .unwrap();
+    import_archive(&root, "t1", &mut conn).unwrap();
+
+    let exported = tournament_archive(&fetch("t1", &mut conn), &mut conn);
+    insert_tournament("t2", "Sub Open", "SO", "sub-open-2", "u", &mut conn)
+        .unwrap();
+    import_archive(
+        &xml::parse(&exported.to_document()).unwrap(),
+        "t2",
+        &mut conn,
+    )
+    .unwrap();
+
+    for tid in ["t1", "t2"] {
+        let sam = speakers::table
+            .filter(speakers::tournament_id.eq(tid))
+            .filter(speakers::name.eq("Sam"))
+            .select(speakers::id)
+            .first::<String>(&mut conn)
+            .unwrap();
+        let is_member = diesel::dsl::select(diesel::dsl::exists(
+            speakers_of_team::table
+                .filter(speakers_of_team::speaker_id.eq(&sam)),
+        ))
+        .get_result::<bool>(&mut conn)
+        .unwrap();
+        assert!(!is_member);
+        let n_substitutions = substitute_speakers_of_debate::table
+            .filter(substitute_speakers_of_debate::speaker_id.eq(&sam))
+            .count()
+            .get_result::<i64>(&mut conn)
+            .unwrap();
+        assert_eq!(n_substitutions, 1);
+        let score = agg_speaker_results_of_debate::table
+            .filter(agg_speaker_results_of_debate::speaker_id.eq(&sam))
+            .select(agg_speaker_results_of_debate::score)
+            .first::<Option<f32>>(&mut conn)
+            .unwrap();
+        assert_eq!(score, Some(75.0));
+    }
+
+    assert_eq!(
+        tournament_archive(&fetch("t2", &mut conn), &mut conn),
+        exported
+    );
+}
@@ -0,0 +1,1173 @@
+//! Creates a tournament from a Debate XML archive (e.g. one exported from
+//! Tabbycat).
+//!
+//! Rounds in which every debate has a ballot are marked as completed, and
+//! their results aggregated so that the standings can be recomputed. Ballots
+//! marked as `ignored` are skipped. If the ballots of any panel differ from
+//! one another (or some are marked as `minority`), the panels voted, and the
+//! tournament is set up to take individual ballots for that kind of round.
+//! Otherwise, `minority` ballots are skipped too, as consensus ballots must
+//! agree. Tabbycat's per-team break eligibilities, adjudicator scores and the
+//! overall score attached to each piece of feedback have no equivalent here,
+//! and are not imported.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use axum::{extract::Form, response::Redirect};
+use chrono::Utc;
+use diesel::{
+    connection::LoadConnection, prelude::*, result::DatabaseErrorKind,
+    sqlite::Sqlite,
+};
+use hypertext::prelude::*;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    auth::User,
+    schema::{
+        answers_of_feedback_from_judges, answers_of_feedback_from_teams,
+        break_categories, debates, feedback_of_judges, feedback_of_teams,
+        feedback_questions, institutions, judges, judges_of_debate,
+        motions_of_round, rooms, rounds, speaker_categories, speakers,
+        speakers_of_category, speakers_of_team, substitute_speakers_of_debate,
+        teams, teams_of_debate, tournaments,
+    },
+    state::Conn,
+    template::Page,
+    tournaments::{
+        Tournament,
+        archive::xml::{self, Element},
+        create::{check_tournament_details, insert_tournament},
+        feedback::{
+            FeedbackFromJudgesQuestionAnswer, FeedbackFromTeamsQuestionAnswer,
+            FeedbackOfJudge, FeedbackOfTeam,
+            manage::config::FeedbackQuestionKind,
+        },
+        rounds::{
+            ballots::{
+                BallotMetadata, BallotRepr, BallotScore, BallotTeamRank,
+                aggregate::aggregate_ballot_set,
+            },
+            draws::DebateRepr,
+        },
+        standings::compute::{
+            refresh_saved_team_standings,
+            speakers::refresh_saved_speaker_standings,
+        },
+    },
+    util_resp::{StandardResponse, SuccessResponse, bad_request, see_other_ok},
+};
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    /// The archive is malformed, or refers to something which it does not
+    /// define.
+    Invalid(String),
+    Database(diesel::result::Error),
+}
+
+impl From<diesel::result::Error> for ArchiveError {
+    fn from(e: diesel::result::Error) -> Self {
+        ArchiveError::Database(e)
+    }
+}
+
+fn invalid<T>(msg: String) -> Result<T, ArchiveError> {
+    Err(ArchiveError::Invalid(msg))
+}
+
+fn required<'a>(
+    element: &'a Element,
+    attr: &str,
+) -> Result<&'a str, ArchiveError> {
+    match element.attr(attr) {
+        Some(value) if !value.trim().is_empty() => Ok(value.trim()),
+        _ => invalid(format!(
+            "a `<{}>` is missing the `{attr}` attribute",
+            element.name
+        )),
+    }
+}
+
+/// Resolves a reference (e.g. `T3`) to an element defined elsewhere in the
+/// archive.
+fn resolve<'a, T>(
+    defined: &'a HashMap<&str, T>,
+    kind: &str,
+    reference: &str,
+) -> Result<&'a T, ArchiveError> {
+    match defined.get(reference) {
+        Some(t) => Ok(t),
+        None => {
+            invalid(format!("there is no {kind} with the id `{reference}`"))
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Format {
+    teams_per_side: i64,
+    substantive_speakers: i64,
+    reply_speakers: bool,
+}
+
+/// Infers the format of the tournament from its debates, falling back to the
+/// `style` attribute if no debates have been recorded.
+fn format_of(root: &Element) -> Format {
+    let debates = root
+        .children("round")
+        .flat_map(|round| round.children("debate"));
+    let sides = debates.clone().flat_map(|debate| debate.children("side"));
+    let mut speeches = sides.clone().flat_map(|side| side.children("speech"));
+    let is_bp = root.attr("style") == Some("bp");
+
+    let teams_per_side =
+        match debates.map(|debate| debate.children("side").count()).max() {
+            Some(n) if n > 2 => 2,
+            Some(_) => 1,
+            None if is_bp => 2,
+            None => 1,
+        };
+    let substantive_speakers = sides
+        .map(|side| {
+            side.children("speech")
+                .filter(|speech| !speech.flag("reply"))
+                .count() as i64
+        })
+        .max()
+        .filter(|n| *n > 0)
+        .unwrap_or(if teams_per_side == 2 { 2 } else { 3 });
+    let reply_speakers = if speeches.clone().next().is_some() {
+        speeches.any(|speech| speech.flag("reply"))
+    } else {
+        teams_per_side == 1
+    };
+
+    Format {
+        teams_per_side,
+        substantive_speakers,
+        reply_speakers,
+    }
+}
+
+/// The points (as stored on ballots) of a team given its rank in a debate.
+fn points_of_rank(rank: i64, n_teams: i64, is_elim: bool) -> i64 {
+    if is_elim {
+        (rank <= n_teams / 2) as i64
+    } else {
+        n_teams - rank
+    }
+}
+
+fn question_kind(question: &Element) -> FeedbackQuestionKind {
+    let options = || {
+        question
+            .attr("choices")
+            .unwrap_or_default()
+            .split("//")
+            .map(|option| option.trim().to_string())
+            .filter(|option| !option.is_empty())
+            .collect()
+    };
+    let bound = |attr: &str, default: i64| {
+        question
+            .attr(attr)
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .map(|value| value as i64)
+            .unwrap_or(default)
+    };
+    match question.attr("type").unwrap_or_default() {
+        "i" | "is" => FeedbackQuestionKind::IntegerScale {
+            min: bound("min-value", 0),
+            max: bound("max-value", 10),
+        },
+        "bc" | "bs" => FeedbackQuestionKind::Boolean {},
+        "ss" => FeedbackQuestionKind::SingleChoice { options: options() },
+        "ms" => FeedbackQuestionKind::MultipleChoice { options: options() },
+        _ => FeedbackQuestionKind::Text {},
+    }
+}
+
+/// The values of an answer in the archive, in the form which
+/// [`FeedbackQuestionKind::parse_answer`] expects.
+fn answer_values(kind: &FeedbackQuestionKind, answer: &str) -> Vec<String> {
+    match kind {
+        FeedbackQuestionKind::Boolean {} => {
+            vec![
+                match answer.trim().to_lowercase().as_str() {
+                    "true" | "yes" | "1" => "yes",
+                    "false" | "no" | "0" => "no",
+                    _ => answer,
+                }
+                .to_string(),
+            ]
+        }
+        FeedbackQuestionKind::MultipleChoice { .. } => {
+            answer.split("//").map(|value| value.to_string()).collect()
+        }
+        _ => vec![answer.to_string()],
+    }
+}
+
+/// A single judge's ballot, as it is assembled from the archive.
+#[derive(Default)]
+struct JudgeBallot {
+    /// team id -> points
+    points: HashMap<String, i64>,
+    /// (team id, speaker id, position, score)
+    scores: Vec<(String, String, i64, Option<f32>)>,
+}
+
+/// Whether the adjudicators of a debate submitted ballots which differ from
+/// one another (i.e. the panel voted, rather than agreeing on a single
+/// consensus ballot).
+fn has_differing_ballots(debate: &Element) -> bool {
+    let counted = |ballot: &&Element| !ballot.flag("ignored");
+    if debate
+        .children("side")
+        .flat_map(|side| side.children("ballot"))
+        .filter(counted)
+        .any(|ballot| ballot.flag("minority"))
+    {
+        return true;
+    }
+
+    // adjudicator -> (side, speech, rank or score) for each of their ballots
+    let mut ballots: BTreeMap<&str, Vec<(usize, Option<usize>, &str)>> =
+        BTreeMap::new();
+    for (k, side) in debate.children("side").enumerate() {
+        for ballot in side.children("ballot").filter(counted) {
+            for judge in ballot.list_attr("adjudicators") {
+                ballots.entry(judge).or_default().push((
+                    k,
+                    None,
+                    ballot.attr("rank").unwrap_or_default().trim(),
+                ));
+            }
+        }
+    }
+    for (k, side) in debate.children("side").enumerate() {
+        for (n, speech) in side.children("speech").enumerate() {
+            for ballot in speech.children("ballot").filter(counted) {
+                for judge in ballot.list_attr("adjudicators") {
+                    if let Some(entries) = ballots.get_mut(judge) {
+                        entries.push((k, Some(n), ballot.text.trim()));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut ballots = ballots.into_values();
+    match ballots.next() {
+        Some(first) => ballots.any(|ballot| ballot != first),
+        None => false,
+    }
+}
+
+/// Adds the contents of the archive to the (newly created) tournament.
+pub fn import_archive(
+    root: &Element,
+    tid: &str,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> Result<(), ArchiveError> {
+    if root.name != "tournament" {
+        return invalid(
+            "the root element of the archive must be `<tournament>`"
+                .to_string(),
+        );
+    }
+
+    let format = format_of(root);
+    let voting_panels = |elim: bool| -> Result<bool, ArchiveError> {
+        let voted = root
+            .children("round")
+            .filter(|round| round.flag("elimination") == elim)
+            .flat_map(|round| round.children("debate"))
+            .any(has_differing_ballots);
+        if voted && format.teams_per_side != 1 {
+            return invalid(
+                "the adjudicators of a debate submitted differing ballots, \
+                 but individual ballots are only supported when there are two \
+                 teams in each debate"
+                    .to_string(),
+            );
+        }
+        Ok(voted)
+    };
+    let ballot_setup = |voted: bool| {
+        if voted { "individual" } else { "consensus" }
+    };
+    diesel::update(tournaments::table.filter(tournaments::id.eq(tid)))
+        .set((
+            tournaments::teams_per_side.eq(format.teams_per_side),
+            tournaments::substantive_speakers.eq(format.substantive_speakers),
+            tournaments::reply_speakers.eq(format.reply_speakers),
+            tournaments::pool_ballot_setup
+                .eq(ballot_setup(voting_panels(false)?)),
+            tournaments::elim_ballot_setup
+                .eq(ballot_setup(voting_panels(true)?)),
+        ))
+        .execute(conn)?;
+    let tournament = tournaments::table
+        .filter(tournaments::id.eq(tid))
+        .first::<Tournament>(conn)?;
+
+    let mut institution_ids = HashMap::new();
+    for institution in root.children("institution") {
+        let id = Uuid::now_v7().to_string();
+        let name = institution.text.trim();
+        diesel::insert_into(institutions::table)
+            .values((
+                institutions::id.eq(&id),
+                institutions::tournament_id.eq(tid),
+                institutions::name.eq(name),
+                institutions::code.eq(institution
+                    .attr("reference")
+                    .map(str::trim)
+                    .filter(|code| !code.is_empty())
+                    .unwrap_or(name)),
+            ))
+            .execute(conn)?;
+        institution_ids.insert(required(institution, "id")?, id);
+    }
+    let institution_of = |element: &Element| match element.attr("institution") {
+        Some(reference) if !reference.trim().is_empty() => {
+            resolve(&institution_ids, "institution", reference.trim())
+                .map(|id| Some(id.clone()))
+        }
+        _ => Ok(None),
+    };
+
+    let mut speaker_category_ids = HashMap::new();
+    for category in root.children("speaker-category") {
+        let id = Uuid::now_v7().to_string();
+        diesel::insert_into(speaker_categories::table)
+            .values((
+                speaker_categories::id.eq(&id),
+                speaker_categories::tournament_id.eq(tid),
+                speaker_categories::name.eq(category.text.trim()),
+            ))
+            .execute(conn)?;
+        speaker_category_ids.insert(required(category, "id")?, id);
+    }
+
+    let mut break_category_ids = HashMap::new();
+    for (i, category) in root.children("break-category").enumerate() {
+        let id = Uuid::now_v7().to_string();
+        diesel::insert_into(break_categories::table)
+            .values((
+                break_categories::id.eq(&id),
+                break_categories::tournament_id.eq(tid),
+                break_categories::name.eq(category.text.trim()),
+                break_categories::priority.eq(i as i64),
+            ))
+            .execute(conn)?;
+        break_category_ids.insert(required(category, "id")?, id);
+    }
+
+    let mut room_ids = HashMap::new();
+    for (i, venue) in root.children("venue").enumerate() {
+        let id = Uuid::now_v7().to_string();
+        diesel::insert_into(rooms::table)
+            .values((
+                rooms::id.eq(&id),
+                rooms::tournament_id.eq(tid),
+                rooms::name.eq(venue.text.trim()),
+                rooms::priority.eq(venue
+                    .attr("priority")
+                    .and_then(|priority| priority.trim().parse::<i64>().ok())
+                    .unwrap_or(0)),
+                rooms::number.eq(i as i64 + 1),
+            ))
+            .execute(conn)?;
+        room_ids.insert(required(venue, "id")?, id);
+    }
+
+    let empty = Element::new("participants");
+    let participants = root.child("participants").unwrap_or(&empty);
+
+    let mut team_ids = HashMap::new();
+    let mut speaker_ids = HashMap::new();
+    // Substitutes are listed with a team, but are not members of it (and may
+    // speak for other teams too).
+    let mut substitute_ids = HashSet::new();
+    for (i, team) in participants.children("team").enumerate() {
+        let team_id = Uuid::now_v7().to_string();
+        diesel::insert_into(teams::table)
+            .values((
+                teams::id.eq(&team_id),
+                teams::tournament_id.eq(tid),
+                teams::name.eq(required(team, "name")?),
+                teams::institution_id.eq(institution_of(team)?),
+                teams::number.eq(i as i64 + 1),
+                teams::code_name.eq(team
+                    .attr("code")
+                    .map(str::trim)
+                    .filter(|code| !code.is_empty())),
+            ))
+            .execute(conn)?;
+
+        for speaker in team.children("speaker") {
+            if speaker.text.trim().is_empty() {
+                return invalid(format!(
+                    "a speaker of {} has no name",
+                    required(team, "name")?
+                ));
+            }
+            let speaker_id = Uuid::now_v7().to_string();
+            diesel::insert_into(speakers::table)
+                .values((
+                    speakers::id.eq(&speaker_id),
+                    speakers::tournament_id.eq(tid),
+                    speakers::name.eq(speaker.text.trim()),
+                    speakers::email
+                        .eq(speaker.attr("email").unwrap_or_default().trim()),
+                    speakers::private_url.eq(Uuid::new_v4().to_string()),
+                ))
+                .execute(conn)?;
+            if speaker.flag("substitute") {
+                substitute_ids.insert(required(speaker, "id")?);
+            } else {
+                diesel::insert_into(speakers_of_team::table)
+                    .values((
+                        speakers_of_team::id.eq(Uuid::now_v7().to_string()),
+                        speakers_of_team::team_id.eq(&team_id),
+                        speakers_of_team::speaker_id.eq(&speaker_id),
+                    ))
+                    .execute(conn)?;
+            }
+            for category in speaker.list_attr("categories") {
+                let category_id = resolve(
+                    &speaker_category_ids,
+                    "speaker category",
+                    category,
+                )?;
+                diesel::insert_into(speakers_of_category::table)
+                    .values((
+                        speakers_of_category::id.eq(Uuid::now_v7().to_string()),
+                        speakers_of_category::tournament_id.eq(tid),
+                        speakers_of_category::category_id.eq(category_id),
+                        speakers_of_category::speaker_id.eq(&speaker_id),
+                    ))
+                    .execute(conn)?;
+            }
+            speaker_ids.insert(required(speaker, "id")?, speaker_id);
+        }
+        team_ids.insert(required(team, "id")?, team_id);
+    }
+
+    let mut judge_ids = HashMap::new();
+    for (i, adjudicator) in participants.children("adjudicator").enumerate() {
+        let id = Uuid::now_v7().to_string();
+        diesel::insert_into(judges::table)
+            .values((
+                judges::id.eq(&id),
+                judges::tournament_id.eq(tid),
+                judges::name.eq(required(adjudicator, "name")?),
+                judges::email
+                    .eq(adjudicator.attr("email").unwrap_or_default().trim()),
+                judges::institution_id.eq(institution_of(adjudicator)?),
+                judges::private_url.eq(Uuid::new_v4().to_string()),
+                judges::number.eq(i as i64 + 1),
+            ))
+            .execute(conn)?;
+        judge_ids.insert(required(adjudicator, "id")?, id);
+    }
+
+    let mut motions = HashMap::new();
+    for motion in root.children("motion") {
+        motions.insert(required(motion, "id")?, motion);
+    }
+
+    let mut debate_ids = HashMap::new();
+    for (i, round) in root.children("round").enumerate() {
+        let round_id = Uuid::now_v7().to_string();
+        let round_name = required(round, "name")?;
+        let is_elim = round.flag("elimination");
+        let is_consensus = if is_elim {
+            tournament.elim_is_consensus()
+        } else {
+            tournament.pool_is_consensus()
+        };
+        let break_category = match round.attr("break-category") {
+            Some(reference) if !reference.trim().is_empty() => Some(
+                resolve(
+                    &break_category_ids,
+                    "break category",
+                    reference.trim(),
+                )?
+                .clone(),
+            ),
+            _ => None,
+        };
+        diesel::insert_into(rounds::table)
+            .values((
+                rounds::id.eq(&round_id),
+                rounds::tournament_id.eq(tid),
+                rounds::seq.eq(i as i64 + 1),
+                rounds::name.eq(round_name),
+                rounds::kind.eq(if is_elim { "E" } else { "P" }),
+                rounds::break_category.eq(break_category),
+                rounds::completed.eq(false),
+            ))
+            .execute(conn)?;
+
+        // archive motion id -> id of the motion in this round
+        let mut motion_ids: HashMap<&str, String> = HashMap::new();
+        let mut debates_of_round = Vec::new();
+        let mut every_debate_has_ballots = true;
+        for (j, debate) in round.children("debate").enumerate() {
+            let archive_id = required(debate, "id")?;
+            let debate_id = Uuid::now_v7().to_string();
+            let room_id = match debate.attr("venue") {
+                Some(reference) if !reference.trim().is_empty() => {
+                    Some(resolve(&room_ids, "venue", reference.trim())?.clone())
+                }
+                _ => None,
+            };
+            diesel::insert_into(debates::table)
+                .values((
+                    debates::id.eq(&debate_id),
+                    debates::tournament_id.eq(tid),
+                    debates::round_id.eq(&round_id),
+                    debates::room_id.eq(room_id),
+                    debates::number.eq(j as i64 + 1),
+                    debates::status.eq("draft"),
+                ))
+                .execute(conn)?;
+
+            let motion_id = match debate.attr("motion") {
+                Some(reference) if !reference.trim().is_empty() => {
+                    let reference = reference.trim();
+                    let motion = resolve(&motions, "motion", reference)?;
+                    if !motion_ids.contains_key(reference) {
+                        let id = Uuid::now_v7().to_string();
+                        diesel::insert_into(motions_of_round::table)
+                            .values((
+                                motions_of_round::id.eq(&id),
+                                motions_of_round::tournament_id.eq(tid),
+                                motions_of_round::round_id.eq(&round_id),
+                                motions_of_round::motion.eq(motion.text.trim()),
+                                motions_of_round::infoslide.eq(motion
+                                    .child("info-slide")
+                                    .map(|slide| slide.text.trim())
+                                    .filter(|slide| !slide.is_empty())),
+                                motions_of_round::published_at
+                                    .eq(Some(Utc::now().naive_utc())),
+                            ))
+                            .execute(conn)?;
+                        motion_ids.insert(reference, id);
+                    }
+                    Some(motion_ids[reference].clone())
+                }
+                _ => None,
+            };
+
+            let adjudicators = debate.list_attr("adjudicators");
+            let chair = debate
+                .attr("chair")
+                .map(str::trim)
+                .or(adjudicators.first().copied());
+            let mut seen = HashSet::new();
+            for (judge, status) in adjudicators
+                .iter()
+                .map(|judge| {
+                    (*judge, if Some(*judge) == chair { "C" } else { "P" })
+                })
+                .chain(chair.map(|chair| (chair, "C")))
+                .chain(
+                    debate
+                        .list_attr("trainees")
+                        .into_iter()
+                        .map(|judge| (judge, "T")),
+                )
+            {
+                if !seen.insert(judge) {
+                    continue;
+                }
+                diesel::insert_into(judges_of_debate::table)
+                    .values((
+                        judges_of_debate::id.eq(Uuid::now_v7().to_string()),
+                        judges_of_debate::tournament_id.eq(tid),
+                        judges_of_debate::debate_id.eq(&debate_id),
+                        judges_of_debate::judge_id.eq(resolve(
+                            &judge_ids,
+                            "adjudicator",
+                            judge,
+                        )?),
+                        judges_of_debate::status.eq(status),
+                    ))
+                    .execute(conn)?;
+            }
+
+            let sides: Vec<&Element> = debate.children("side").collect();
+            if sides.len() as i64 != 2 * format.teams_per_side {
+                return invalid(format!(
+                    "debate {archive_id} has {} teams, but other debates have \
+                     {}",
+                    sides.len(),
+                    2 * format.teams_per_side
+                ));
+            }
+            let n_teams = sides.len() as i64;
+
+            let mut ballots: BTreeMap<&str, JudgeBallot> = BTreeMap::new();
+            for (k, side) in sides.iter().enumerate() {
+                let team_id =
+                    resolve(&team_ids, "team", required(side, "team")?)?;
+                diesel::insert_into(teams_of_debate::table)
+                    .values((
+                        teams_of_debate::id.eq(Uuid::now_v7().to_string()),
+                        teams_of_debate::tournament_id.eq(tid),
+                        teams_of_debate::debate_id.eq(&debate_id),
+                        teams_of_debate::team_id.eq(team_id),
+                        teams_of_debate::side.eq(k as i64 % 2),
+                        teams_of_debate::seq.eq(k as i64 / 2),
+                    ))
+                    .execute(conn)?;
+
+                for ballot in side.children("ballot") {
+                    if ballot.flag("ignored")
+                        || (is_consensus && ballot.flag("minority"))
+                    {
+                        continue;
+                    }
+                    let rank = match required(ballot, "rank")?.parse::<i64>() {
+                        Ok(rank) if (1..=n_teams).contains(&rank) => rank,
+                        _ => {
+                            return invalid(format!(
+                                "a ballot in debate {archive_id} has an \
+                                 invalid rank"
+                            ));
+                        }
+                    };
+                    for judge in ballot.list_attr("adjudicators") {
+                        resolve(&judge_ids, "adjudicator", judge)?;
+                        if !seen.contains(judge) {
+                            return invalid(format!(
+                                "{judge} has a ballot in debate {archive_id}, \
+                                 but is not on its panel"
+                            ));
+                        }
+                        ballots.entry(judge).or_default().points.insert(
+                            team_id.clone(),
+                            points_of_rank(rank, n_teams, is_elim),
+                        );
+                    }
+                }
+
+                let mut position = 0;
+                let mut substitutes_of_side = HashSet::new();
+                for speech in side.children("speech") {
+                    let speaker = required(speech, "speaker")?;
+                    let speaker_id = resolve(&speaker_ids, "speaker", speaker)?;
+                    if substitute_ids.contains(speaker)
+                        && substitutes_of_side.insert(speaker)
+                    {
+                        diesel::insert_into(
+                            substitute_speakers_of_debate::table,
+                        )
+                        .values((
+                            substitute_speakers_of_debate::id
+                                .eq(Uuid::now_v7().to_string()),
+                            substitute_speakers_of_debate::tournament_id
+                                .eq(tid),
+                            substitute_speakers_of_debate::debate_id
+                                .eq(&debate_id),
+                            substitute_speakers_of_debate::team_id.eq(team_id),
+                            substitute_speakers_of_debate::speaker_id
+                                .eq(speaker_id),
+                        ))
+                        .execute(conn)?;
+                    }
+                    let speech_position = if speech.flag("reply") {
+                        format.substantive_speakers
+                    } else {
+                        position += 1;
+                        position - 1
+                    };
+                    for ballot in speech.children("ballot") {
+                        let score = match ballot.text.trim() {
+                            "" => None,
+                            score => match score.parse::<f32>() {
+                                Ok(score) => Some(score),
+                                Err(_) => {
+                                    return invalid(format!(
+                                        "`{score}` (in debate {archive_id}) \
+                                         is not a valid speaker score"
+                                    ));
+                                }
+                            },
+                        };
+                        for judge in ballot.list_attr("adjudicators") {
+                            // only the speaks of judges whose team ballots
+                            // were imported are needed
+                            if let Some(ballot) = ballots.get_mut(judge) {
+                                ballot.scores.push((
+                                    team_id.clone(),
+                                    speaker_id.clone(),
+                                    speech_position,
+                                    score,
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+
+            if ballots.is_empty() {
+                every_debate_has_ballots = false;
+            } else {
+                let Some(motion_id) = &motion_id else {
+                    return invalid(format!(
+                        "debate {archive_id} has ballots, but no motion"
+                    ));
+                };
+                for (judge, judge_ballot) in ballots {
+                    if judge_ballot.points.len() as i64 != n_teams {
+                        return invalid(format!(
+                            "the ballot of {judge} in debate {archive_id} does \
+                             not rank every team"
+                        ));
+                    }
+                    if !judge_ballot.scores.is_empty() {
+                        for team_id in judge_ballot.points.keys() {
+                            let positions: HashSet<i64> = judge_ballot
+                                .scores
+                                .iter()
+                                .filter(|(team, ..)| team == team_id)
+                                .map(|(_, _, position, _)| *position)
+                                .collect();
+                            if !(0..format.substantive_speakers)
+                                .all(|position| positions.contains(&position))
+                            {
+                                return invalid(format!(
+                                    "the ballot of {judge} in debate \
+                                     {archive_id} is missing speeches"
+                                ));
+                            }
+                        }
+                    }
+
+                    let ballot_id = Uuid::now_v7().to_string();
+                    BallotRepr::new_prelim(
+                        BallotMetadata {
+                            id: ballot_id.clone(),
+                            tournament_id: tid.to_string(),
+                            debate_id: debate_id.clone(),
+                            judge_id: judge_ids[judge].clone(),
+                            submitted_at: Utc::now().naive_utc(),
+                            motion_id: motion_id.clone(),
+                            version: 0,
+                            change: Some("Imported from archive".to_string()),
+                            editor_id: None,
+                        },
+                        judge_ballot
+                            .scores
+                            .into_iter()
+                            .map(|(team_id, speaker_id, position, score)| {
+                                BallotScore {
+                                    id: Uuid::now_v7().to_string(),
+                                    tournament_id: tid.to_string(),
+                                    ballot_id: ballot_id.clone(),
+                                    team_id,
+                                    speaker_id,
+                                    speaker_position: position,
+                                    score,
+                                }
+                            })
+                            .collect(),
+                        judge_ballot
+                            .points
+                            .into_iter()
+                            .map(|(team_id, points)| BallotTeamRank {
+                                id: Uuid::now_v7().to_string(),
+                                tournament_id: tid.to_string(),
+                                ballot_id: ballot_id.clone(),
+                                team_id,
+                                points,
+                            })
+                            .collect(),
+                    )
+                    .insert(conn);
+                }
+            }
+
+            debate_ids.insert(archive_id, debate_id.clone());
+            debates_of_round.push((archive_id, debate_id));
+        }
+
+        if debates_of_round.is_empty() {
+            continue;
+        }
+        diesel::update(rounds::table.filter(rounds::id.eq(&round_id)))
+            .set((
+                rounds::draw_status.eq("released_full"),
+                rounds::draw_released_at.eq(Some(Utc::now().naive_utc())),
+            ))
+            .execute(conn)?;
+
+        if every_debate_has_ballots {
+            for (archive_id, debate_id) in &debates_of_round {
+                let debate = DebateRepr::fetch(debate_id, conn);
+                let ballots = debate.latest_ballots(conn);
+                let problems = if is_consensus {
+                    BallotRepr::problems_of_set(&ballots, &tournament, &debate)
+                } else {
+                    Vec::new()
+                };
+                if !problems.is_empty() {
+                    return invalid(format!(
+                        "the ballots of debate {archive_id} ({round_name}) \
+                         disagree: {}",
+                        problems.join(" ")
+                    ));
+                }
+                aggregate_ballot_set(&ballots, &tournament, &debate, conn);
+                diesel::update(
+                    debates::table.filter(debates::id.eq(debate_id)),
+                )
+                .set(debates::status.eq("confirmed"))
+                .execute(conn)?;
+            }
+            diesel::update(rounds::table.filter(rounds::id.eq(&round_id)))
+                .set(rounds::completed.eq(true))
+                .execute(conn)?;
+        }
+    }
+
+    let mut questions = HashMap::new();
+    for (i, question) in root.children("question").enumerate() {
+        let id = Uuid::now_v7().to_string();
+        let kind = question_kind(question);
+        let text = match question.text.trim() {
+            "" => required(question, "name")?,
+            text => text,
+        };
+        diesel::insert_into(feedback_questions::table)
+            .values((
+                feedback_questions::id.eq(&id),
+                feedback_questions::tournament_id.eq(tid),
+                feedback_questions::question.eq(text),
+                feedback_questions::kind
+                    .eq(serde_json::to_string(&kind).unwrap()),
+                feedback_questions::seq.eq(i as i64),
+                feedback_questions::for_judges.eq(question.flag("from-adj")),
+                feedback_questions::for_teams.eq(question.flag("from-team")),
+                feedback_questions::required.eq(question.flag("required")),
+                feedback_questions::paths.eq(None::<String>),
+            ))
+            .execute(conn)?;
+        questions.insert(required(question, "id")?, (id, kind));
+    }
+
+    for adjudicator in participants.children("adjudicator") {
+        let target_judge_id = &judge_ids[required(adjudicator, "id")?];
+        for feedback in adjudicator.children("feedback") {
+            let feedback_id = Uuid::now_v7().to_string();
+            let debate_id =
+                resolve(&debate_ids, "debate", required(feedback, "debate")?)?
+                    .clone();
+            let from_team = match (
+                feedback.attr("source-adjudicator"),
+                feedback.attr("source-team"),
+            ) {
+                (Some(judge), None) => {
+                    diesel::insert_into(feedback_of_judges::table)
+                        .values(FeedbackOfJudge {
+                            id: feedback_id.clone(),
+                            tournament_id: tid.to_string(),
+                            debate_id,
+                            judge_id: resolve(
+                                &judge_ids,
+                                "adjudicator",
+                                judge,
+                            )?
+                            .clone(),
+                            target_judge_id: target_judge_id.clone(),
+                        })
+                        .execute(conn)?;
+                    false
+                }
+                (None, Some(team)) => {
+                    diesel::insert_into(feedback_of_teams::table)
+                        .values(FeedbackOfTeam {
+                            id: feedback_id.clone(),
+                            tournament_id: tid.to_string(),
+                            debate_id,
+                            team_id: resolve(&team_ids, "team", team)?.clone(),
+                            target_judge_id: target_judge_id.clone(),
+                        })
+                        .execute(conn)?;
+                    true
+                }
+                _ => {
+                    return invalid(format!(
+                        "feedback on {} must have exactly one source",
+                        required(adjudicator, "name")?
+                    ));
+                }
+            };
+
+            for answer in feedback.children("answer") {
+                let (question_id, kind) = resolve(
+                    &questions,
+                    "question",
+                    required(answer, "question")?,
+                )?;
+                let answer = match kind
+                    .parse_answer(&answer_values(kind, &answer.text))
+                {
+                    Ok(Some(answer)) => answer,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        return invalid(format!(
+                            "feedback on {}: {e}",
+                            required(adjudicator, "name")?
+                        ));
+                    }
+                };
+                if from_team {
+                    diesel::insert_into(answers_of_feedback_from_teams::table)
+                        .values(FeedbackFromTeamsQuestionAnswer {
+                            id: Uuid::now_v7().to_string(),
+                            tournament_id: tid.to_string(),
+                            feedback_id: feedback_id.clone(),
+                            question_id: question_id.clone(),
+                            answer,
+                        })
+                        .execute(conn)?;
+                } else {
+                    diesel::insert_into(answers_of_feedback_from_judges::table)
+                        .values(FeedbackFromJudgesQuestionAnswer {
+                            id: Uuid::now_v7().to_string(),
+                            tournament_id: tid.to_string(),
+                            feedback_id: feedback_id.clone(),
+                            question_id: question_id.clone(),
+                            answer,
+                        })
+                        .execute(conn)?;
+                }
+            }
+        }
+    }
+
+    refresh_saved_team_standings(tid, conn)?;
+    refresh_saved_speaker_standings(tid, conn)?;
+
+    Ok(())
+}
+
+pub async fn import_archive_page(user: User<true>) -> SuccessResponse {
+    SuccessResponse::Success(
+        Page::new()
+            .user(user)
+            .body(maud! {
+                h1 { "Import a tournament" }
+                p class="text-muted" {
+                    "Create a tournament from a Debate XML archive (for example, "
+                    "one exported from Tabbycat). Rounds in which every debate "
+                    "has a ballot are imported as completed rounds."
+                }
+                form method="post" {
+                    div class="mb-3" {
+                        label for="archiveName" class="form-label" {
+                            "Tournament name"
+                        }
+                        input type="text" class="form-control" id="archiveName" name="name" maxlength="32";
+                        div class="form-text" {
+                            "Leave blank to use the name in the archive."
+                        }
+                    }
+                    div class="mb-3" {
+                        label for="archiveAbbrv" class="form-label" {
+                            "Tournament abbreviation"
+                        }
+                        input type="text" class="form-control" id="archiveAbbrv" name="abbrv" maxlength="8";
+                        div class="form-text" {
+                            "Leave blank to use the abbreviation in the archive."
+                        }
+                    }
+                    div class="mb-3" {
+                        label for="archiveSlug" class="form-label" {
+                            "Tournament slug"
+                        }
+                        input type="text" class="form-control" id="archiveSlug" name="slug" required pattern="[a-zA-Z0-9]+";
+                    }
+                    div class="mb-3" {
+                        label for="archive" class="form-label" { "Archive" }
+                        textarea class="form-control font-monospace" id="archive" name="archive" rows="12" required {}
+                    }
+                    button type="submit" class="btn btn-primary" { "Import" }
+                }
+            })
+            .render(),
+    )
+}
+
+#[derive(Deserialize)]
+pub struct ImportArchiveForm {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    abbrv: String,
+    slug: String,
+    archive: String,
+}
+
+pub async fn do_import_archive(
+    user: User<true>,
+    mut conn: Conn<true>,
+    Form(form): Form<ImportArchiveForm>,
+) -> StandardResponse {
+    let root = match xml::parse(&form.archive) {
+        Ok(root) => root,
+        Err(e) => {
+            return bad_request(
+                maud! { p { "Error: the archive is not valid XML (" (e) ")." } }
+                    .render(),
+            );
+        }
+    };
+
+    let or_archive = |value: &str, attr: &str| match value.trim() {
+        "" => root.attr(attr).unwrap_or_default().trim().to_string(),
+        value => value.to_string(),
+    };
+    let name = or_archive(&form.name, "name");
+    let abbrv = or_archive(&form.abbrv, "short");
+    if let Err(e) = check_tournament_details(&name, &abbrv, &form.slug) {
+        return bad_request(maud! { p { (e) } }.render());
+    }
+
+    let tid = Uuid::now_v7().to_string();
+    let res = conn.transaction::<_, ArchiveError, _>(|conn| {
+        insert_tournament(&tid, &name, &abbrv, &form.slug, &user.id, conn)?;
+        import_archive(&root, &tid, conn)
+    });
+
+    match res {
+        Ok(()) => see_other_ok(Redirect::to(&format!("/tournaments/{tid}"))),
+        Err(ArchiveError::Invalid(e)) => bad_request(
+            maud! { p { "Error: the archive could not be imported: " (e) } }
+                .render(),
+        ),
+        Err(ArchiveError::Database(diesel::result::Error::DatabaseError(
+            DatabaseErrorKind::UniqueViolation,
+            _,
+        ))) => {
+            bad_request(maud! { p { "Tournament already exists." } }.render())
+        }
+        Err(ArchiveError::Database(e)) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_format_is_inferred_from_debates() {
+    let archive = |sides: usize, speeches: &str| {
+        let side = format!("<side team=\"T1\">{speeches}</side>");
+        xml::parse(&format!(
+            "<tournament style=\"bp\"><round name=\"R1\"><debate id=\"D1\">\
+             {}</debate></round></tournament>",
+            side.repeat(sides)
+        ))
+        .unwrap()
+    };
+
+    assert_eq!(
+        format_of(&archive(
+            2,
+            "<speech speaker=\"S1\"/><speech speaker=\"S2\"/>\
+             <speech speaker=\"S3\"/><speech speaker=\"S1\" reply=\"true\"/>"
+        )),
+        Format {
+            teams_per_side: 1,
+            substantive_speakers: 3,
+            reply_speakers: true,
+        }
+    );
+    assert_eq!(
+        format_of(&archive(4, "")),
+        Format {
+            teams_per_side: 2,
+            substantive_speakers: 2,
+            reply_speakers: false,
+        }
+    );
+
+    assert_eq!(points_of_rank(1, 4, false), 3);
+    assert_eq!(points_of_rank(2, 4, true), 1);
+    assert_eq!(points_of_rank(3, 4, true), 0);
+    assert_eq!(points_of_rank(2, 2, false), 0);
+}
+
+#[cfg(test)]
+#[test]
+fn test_voting_panels_are_imported_as_individual_ballots() {
+    use diesel_migrations::MigrationHarness;
+
+    use crate::schema::{agg_team_results_of_debate, ballots};
+
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+    conn.run_pending_migrations(crate::MIGRATIONS).unwrap();
+    insert_tournament("t", "Voting Open", "VO", "voting-open", "u", &mut conn)
+        .unwrap();
+
+    // A1 and A2 vote for Alpha, and A3 (in the minority) for Beta
+    let root = xml::parse(
+        "<tournament name=\"Voting Open\" style=\"ap\">\
+         <round name=\"Round 1\">\
+         <debate id=\"D1\" adjudicators=\"A1 A2 A3\" chair=\"A1\" \
+          motion=\"M1\">\
+         <side team=\"T1\">\
+         <ballot adjudicators=\"A1 A2\" rank=\"1\"/>\
+         <ballot adjudicators=\"A3\" rank=\"2\" minority=\"true\"/>\
+         <speech speaker=\"S1\"><ballot adjudicators=\"A1\">76</ballot>\
+         <ballot adjudicators=\"A2\">75</ballot>\
+         <ballot adjudicators=\"A3\">74</ballot></speech>\
+         </side>\
+         <side team=\"T2\">\
+         <ballot adjudicators=\"A1 A2\" rank=\"2\"/>\
+         <ballot adjudicators=\"A3\" rank=\"1\" minority=\"true\"/>\
+         <speech speaker=\"S2\"><ballot adjudicators=\"A1\">74</ballot>\
+         <ballot adjudicators=\"A2\">73</ballot>\
+         <ballot adjudicators=\"A3\">77</ballot></speech>\
+         </side>\
+         </debate>\
+         </round>\
+         <participants>\
+         <team id=\"T1\" name=\"Alpha\"><speaker id=\"S1\">Ann</speaker></team>\
+         <team id=\"T2\" name=\"Beta\"><speaker id=\"S2\">Ben</speaker></team>\
+         <adjudicator id=\"A1\" name=\"Cat\"/>\
+         <adjudicator id=\"A2\" name=\"Dan\"/>\
+         <adjudicator id=\"A3\" name=\"Eve\"/>\
+         </participants>\
+         <motion id=\"M1\">This House would vote</motion>\
+         </tournament>",
+    )
+    .unwrap();
+    import_archive(&root, "t", &mut conn).unwrap();
+
+    let tournament = tournaments::table
+        .filter(tournaments::id.eq("t"))
+        .first::<Tournament>(&mut conn)
+        .unwrap();
+    assert_eq!(tournament.pool_ballot_setup, "individual");
+    assert_eq!(tournament.elim_ballot_setup, "consensus");
+
+    let n_ballots: i64 = ballots::table.count().get_result(&mut conn).unwrap();
+    assert_eq!(n_ballots, 3);
+    let points_of = |name: &str, conn: &mut SqliteConnection| {
+        agg_team_results_of_debate::table
+            .inner_join(teams::table)
+            .filter(teams::name.eq(name))
+            .select(agg_team_results_of_debate::points)
+            .first::<Option<i64>>(conn)
+            .unwrap()
+    };
+    assert_eq!(points_of("Alpha", &mut conn), Some(1));
+    assert_eq!(points_of("Beta", &mut conn), Some(0));
+}
@@ -0,0 +1,11 @@
+//! Import and export of tournaments as Debate XML archives, the format which
+//! Tabbycat (and other tab software) uses to move tournaments between
+//! systems.
+//!
+//! Archives refer to participants, rooms, motions and so on by ids which are
+//! local to the archive (e.g. `T1` for the first team), rather than by the ids
+//! used in the database.
+
+pub mod export;
+pub mod import;
+pub mod xml;
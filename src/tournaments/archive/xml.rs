@@ -0,0 +1,265 @@
+//! A simple element tree for Debate XML archives, read with `quick-xml`.
+//!
+//! Only elements, attributes and text are kept. Comments, processing
+//! instructions and doctypes are skipped when reading, and the text of an
+//! element with mixed content is concatenated (archives only ever place text
+//! before the children of an element, e.g. a motion and its info slide).
+
+use std::fmt::{Display, Write};
+
+use quick_xml::{
+    escape::escape,
+    events::{BytesStart, Event},
+    reader::Reader,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Element {
+    pub name: String,
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    pub text: String,
+}
+
+impl Element {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            attrs: Vec::new(),
+            children: Vec::new(),
+            text: String::new(),
+        }
+    }
+
+    pub fn with_attr(mut self, name: &str, value: impl ToString) -> Self {
+        self.attrs.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.text = text.to_string();
+        self
+    }
+
+    pub fn with_child(mut self, child: Element) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    pub fn push(&mut self, child: Element) {
+        self.children.push(child);
+    }
+
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// An attribute which holds a space-separated list (e.g. the
+    /// `adjudicators` of a debate).
+    pub fn list_attr(&self, name: &str) -> Vec<&str> {
+        self.attr(name)
+            .map(|value| value.split_whitespace().collect())
+            .unwrap_or_default()
+    }
+
+    /// A boolean attribute, which is false if absent.
+    pub fn flag(&self, name: &str) -> bool {
+        self.attr(name)
+            .is_some_and(|value| value.trim().eq_ignore_ascii_case("true"))
+    }
+
+    pub fn children<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// Writes this element as a complete XML document.
+    pub fn to_document(&self) -> String {
+        let mut out =
+            String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        self.write_to(&mut out, 0);
+        out
+    }
+
+    fn write_to(&self, out: &mut String, depth: usize) {
+        let indent = "  ".repeat(depth);
+        write!(out, "{indent}<{}", self.name).unwrap();
+        for (name, value) in &self.attrs {
+            write!(out, " {name}=\"{}\"", escape(value.as_str())).unwrap();
+        }
+        if self.children.is_empty() && self.text.is_empty() {
+            out.push_str("/>\n");
+            return;
+        }
+        out.push('>');
+        out.push_str(&escape(self.text.as_str()));
+        if !self.children.is_empty() {
+            out.push('\n');
+            for child in &self.children {
+                child.write_to(out, depth + 1);
+            }
+            out.push_str(&indent);
+        }
+        writeln!(out, "</{}>", self.name).unwrap();
+    }
+}
+
+/// The deepest nesting of elements which will be parsed. Debate XML archives
+/// are only a few levels deep, so anything deeper is certainly not one.
+const MAX_DEPTH: usize = 64;
+
+/// Parses an XML document, returning its root element.
+pub fn parse(input: &str) -> Result<Element, String> {
+    let mut reader = Reader::from_str(input);
+    let error = |reader: &Reader<&[u8]>, msg: &dyn Display| {
+        let pos = (reader.buffer_position() as usize).min(input.len());
+        let line = input.as_bytes()[..pos]
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count()
+            + 1;
+        format!("line {line}: {msg}")
+    };
+
+    // the elements which have been opened but not yet closed, outermost first
+    let mut open: Vec<Element> = Vec::new();
+    let mut root = None;
+    loop {
+        let event = reader.read_event().map_err(|e| error(&reader, &e))?;
+        // self-closing tags are opened and closed at once
+        let self_closing = matches!(event, Event::Empty(_));
+        match event {
+            Event::Start(start) | Event::Empty(start) => {
+                if root.is_some() {
+                    return Err(error(
+                        &reader,
+                        &"unexpected content after the root element",
+                    ));
+                }
+                if open.len() >= MAX_DEPTH {
+                    return Err(error(
+                        &reader,
+                        &format!(
+                            "elements are nested more than {MAX_DEPTH} levels deep"
+                        ),
+                    ));
+                }
+                let element =
+                    element_of(&start).map_err(|e| error(&reader, &e))?;
+                if self_closing {
+                    close(element, &mut open, &mut root);
+                } else {
+                    open.push(element);
+                }
+            }
+            Event::End(_) => {
+                // quick-xml has already checked that the names match
+                let mut element = open.pop().unwrap();
+                element.text = element.text.trim().to_string();
+                close(element, &mut open, &mut root);
+            }
+            Event::Text(text) => {
+                let text = text.unescape().map_err(|e| error(&reader, &e))?;
+                match open.last_mut() {
+                    Some(element) => element.text.push_str(&text),
+                    None if text.trim().is_empty() => {}
+                    None => {
+                        return Err(error(
+                            &reader,
+                            &"unexpected text outside the root element",
+                        ));
+                    }
+                }
+            }
+            Event::CData(cdata) => {
+                let Some(element) = open.last_mut() else {
+                    return Err(error(
+                        &reader,
+                        &"unexpected CDATA outside the root element",
+                    ));
+                };
+                element.text.push_str(
+                    std::str::from_utf8(&cdata)
+                        .map_err(|e| error(&reader, &e))?,
+                );
+            }
+            Event::Eof => {
+                return match (open.last(), root) {
+                    (Some(element), _) => Err(error(
+                        &reader,
+                        &format!("missing `</{}>`", element.name),
+                    )),
+                    (None, Some(root)) => Ok(root),
+                    (None, None) => Err(error(&reader, &"no root element")),
+                };
+            }
+            Event::Comment(_)
+            | Event::Decl(_)
+            | Event::PI(_)
+            | Event::DocType(_) => {}
+        }
+    }
+}
+
+/// Converts an opening tag into an (as yet childless) element.
+fn element_of(start: &BytesStart) -> Result<Element, String> {
+    let name = std::str::from_utf8(start.name().as_ref())
+        .map_err(|e| e.to_string())?;
+    let mut element = Element::new(name);
+    for attr in start.attributes() {
+        let attr = attr.map_err(|e| e.to_string())?;
+        let key = std::str::from_utf8(attr.key.as_ref())
+            .map_err(|e| e.to_string())?;
+        let value = attr.unescape_value().map_err(|e| e.to_string())?;
+        element.attrs.push((key.to_string(), value.into_owned()));
+    }
+    Ok(element)
+}
+
+/// Attaches a closed element to its parent (or makes it the root, if it has
+/// no parent).
+fn close(element: Element, open: &mut [Element], root: &mut Option<Element>) {
+    match open.last_mut() {
+        Some(parent) => parent.children.push(element),
+        None => *root = Some(element),
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_xml_round_trips() {
+    let element = Element::new("tournament")
+        .with_attr("name", "Q&A \"Open\"")
+        .with_child(
+            Element::new("motion")
+                .with_attr("id", "M1")
+                .with_text("This House would <not> panic")
+                .with_child(Element::new("info-slide").with_text("Ünïcode")),
+        )
+        .with_child(Element::new("venue").with_attr("id", "V1"));
+
+    assert_eq!(parse(&element.to_document()).unwrap(), element);
+
+    let parsed = parse(
+        "<?xml version=\"1.0\"?>\n<!-- archive -->\n<a x='1 2'>one &#38; \
+         <![CDATA[<two>]]><b/></a>",
+    )
+    .unwrap();
+    assert_eq!(parsed.list_attr("x"), vec!["1", "2"]);
+    assert_eq!(parsed.text, "one & <two>");
+    assert!(parsed.child("b").is_some());
+
+    assert!(parse("<a><b></a>").is_err());
+
+    let nested = "<a>".repeat(100_000) + &"</a>".repeat(100_000);
+    assert!(parse(&nested).unwrap_err().contains("nested"));
+}
@@ -0,0 +1,729 @@
+//! Management of speaker, team and break categories.
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Form, Path},
+    response::Redirect,
+};
+use diesel::{prelude::*, result::DatabaseErrorKind};
+use hypertext::prelude::*;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    auth::User,
+    schema::{
+        break_categories, speaker_categories, speakers, speakers_of_category,
+        team_categories,
+    },
+    state::Conn,
+    template::Page,
+    tournaments::{
+        Tournament,
+        categories::{
+            BreakCategory, SpeakerCategory, TeamCategory,
+            speaker::{STANDARD_SPEAKER_CATEGORIES, categories_of_speakers},
+            team::eligible_teams_in_rank_order,
+        },
+        manage::sidebar::SidebarWrapper,
+        participants::{Speaker, TournamentParticipants},
+        rounds::TournamentRounds,
+        standings::compute::TeamStandings,
+    },
+    util_resp::{
+        StandardResponse, bad_request, err_not_found, see_other_ok, success,
+    },
+};
+
+fn redirect_to_categories(tournament_id: &str) -> StandardResponse {
+    see_other_ok(Redirect::to(&format!(
+        "/tournaments/{tournament_id}/categories"
+    )))
+}
+
+fn validate_category_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() || name.len() > 64 {
+        return Err(
+            "Error: the name must be between 1 and 64 characters long."
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+pub async fn manage_categories_page(
+    Path(tournament_id): Path<String>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let rounds = TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+
+    let speaker_categories =
+        SpeakerCategory::of_tournament(&tournament.id, &mut *conn);
+    let speaker_counts: HashMap<String, usize> = speaker_categories
+        .iter()
+        .map(|c| (c.id.clone(), c.speaker_ids(&mut *conn).len()))
+        .collect();
+
+    let team_categories =
+        TeamCategory::of_tournament(&tournament.id, &mut *conn);
+    let team_counts: HashMap<String, usize> = team_categories
+        .iter()
+        .map(|c| (c.id.clone(), c.team_ids(&mut *conn).len()))
+        .collect();
+
+    let break_categories = break_categories::table
+        .filter(break_categories::tournament_id.eq(&tournament.id))
+        .order_by(break_categories::priority.asc())
+        .load::<BreakCategory>(&mut *conn)
+        .unwrap();
+
+    let missing_standard = STANDARD_SPEAKER_CATEGORIES
+        .iter()
+        .any(|name| !speaker_categories.iter().any(|c| c.name == *name));
+
+    let name_of_speaker_category = |id: &str| {
+        speaker_categories
+            .iter()
+            .find(|c| c.id == id)
+            .map(|c| c.name.clone())
+            .unwrap_or_default()
+    };
+
+    success(
+        Page::new()
+            .user(user)
+            .tournament(tournament.clone())
+            .body(maud! {
+                SidebarWrapper tournament=(&tournament) rounds=(&rounds) active_page=(None) selected_seq=(None) {
+                    h1 { "Categories" }
+
+                    h2 class="h4 mt-4" { "Speaker categories" }
+                    p class="text-muted" {
+                        "Speakers can be placed in any number of categories. "
+                        a href=(format!("/tournaments/{}/categories/speakers", tournament.id)) {
+                            "Assign speakers to categories"
+                        }
+                    }
+                    table class="table" {
+                        thead {
+                            tr {
+                                th { "Name" }
+                                th { "Speakers" }
+                                th {}
+                            }
+                        }
+                        tbody {
+                            @for category in &speaker_categories {
+                                tr {
+                                    td { (category.name) }
+                                    td { (speaker_counts[&category.id]) }
+                                    td class="text-end" {
+                                        a href=(format!("/tournaments/{}/tab/speaker/{}", tournament.id, category.id)) class="btn btn-sm btn-outline-secondary me-2" {
+                                            "Speaker tab"
+                                        }
+                                        form method="post" class="d-inline" action=(format!("/tournaments/{}/categories/speaker/{}/delete", tournament.id, category.id)) {
+                                            button type="submit" class="btn btn-sm btn-outline-danger" { "Delete" }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    div class="d-flex gap-3 align-items-end mb-5" {
+                        form method="post" action=(format!("/tournaments/{}/categories/speaker", tournament.id)) class="d-flex gap-2" {
+                            input type="text" class="form-control" name="name" placeholder="Category name" required;
+                            button type="submit" class="btn btn-primary" { "Add" }
+                        }
+                        @if missing_standard {
+                            form method="post" action=(format!("/tournaments/{}/categories/speaker/standard", tournament.id)) {
+                                button type="submit" class="btn btn-outline-primary" {
+                                    "Add ESL, EFL and Novice"
+                                }
+                            }
+                        }
+                    }
+
+                    h2 class="h4" { "Team categories" }
+                    p class="text-muted" {
+                        "A team is placed in a team category based on the "
+                        "categories of its speakers."
+                    }
+                    table class="table" {
+                        thead {
+                            tr {
+                                th { "Name" }
+                                th { "Rule" }
+                                th { "Teams" }
+                                th {}
+                            }
+                        }
+                        tbody {
+                            @for category in &team_categories {
+                                tr {
+                                    td { (category.name) }
+                                    td {
+                                        (category.rule_description()) " "
+                                        (name_of_speaker_category(&category.speaker_category_id))
+                                    }
+                                    td { (team_counts[&category.id]) }
+                                    td class="text-end" {
+                                        a href=(format!("/tournaments/{}/tab/team/{}", tournament.id, category.id)) class="btn btn-sm btn-outline-secondary me-2" {
+                                            "Team tab"
+                                        }
+                                        form method="post" class="d-inline" action=(format!("/tournaments/{}/categories/team/{}/delete", tournament.id, category.id)) {
+                                            button type="submit" class="btn btn-sm btn-outline-danger" { "Delete" }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    @if speaker_categories.is_empty() {
+                        p class="text-muted mb-5" { "Add a speaker category to create team categories." }
+                    } @else {
+                        form method="post" action=(format!("/tournaments/{}/categories/team", tournament.id)) class="row g-2 mb-5" {
+                            div class="col-md-4" {
+                                input type="text" class="form-control" name="name" placeholder="Category name" required;
+                            }
+                            div class="col-md-3" {
+                                select class="form-select" name="rule" {
+                                    option value="all" { "every speaker is" }
+                                    option value="any" { "at least one speaker is" }
+                                }
+                            }
+                            div class="col-md-3" {
+                                select class="form-select" name="speaker_category_id" {
+                                    @for category in &speaker_categories {
+                                        option value=(category.id) { (category.name) }
+                                    }
+                                }
+                            }
+                            div class="col-md-2" {
+                                button type="submit" class="btn btn-primary w-100" { "Add" }
+                            }
+                        }
+                    }
+
+                    h2 class="h4" { "Break categories" }
+                    table class="table" {
+                        thead {
+                            tr {
+                                th { "Name" }
+                                th { "Priority" }
+                                th { "Eligible teams" }
+                                th {}
+                            }
+                        }
+                        tbody {
+                            @for category in &break_categories {
+                                tr {
+                                    td { (category.name) }
+                                    td { (category.priority) }
+                                    td {
+                                        form method="post" class="d-flex gap-2" action=(format!("/tournaments/{}/categories/break/{}/eligibility", tournament.id, category.id)) {
+                                            select class="form-select form-select-sm" name="team_category_id" {
+                                                option value="" selected[category.team_category_id.is_none()] { "All teams" }
+                                                @for team_category in &team_categories {
+                                                    option value=(team_category.id) selected[category.team_category_id.as_deref() == Some(team_category.id.as_str())] {
+                                                        (team_category.name) " teams"
+                                                    }
+                                                }
+                                            }
+                                            button type="submit" class="btn btn-sm btn-outline-primary" { "Save" }
+                                        }
+                                    }
+                                    td class="text-end" {
+                                        a href=(format!("/tournaments/{}/categories/break/{}", tournament.id, category.id)) class="btn btn-sm btn-outline-secondary" {
+                                            "Eligible teams"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    form method="post" action=(format!("/tournaments/{}/categories/break", tournament.id)) class="row g-2" {
+                        div class="col-md-4" {
+                            input type="text" class="form-control" name="name" placeholder="Category name" required;
+                        }
+                        div class="col-md-2" {
+                            input type="number" class="form-control" name="priority" placeholder="Priority" value=(break_categories.len()) required;
+                        }
+                        div class="col-md-4" {
+                            select class="form-select" name="team_category_id" {
+                                option value="" { "All teams" }
+                                @for team_category in &team_categories {
+                                    option value=(team_category.id) { (team_category.name) " teams" }
+                                }
+                            }
+                        }
+                        div class="col-md-2" {
+                            button type="submit" class="btn btn-primary w-100" { "Add" }
+                        }
+                    }
+                }
+            })
+            .render(),
+    )
+}
+
+#[derive(Deserialize)]
+pub struct CategoryNameForm {
+    name: String,
+}
+
+fn insert_speaker_category(
+    tournament_id: &str,
+    name: &str,
+    conn: &mut SqliteConnection,
+) -> Result<(), diesel::result::Error> {
+    diesel::insert_into(speaker_categories::table)
+        .values((
+            speaker_categories::id.eq(Uuid::now_v7().to_string()),
+            speaker_categories::tournament_id.eq(tournament_id),
+            speaker_categories::name.eq(name.trim()),
+        ))
+        .execute(conn)
+        .map(|n| assert_eq!(n, 1))
+}
+
+pub async fn do_create_speaker_category(
+    Path(tournament_id): Path<String>,
+    user: User<true>,
+    mut conn: Conn<true>,
+    Form(form): Form<CategoryNameForm>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+
+    if let Err(e) = validate_category_name(&form.name) {
+        return bad_request(maud! { (e) }.render());
+    }
+
+    match insert_speaker_category(&tournament.id, &form.name, &mut *conn) {
+        Ok(()) => redirect_to_categories(&tournament.id),
+        Err(diesel::result::Error::DatabaseError(
+            DatabaseErrorKind::UniqueViolation,
+            _,
+        )) => bad_request(
+            maud! { "Error: a speaker category with that name already exists." }
+                .render(),
+        ),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Adds whichever of [`STANDARD_SPEAKER_CATEGORIES`] do not already exist.
+pub async fn do_create_standard_speaker_categories(
+    Path(tournament_id): Path<String>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+
+    let existing = SpeakerCategory::of_tournament(&tournament.id, &mut *conn);
+    for name in STANDARD_SPEAKER_CATEGORIES {
+        if !existing.iter().any(|c| c.name == name) {
+            insert_speaker_category(&tournament.id, name, &mut *conn)?;
+        }
+    }
+
+    redirect_to_categories(&tournament.id)
+}
+
+pub async fn do_delete_speaker_category(
+    Path((tournament_id, category_id)): Path<(String, String)>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let category =
+        SpeakerCategory::fetch(&category_id, &tournament.id, &mut *conn)?;
+
+    let used_by_team_category = diesel::dsl::select(diesel::dsl::exists(
+        team_categories::table
+            .filter(team_categories::speaker_category_id.eq(&category.id)),
+    ))
+    .get_result::<bool>(&mut *conn)?;
+    if used_by_team_category {
+        return bad_request(
+            maud! {
+                "Error: " (category.name) " is used by a team category. "
+                "Delete the team category first."
+            }
+            .render(),
+        );
+    }
+
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        diesel::delete(
+            speakers_of_category::table
+                .filter(speakers_of_category::category_id.eq(&category.id)),
+        )
+        .execute(conn)?;
+        diesel::delete(speaker_categories::table.find(&category.id))
+            .execute(conn)
+    })?;
+
+    redirect_to_categories(&tournament.id)
+}
+
+/// Lists every speaker, along with buttons to add them to (or remove them
+/// from) each speaker category.
+pub async fn assign_speaker_categories_page(
+    Path(tournament_id): Path<String>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let rounds = TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+
+    let categories = SpeakerCategory::of_tournament(&tournament.id, &mut *conn);
+    let categories_of_speakers =
+        categories_of_speakers(&tournament.id, &mut *conn);
+    let speakers = speakers::table
+        .filter(speakers::tournament_id.eq(&tournament.id))
+        .order_by(speakers::name.asc())
+        .load::<Speaker>(&mut *conn)?;
+
+    success(
+        Page::new()
+            .user(user)
+            .tournament(tournament.clone())
+            .body(maud! {
+                SidebarWrapper tournament=(&tournament) rounds=(&rounds) active_page=(None) selected_seq=(None) {
+                    a href=(format!("/tournaments/{}/categories", tournament.id)) class="btn btn-sm btn-outline-secondary mb-3" {
+                        "← Back to Categories"
+                    }
+                    h1 { "Speaker categories" }
+                    @if categories.is_empty() {
+                        p class="text-muted" { "No speaker categories have been created yet." }
+                    } @else {
+                        table class="table table-sm align-middle" {
+                            thead {
+                                tr {
+                                    th { "Speaker" }
+                                    @for category in &categories {
+                                        th class="text-center" { (category.name) }
+                                    }
+                                }
+                            }
+                            tbody {
+                                @for speaker in &speakers {
+                                    tr {
+                                        td { (speaker.name) }
+                                        @for category in &categories {
+                                            @let in_category = categories_of_speakers
+                                                .get(&speaker.id)
+                                                .is_some_and(|c| c.contains(&category.id));
+                                            td class="text-center" {
+                                                form method="post" action=(format!("/tournaments/{}/categories/speakers/{}/{}/toggle", tournament.id, speaker.id, category.id)) {
+                                                    @if in_category {
+                                                        button type="submit" class="btn btn-sm btn-success" title="Remove from category" { "✓" }
+                                                    } @else {
+                                                        button type="submit" class="btn btn-sm btn-outline-secondary" title="Add to category" { "–" }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+            .render(),
+    )
+}
+
+pub async fn do_toggle_speaker_category(
+    Path((tournament_id, speaker_id, category_id)): Path<(
+        String,
+        String,
+        String,
+    )>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let category =
+        SpeakerCategory::fetch(&category_id, &tournament.id, &mut *conn)?;
+
+    let speaker_exists = diesel::dsl::select(diesel::dsl::exists(
+        speakers::table
+            .filter(speakers::id.eq(&speaker_id))
+            .filter(speakers::tournament_id.eq(&tournament.id)),
+    ))
+    .get_result::<bool>(&mut *conn)?;
+    if !speaker_exists {
+        return err_not_found();
+    }
+
+    let removed = diesel::delete(
+        speakers_of_category::table
+            .filter(speakers_of_category::category_id.eq(&category.id))
+            .filter(speakers_of_category::speaker_id.eq(&speaker_id)),
+    )
+    .execute(&mut *conn)?;
+
+    if removed == 0 {
+        diesel::insert_into(speakers_of_category::table)
+            .values((
+                speakers_of_category::id.eq(Uuid::now_v7().to_string()),
+                speakers_of_category::tournament_id.eq(&tournament.id),
+                speakers_of_category::category_id.eq(&category.id),
+                speakers_of_category::speaker_id.eq(&speaker_id),
+            ))
+            .execute(&mut *conn)?;
+    }
+
+    see_other_ok(Redirect::to(&format!(
+        "/tournaments/{}/categories/speakers",
+        tournament.id
+    )))
+}
+
+#[derive(Deserialize)]
+pub struct CreateTeamCategoryForm {
+    name: String,
+    speaker_category_id: String,
+    rule: String,
+}
+
+pub async fn do_create_team_category(
+    Path(tournament_id): Path<String>,
+    user: User<true>,
+    mut conn: Conn<true>,
+    Form(form): Form<CreateTeamCategoryForm>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+
+    if let Err(e) = validate_category_name(&form.name) {
+        return bad_request(maud! { (e) }.render());
+    }
+    if !["all", "any"].contains(&form.rule.as_str()) {
+        return bad_request(
+            maud! { "Error: the rule should be one of 'all' or 'any'." }
+                .render(),
+        );
+    }
+    let speaker_category = SpeakerCategory::fetch(
+        &form.speaker_category_id,
+        &tournament.id,
+        &mut *conn,
+    )?;
+
+    let res = diesel::insert_into(team_categories::table)
+        .values((
+            team_categories::id.eq(Uuid::now_v7().to_string()),
+            team_categories::tournament_id.eq(&tournament.id),
+            team_categories::name.eq(form.name.trim()),
+            team_categories::speaker_category_id.eq(&speaker_category.id),
+            team_categories::rule.eq(&form.rule),
+        ))
+        .execute(&mut *conn);
+
+    match res {
+        Ok(n) => {
+            assert_eq!(n, 1);
+            redirect_to_categories(&tournament.id)
+        }
+        Err(diesel::result::Error::DatabaseError(
+            DatabaseErrorKind::UniqueViolation,
+            _,
+        )) => bad_request(
+            maud! { "Error: a team category with that name already exists." }
+                .render(),
+        ),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn do_delete_team_category(
+    Path((tournament_id, category_id)): Path<(String, String)>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let category =
+        TeamCategory::fetch(&category_id, &tournament.id, &mut *conn)?;
+
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        // break categories restricted to this category become open to all
+        // teams
+        diesel::update(
+            break_categories::table
+                .filter(break_categories::team_category_id.eq(&category.id)),
+        )
+        .set(break_categories::team_category_id.eq(None::<String>))
+        .execute(conn)?;
+        diesel::delete(team_categories::table.find(&category.id)).execute(conn)
+    })?;
+
+    redirect_to_categories(&tournament.id)
+}
+
+#[derive(Deserialize)]
+pub struct BreakEligibilityForm {
+    #[serde(default)]
+    team_category_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct CreateBreakCategoryForm {
+    name: String,
+    priority: i64,
+    #[serde(default)]
+    team_category_id: String,
+}
+
+/// Checks that the (possibly empty) team category ID submitted in a form
+/// belongs to this tournament.
+fn team_category_of_form(
+    team_category_id: &str,
+    tournament: &Tournament,
+    conn: &mut SqliteConnection,
+) -> Result<Option<String>, crate::util_resp::FailureResponse> {
+    if team_category_id.is_empty() {
+        Ok(None)
+    } else {
+        TeamCategory::fetch(team_category_id, &tournament.id, conn)
+            .map(|category| Some(category.id))
+    }
+}
+
+pub async fn do_create_break_category(
+    Path(tournament_id): Path<String>,
+    user: User<true>,
+    mut conn: Conn<true>,
+    Form(form): Form<CreateBreakCategoryForm>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+
+    if let Err(e) = validate_category_name(&form.name) {
+        return bad_request(maud! { (e) }.render());
+    }
+    let team_category_id =
+        team_category_of_form(&form.team_category_id, &tournament, &mut *conn)?;
+
+    diesel::insert_into(break_categories::table)
+        .values((
+            break_categories::id.eq(Uuid::now_v7().to_string()),
+            break_categories::tournament_id.eq(&tournament.id),
+            break_categories::name.eq(form.name.trim()),
+            break_categories::priority.eq(form.priority),
+            break_categories::team_category_id.eq(team_category_id),
+        ))
+        .execute(&mut *conn)?;
+
+    redirect_to_categories(&tournament.id)
+}
+
+pub async fn do_set_break_category_eligibility(
+    Path((tournament_id, category_id)): Path<(String, String)>,
+    user: User<true>,
+    mut conn: Conn<true>,
+    Form(form): Form<BreakEligibilityForm>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+
+    let team_category_id =
+        team_category_of_form(&form.team_category_id, &tournament, &mut *conn)?;
+
+    let n = diesel::update(
+        break_categories::table
+            .filter(break_categories::id.eq(&category_id))
+            .filter(break_categories::tournament_id.eq(&tournament.id)),
+    )
+    .set(break_categories::team_category_id.eq(team_category_id))
+    .execute(&mut *conn)?;
+
+    if n == 0 {
+        return err_not_found();
+    }
+
+    redirect_to_categories(&tournament.id)
+}
+
+/// The teams which are eligible to break in a category, in standings order.
+pub async fn view_break_category_page(
+    Path((tournament_id, category_id)): Path<(String, String)>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let rounds = TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+
+    let category =
+        BreakCategory::fetch(&category_id, &tournament.id, &mut *conn)?;
+    let eligible_team_ids = category.eligible_team_ids(&mut *conn);
+    let standings = TeamStandings::recompute(&tournament.id, &mut *conn);
+    let participants = TournamentParticipants::load(&tournament.id, &mut *conn);
+    let ranked = eligible_teams_in_rank_order(
+        &standings.teams_in_rank_order,
+        eligible_team_ids.as_ref(),
+    );
+
+    success(
+        Page::new()
+            .user(user)
+            .tournament(tournament.clone())
+            .body(maud! {
+                SidebarWrapper tournament=(&tournament) rounds=(&rounds) active_page=(None) selected_seq=(None) {
+                    h1 { (category.name) " break" }
+                    p class="text-muted" {
+                        @if eligible_team_ids.is_some() {
+                            "Only teams in the team category chosen for this break "
+                            "category are eligible to break in it. "
+                        } @else {
+                            "Every team is eligible to break in this category. "
+                        }
+                        "Eligible teams are listed in standings order; when "
+                        "checking in teams for this category's elimination "
+                        "rounds, only these teams can be marked as available."
+                    }
+                    table class="table" {
+                        thead {
+                            tr {
+                                th scope="col" { "#" }
+                                th scope="col" { "Team" }
+                                @for metric in &standings.metrics {
+                                    th scope="col" { (metric.to_string()) }
+                                }
+                            }
+                        }
+                        tbody {
+                            @for (rank, teams) in &ranked {
+                                @for team in teams {
+                                    tr {
+                                        th scope="row" {
+                                            @if teams.len() > 1 { "=" }
+                                            (rank)
+                                        }
+                                        td { (participants.canonical_name_of_team(team)) }
+                                        @for metric in standings.ranked_metrics_of_team.get(&team.id).unwrap() {
+                                            td { (metric.1.to_string()) }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+            .render(),
+    )
+}
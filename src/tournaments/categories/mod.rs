@@ -1,4 +1,6 @@
+pub mod manage;
 pub mod speaker;
 pub mod team;
 
-pub use team::BreakCategory;
+pub use speaker::SpeakerCategory;
+pub use team::{BreakCategory, TeamCategory};
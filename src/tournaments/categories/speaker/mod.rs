@@ -1 +1,81 @@
+use std::collections::{HashMap, HashSet};
 
+use diesel::{connection::LoadConnection, prelude::*, sqlite::Sqlite};
+
+use crate::{
+    schema::{speaker_categories, speakers_of_category},
+    util_resp::FailureResponse,
+};
+
+/// Categories which most tournaments use. These can be added in one go from
+/// the category management page.
+pub const STANDARD_SPEAKER_CATEGORIES: [&str; 3] = ["ESL", "EFL", "Novice"];
+
+#[derive(Queryable, Clone, Debug)]
+pub struct SpeakerCategory {
+    pub id: String,
+    pub tournament_id: String,
+    pub name: String,
+}
+
+impl SpeakerCategory {
+    pub fn of_tournament(
+        tournament_id: &str,
+        conn: &mut impl LoadConnection<Backend = Sqlite>,
+    ) -> Vec<Self> {
+        speaker_categories::table
+            .filter(speaker_categories::tournament_id.eq(tournament_id))
+            .order_by(speaker_categories::name.asc())
+            .load::<Self>(conn)
+            .unwrap()
+    }
+
+    pub fn fetch(
+        category_id: &str,
+        tournament_id: &str,
+        conn: &mut impl LoadConnection<Backend = Sqlite>,
+    ) -> Result<Self, FailureResponse> {
+        speaker_categories::table
+            .filter(speaker_categories::id.eq(category_id))
+            .filter(speaker_categories::tournament_id.eq(tournament_id))
+            .first::<Self>(conn)
+            .optional()
+            .unwrap()
+            .ok_or(FailureResponse::NotFound(()))
+    }
+
+    /// The IDs of the speakers in this category.
+    pub fn speaker_ids(
+        &self,
+        conn: &mut impl LoadConnection<Backend = Sqlite>,
+    ) -> HashSet<String> {
+        speakers_of_category::table
+            .filter(speakers_of_category::category_id.eq(&self.id))
+            .select(speakers_of_category::speaker_id)
+            .load::<String>(conn)
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Returns a map from the ID of each speaker to the IDs of the categories that
+/// speaker is in. Speakers who are not in any category are omitted.
+pub fn categories_of_speakers(
+    tournament_id: &str,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> HashMap<String, HashSet<String>> {
+    let mut map: HashMap<String, HashSet<String>> = HashMap::new();
+    for (speaker_id, category_id) in speakers_of_category::table
+        .filter(speakers_of_category::tournament_id.eq(tournament_id))
+        .select((
+            speakers_of_category::speaker_id,
+            speakers_of_category::category_id,
+        ))
+        .load::<(String, String)>(conn)
+        .unwrap()
+    {
+        map.entry(speaker_id).or_default().insert(category_id);
+    }
+    map
+}
@@ -1,4 +1,15 @@
-use diesel::prelude::Queryable;
+use std::collections::{HashMap, HashSet};
+
+use diesel::{connection::LoadConnection, prelude::*, sqlite::Sqlite};
+
+use crate::{
+    schema::{
+        break_categories, speakers_of_category, speakers_of_team,
+        team_categories, teams,
+    },
+    tournaments::{standings::compute::rank_subset, teams::Team},
+    util_resp::FailureResponse,
+};
 
 #[derive(Queryable, Clone, Debug)]
 pub struct BreakCategory {
@@ -6,4 +17,205 @@ pub struct BreakCategory {
     pub tournament_id: String,
     pub name: String,
     pub priority: i64,
+    /// If set, only teams in this team category are eligible to break in this
+    /// category.
+    pub team_category_id: Option<String>,
+}
+
+impl BreakCategory {
+    pub fn fetch(
+        category_id: &str,
+        tournament_id: &str,
+        conn: &mut impl LoadConnection<Backend = Sqlite>,
+    ) -> Result<Self, FailureResponse> {
+        break_categories::table
+            .filter(break_categories::id.eq(category_id))
+            .filter(break_categories::tournament_id.eq(tournament_id))
+            .first::<Self>(conn)
+            .optional()
+            .unwrap()
+            .ok_or(FailureResponse::NotFound(()))
+    }
+
+    /// The IDs of the teams which are eligible to break in this category, or
+    /// `None` if every team is eligible.
+    pub fn eligible_team_ids(
+        &self,
+        conn: &mut impl LoadConnection<Backend = Sqlite>,
+    ) -> Option<HashSet<String>> {
+        self.team_category_id.as_ref().map(|category_id| {
+            TeamCategory::fetch(category_id, &self.tournament_id, conn)
+                .expect("break category refers to a missing team category")
+                .team_ids(conn)
+        })
+    }
+}
+
+/// Whether a team may break in a category with the given eligible teams (as
+/// returned by [`BreakCategory::eligible_team_ids`]).
+pub fn is_eligible_to_break(
+    eligible_team_ids: Option<&HashSet<String>>,
+    team_id: &str,
+) -> bool {
+    eligible_team_ids.is_none_or(|ids| ids.contains(team_id))
+}
+
+/// The teams which are eligible to break in a category, re-ranked amongst
+/// themselves (so that the top eligible team is first, even if it is not
+/// first overall).
+pub fn eligible_teams_in_rank_order<'a>(
+    teams_in_rank_order: &'a [Vec<Team>],
+    eligible_team_ids: Option<&HashSet<String>>,
+) -> Vec<(i64, Vec<&'a Team>)> {
+    rank_subset(teams_in_rank_order, |team| {
+        is_eligible_to_break(eligible_team_ids, &team.id)
+    })
+}
+
+#[derive(Queryable, Clone, Debug)]
+/// A category which teams are placed into based on the speaker categories of
+/// their speakers (for example, a team is "ESL" if all its speakers are ESL).
+pub struct TeamCategory {
+    pub id: String,
+    pub tournament_id: String,
+    pub name: String,
+    pub speaker_category_id: String,
+    /// Either "all" or "any".
+    pub rule: String,
+}
+
+impl TeamCategory {
+    pub fn of_tournament(
+        tournament_id: &str,
+        conn: &mut impl LoadConnection<Backend = Sqlite>,
+    ) -> Vec<Self> {
+        team_categories::table
+            .filter(team_categories::tournament_id.eq(tournament_id))
+            .order_by(team_categories::name.asc())
+            .load::<Self>(conn)
+            .unwrap()
+    }
+
+    pub fn fetch(
+        category_id: &str,
+        tournament_id: &str,
+        conn: &mut impl LoadConnection<Backend = Sqlite>,
+    ) -> Result<Self, FailureResponse> {
+        team_categories::table
+            .filter(team_categories::id.eq(category_id))
+            .filter(team_categories::tournament_id.eq(tournament_id))
+            .first::<Self>(conn)
+            .optional()
+            .unwrap()
+            .ok_or(FailureResponse::NotFound(()))
+    }
+
+    pub fn rule_description(&self) -> &'static str {
+        match self.rule.as_str() {
+            "all" => "every speaker is",
+            "any" => "at least one speaker is",
+            other => unreachable!("invalid team category rule: {other}"),
+        }
+    }
+
+    /// The IDs of the teams in this category.
+    pub fn team_ids(
+        &self,
+        conn: &mut impl LoadConnection<Backend = Sqlite>,
+    ) -> HashSet<String> {
+        let in_category: HashSet<String> = speakers_of_category::table
+            .filter(
+                speakers_of_category::category_id.eq(&self.speaker_category_id),
+            )
+            .select(speakers_of_category::speaker_id)
+            .load::<String>(conn)
+            .unwrap()
+            .into_iter()
+            .collect();
+
+        let mut speakers_of_teams: HashMap<String, Vec<bool>> = HashMap::new();
+        for (team_id, speaker_id) in speakers_of_team::table
+            .inner_join(
+                teams::table.on(teams::id.eq(speakers_of_team::team_id)),
+            )
+            .filter(teams::tournament_id.eq(&self.tournament_id))
+            .select((speakers_of_team::team_id, speakers_of_team::speaker_id))
+            .load::<(String, String)>(conn)
+            .unwrap()
+        {
+            speakers_of_teams
+                .entry(team_id)
+                .or_default()
+                .push(in_category.contains(&speaker_id));
+        }
+
+        speakers_of_teams
+            .into_iter()
+            .filter(|(_, speakers)| team_satisfies_rule(&self.rule, speakers))
+            .map(|(team_id, _)| team_id)
+            .collect()
+    }
+}
+
+/// Whether a team is in a team category, given (for each of its speakers)
+/// whether that speaker is in the relevant speaker category. Teams without
+/// any speakers are never in a category.
+fn team_satisfies_rule(rule: &str, speakers_in_category: &[bool]) -> bool {
+    if speakers_in_category.is_empty() {
+        return false;
+    }
+    match rule {
+        "all" => speakers_in_category.iter().all(|x| *x),
+        "any" => speakers_in_category.iter().any(|x| *x),
+        other => unreachable!("invalid team category rule: {other}"),
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_team_category_rules() {
+    assert!(team_satisfies_rule("all", &[true, true]));
+    assert!(!team_satisfies_rule("all", &[true, false]));
+    assert!(team_satisfies_rule("any", &[false, true]));
+    assert!(!team_satisfies_rule("any", &[false, false]));
+    assert!(!team_satisfies_rule("all", &[]));
+}
+
+#[cfg(test)]
+#[test]
+fn test_only_eligible_teams_break() {
+    let team = |id: &str| Team {
+        id: id.to_string(),
+        tournament_id: "t".to_string(),
+        name: id.to_string(),
+        institution_id: None,
+        number: 0,
+        code_name: None,
+        emoji: None,
+        use_institution_prefix: false,
+    };
+    let ranked =
+        vec![vec![team("a")], vec![team("b"), team("c")], vec![team("d")]];
+    let ids = |ranked: Vec<(i64, Vec<&Team>)>| {
+        ranked
+            .into_iter()
+            .map(|(rank, teams)| {
+                (
+                    rank,
+                    teams.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let esl: HashSet<String> = ["c", "d"].map(String::from).into();
+    assert_eq!(
+        ids(eligible_teams_in_rank_order(&ranked, Some(&esl))),
+        vec![(1, vec!["c"]), (2, vec!["d"])]
+    );
+    assert_eq!(
+        ids(eligible_teams_in_rank_order(&ranked, None)),
+        vec![(1, vec!["a"]), (2, vec!["b", "c"]), (4, vec!["d"])]
+    );
+    assert!(!is_eligible_to_break(Some(&esl), "a"));
 }
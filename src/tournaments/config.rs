@@ -46,14 +46,21 @@ pub enum RankableTeamMetric {
     /// The total number of points the teams this team has debated against have
     /// achieved.
     DrawStrengthByWins,
-    /// The sum of speaks of all the teams that the given team has faced.
+    /// The sum of the total speaker scores of all the teams that the given
+    /// team has faced.
     DrawStrengthBySpeaks,
+    /// The average of the total speaker scores of the teams that the given
+    /// team has faced.
+    AverageDrawStrengthBySpeaks,
     /// The total number of times a team has achieved this many points.
     NTimesAchieved(u8),
     /// The total speaker score of all the speakers on the team.
     TotalSpeakerScore,
     /// The average total speaker score.
     AverageTotalSpeakerScore,
+    /// The average total speaker score, after each adjudicator's scores have
+    /// been standardised (see `speaks_standardisation`).
+    StandardisedAverageTotalSpeakerScore,
 }
 
 impl RankableTeamMetric {
@@ -66,8 +73,10 @@ impl RankableTeamMetric {
             RankableTeamMetric::Ballots => 3,
             RankableTeamMetric::TotalSpeakerScore => 4,
             RankableTeamMetric::DrawStrengthBySpeaks => 5,
-            RankableTeamMetric::NTimesAchieved(_) => 6,
-            RankableTeamMetric::DrawStrengthByWins => 7,
+            RankableTeamMetric::AverageDrawStrengthBySpeaks => 6,
+            RankableTeamMetric::NTimesAchieved(_) => 7,
+            RankableTeamMetric::DrawStrengthByWins => 8,
+            RankableTeamMetric::StandardisedAverageTotalSpeakerScore => 9,
         }
     }
 }
@@ -86,6 +95,9 @@ impl Serialize for RankableTeamMetric {
             RankableTeamMetric::DrawStrengthBySpeaks => {
                 serializer.serialize_str("draw_strength_by_speaks")
             }
+            RankableTeamMetric::AverageDrawStrengthBySpeaks => {
+                serializer.serialize_str("avg_draw_strength_by_speaks")
+            }
             RankableTeamMetric::NTimesAchieved(n) => {
                 // Dynamically create the string here
                 let s = format!("n_times_achieved_{}", n);
@@ -97,6 +109,9 @@ impl Serialize for RankableTeamMetric {
             RankableTeamMetric::AverageTotalSpeakerScore => {
                 serializer.serialize_str("avg_total_speaker_score")
             }
+            RankableTeamMetric::StandardisedAverageTotalSpeakerScore => {
+                serializer.serialize_str("std_avg_total_speaker_score")
+            }
         }
     }
 }
@@ -129,12 +144,18 @@ impl<'de> Deserialize<'de> for RankableTeamMetric {
                     "draw_strength_by_speaks" => {
                         Ok(RankableTeamMetric::DrawStrengthBySpeaks)
                     }
+                    "avg_draw_strength_by_speaks" => {
+                        Ok(RankableTeamMetric::AverageDrawStrengthBySpeaks)
+                    }
                     "total_speaker_score" => {
                         Ok(RankableTeamMetric::TotalSpeakerScore)
                     }
                     "avg_total_speaker_score" => {
                         Ok(RankableTeamMetric::AverageTotalSpeakerScore)
                     }
+                    "std_avg_total_speaker_score" => Ok(
+                        RankableTeamMetric::StandardisedAverageTotalSpeakerScore,
+                    ),
                     s if s.starts_with("n_times_achieved_") => {
                         // Parse the number from the end of the string
                         let num_str = s.trim_start_matches("n_times_achieved_");
@@ -165,12 +186,14 @@ fn test_all_variants_roundtrip() {
         RankableTeamMetric::Ballots,
         RankableTeamMetric::DrawStrengthByWins,
         RankableTeamMetric::DrawStrengthBySpeaks,
+        RankableTeamMetric::AverageDrawStrengthBySpeaks,
         RankableTeamMetric::NTimesAchieved(0),
         RankableTeamMetric::NTimesAchieved(1),
         RankableTeamMetric::NTimesAchieved(2),
         RankableTeamMetric::NTimesAchieved(3),
         RankableTeamMetric::TotalSpeakerScore,
         RankableTeamMetric::AverageTotalSpeakerScore,
+        RankableTeamMetric::StandardisedAverageTotalSpeakerScore,
     ];
 
     for original_metric in metrics_to_test {
@@ -196,7 +219,10 @@ impl std::fmt::Display for RankableTeamMetric {
             RankableTeamMetric::Ballots => "#ballots",
             RankableTeamMetric::DrawStrengthByWins => "draw strength by wins",
             RankableTeamMetric::DrawStrengthBySpeaks => {
-                "draw strength by (average) total speaker score"
+                "draw strength by total speaker score"
+            }
+            RankableTeamMetric::AverageDrawStrengthBySpeaks => {
+                "avg draw strength by total speaker score"
             }
             RankableTeamMetric::NTimesAchieved(points) => {
                 return write!(
@@ -221,6 +247,9 @@ impl std::fmt::Display for RankableTeamMetric {
             RankableTeamMetric::AverageTotalSpeakerScore => {
                 "avg total speaker score"
             }
+            RankableTeamMetric::StandardisedAverageTotalSpeakerScore => {
+                "standardised avg total speaker score"
+            }
         })
     }
 }
@@ -229,12 +258,30 @@ impl std::fmt::Display for RankableTeamMetric {
 pub enum UnrankableTeamMetric {
     #[serde(rename = "draw_strength_by_rank")]
     DrawStrengthByRank,
+    #[serde(rename = "draw_strength_by_speaks")]
+    DrawStrengthBySpeaks,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Debug)]
+/// A metric upon which speakers can be ranked.
 pub enum SpeakerMetric {
+    /// The standard deviation of the speaker's scores. Lower is better.
     StdDev,
     /// Average
     Avg,
     Total,
+    /// The average of the speaker's scores, after each adjudicator's scores
+    /// have been standardised (see `speaks_standardisation`).
+    StandardisedAvg,
+}
+
+impl std::fmt::Display for SpeakerMetric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SpeakerMetric::StdDev => "std dev",
+            SpeakerMetric::Avg => "average",
+            SpeakerMetric::Total => "total",
+            SpeakerMetric::StandardisedAvg => "standardised average",
+        })
+    }
 }
@@ -1,6 +1,6 @@
 use axum::{extract::Form, response::Redirect};
 use chrono::Utc;
-use diesel::{prelude::*, result::DatabaseErrorKind};
+use diesel::{prelude::*, result::DatabaseErrorKind, sqlite::Sqlite};
 use hypertext::prelude::*;
 use serde::Deserialize;
 
@@ -86,32 +86,41 @@ pub struct CreateTournamentForm {
     slug: String,
 }
 
-pub async fn do_create_tournament(
-    user: User<true>,
-    mut conn: Conn<true>,
-    Form(form): Form<CreateTournamentForm>,
-) -> StandardResponse {
-    let tid = uuid::Uuid::now_v7().to_string();
-
-    if !(4..=32).contains(&form.name.len()) {
-        return bad_request(
-            maud! {p {"Tournament name must be between 4 and 32 characters."}}
-                .render(),
+/// Checks the name, abbreviation and slug of a new tournament.
+pub fn check_tournament_details(
+    name: &str,
+    abbrv: &str,
+    slug: &str,
+) -> Result<(), String> {
+    if !(4..=32).contains(&name.len()) {
+        return Err(
+            "Tournament name must be between 4 and 32 characters.".to_string()
         );
     }
-    if !(2..=8).contains(&form.abbrv.len()) {
-        return bad_request(maud! {p {"Tournament abbreviation must be between 2 and 8 characters."}}.render());
-    }
-    if let Err(e) = is_valid_slug(&form.slug) {
-        return bad_request(maud! {p {(e)}}.render());
+    if !(2..=8).contains(&abbrv.len()) {
+        return Err("Tournament abbreviation must be between 2 and 8 \
+                    characters."
+            .to_string());
     }
+    is_valid_slug(slug)
+}
 
-    let res = diesel::insert_into(tournaments::table)
+/// Inserts a tournament with the default configuration, and makes `user_id`
+/// its superuser.
+pub fn insert_tournament(
+    tid: &str,
+    name: &str,
+    abbrv: &str,
+    slug: &str,
+    user_id: &str,
+    conn: &mut impl Connection<Backend = Sqlite>,
+) -> QueryResult<()> {
+    let n = diesel::insert_into(tournaments::table)
         .values((
-            tournaments::id.eq(&tid),
-            tournaments::name.eq(&form.name),
-            tournaments::abbrv.eq(&form.abbrv),
-            tournaments::slug.eq(&form.slug),
+            tournaments::id.eq(tid),
+            tournaments::name.eq(name),
+            tournaments::abbrv.eq(abbrv),
+            tournaments::slug.eq(slug),
             tournaments::created_at.eq(Utc::now().naive_utc()),
             tournaments::team_tab_public.eq(false),
             tournaments::speaker_tab_public.eq(false),
@@ -146,12 +155,46 @@ pub async fn do_create_tournament(
             tournaments::repeat_pullup_penalty.eq(0),
             tournaments::exclude_from_speaker_standings_after.eq(-1),
         ))
-        .execute(&mut *conn);
+        .execute(conn)?;
+    assert_eq!(n, 1);
+
+    let n = diesel::insert_into(org::table)
+        .values((
+            org::id.eq(uuid::Uuid::now_v7().to_string()),
+            org::user_id.eq(user_id),
+            org::tournament_id.eq(tid),
+            org::is_superuser.eq(true),
+        ))
+        .execute(conn)?;
+    assert_eq!(n, 1);
+
+    Ok(())
+}
+
+pub async fn do_create_tournament(
+    user: User<true>,
+    mut conn: Conn<true>,
+    Form(form): Form<CreateTournamentForm>,
+) -> StandardResponse {
+    let tid = uuid::Uuid::now_v7().to_string();
+
+    if let Err(e) =
+        check_tournament_details(&form.name, &form.abbrv, &form.slug)
+    {
+        return bad_request(maud! {p {(e)}}.render());
+    }
+
+    let res = insert_tournament(
+        &tid,
+        &form.name,
+        &form.abbrv,
+        &form.slug,
+        &user.id,
+        &mut *conn,
+    );
 
     match res {
-        Ok(n) => {
-            assert_eq!(n, 1);
-        }
+        Ok(()) => (),
         Err(diesel::result::Error::DatabaseError(e, _))
             if e == DatabaseErrorKind::UniqueViolation =>
         {
@@ -164,16 +207,5 @@ pub async fn do_create_tournament(
         }
     }
 
-    let n = diesel::insert_into(org::table)
-        .values((
-            org::id.eq(uuid::Uuid::now_v7().to_string()),
-            org::user_id.eq(user.id),
-            org::tournament_id.eq(&tid),
-            org::is_superuser.eq(true),
-        ))
-        .execute(&mut *conn)
-        .unwrap();
-    assert_eq!(n, 1);
-
     see_other_ok(Redirect::to(&format!("/tournaments/{tid}")))
 }
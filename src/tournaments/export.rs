@@ -0,0 +1,931 @@
+//! Exports of feedback, ballots and results (as CSV or JSON), for analysis in
+//! a spreadsheet after the tournament.
+
+use std::collections::{HashMap, HashSet};
+
+use axum::extract::{Path, Query};
+use diesel::{connection::LoadConnection, prelude::*, sqlite::Sqlite};
+use hypertext::{Renderable, maud, prelude::*};
+use serde::Deserialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    auth::User,
+    schema::{
+        agg_speaker_results_of_debate, agg_team_results_of_debate,
+        answers_of_feedback_from_judges, answers_of_feedback_from_teams,
+        ballots, debates, feedback_of_judges, feedback_of_teams,
+        feedback_questions, judges, rounds, speaker_scores_of_ballot, speakers,
+        team_ranks_of_ballot, teams,
+    },
+    state::Conn,
+    template::Page,
+    tournaments::{
+        Tournament,
+        feedback::manage::config::FeedbackQuestionKind,
+        manage::sidebar::SidebarWrapper,
+        rounds::{Round, TournamentRounds},
+    },
+    util_resp::{StandardResponse, download, success, unauthorized},
+};
+
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    format: ExportFormat,
+    /// Replace the names and ids of the teams and judges who submitted
+    /// feedback or ballots with pseudonyms.
+    #[serde(default)]
+    pseudonymise: bool,
+}
+
+/// Tabular data, which is written either as CSV or as a JSON list of objects.
+struct Table {
+    columns: &'static [&'static str],
+    rows: Vec<Vec<Value>>,
+}
+
+impl Table {
+    fn to_csv(&self) -> Vec<u8> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record(self.columns).unwrap();
+        for row in &self.rows {
+            writer
+                .write_record(row.iter().map(|value| match value {
+                    Value::Null => String::new(),
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                }))
+                .unwrap();
+        }
+        writer.into_inner().unwrap()
+    }
+
+    fn to_json(&self) -> Vec<u8> {
+        let records: Vec<serde_json::Map<String, Value>> = self
+            .rows
+            .iter()
+            .map(|row| {
+                self.columns
+                    .iter()
+                    .map(|column| column.to_string())
+                    .zip(row.iter().cloned())
+                    .collect()
+            })
+            .collect();
+        serde_json::to_vec_pretty(&records).unwrap()
+    }
+
+    fn download(&self, name: &str, format: ExportFormat) -> StandardResponse {
+        match format {
+            ExportFormat::Csv => {
+                download("text/csv", format!("{name}.csv"), self.to_csv())
+            }
+            ExportFormat::Json => download(
+                "application/json",
+                format!("{name}.json"),
+                self.to_json(),
+            ),
+        }
+    }
+}
+
+/// Assigns each id a stable pseudonym (e.g. "Team 3"), in order of first use.
+struct Pseudonyms {
+    enabled: bool,
+    assigned: HashMap<String, String>,
+    counts: HashMap<&'static str, usize>,
+}
+
+impl Pseudonyms {
+    fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            assigned: HashMap::new(),
+            counts: HashMap::new(),
+        }
+    }
+
+    /// Returns `(id, name)`, or the pseudonym (twice) if pseudonymisation is
+    /// enabled.
+    fn of(
+        &mut self,
+        kind: &'static str,
+        id: &str,
+        name: &str,
+    ) -> (Value, Value) {
+        if !self.enabled {
+            return (id.into(), name.into());
+        }
+        let pseudonym = match self.assigned.get(id) {
+            Some(pseudonym) => pseudonym.clone(),
+            None => {
+                let count = self.counts.entry(kind).or_default();
+                *count += 1;
+                let pseudonym = format!("{kind} {count}");
+                self.assigned.insert(id.to_string(), pseudonym.clone());
+                pseudonym
+            }
+        };
+        (pseudonym.clone().into(), pseudonym.into())
+    }
+
+    /// Returns the id, or (if pseudonymisation is enabled) a random id which
+    /// is only stable within this export, so that rows cannot be joined with
+    /// other exports to recover who they came from.
+    fn opaque(&mut self, id: &str) -> Value {
+        if !self.enabled {
+            return id.into();
+        }
+        self.assigned
+            .entry(id.to_string())
+            .or_insert_with(|| Uuid::new_v4().to_string())
+            .clone()
+            .into()
+    }
+}
+
+/// `(round name, round seq, debate number)` of each debate.
+fn debates_of_tournament(
+    tid: &str,
+    only_published: bool,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> HashMap<String, (String, i64, i64)> {
+    debates::table
+        .inner_join(rounds::table.on(rounds::id.eq(debates::round_id)))
+        .filter(debates::tournament_id.eq(tid))
+        .select((
+            debates::id,
+            rounds::name,
+            rounds::seq,
+            debates::number,
+            rounds::results_published_at.is_not_null(),
+        ))
+        .load::<(String, String, i64, i64, bool)>(conn)
+        .unwrap()
+        .into_iter()
+        .filter(|(.., published)| !only_published || *published)
+        .map(|(id, round, seq, number, _)| (id, (round, seq, number)))
+        .collect()
+}
+
+fn names_of(rows: Vec<(String, String)>) -> HashMap<String, String> {
+    rows.into_iter().collect()
+}
+
+fn feedback_table(
+    tid: &str,
+    pseudonymise: bool,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> Table {
+    let debates = debates_of_tournament(tid, false, conn);
+    let judge_names = names_of(
+        judges::table
+            .filter(judges::tournament_id.eq(tid))
+            .select((judges::id, judges::name))
+            .load::<(String, String)>(conn)
+            .unwrap(),
+    );
+    let team_names = names_of(
+        teams::table
+            .filter(teams::tournament_id.eq(tid))
+            .select((teams::id, teams::name))
+            .load::<(String, String)>(conn)
+            .unwrap(),
+    );
+    let questions: HashMap<String, (String, Option<FeedbackQuestionKind>)> =
+        feedback_questions::table
+            .filter(feedback_questions::tournament_id.eq(tid))
+            .select((
+                feedback_questions::id,
+                feedback_questions::question,
+                feedback_questions::kind,
+            ))
+            .load::<(String, String, String)>(conn)
+            .unwrap()
+            .into_iter()
+            .map(|(id, question, kind)| {
+                (id, (question, serde_json::from_str(&kind).ok()))
+            })
+            .collect();
+
+    // (feedback id, debate id, source id, target judge id, from team)
+    let mut submissions: Vec<(String, String, String, String, bool)> =
+        feedback_of_judges::table
+            .filter(feedback_of_judges::tournament_id.eq(tid))
+            .select((
+                feedback_of_judges::id,
+                feedback_of_judges::debate_id,
+                feedback_of_judges::judge_id,
+                feedback_of_judges::target_judge_id,
+            ))
+            .load::<(String, String, String, String)>(conn)
+            .unwrap()
+            .into_iter()
+            .map(|(id, debate, source, target)| {
+                (id, debate, source, target, false)
+            })
+            .collect();
+    submissions.extend(
+        feedback_of_teams::table
+            .filter(feedback_of_teams::tournament_id.eq(tid))
+            .select((
+                feedback_of_teams::id,
+                feedback_of_teams::debate_id,
+                feedback_of_teams::team_id,
+                feedback_of_teams::target_judge_id,
+            ))
+            .load::<(String, String, String, String)>(conn)
+            .unwrap()
+            .into_iter()
+            .map(|(id, debate, source, target)| {
+                (id, debate, source, target, true)
+            }),
+    );
+    // feedback ids are v7 UUIDs, so sorting by id sorts by submission time
+    submissions.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut latest: HashMap<(&str, &str, &str), &str> = HashMap::new();
+    for (id, debate, source, target, _) in &submissions {
+        latest.insert(
+            (debate.as_str(), source.as_str(), target.as_str()),
+            id.as_str(),
+        );
+    }
+    let latest: HashSet<&str> = latest.into_values().collect();
+
+    let mut answers_of: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    for (feedback_id, question_id, answer) in
+        answers_of_feedback_from_judges::table
+            .filter(answers_of_feedback_from_judges::tournament_id.eq(tid))
+            .select((
+                answers_of_feedback_from_judges::feedback_id,
+                answers_of_feedback_from_judges::question_id,
+                answers_of_feedback_from_judges::answer,
+            ))
+            .load::<(String, String, String)>(conn)
+            .unwrap()
+            .into_iter()
+            .chain(
+                answers_of_feedback_from_teams::table
+                    .filter(
+                        answers_of_feedback_from_teams::tournament_id.eq(tid),
+                    )
+                    .select((
+                        answers_of_feedback_from_teams::feedback_id,
+                        answers_of_feedback_from_teams::question_id,
+                        answers_of_feedback_from_teams::answer,
+                    ))
+                    .load::<(String, String, String)>(conn)
+                    .unwrap(),
+            )
+    {
+        answers_of
+            .entry(feedback_id)
+            .or_default()
+            .push((question_id, answer));
+    }
+
+    let mut pseudonyms = Pseudonyms::new(pseudonymise);
+    let mut rows = Vec::new();
+    for (id, debate, source, target, from_team) in &submissions {
+        let Some((round, seq, number)) = debates.get(debate) else {
+            continue;
+        };
+        let (source_id, source_name) = if *from_team {
+            pseudonyms.of(
+                "Team",
+                source,
+                team_names
+                    .get(source)
+                    .map(String::as_str)
+                    .unwrap_or_default(),
+            )
+        } else {
+            pseudonyms.of(
+                "Judge",
+                source,
+                judge_names
+                    .get(source)
+                    .map(String::as_str)
+                    .unwrap_or_default(),
+            )
+        };
+        let prefix = vec![
+            pseudonyms.opaque(id),
+            round.as_str().into(),
+            (*seq).into(),
+            pseudonyms.opaque(debate),
+            if pseudonymise {
+                Value::Null
+            } else {
+                (*number).into()
+            },
+            if *from_team { "team" } else { "judge" }.into(),
+            source_id,
+            source_name,
+            target.as_str().into(),
+            judge_names.get(target).cloned().unwrap_or_default().into(),
+            latest.contains(id.as_str()).into(),
+        ];
+
+        let answers = answers_of.remove(id).unwrap_or_default();
+        if answers.is_empty() {
+            rows.push(
+                prefix
+                    .into_iter()
+                    .chain([Value::Null, Value::Null, Value::Null])
+                    .collect(),
+            );
+        }
+        for (question_id, answer) in answers {
+            let (question, kind) = questions
+                .get(&question_id)
+                .map(|(question, kind)| (question.as_str(), kind.as_ref()))
+                .unwrap_or_default();
+            let answer = match kind {
+                Some(kind) => kind.display_answer(&answer),
+                None => answer,
+            };
+            rows.push(
+                prefix
+                    .iter()
+                    .cloned()
+                    .chain([question_id.into(), question.into(), answer.into()])
+                    .collect(),
+            );
+        }
+    }
+
+    Table {
+        columns: &[
+            "feedback_id",
+            "round",
+            "round_seq",
+            "debate_id",
+            "debate_number",
+            "source_kind",
+            "source_id",
+            "source_name",
+            "target_judge_id",
+            "target_name",
+            "is_latest",
+            "question_id",
+            "question",
+            "answer",
+        ],
+        rows,
+    }
+}
+
+/// Combines the points of each team with the scores of its speakers, giving
+/// one row per speech (or per team, if no speeches were recorded).
+fn team_and_speaker_rows(
+    points: Vec<(String, Option<i64>)>,
+    speeches: Vec<(String, String, i64, Option<f32>)>,
+) -> Vec<(String, Option<i64>, Option<(String, i64, Option<f32>)>)> {
+    let mut rows = Vec::new();
+    let mut teams: Vec<(String, Option<i64>)> = points;
+    for (team, ..) in &speeches {
+        if !teams.iter().any(|(t, _)| t == team) {
+            teams.push((team.clone(), None));
+        }
+    }
+    for (team, points) in teams {
+        let mut speeches_of_team: Vec<&(String, String, i64, Option<f32>)> =
+            speeches.iter().filter(|(t, ..)| *t == team).collect();
+        speeches_of_team.sort_by_key(|(_, _, position, _)| *position);
+        if speeches_of_team.is_empty() {
+            rows.push((team, points, None));
+        }
+        for (_, speaker, position, score) in speeches_of_team {
+            rows.push((
+                team.clone(),
+                points,
+                Some((speaker.clone(), *position, *score)),
+            ));
+        }
+    }
+    rows
+}
+
+fn ballots_table(
+    tid: &str,
+    pseudonymise: bool,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> Table {
+    let debates = debates_of_tournament(tid, false, conn);
+    let team_names = names_of(
+        teams::table
+            .filter(teams::tournament_id.eq(tid))
+            .select((teams::id, teams::name))
+            .load::<(String, String)>(conn)
+            .unwrap(),
+    );
+    let speaker_names = names_of(
+        speakers::table
+            .filter(speakers::tournament_id.eq(tid))
+            .select((speakers::id, speakers::name))
+            .load::<(String, String)>(conn)
+            .unwrap(),
+    );
+
+    let ballots = ballots::table
+        .inner_join(judges::table.on(judges::id.eq(ballots::judge_id)))
+        .filter(ballots::tournament_id.eq(tid))
+        .order_by((ballots::debate_id, ballots::judge_id, ballots::version))
+        .select((
+            ballots::id,
+            ballots::debate_id,
+            ballots::judge_id,
+            judges::name,
+            ballots::version,
+            ballots::submitted_at,
+        ))
+        .load::<(String, String, String, String, i64, chrono::NaiveDateTime)>(
+            conn,
+        )
+        .unwrap();
+    let mut latest_version: HashMap<(&str, &str), i64> = HashMap::new();
+    for (_, debate, judge, _, version, _) in &ballots {
+        let latest = latest_version.entry((debate, judge)).or_insert(*version);
+        *latest = (*latest).max(*version);
+    }
+
+    let mut points_of: HashMap<String, Vec<(String, Option<i64>)>> =
+        HashMap::new();
+    for (ballot, team, points) in team_ranks_of_ballot::table
+        .filter(team_ranks_of_ballot::tournament_id.eq(tid))
+        .select((
+            team_ranks_of_ballot::ballot_id,
+            team_ranks_of_ballot::team_id,
+            team_ranks_of_ballot::points,
+        ))
+        .load::<(String, String, i64)>(conn)
+        .unwrap()
+    {
+        points_of
+            .entry(ballot)
+            .or_default()
+            .push((team, Some(points)));
+    }
+    let mut speeches_of: HashMap<
+        String,
+        Vec<(String, String, i64, Option<f32>)>,
+    > = HashMap::new();
+    for (ballot, team, speaker, position, score) in
+        speaker_scores_of_ballot::table
+            .filter(speaker_scores_of_ballot::tournament_id.eq(tid))
+            .select((
+                speaker_scores_of_ballot::ballot_id,
+                speaker_scores_of_ballot::team_id,
+                speaker_scores_of_ballot::speaker_id,
+                speaker_scores_of_ballot::speaker_position,
+                speaker_scores_of_ballot::score,
+            ))
+            .load::<(String, String, String, i64, Option<f32>)>(conn)
+            .unwrap()
+    {
+        speeches_of
+            .entry(ballot)
+            .or_default()
+            .push((team, speaker, position, score));
+    }
+
+    let mut pseudonyms = Pseudonyms::new(pseudonymise);
+    let mut rows = Vec::new();
+    for (id, debate, judge, judge_name, version, submitted_at) in &ballots {
+        let Some((round, seq, number)) = debates.get(debate) else {
+            continue;
+        };
+        let (judge_id, judge_name) = pseudonyms.of("Judge", judge, judge_name);
+        let is_latest =
+            latest_version[&(debate.as_str(), judge.as_str())] == *version;
+
+        for (team, points, speech) in team_and_speaker_rows(
+            points_of.remove(id).unwrap_or_default(),
+            speeches_of.remove(id).unwrap_or_default(),
+        ) {
+            let (speaker, position, score) = match speech {
+                Some((speaker, position, score)) => {
+                    let name = speaker_names.get(&speaker).cloned();
+                    (
+                        (Value::from(speaker), Value::from(name)),
+                        Value::from(position),
+                        Value::from(score),
+                    )
+                }
+                None => ((Value::Null, Value::Null), Value::Null, Value::Null),
+            };
+            rows.push(vec![
+                id.as_str().into(),
+                round.as_str().into(),
+                (*seq).into(),
+                debate.as_str().into(),
+                (*number).into(),
+                judge_id.clone(),
+                judge_name.clone(),
+                (*version).into(),
+                submitted_at.format("%Y-%m-%d %H:%M:%S").to_string().into(),
+                is_latest.into(),
+                team_names.get(&team).cloned().into(),
+                team.into(),
+                points.into(),
+                speaker.0,
+                speaker.1,
+                position,
+                score,
+            ]);
+        }
+    }
+
+    Table {
+        columns: &[
+            "ballot_id",
+            "round",
+            "round_seq",
+            "debate_id",
+            "debate_number",
+            "judge_id",
+            "judge_name",
+            "version",
+            "submitted_at",
+            "is_latest",
+            "team_name",
+            "team_id",
+            "points",
+            "speaker_id",
+            "speaker_name",
+            "speaker_position",
+            "score",
+        ],
+        rows,
+    }
+}
+
+/// The aggregated result of each debate. If `public` is set, only rounds
+/// whose results have been published are included, and speaker scores are
+/// left out (as they are not shown on the public results page).
+fn results_table(
+    tid: &str,
+    public: bool,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> Table {
+    let debates = debates_of_tournament(tid, public, conn);
+    let team_names = names_of(
+        teams::table
+            .filter(teams::tournament_id.eq(tid))
+            .select((teams::id, teams::name))
+            .load::<(String, String)>(conn)
+            .unwrap(),
+    );
+    let speaker_names = names_of(
+        speakers::table
+            .filter(speakers::tournament_id.eq(tid))
+            .select((speakers::id, speakers::name))
+            .load::<(String, String)>(conn)
+            .unwrap(),
+    );
+
+    let mut points_of: HashMap<String, Vec<(String, Option<i64>)>> =
+        HashMap::new();
+    for (debate, team, points) in agg_team_results_of_debate::table
+        .filter(agg_team_results_of_debate::tournament_id.eq(tid))
+        .select((
+            agg_team_results_of_debate::debate_id,
+            agg_team_results_of_debate::team_id,
+            agg_team_results_of_debate::points,
+        ))
+        .load::<(String, String, Option<i64>)>(conn)
+        .unwrap()
+    {
+        points_of.entry(debate).or_default().push((team, points));
+    }
+    let mut speeches_of: HashMap<
+        String,
+        Vec<(String, String, i64, Option<f32>)>,
+    > = HashMap::new();
+    if !public {
+        for (debate, team, speaker, position, score) in
+            agg_speaker_results_of_debate::table
+                .filter(agg_speaker_results_of_debate::tournament_id.eq(tid))
+                .select((
+                    agg_speaker_results_of_debate::debate_id,
+                    agg_speaker_results_of_debate::team_id,
+                    agg_speaker_results_of_debate::speaker_id,
+                    agg_speaker_results_of_debate::position,
+                    agg_speaker_results_of_debate::score,
+                ))
+                .load::<(String, String, String, i64, Option<f32>)>(conn)
+                .unwrap()
+        {
+            speeches_of
+                .entry(debate)
+                .or_default()
+                .push((team, speaker, position, score));
+        }
+    }
+
+    let mut debate_ids: Vec<&String> = debates.keys().collect();
+    debate_ids.sort_by_key(|id| {
+        let (_, seq, number) = &debates[*id];
+        (*seq, *number, *id)
+    });
+
+    let mut rows = Vec::new();
+    for debate in debate_ids {
+        let (round, seq, number) = &debates[debate];
+        for (team, points, speech) in team_and_speaker_rows(
+            points_of.remove(debate).unwrap_or_default(),
+            speeches_of.remove(debate).unwrap_or_default(),
+        ) {
+            let mut row: Vec<Value> = vec![
+                round.as_str().into(),
+                (*seq).into(),
+                debate.as_str().into(),
+                (*number).into(),
+                team_names.get(&team).cloned().into(),
+                team.into(),
+                points.into(),
+            ];
+            if !public {
+                match speech {
+                    Some((speaker, position, score)) => row.extend([
+                        speaker_names.get(&speaker).cloned().into(),
+                        speaker.into(),
+                        position.into(),
+                        score.into(),
+                    ]),
+                    None => row.extend(std::iter::repeat_n(Value::Null, 4)),
+                }
+            }
+            rows.push(row);
+        }
+    }
+
+    const TEAM_COLUMNS: &[&str] = &[
+        "round",
+        "round_seq",
+        "debate_id",
+        "debate_number",
+        "team_name",
+        "team_id",
+        "points",
+    ];
+    const ALL_COLUMNS: &[&str] = &[
+        "round",
+        "round_seq",
+        "debate_id",
+        "debate_number",
+        "team_name",
+        "team_id",
+        "points",
+        "speaker_name",
+        "speaker_id",
+        "speaker_position",
+        "score",
+    ];
+    Table {
+        columns: if public { TEAM_COLUMNS } else { ALL_COLUMNS },
+        rows,
+    }
+}
+
+pub async fn export_page(
+    Path(tournament_id): Path<String>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let rounds = TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+    let current_rounds = Round::current_rounds(&tournament_id, &mut *conn);
+
+    let exports = [
+        (
+            "feedback",
+            "Feedback",
+            "One row per answer: source, target, debate, round, question and answer.",
+        ),
+        (
+            "ballots",
+            "Ballots",
+            "One row per speech on every ballot (including superseded versions).",
+        ),
+        (
+            "results",
+            "Results",
+            "The aggregated result of every debate, with speaker scores.",
+        ),
+    ];
+
+    success(
+        Page::new()
+            .user(user)
+            .tournament(tournament.clone())
+            .current_rounds(current_rounds)
+            .body(maud! {
+                SidebarWrapper tournament=(&tournament) rounds=(&rounds) active_page=(None) selected_seq=(None) {
+                    h1 { "Export data" }
+                    @for (path, name, description) in exports {
+                        div class="card mb-3" {
+                            div class="card-body" {
+                                h2 class="h5" { (name) }
+                                p class="text-muted" { (description) }
+                                form method="get" action=(format!("/tournaments/{}/export/{}", tournament.id, path)) class="d-flex flex-wrap gap-3 align-items-center" {
+                                    select class="form-select w-auto" name="format" {
+                                        option value="csv" { "CSV" }
+                                        option value="json" { "JSON" }
+                                    }
+                                    @if path != "results" {
+                                        div class="form-check" {
+                                            input type="checkbox" class="form-check-input" id=(format!("{path}-pseudonymise")) name="pseudonymise" value="true";
+                                            label class="form-check-label" for=(format!("{path}-pseudonymise")) {
+                                                "Pseudonymise submitters"
+                                            }
+                                        }
+                                    }
+                                    button type="submit" class="btn btn-primary" { "Download" }
+                                }
+                            }
+                        }
+                    }
+                    div class="card mb-3" {
+                        div class="card-body" {
+                            h2 class="h5" { "Debate XML archive" }
+                            p class="text-muted" {
+                                "The whole tournament (participants, rounds, motions, ballots "
+                                "and feedback), for import into Tabbycat or another tab system."
+                            }
+                            a href=(format!("/tournaments/{}/export/archive", tournament.id)) class="btn btn-primary" {
+                                "Download"
+                            }
+                        }
+                    }
+                }
+            })
+            .render(),
+    )
+}
+
+pub async fn export_feedback(
+    Path(tournament_id): Path<String>,
+    Query(query): Query<ExportQuery>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+
+    feedback_table(&tournament.id, query.pseudonymise, &mut *conn)
+        .download(&format!("{}-feedback", tournament.slug), query.format)
+}
+
+pub async fn export_ballots(
+    Path(tournament_id): Path<String>,
+    Query(query): Query<ExportQuery>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+
+    ballots_table(&tournament.id, query.pseudonymise, &mut *conn)
+        .download(&format!("{}-ballots", tournament.slug), query.format)
+}
+
+/// Superusers may export every result. Other users may only export the
+/// results which are shown on the public results pages.
+pub async fn export_results(
+    Path(tournament_id): Path<String>,
+    Query(query): Query<ExportQuery>,
+    user: Option<User<true>>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    let is_superuser = match &user {
+        Some(user) => tournament
+            .check_user_is_superuser(&user.id, &mut *conn)
+            .is_ok(),
+        None => false,
+    };
+    if !is_superuser && !tournament.show_round_results {
+        return unauthorized();
+    }
+
+    results_table(&tournament.id, !is_superuser, &mut *conn)
+        .download(&format!("{}-results", tournament.slug), query.format)
+}
+
+#[cfg(test)]
+#[test]
+fn test_pseudonyms_are_stable() {
+    let mut pseudonyms = Pseudonyms::new(true);
+    assert_eq!(
+        pseudonyms.of("Team", "b", "Beta"),
+        ("Team 1".into(), "Team 1".into())
+    );
+    assert_eq!(
+        pseudonyms.of("Judge", "j", "Jo"),
+        ("Judge 1".into(), "Judge 1".into())
+    );
+    assert_eq!(
+        pseudonyms.of("Team", "a", "Alpha"),
+        ("Team 2".into(), "Team 2".into())
+    );
+    assert_eq!(
+        pseudonyms.of("Team", "b", "Beta"),
+        ("Team 1".into(), "Team 1".into())
+    );
+
+    let mut disabled = Pseudonyms::new(false);
+    assert_eq!(
+        disabled.of("Team", "b", "Beta"),
+        ("b".into(), "Beta".into())
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_pseudonymised_feedback_contains_no_real_ids() {
+    use diesel::sqlite::SqliteConnection;
+    use diesel_migrations::MigrationHarness;
+
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+    conn.run_pending_migrations(crate::MIGRATIONS).unwrap();
+    diesel::insert_into(rounds::table)
+        .values((
+            rounds::id.eq("round-id"),
+            rounds::tournament_id.eq("t"),
+            rounds::seq.eq(1),
+            rounds::name.eq("Round 1"),
+            rounds::kind.eq("P"),
+            rounds::completed.eq(true),
+            rounds::draw_status.eq("released_full"),
+        ))
+        .execute(&mut conn)
+        .unwrap();
+    diesel::insert_into(debates::table)
+        .values((
+            debates::id.eq("debate-id"),
+            debates::tournament_id.eq("t"),
+            debates::round_id.eq("round-id"),
+            debates::number.eq(7),
+            debates::status.eq("confirmed"),
+        ))
+        .execute(&mut conn)
+        .unwrap();
+    diesel::insert_into(teams::table)
+        .values((
+            teams::id.eq("team-id"),
+            teams::tournament_id.eq("t"),
+            teams::name.eq("Alpha"),
+            teams::number.eq(1),
+        ))
+        .execute(&mut conn)
+        .unwrap();
+    diesel::insert_into(judges::table)
+        .values((
+            judges::id.eq("judge-id"),
+            judges::tournament_id.eq("t"),
+            judges::name.eq("Cat"),
+            judges::email.eq(""),
+            judges::private_url.eq("judge-url"),
+            judges::number.eq(1),
+        ))
+        .execute(&mut conn)
+        .unwrap();
+    diesel::insert_into(feedback_of_teams::table)
+        .values((
+            feedback_of_teams::id.eq("feedback-id"),
+            feedback_of_teams::tournament_id.eq("t"),
+            feedback_of_teams::debate_id.eq("debate-id"),
+            feedback_of_teams::team_id.eq("team-id"),
+            feedback_of_teams::target_judge_id.eq("judge-id"),
+        ))
+        .execute(&mut conn)
+        .unwrap();
+
+    let real =
+        String::from_utf8(feedback_table("t", false, &mut conn).to_csv())
+            .unwrap();
+    let table = feedback_table("t", true, &mut conn);
+    let pseudonymised = String::from_utf8(table.to_csv()).unwrap();
+
+    for secret in ["feedback-id", "debate-id", "team-id", "Alpha"] {
+        assert!(real.contains(secret));
+        assert!(!pseudonymised.contains(secret), "{secret} was exported");
+    }
+    assert!(pseudonymised.contains("Team 1"));
+    let number = table.columns.iter().position(|c| *c == "debate_number");
+    assert_eq!(table.rows[0][number.unwrap()], Value::Null);
+}
@@ -13,8 +13,10 @@ use crate::{
     state::Conn,
     template::Page,
     tournaments::{
-        Tournament, feedback::FeedbackQuestion,
-        manage::sidebar::SidebarWrapper, rounds::TournamentRounds,
+        Tournament,
+        feedback::{FeedbackPath, FeedbackQuestion},
+        manage::sidebar::SidebarWrapper,
+        rounds::TournamentRounds,
     },
     util_resp::{
         StandardResponse, bad_request, err_not_found, see_other_ok, success,
@@ -65,6 +67,9 @@ impl Renderable for FeedbackConfigRenderer {
             SidebarWrapper tournament=(&self.tournament) rounds=(&self.rounds) active_page=(None) selected_seq=(None) {
                 div class="d-flex justify-content-between flex-wrap flex-md-nowrap align-items-center pt-3 pb-2 mb-3 border-bottom" {
                     h1 class="h2" { "Feedback Configuration" }
+                    a href=(format!("/tournaments/{}/feedback/exemptions", self.tournament.id)) class="btn btn-outline-secondary" {
+                        "Exemptions"
+                    }
                 }
 
                 div class="table-responsive" {
@@ -82,7 +87,17 @@ impl Renderable for FeedbackConfigRenderer {
                                 tr {
                                     td { (question.seq) }
                                     td { (question.question) }
-                                    td { (question.kind) }
+                                    td {
+                                        (serde_json::from_str::<FeedbackQuestionKind>(&question.kind).map(|kind| kind.describe()).unwrap_or_else(|_| question.kind.clone()))
+                                        @if !question.required {
+                                            span class="badge bg-secondary ms-2" { "optional" }
+                                        }
+                                        @if let Some(paths) = question.paths() {
+                                            div class="small text-muted" {
+                                                "Only for: " (paths.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))
+                                            }
+                                        }
+                                    }
                                     td {
                                         div class="btn-group" role="group" {
                                             a
@@ -124,14 +139,11 @@ impl Renderable for FeedbackConfigRenderer {
                         label for="question" class="form-label" { "Question" }
                         input type="text" class="form-control" id="question" name="question" required;
                     }
-                    div class="mb-3" {
-                        label for="kind" class="form-label" { "Type" }
-                        select class="form-select" id="kind" name="kind" required {
-                            option value="score" { "Score (1-10)" }
-                            option value="text" { "Text" }
-                            option value="bool" { "Yes/No" }
-                        }
-                    }
+                    (QuestionKindFields {
+                        kind: None,
+                        required: true,
+                        paths: None,
+                    })
                     div class="mb-3" {
                         label for="judges"  class="form-labale" {"For judges?"}
                         input type="checkbox" class="form-check-input" id="judges" name="for_judges" value="true";
@@ -207,20 +219,11 @@ impl Renderable for EditFeedbackQuestionRenderer {
                             value=(self.question.question)
                             required;
                     }
-                    div class="mb-3" {
-                        label for="kind" class="form-label" { "Type" }
-                        select class="form-select" id="kind" name="kind" required {
-                            option value="score" selected[matches!(kind, FeedbackQuestionKind::IntegerScale { .. })] {
-                                "Score (1-10)"
-                            }
-                            option value="text" selected[matches!(kind, FeedbackQuestionKind::Text { .. })] {
-                                "Text"
-                            }
-                            option value="bool" selected[matches!(kind, FeedbackQuestionKind::Boolean { .. })] {
-                                "Yes/No"
-                            }
-                        }
-                    }
+                    (QuestionKindFields {
+                        kind: Some(&kind),
+                        required: self.question.required,
+                        paths: self.question.paths(),
+                    })
                     input type="hidden" name="seq" value=(self.question.seq);
 
                     button type="submit" class="btn btn-primary" { "Save Changes" }
@@ -238,6 +241,78 @@ impl Renderable for EditFeedbackQuestionRenderer {
     }
 }
 
+/// The inputs (shared by the forms to add and edit a question) which describe
+/// what kind of answer is expected and when the question is asked.
+struct QuestionKindFields<'a> {
+    kind: Option<&'a FeedbackQuestionKind>,
+    required: bool,
+    paths: Option<Vec<FeedbackPath>>,
+}
+
+impl Renderable for QuestionKindFields<'_> {
+    fn render_to(
+        &self,
+        buffer: &mut hypertext::Buffer<hypertext::context::Node>,
+    ) {
+        let kind = self.kind.map(|kind| kind.form_value()).unwrap_or("score");
+        let (min, max) = match self.kind {
+            Some(FeedbackQuestionKind::IntegerScale { min, max }) => {
+                (*min, *max)
+            }
+            _ => (1, 10),
+        };
+        let options = self
+            .kind
+            .map(|kind| kind.options().join("\n"))
+            .unwrap_or_default();
+
+        maud! {
+            div class="mb-3" {
+                label for="kind" class="form-label" { "Type" }
+                select class="form-select" id="kind" name="kind" required {
+                    option value="score" selected[kind == "score"] { "Score" }
+                    option value="text" selected[kind == "text"] { "Text" }
+                    option value="bool" selected[kind == "bool"] { "Yes/No" }
+                    option value="single" selected[kind == "single"] { "Single choice" }
+                    option value="multiple" selected[kind == "multiple"] { "Multiple choice" }
+                }
+            }
+            div class="row mb-3" {
+                div class="col" {
+                    label for="min" class="form-label" { "Minimum score" }
+                    input type="number" class="form-control" id="min" name="min" value=(min);
+                }
+                div class="col" {
+                    label for="max" class="form-label" { "Maximum score" }
+                    input type="number" class="form-control" id="max" name="max" value=(max);
+                }
+                div class="form-text" { "Only used for score questions." }
+            }
+            div class="mb-3" {
+                label for="options" class="form-label" { "Options (one per line)" }
+                textarea class="form-control" id="options" name="options" rows="3" { (options) }
+                div class="form-text" { "Only used for choice questions." }
+            }
+            div class="mb-3 form-check" {
+                input type="checkbox" class="form-check-input" id="required" name="required" value="true" checked[self.required];
+                label for="required" class="form-check-label" { "Answer required" }
+            }
+            div class="mb-3" {
+                span class="form-label d-block" { "Only ask on these paths (leave blank to ask on all of them)" }
+                @for path in FeedbackPath::ALL {
+                    @let value = serde_json::to_value(path).unwrap().as_str().unwrap().to_string();
+                    div class="form-check form-check-inline" {
+                        input type="checkbox" class="form-check-input" id=(format!("path-{value}")) name="paths" value=(value)
+                            checked[self.paths.as_ref().is_some_and(|paths| paths.contains(&path))];
+                        label for=(format!("path-{value}")) class="form-check-label" { (path.to_string()) }
+                    }
+                }
+            }
+        }
+        .render_to(buffer);
+    }
+}
+
 #[derive(Deserialize)]
 pub struct AddQuestionForm {
     question: String,
@@ -246,24 +321,227 @@ pub struct AddQuestionForm {
     for_judges: bool,
     #[serde(default)]
     for_teams: bool,
+    #[serde(default)]
+    min: String,
+    #[serde(default)]
+    max: String,
+    #[serde(default)]
+    options: String,
+    #[serde(default)]
+    required: bool,
+    #[serde(default)]
+    paths: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum FeedbackQuestionKind {
-    IntegerScale { min: i64, max: i64 },
+    IntegerScale {
+        min: i64,
+        max: i64,
+    },
     Text {},
     Boolean {},
+    /// Exactly one of the options must be chosen.
+    SingleChoice {
+        options: Vec<String>,
+    },
+    /// Any number of the options may be chosen. The answer is stored as a
+    /// JSON list of the chosen options.
+    MultipleChoice {
+        options: Vec<String>,
+    },
+}
+
+impl FeedbackQuestionKind {
+    /// The name of the kind, as used in the question forms.
+    pub fn form_value(&self) -> &'static str {
+        match self {
+            FeedbackQuestionKind::IntegerScale { .. } => "score",
+            FeedbackQuestionKind::Text {} => "text",
+            FeedbackQuestionKind::Boolean {} => "bool",
+            FeedbackQuestionKind::SingleChoice { .. } => "single",
+            FeedbackQuestionKind::MultipleChoice { .. } => "multiple",
+        }
+    }
+
+    pub fn options(&self) -> &[String] {
+        match self {
+            FeedbackQuestionKind::SingleChoice { options }
+            | FeedbackQuestionKind::MultipleChoice { options } => options,
+            _ => &[],
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            FeedbackQuestionKind::IntegerScale { min, max } => {
+                format!("Score ({min}-{max})")
+            }
+            FeedbackQuestionKind::Text {} => "Text".to_string(),
+            FeedbackQuestionKind::Boolean {} => "Yes/No".to_string(),
+            FeedbackQuestionKind::SingleChoice { options } => {
+                format!("Single choice ({})", options.join(", "))
+            }
+            FeedbackQuestionKind::MultipleChoice { options } => {
+                format!("Multiple choice ({})", options.join(", "))
+            }
+        }
+    }
+
+    /// Checks the values submitted for a question of this kind, and converts
+    /// them into the answer which is stored. Returns `Ok(None)` if the
+    /// question was left blank.
+    pub fn parse_answer(
+        &self,
+        values: &[String],
+    ) -> Result<Option<String>, String> {
+        let values: Vec<&str> = values
+            .iter()
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+            .collect();
+        if values.is_empty() {
+            return Ok(None);
+        }
+
+        match self {
+            FeedbackQuestionKind::MultipleChoice { options } => {
+                if let Some(value) = values
+                    .iter()
+                    .find(|value| !options.iter().any(|o| o == *value))
+                {
+                    return Err(format!("`{value}` is not one of the options"));
+                }
+                Ok(Some(serde_json::to_string(&values).unwrap()))
+            }
+            _ if values.len() > 1 => {
+                Err("only one answer may be given".to_string())
+            }
+            FeedbackQuestionKind::IntegerScale { min, max } => {
+                match values[0].parse::<i64>() {
+                    Ok(n) if (*min..=*max).contains(&n) => {
+                        Ok(Some(n.to_string()))
+                    }
+                    _ => Err(format!(
+                        "the answer must be a whole number from {min} to {max}"
+                    )),
+                }
+            }
+            FeedbackQuestionKind::Text {} => Ok(Some(values[0].to_string())),
+            FeedbackQuestionKind::Boolean {} => match values[0] {
+                "yes" | "no" => Ok(Some(values[0].to_string())),
+                _ => Err("the answer must be yes or no".to_string()),
+            },
+            FeedbackQuestionKind::SingleChoice { options } => {
+                if options.iter().any(|o| o == values[0]) {
+                    Ok(Some(values[0].to_string()))
+                } else {
+                    Err(format!("`{}` is not one of the options", values[0]))
+                }
+            }
+        }
+    }
+
+    /// Formats a stored answer for display.
+    pub fn display_answer(&self, answer: &str) -> String {
+        match self {
+            FeedbackQuestionKind::MultipleChoice { .. } => {
+                serde_json::from_str::<Vec<String>>(answer)
+                    .map(|chosen| chosen.join(", "))
+                    .unwrap_or_else(|_| answer.to_string())
+            }
+            // older answers were submitted using a checkbox
+            FeedbackQuestionKind::Boolean {} if answer == "on" => {
+                "yes".to_string()
+            }
+            _ => answer.to_string(),
+        }
+    }
+
+    /// The numeric value of an answer, for questions which have one.
+    pub fn numeric_answer(&self, answer: &str) -> Option<f64> {
+        match self {
+            FeedbackQuestionKind::IntegerScale { .. } => answer.parse().ok(),
+            _ => None,
+        }
+    }
 }
 
 fn feedback_question_kind_from_form(
     kind: &str,
-) -> Option<FeedbackQuestionKind> {
+    min: &str,
+    max: &str,
+    options: &str,
+) -> Result<FeedbackQuestionKind, String> {
+    let options = || -> Result<Vec<String>, String> {
+        let options: Vec<String> = options
+            .lines()
+            .map(|option| option.trim())
+            .filter(|option| !option.is_empty())
+            .map(ToString::to_string)
+            .collect();
+        if options.len() < 2 {
+            return Err("Error: a choice question needs at least two options \
+                        (one per line)."
+                .to_string());
+        }
+        if (1..options.len()).any(|i| options[..i].contains(&options[i])) {
+            return Err("Error: the options must be distinct.".to_string());
+        }
+        Ok(options)
+    };
+
     match kind {
-        // todo: should be possible to add non 1-10 scales
-        "score" => Some(FeedbackQuestionKind::IntegerScale { min: 1, max: 10 }),
-        "text" => Some(FeedbackQuestionKind::Text {}),
-        "bool" => Some(FeedbackQuestionKind::Boolean {}),
-        _ => None,
+        "score" => {
+            let parse = |value: &str, default: i64| {
+                if value.trim().is_empty() {
+                    Ok(default)
+                } else {
+                    value.trim().parse::<i64>().map_err(|_| {
+                        "Error: the bounds of a scale must be whole numbers."
+                            .to_string()
+                    })
+                }
+            };
+            let (min, max) = (parse(min, 1)?, parse(max, 10)?);
+            if min >= max {
+                return Err("Error: the minimum of a scale must be less than \
+                            its maximum."
+                    .to_string());
+            }
+            Ok(FeedbackQuestionKind::IntegerScale { min, max })
+        }
+        "text" => Ok(FeedbackQuestionKind::Text {}),
+        "bool" => Ok(FeedbackQuestionKind::Boolean {}),
+        "single" => Ok(FeedbackQuestionKind::SingleChoice {
+            options: options()?,
+        }),
+        "multiple" => Ok(FeedbackQuestionKind::MultipleChoice {
+            options: options()?,
+        }),
+        _ => Err("Error: invalid question kind.".to_string()),
+    }
+}
+
+/// Parses the paths selected in the question forms. Selecting none (or all)
+/// of them means that the question is asked on every path.
+fn feedback_paths_from_form(
+    paths: &[String],
+) -> Result<Option<String>, String> {
+    let mut selected = Vec::new();
+    for path in paths {
+        match serde_json::from_value::<FeedbackPath>(serde_json::Value::String(
+            path.clone(),
+        )) {
+            Ok(path) if !selected.contains(&path) => selected.push(path),
+            Ok(_) => (),
+            Err(_) => return Err("Error: invalid feedback path.".to_string()),
+        }
+    }
+    if selected.is_empty() || selected.len() == FeedbackPath::ALL.len() {
+        Ok(None)
+    } else {
+        Ok(Some(serde_json::to_string(&selected).unwrap()))
     }
 }
 
@@ -276,15 +554,18 @@ pub async fn add_feedback_question(
     let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
     tournament.check_user_is_superuser(&user.id, &mut *conn)?;
 
-    let Some(feedback_q_kind) = feedback_question_kind_from_form(&form.kind)
-    else {
-        // todo: proper error message
-        return bad_request(
-            maud! {
-                "Error: invalid question kind."
-            }
-            .render(),
-        );
+    let feedback_q_kind = match feedback_question_kind_from_form(
+        &form.kind,
+        &form.min,
+        &form.max,
+        &form.options,
+    ) {
+        Ok(kind) => kind,
+        Err(e) => return bad_request(maud! { (e) }.render()),
+    };
+    let paths = match feedback_paths_from_form(&form.paths) {
+        Ok(paths) => paths,
+        Err(e) => return bad_request(maud! { (e) }.render()),
     };
 
     let max_seq = feedback_questions::table
@@ -302,6 +583,8 @@ pub async fn add_feedback_question(
         seq: max_seq + 1,
         for_judges: form.for_judges,
         for_teams: form.for_teams,
+        required: form.required,
+        paths,
     };
 
     diesel::insert_into(feedback_questions::table)
@@ -321,6 +604,16 @@ pub struct EditQuestionForm {
     question: String,
     kind: String,
     seq: i64,
+    #[serde(default)]
+    min: String,
+    #[serde(default)]
+    max: String,
+    #[serde(default)]
+    options: String,
+    #[serde(default)]
+    required: bool,
+    #[serde(default)]
+    paths: Vec<String>,
 }
 
 pub async fn edit_feedback_question(
@@ -334,15 +627,18 @@ pub async fn edit_feedback_question(
     let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
     tournament.check_user_is_superuser(&user.id, &mut *conn)?;
 
-    let Some(feedback_q_kind) = feedback_question_kind_from_form(&form.kind)
-    else {
-        // todo: proper error message
-        return bad_request(
-            maud! {
-                "Error: invalid question kind."
-            }
-            .render(),
-        );
+    let feedback_q_kind = match feedback_question_kind_from_form(
+        &form.kind,
+        &form.min,
+        &form.max,
+        &form.options,
+    ) {
+        Ok(kind) => kind,
+        Err(e) => return bad_request(maud! { (e) }.render()),
+    };
+    let paths = match feedback_paths_from_form(&form.paths) {
+        Ok(paths) => paths,
+        Err(e) => return bad_request(maud! { (e) }.render()),
     };
 
     diesel::update(feedback_questions::table.find(&form.question_id))
@@ -351,6 +647,8 @@ pub async fn edit_feedback_question(
             feedback_questions::kind
                 .eq(serde_json::to_string(&feedback_q_kind).unwrap()),
             feedback_questions::seq.eq(&form.seq),
+            feedback_questions::required.eq(form.required),
+            feedback_questions::paths.eq(paths),
         ))
         .execute(&mut *conn)
         .unwrap();
@@ -479,3 +777,26 @@ pub async fn move_feedback_question_down(
         tournament_id
     )))
 }
+
+#[cfg(test)]
+#[test]
+fn test_answers_are_checked_against_the_question_kind() {
+    let scale =
+        feedback_question_kind_from_form("score", "0", "5", "").unwrap();
+    assert_eq!(scale.parse_answer(&["5".to_string()]), Ok(Some("5".into())));
+    assert!(scale.parse_answer(&["6".to_string()]).is_err());
+    assert_eq!(scale.parse_answer(&[" ".to_string()]), Ok(None));
+
+    let multiple =
+        feedback_question_kind_from_form("multiple", "", "", "Clear\nFair\n")
+            .unwrap();
+    assert_eq!(
+        multiple.parse_answer(&["Clear".to_string(), "Fair".to_string()]),
+        Ok(Some(r#"["Clear","Fair"]"#.to_string()))
+    );
+    assert!(multiple.parse_answer(&["Rude".to_string()]).is_err());
+
+    assert!(
+        feedback_question_kind_from_form("single", "", "", "Only").is_err()
+    );
+}
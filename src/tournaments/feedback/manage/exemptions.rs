@@ -0,0 +1,268 @@
+//! Teams and judges who are exempt from the tournament's feedback requirement
+//! (e.g. because they left the tournament early).
+
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Form, Path},
+    response::Redirect,
+};
+use chrono::Utc;
+use diesel::prelude::*;
+use hypertext::prelude::*;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    auth::User,
+    schema::{feedback_exemptions, judges, teams},
+    state::Conn,
+    template::Page,
+    tournaments::{
+        Tournament,
+        feedback::requirement::FeedbackRequirement,
+        manage::sidebar::SidebarWrapper,
+        participants::Judge,
+        rounds::{Round, TournamentRounds},
+        teams::Team,
+    },
+    util_resp::{
+        StandardResponse, bad_request, err_not_found, see_other_ok, success,
+    },
+};
+
+#[derive(Queryable, Debug, Clone)]
+pub struct FeedbackExemption {
+    pub id: String,
+    pub tournament_id: String,
+    pub team_id: Option<String>,
+    pub judge_id: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+pub async fn feedback_exemptions_page(
+    Path(tournament_id): Path<String>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let rounds = TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+
+    let exemptions = feedback_exemptions::table
+        .filter(feedback_exemptions::tournament_id.eq(&tournament.id))
+        .order_by(feedback_exemptions::created_at.asc())
+        .load::<FeedbackExemption>(&mut *conn)?;
+    let teams = teams::table
+        .filter(teams::tournament_id.eq(&tournament.id))
+        .order_by(teams::name.asc())
+        .load::<Team>(&mut *conn)?;
+    let judges = judges::table
+        .filter(judges::tournament_id.eq(&tournament.id))
+        .order_by(judges::name.asc())
+        .load::<Judge>(&mut *conn)?;
+
+    let exempt: HashSet<&str> = exemptions
+        .iter()
+        .filter_map(|e| e.team_id.as_deref().or(e.judge_id.as_deref()))
+        .collect();
+    // (exemption, name of the team or judge, "team" or "judge")
+    let rows: Vec<(&FeedbackExemption, &str, &str)> = exemptions
+        .iter()
+        .map(|exemption| {
+            let (name, kind) = match (&exemption.team_id, &exemption.judge_id) {
+                (Some(team_id), _) => (
+                    teams
+                        .iter()
+                        .find(|team| &team.id == team_id)
+                        .map(|team| team.name.as_str()),
+                    "team",
+                ),
+                (_, judge_id) => (
+                    judges
+                        .iter()
+                        .find(|judge| judge_id.as_ref() == Some(&judge.id))
+                        .map(|judge| judge.name.as_str()),
+                    "judge",
+                ),
+            };
+            (exemption, name.unwrap_or("—"), kind)
+        })
+        .collect();
+
+    let current_rounds = Round::current_rounds(&tournament_id, &mut *conn);
+
+    success(
+        Page::new()
+            .user(user)
+            .tournament(tournament.clone())
+            .current_rounds(current_rounds)
+            .body(maud! {
+                SidebarWrapper tournament=(&tournament) rounds=(&rounds) active_page=(None) selected_seq=(None) {
+                    h1 { "Feedback exemptions" }
+                    p class="text-muted" {
+                        "Exempt teams and judges are never warned about outstanding "
+                        "feedback, and may always submit ballots."
+                    }
+                    @if FeedbackRequirement::of(&tournament) == FeedbackRequirement::Off {
+                        div class="alert alert-info" {
+                            "Feedback is not currently required (`feedback_requirement` "
+                            "is set to 'off' in the tournament configuration), so "
+                            "exemptions have no effect."
+                        }
+                    }
+
+                    div class="row g-4 mb-5" {
+                        div class="col-md-6" {
+                            h2 class="h5" { "Exempt a team" }
+                            form method="post" action=(format!("/tournaments/{}/feedback/exemptions/add", tournament.id)) class="d-flex gap-2" {
+                                select class="form-select" name="team_id" required {
+                                    @for team in teams.iter().filter(|team| !exempt.contains(team.id.as_str())) {
+                                        option value=(team.id) { (team.name) }
+                                    }
+                                }
+                                button type="submit" class="btn btn-primary" { "Exempt" }
+                            }
+                        }
+                        div class="col-md-6" {
+                            h2 class="h5" { "Exempt a judge" }
+                            form method="post" action=(format!("/tournaments/{}/feedback/exemptions/add", tournament.id)) class="d-flex gap-2" {
+                                select class="form-select" name="judge_id" required {
+                                    @for judge in judges.iter().filter(|judge| !exempt.contains(judge.id.as_str())) {
+                                        option value=(judge.id) { (judge.name) }
+                                    }
+                                }
+                                button type="submit" class="btn btn-primary" { "Exempt" }
+                            }
+                        }
+                    }
+
+                    @if rows.is_empty() {
+                        p class="text-muted" { "Nobody has been exempted." }
+                    } @else {
+                        table class="table table-sm" {
+                            thead {
+                                tr {
+                                    th scope="col" { "Team or judge" }
+                                    th scope="col" { "Since" }
+                                    th scope="col" {}
+                                }
+                            }
+                            tbody {
+                                @for (exemption, name, kind) in &rows {
+                                    tr {
+                                        td {
+                                            (name)
+                                            span class="badge bg-secondary ms-2" { (kind) }
+                                        }
+                                        td { (exemption.created_at.format("%Y-%m-%d %H:%M").to_string()) }
+                                        td class="text-end" {
+                                            form method="post" action=(format!("/tournaments/{}/feedback/exemptions/{}/delete", tournament.id, exemption.id)) {
+                                                button type="submit" class="btn btn-sm btn-outline-danger" { "Remove" }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+            .render(),
+    )
+}
+
+#[derive(Deserialize)]
+pub struct ExemptionForm {
+    #[serde(default)]
+    team_id: String,
+    #[serde(default)]
+    judge_id: String,
+}
+
+pub async fn do_add_feedback_exemption(
+    Path(tournament_id): Path<String>,
+    user: User<true>,
+    mut conn: Conn<true>,
+    Form(form): Form<ExemptionForm>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+
+    let (team_id, judge_id) =
+        match (form.team_id.is_empty(), form.judge_id.is_empty()) {
+            (false, true) => {
+                let team =
+                    Team::fetch(&form.team_id, &tournament.id, &mut *conn)?;
+                (Some(team.id), None)
+            }
+            (true, false) => {
+                let Some(judge) = judges::table
+                    .filter(judges::tournament_id.eq(&tournament.id))
+                    .filter(judges::id.eq(&form.judge_id))
+                    .first::<Judge>(&mut *conn)
+                    .optional()?
+                else {
+                    return err_not_found();
+                };
+                (None, Some(judge.id))
+            }
+            _ => {
+                return bad_request(
+                maud! { "Error: choose exactly one team or judge to exempt." }
+                    .render(),
+            );
+            }
+        };
+
+    let already_exempt = feedback_exemptions::table
+        .filter(feedback_exemptions::tournament_id.eq(&tournament.id))
+        .filter(
+            feedback_exemptions::team_id
+                .eq(&team_id)
+                .or(feedback_exemptions::judge_id.eq(&judge_id)),
+        )
+        .count()
+        .get_result::<i64>(&mut *conn)?
+        > 0;
+    if !already_exempt {
+        diesel::insert_into(feedback_exemptions::table)
+            .values((
+                feedback_exemptions::id.eq(Uuid::now_v7().to_string()),
+                feedback_exemptions::tournament_id.eq(&tournament.id),
+                feedback_exemptions::team_id.eq(&team_id),
+                feedback_exemptions::judge_id.eq(&judge_id),
+                feedback_exemptions::created_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(&mut *conn)?;
+    }
+
+    see_other_ok(Redirect::to(&format!(
+        "/tournaments/{}/feedback/exemptions",
+        tournament.id
+    )))
+}
+
+pub async fn do_delete_feedback_exemption(
+    Path((tournament_id, exemption_id)): Path<(String, String)>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+
+    let n = diesel::delete(
+        feedback_exemptions::table
+            .filter(feedback_exemptions::tournament_id.eq(&tournament.id))
+            .filter(feedback_exemptions::id.eq(&exemption_id)),
+    )
+    .execute(&mut *conn)?;
+    if n == 0 {
+        return err_not_found();
+    }
+
+    see_other_ok(Redirect::to(&format!(
+        "/tournaments/{}/feedback/exemptions",
+        tournament.id
+    )))
+}
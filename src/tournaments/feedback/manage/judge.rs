@@ -0,0 +1,459 @@
+//! A summary of the feedback given on a single judge, to help the adjudication
+//! core decide whether the judge should be promoted or demoted.
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Form, Path},
+    response::Redirect,
+};
+use diesel::prelude::*;
+use hypertext::{Renderable, maud, prelude::*};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    auth::User,
+    schema::{hidden_feedback_comments, judges},
+    state::Conn,
+    template::Page,
+    tournaments::{
+        Tournament,
+        feedback::{
+            FeedbackQuestion,
+            manage::config::FeedbackQuestionKind,
+            received::{ReceivedFeedback, received_feedback},
+            release::{
+                ReleasedFeedback, hidden_comments, questions_with_kinds,
+            },
+        },
+        manage::sidebar::SidebarWrapper,
+        participants::Judge,
+        rounds::{Round, TournamentRounds},
+    },
+    util_resp::{StandardResponse, err_not_found, see_other_ok, success},
+};
+
+/// Answers which are at least this many standard deviations from the judge's
+/// mean are flagged as outliers.
+const OUTLIER_THRESHOLD: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Summary {
+    n: usize,
+    mean: f64,
+    std_dev: f64,
+}
+
+impl Summary {
+    fn of(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let n = values.len();
+        let mean = values.iter().sum::<f64>() / n as f64;
+        let variance =
+            values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n as f64;
+        Some(Summary {
+            n,
+            mean,
+            std_dev: variance.sqrt(),
+        })
+    }
+
+    /// How many standard deviations `value` is from the mean. This is `None`
+    /// if every value is the same.
+    fn z_score(&self, value: f64) -> Option<f64> {
+        (self.std_dev > 0.0).then(|| (value - self.mean) / self.std_dev)
+    }
+}
+
+/// The analysis of one numeric question.
+struct QuestionAnalysis<'a> {
+    question: &'a FeedbackQuestion,
+    min: i64,
+    max: i64,
+    /// `(feedback, value)` for each answer given.
+    values: Vec<(&'a ReceivedFeedback, f64)>,
+    overall: Summary,
+    from_teams: Option<Summary>,
+    from_judges: Option<Summary>,
+    /// `(round name, summary)`, in round order.
+    by_round: Vec<(&'a str, Summary)>,
+}
+
+impl<'a> QuestionAnalysis<'a> {
+    fn new(
+        question: &'a FeedbackQuestion,
+        kind: &FeedbackQuestionKind,
+        feedback: &'a [ReceivedFeedback],
+    ) -> Option<Self> {
+        let FeedbackQuestionKind::IntegerScale { min, max } = kind else {
+            return None;
+        };
+        let values: Vec<(&ReceivedFeedback, f64)> = feedback
+            .iter()
+            .filter_map(|f| {
+                let answer = f.answers.get(&question.id)?;
+                Some((f, kind.numeric_answer(answer)?))
+            })
+            .collect();
+
+        let summary_where = |pred: &dyn Fn(&ReceivedFeedback) -> bool| {
+            Summary::of(
+                &values
+                    .iter()
+                    .filter(|(f, _)| pred(f))
+                    .map(|(_, v)| *v)
+                    .collect::<Vec<_>>(),
+            )
+        };
+
+        // `feedback` is in round order, so the rounds are too
+        let mut round_ids: Vec<(&str, &str)> = Vec::new();
+        for &(f, _) in &values {
+            if !round_ids.iter().any(|(id, _)| *id == f.round_id) {
+                round_ids.push((&f.round_id, &f.round_name));
+            }
+        }
+        let by_round = round_ids
+            .into_iter()
+            .filter_map(|(round_id, name)| {
+                Some((name, summary_where(&|f| f.round_id == round_id)?))
+            })
+            .collect();
+
+        Some(QuestionAnalysis {
+            question,
+            min: *min,
+            max: *max,
+            overall: summary_where(&|_| true)?,
+            from_teams: summary_where(&|f| f.from_team),
+            from_judges: summary_where(&|f| !f.from_team),
+            by_round,
+            values,
+        })
+    }
+
+    /// The number of answers with each value from `min` to `max`.
+    fn distribution(&self) -> Vec<(i64, usize)> {
+        (self.min..=self.max)
+            .map(|value| {
+                let count = self
+                    .values
+                    .iter()
+                    .filter(|(_, v)| *v == value as f64)
+                    .count();
+                (value, count)
+            })
+            .collect()
+    }
+
+    /// The answers which are far from the judge's mean.
+    fn outliers(&self) -> Vec<(&'a ReceivedFeedback, f64, f64)> {
+        self.values
+            .iter()
+            .filter_map(|(f, value)| {
+                let z = self.overall.z_score(*value)?;
+                (z.abs() >= OUTLIER_THRESHOLD).then_some((*f, *value, z))
+            })
+            .collect()
+    }
+}
+
+fn format_summary(summary: Option<Summary>) -> String {
+    match summary {
+        Some(s) => format!("{:.2} (n = {})", s.mean, s.n),
+        None => "—".to_string(),
+    }
+}
+
+pub async fn judge_feedback_page(
+    Path((tournament_id, judge_id)): Path<(String, String)>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let rounds = TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+
+    let Some(judge) = judges::table
+        .filter(judges::tournament_id.eq(&tournament.id))
+        .filter(judges::id.eq(&judge_id))
+        .first::<Judge>(&mut *conn)
+        .optional()?
+    else {
+        return err_not_found();
+    };
+
+    let questions = questions_with_kinds(&tournament.id, &mut *conn);
+    let feedback = received_feedback(&tournament.id, &judge.id, &mut *conn);
+    let hidden = hidden_comments(&tournament.id, &mut *conn);
+
+    let analyses: Vec<QuestionAnalysis> = questions
+        .iter()
+        .filter_map(|(question, kind)| {
+            QuestionAnalysis::new(question, kind, &feedback)
+        })
+        .collect();
+    let outliers: Vec<(&str, &ReceivedFeedback, f64, f64)> = analyses
+        .iter()
+        .flat_map(|analysis| {
+            analysis.outliers().into_iter().map(|(f, value, z)| {
+                (analysis.question.question.as_str(), f, value, z)
+            })
+        })
+        .collect();
+    let kind_of_question: HashMap<&str, &FeedbackQuestionKind> = questions
+        .iter()
+        .map(|(question, kind)| (question.id.as_str(), kind))
+        .collect();
+
+    let current_rounds = Round::current_rounds(&tournament_id, &mut *conn);
+
+    success(
+        Page::new()
+            .user(user)
+            .tournament(tournament.clone())
+            .current_rounds(current_rounds)
+            .body(maud! {
+                SidebarWrapper tournament=(&tournament) rounds=(&rounds) active_page=(None) selected_seq=(None) {
+                    h1 { "Feedback on " (judge.name) }
+                    p class="text-muted" {
+                        (feedback.len()) " pieces of feedback received ("
+                        (feedback.iter().filter(|f| f.from_team).count()) " from teams, "
+                        (feedback.iter().filter(|f| !f.from_team).count()) " from judges)."
+                    }
+
+                    @for analysis in &analyses {
+                        div class="card mb-4" {
+                            div class="card-body" {
+                                h2 class="h5" { (analysis.question.question) }
+                                p {
+                                    "Mean " strong { (format!("{:.2}", analysis.overall.mean)) }
+                                    " (standard deviation " (format!("{:.2}", analysis.overall.std_dev))
+                                    ", n = " (analysis.overall.n) ")"
+                                }
+                                div class="row g-4" {
+                                    div class="col-md-4" {
+                                        h3 class="h6" { "Distribution" }
+                                        table class="table table-sm mb-0" {
+                                            tbody {
+                                                @for (value, count) in analysis.distribution() {
+                                                    tr {
+                                                        th scope="row" class="w-25" { (value) }
+                                                        td {
+                                                            div class="bg-primary" style=(format!("height: 0.75rem; width: {}%", count * 100 / analysis.overall.n)) {}
+                                                        }
+                                                        td class="text-end w-25" { (count) }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                    div class="col-md-4" {
+                                        h3 class="h6" { "By round" }
+                                        table class="table table-sm mb-0" {
+                                            tbody {
+                                                @for (round_name, summary) in &analysis.by_round {
+                                                    tr {
+                                                        th scope="row" { (round_name) }
+                                                        td { (format_summary(Some(*summary))) }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                    div class="col-md-4" {
+                                        h3 class="h6" { "By source" }
+                                        table class="table table-sm mb-0" {
+                                            tbody {
+                                                tr {
+                                                    th scope="row" { "Teams" }
+                                                    td { (format_summary(analysis.from_teams)) }
+                                                }
+                                                tr {
+                                                    th scope="row" { "Judges" }
+                                                    td { (format_summary(analysis.from_judges)) }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    @if analyses.is_empty() {
+                        p class="text-muted" { "There are no numeric answers to summarise." }
+                    }
+
+                    h2 class="h4 mt-4" { "Outliers" }
+                    p class="text-muted" {
+                        "Answers at least " (OUTLIER_THRESHOLD.to_string()) " standard deviations from this judge's mean."
+                    }
+                    @if outliers.is_empty() {
+                        p { "None." }
+                    } @else {
+                        table class="table table-sm" {
+                            thead {
+                                tr {
+                                    th scope="col" { "Round" }
+                                    th scope="col" { "From" }
+                                    th scope="col" { "Question" }
+                                    th scope="col" { "Answer" }
+                                    th scope="col" { "Standard deviations from mean" }
+                                }
+                            }
+                            tbody {
+                                @for (question, f, value, z) in &outliers {
+                                    tr {
+                                        td { (f.round_name) }
+                                        td {
+                                            (f.source_name)
+                                            span class="badge bg-secondary ms-2" {
+                                                @if f.from_team { "team" } @else { "judge" }
+                                            }
+                                        }
+                                        td { (question) }
+                                        td { (value.to_string()) }
+                                        td { (format!("{z:+.2}")) }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    h2 class="h4 mt-4" { "All feedback" }
+                    div class="table-responsive" {
+                        table class="table table-sm table-striped" {
+                            thead {
+                                tr {
+                                    th scope="col" { "Round" }
+                                    th scope="col" { "From" }
+                                    @for (question, _) in &questions {
+                                        th scope="col" { (question.question) }
+                                    }
+                                }
+                            }
+                            tbody {
+                                @for f in &feedback {
+                                    tr {
+                                        td { (f.round_name) }
+                                        td {
+                                            (f.source_name)
+                                            span class="badge bg-secondary ms-2" {
+                                                @if f.from_team { "team" } @else { "judge" }
+                                            }
+                                        }
+                                        @for (question, kind) in &questions {
+                                            td {
+                                                @if let Some(answer) = f.answers.get(&question.id) {
+                                                    @if matches!(kind, FeedbackQuestionKind::Text {}) {
+                                                        @let is_hidden = hidden.contains(&(f.id.clone(), question.id.clone()));
+                                                        span class=(if is_hidden { "text-decoration-line-through text-muted" } else { "" }) {
+                                                            (answer)
+                                                        }
+                                                        form method="post" action=(format!("/tournaments/{}/judges/{}/feedback/comments", tournament.id, judge.id)) class="d-inline ms-2" {
+                                                            input type="hidden" name="feedback_id" value=(f.id);
+                                                            input type="hidden" name="question_id" value=(question.id);
+                                                            button type="submit" class="btn btn-link btn-sm p-0" {
+                                                                @if is_hidden { "Show to judge" } @else { "Hide from judge" }
+                                                            }
+                                                        }
+                                                    } @else {
+                                                        (kind_of_question[question.id.as_str()].display_answer(answer))
+                                                    }
+                                                } @else {
+                                                    span class="text-muted" { "—" }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    h2 class="h4 mt-4" { "What this judge sees" }
+                    p class="text-muted" {
+                        @if tournament.release_feedback_to_judges {
+                            "Feedback has been released, so this is shown on the judge's private URL page."
+                        } @else {
+                            "Feedback has not been released (set `release_feedback_to_judges` in the "
+                            "tournament configuration to release it). Once it is, this will be "
+                            "shown on the judge's private URL page."
+                        }
+                    }
+                    div class="card" {
+                        div class="card-body" {
+                            ReleasedFeedback questions=(&questions) feedback=(&feedback) hidden=(&hidden);
+                        }
+                    }
+                }
+            })
+            .render(),
+    )
+}
+
+#[derive(Deserialize)]
+pub struct HiddenCommentForm {
+    feedback_id: String,
+    question_id: String,
+}
+
+/// Hides a comment from the judge it is about (or shows it again, if it is
+/// already hidden).
+pub async fn do_toggle_hidden_comment(
+    Path((tournament_id, judge_id)): Path<(String, String)>,
+    user: User<true>,
+    mut conn: Conn<true>,
+    Form(form): Form<HiddenCommentForm>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+
+    // only comments which were actually given on this judge can be hidden
+    let feedback = received_feedback(&tournament.id, &judge_id, &mut *conn);
+    if !feedback.iter().any(|f| {
+        f.id == form.feedback_id && f.answers.contains_key(&form.question_id)
+    }) {
+        return err_not_found();
+    }
+
+    let n = diesel::delete(
+        hidden_feedback_comments::table
+            .filter(hidden_feedback_comments::tournament_id.eq(&tournament.id))
+            .filter(hidden_feedback_comments::feedback_id.eq(&form.feedback_id))
+            .filter(
+                hidden_feedback_comments::question_id.eq(&form.question_id),
+            ),
+    )
+    .execute(&mut *conn)?;
+    if n == 0 {
+        diesel::insert_into(hidden_feedback_comments::table)
+            .values((
+                hidden_feedback_comments::id.eq(Uuid::now_v7().to_string()),
+                hidden_feedback_comments::tournament_id.eq(&tournament.id),
+                hidden_feedback_comments::feedback_id.eq(&form.feedback_id),
+                hidden_feedback_comments::question_id.eq(&form.question_id),
+            ))
+            .execute(&mut *conn)?;
+    }
+
+    see_other_ok(Redirect::to(&format!(
+        "/tournaments/{}/judges/{}/feedback",
+        tournament.id, judge_id
+    )))
+}
+
+#[cfg(test)]
+#[test]
+fn test_summary_and_outliers() {
+    let summary =
+        Summary::of(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]).unwrap();
+    assert_eq!(summary.mean, 5.0);
+    assert_eq!(summary.std_dev, 2.0);
+    assert_eq!(summary.z_score(9.0), Some(2.0));
+    assert_eq!(Summary::of(&[3.0, 3.0]).unwrap().z_score(3.0), None);
+    assert_eq!(Summary::of(&[]), None);
+}
@@ -1,2 +1,5 @@
 pub mod config;
+pub mod exemptions;
+pub mod judge;
+pub mod progress;
 pub mod table;
@@ -0,0 +1,500 @@
+//! Which teams and judges have (and have not) submitted feedback in a round.
+
+use std::collections::{HashMap, HashSet};
+
+use axum::extract::{Host, Path, Query};
+use diesel::{connection::LoadConnection, prelude::*, sqlite::Sqlite};
+use hypertext::{Renderable, maud, prelude::*};
+use serde::Deserialize;
+
+use crate::{
+    auth::User,
+    schema::{
+        debates, feedback_of_judges, feedback_of_teams, institutions, speakers,
+        speakers_of_team, teams, teams_of_debate,
+    },
+    state::Conn,
+    template::Page,
+    tournaments::{
+        HTTP_SCHEME, Tournament,
+        feedback::targets::{feedback_targets, panel_of_debate},
+        manage::sidebar::{SidebarPage, SidebarWrapper},
+        participants::Judge,
+        rounds::{Round, TournamentRounds, draws::Debate},
+        teams::Team,
+    },
+    util_resp::{StandardResponse, download, err_not_found, success},
+};
+
+/// A team or judge who is expected to give feedback in a debate.
+pub struct ExpectedFeedback {
+    pub round: Round,
+    pub from_team: bool,
+    /// The id of the team or judge.
+    pub source_id: String,
+    pub name: String,
+    pub institution_id: Option<String>,
+    /// The name and private URL of each person who may submit the feedback
+    /// (for a team, this is every speaker).
+    pub submitters: Vec<(String, String)>,
+    /// The judges who feedback is expected on.
+    pub targets: Vec<Judge>,
+    /// The ids of the targets who feedback has been submitted on.
+    pub submitted_on: HashSet<String>,
+}
+
+impl ExpectedFeedback {
+    pub fn missing(&self) -> Vec<&Judge> {
+        self.targets
+            .iter()
+            .filter(|target| !self.submitted_on.contains(&target.id))
+            .collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.missing().is_empty()
+    }
+}
+
+/// Lists the feedback which is expected in the given rounds, according to the
+/// draw and the feedback paths which are open in each round. Participants who
+/// may not give feedback on anybody are left out.
+pub fn expected_feedback(
+    tournament: &Tournament,
+    rounds: &[Round],
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> Vec<ExpectedFeedback> {
+    let mut expected = Vec::new();
+
+    for round in rounds {
+        let debates = debates::table
+            .filter(debates::round_id.eq(&round.id))
+            .order_by(debates::number.asc())
+            .load::<Debate>(conn)
+            .unwrap();
+
+        for debate in &debates {
+            expected.extend(expected_feedback_in_debate(
+                tournament, round, debate, conn,
+            ));
+        }
+    }
+
+    expected
+}
+
+/// As [`expected_feedback`], but for a single debate.
+pub fn expected_feedback_in_debate(
+    tournament: &Tournament,
+    round: &Round,
+    debate: &Debate,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> Vec<ExpectedFeedback> {
+    let mut expected = Vec::new();
+    let paths = tournament.feedback_paths(round);
+    let panel = panel_of_debate(&debate.id, conn);
+
+    let submitted_by_judges: HashSet<(String, String)> =
+        feedback_of_judges::table
+            .filter(feedback_of_judges::debate_id.eq(&debate.id))
+            .select((
+                feedback_of_judges::judge_id,
+                feedback_of_judges::target_judge_id,
+            ))
+            .load::<(String, String)>(conn)
+            .unwrap()
+            .into_iter()
+            .collect();
+    for (judge, _) in &panel {
+        let targets = feedback_targets(Some(&judge.id), &panel, &paths);
+        if targets.is_empty() {
+            continue;
+        }
+        expected.push(ExpectedFeedback {
+            round: round.clone(),
+            from_team: false,
+            source_id: judge.id.clone(),
+            name: judge.name.clone(),
+            institution_id: judge.institution_id.clone(),
+            submitters: vec![(judge.name.clone(), judge.private_url.clone())],
+            submitted_on: targets
+                .iter()
+                .filter(|target| {
+                    submitted_by_judges
+                        .contains(&(judge.id.clone(), target.id.clone()))
+                })
+                .map(|target| target.id.clone())
+                .collect(),
+            targets: targets.into_iter().cloned().collect(),
+        });
+    }
+
+    let team_targets = feedback_targets(None, &panel, &paths);
+    if team_targets.is_empty() {
+        return expected;
+    }
+    let teams = teams_of_debate::table
+        .inner_join(teams::table.on(teams::id.eq(teams_of_debate::team_id)))
+        .filter(teams_of_debate::debate_id.eq(&debate.id))
+        .order_by((teams_of_debate::side.asc(), teams_of_debate::seq.asc()))
+        .select(teams::all_columns)
+        .load::<Team>(conn)
+        .unwrap();
+    let submitted_by_teams: HashSet<(String, String)> =
+        feedback_of_teams::table
+            .filter(feedback_of_teams::debate_id.eq(&debate.id))
+            .select((
+                feedback_of_teams::team_id,
+                feedback_of_teams::target_judge_id,
+            ))
+            .load::<(String, String)>(conn)
+            .unwrap()
+            .into_iter()
+            .collect();
+    let mut speakers_of: HashMap<String, Vec<(String, String)>> =
+        HashMap::new();
+    for (team_id, name, private_url) in speakers_of_team::table
+        .inner_join(
+            speakers::table.on(speakers::id.eq(speakers_of_team::speaker_id)),
+        )
+        .filter(
+            speakers_of_team::team_id.eq_any(
+                teams
+                    .iter()
+                    .map(|team| team.id.as_str())
+                    .collect::<Vec<_>>(),
+            ),
+        )
+        .order_by(speakers::name.asc())
+        .select((
+            speakers_of_team::team_id,
+            speakers::name,
+            speakers::private_url,
+        ))
+        .load::<(String, String, String)>(conn)
+        .unwrap()
+    {
+        speakers_of
+            .entry(team_id)
+            .or_default()
+            .push((name, private_url));
+    }
+
+    for team in teams {
+        expected.push(ExpectedFeedback {
+            round: round.clone(),
+            from_team: true,
+            submitters: speakers_of.remove(&team.id).unwrap_or_default(),
+            submitted_on: team_targets
+                .iter()
+                .filter(|target| {
+                    submitted_by_teams
+                        .contains(&(team.id.clone(), target.id.clone()))
+                })
+                .map(|target| target.id.clone())
+                .collect(),
+            targets: team_targets.iter().copied().cloned().collect(),
+            source_id: team.id,
+            name: team.name,
+            institution_id: team.institution_id,
+        });
+    }
+
+    expected
+}
+
+#[derive(Deserialize)]
+pub struct FeedbackProgressQuery {
+    /// Only show teams and judges from this institution.
+    #[serde(default)]
+    institution: Option<String>,
+    #[serde(default)]
+    missing_only: bool,
+}
+
+impl FeedbackProgressQuery {
+    fn institution(&self) -> Option<&str> {
+        self.institution.as_deref().filter(|id| !id.is_empty())
+    }
+
+    fn includes(&self, feedback: &ExpectedFeedback) -> bool {
+        (!self.missing_only || !feedback.is_complete())
+            && self
+                .institution()
+                .is_none_or(|id| feedback.institution_id.as_deref() == Some(id))
+    }
+
+    /// The query string which reproduces this filter.
+    fn to_query_string(&self) -> String {
+        let mut params = Vec::new();
+        if let Some(id) = self.institution() {
+            params.push(format!("institution={id}"));
+        }
+        if self.missing_only {
+            params.push("missing_only=true".to_string());
+        }
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!("?{}", params.join("&"))
+        }
+    }
+}
+
+fn feedback_url(
+    tournament_id: &str,
+    round_id: &str,
+    private_url: &str,
+) -> String {
+    format!(
+        "/tournaments/{tournament_id}/privateurls/{private_url}/rounds/{round_id}/feedback/submit"
+    )
+}
+
+fn institution_names(
+    tournament_id: &str,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> Vec<(String, String)> {
+    institutions::table
+        .filter(institutions::tournament_id.eq(tournament_id))
+        .order_by(institutions::name.asc())
+        .select((institutions::id, institutions::name))
+        .load::<(String, String)>(conn)
+        .unwrap()
+}
+
+pub async fn feedback_progress_page(
+    Path((tournament_id, round_seq)): Path<(String, i64)>,
+    Query(query): Query<FeedbackProgressQuery>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let rounds = TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+
+    let rounds_of_seq = Round::of_seq(round_seq, &tournament.id, &mut *conn);
+    if rounds_of_seq.is_empty() {
+        return err_not_found();
+    }
+
+    let expected = expected_feedback(&tournament, &rounds_of_seq, &mut *conn);
+    let n_complete = expected.iter().filter(|f| f.is_complete()).count();
+    let shown: Vec<&ExpectedFeedback> =
+        expected.iter().filter(|f| query.includes(f)).collect();
+
+    let institutions = institution_names(&tournament.id, &mut *conn);
+    let name_of_institution: HashMap<&str, &str> = institutions
+        .iter()
+        .map(|(id, name)| (id.as_str(), name.as_str()))
+        .collect();
+
+    let current_rounds = Round::current_rounds(&tournament_id, &mut *conn);
+
+    success(
+        Page::new()
+            .user(user)
+            .tournament(tournament.clone())
+            .current_rounds(current_rounds)
+            .body(maud! {
+                SidebarWrapper tournament=(&tournament) rounds=(&rounds) active_page=(Some(SidebarPage::Feedback)) selected_seq=(Some(round_seq)) {
+                    h1 { "Feedback progress" }
+                    p class="text-muted" {
+                        (n_complete) " of " (expected.len()) " teams and judges have submitted all of their feedback."
+                    }
+
+                    form method="get" class="row g-2 align-items-center mb-3" {
+                        div class="col-auto" {
+                            select class="form-select" name="institution" {
+                                option value="" selected[query.institution().is_none()] { "All institutions" }
+                                @for (id, name) in &institutions {
+                                    option value=(id) selected[query.institution() == Some(id.as_str())] { (name) }
+                                }
+                            }
+                        }
+                        div class="col-auto form-check ms-2" {
+                            input type="checkbox" class="form-check-input" id="missing_only" name="missing_only" value="true" checked[query.missing_only];
+                            label class="form-check-label" for="missing_only" { "Only show missing" }
+                        }
+                        div class="col-auto" {
+                            button type="submit" class="btn btn-outline-primary" { "Filter" }
+                        }
+                        div class="col-auto" {
+                            a class="btn btn-outline-secondary" href=(format!("/tournaments/{}/rounds/{}/feedback/missing.csv{}", tournament.id, round_seq, query.to_query_string())) {
+                                "Download missing (CSV)"
+                            }
+                        }
+                    }
+
+                    table class="table table-sm" {
+                        thead {
+                            tr {
+                                th scope="col" { "From" }
+                                th scope="col" { "Institution" }
+                                th scope="col" { "Feedback on" }
+                                th scope="col" { "Status" }
+                                th scope="col" { "Private URL" }
+                            }
+                        }
+                        tbody {
+                            @for feedback in &shown {
+                                tr {
+                                    td {
+                                        (feedback.name)
+                                        span class="badge bg-secondary ms-2" {
+                                            @if feedback.from_team { "team" } @else { "judge" }
+                                        }
+                                        @if rounds_of_seq.len() > 1 {
+                                            span class="text-muted small ms-2" { (feedback.round.name) }
+                                        }
+                                    }
+                                    td {
+                                        (feedback.institution_id.as_deref().and_then(|id| name_of_institution.get(id).copied()).unwrap_or("—"))
+                                    }
+                                    td {
+                                        @for target in &feedback.targets {
+                                            div {
+                                                @if feedback.submitted_on.contains(&target.id) {
+                                                    (target.name) " ✓"
+                                                } @else {
+                                                    span class="text-danger" { (target.name) }
+                                                }
+                                            }
+                                        }
+                                    }
+                                    td {
+                                        @if feedback.is_complete() {
+                                            span class="badge bg-success" { "submitted" }
+                                        } @else {
+                                            span class="badge bg-danger" { "missing" }
+                                        }
+                                    }
+                                    td {
+                                        @for (name, private_url) in &feedback.submitters {
+                                            div {
+                                                a href=(feedback_url(&tournament.id, &feedback.round.id, private_url)) {
+                                                    (name)
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                            @if shown.is_empty() {
+                                tr {
+                                    td colspan="5" class="text-center text-muted" { "Nothing to show." }
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+            .render(),
+    )
+}
+
+/// A CSV file listing the feedback which has not yet been submitted, so that
+/// e.g. institution representatives can chase it up.
+pub async fn missing_feedback_csv(
+    Path((tournament_id, round_seq)): Path<(String, i64)>,
+    Query(query): Query<FeedbackProgressQuery>,
+    Host(host): Host,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+
+    let rounds_of_seq = Round::of_seq(round_seq, &tournament.id, &mut *conn);
+    if rounds_of_seq.is_empty() {
+        return err_not_found();
+    }
+
+    let expected = expected_feedback(&tournament, &rounds_of_seq, &mut *conn);
+    let name_of_institution: HashMap<String, String> =
+        institution_names(&tournament.id, &mut *conn)
+            .into_iter()
+            .collect();
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record([
+            "round",
+            "from",
+            "kind",
+            "institution",
+            "missing_feedback_on",
+            "submitter",
+            "private_url",
+        ])
+        .unwrap();
+    for feedback in expected
+        .iter()
+        .filter(|f| !f.is_complete() && query.includes(f))
+    {
+        let missing = feedback
+            .missing()
+            .iter()
+            .map(|judge| judge.name.as_str())
+            .collect::<Vec<_>>()
+            .join("; ");
+        let institution = feedback
+            .institution_id
+            .as_ref()
+            .and_then(|id| name_of_institution.get(id))
+            .map(String::as_str)
+            .unwrap_or_default();
+        // one row per person, so that the file can be used for a mail merge
+        for (submitter, private_url) in &feedback.submitters {
+            let url = format!(
+                "{HTTP_SCHEME}{host}{}",
+                feedback_url(&tournament.id, &feedback.round.id, private_url)
+            );
+            writer
+                .write_record([
+                    feedback.round.name.as_str(),
+                    feedback.name.as_str(),
+                    if feedback.from_team { "team" } else { "judge" },
+                    institution,
+                    missing.as_str(),
+                    submitter.as_str(),
+                    url.as_str(),
+                ])
+                .unwrap();
+        }
+    }
+
+    download(
+        "text/csv",
+        format!("round-{round_seq}-missing-feedback.csv"),
+        writer.into_inner().unwrap(),
+    )
+}
+
+#[cfg(test)]
+#[test]
+fn test_progress_counts_teams_and_judges_who_owe_feedback() {
+    use crate::tournaments::feedback::requirement::tests::tournament_with_completed_debate;
+
+    let (mut conn, tournament) = tournament_with_completed_debate("off");
+    let rounds = Round::of_seq(1, "t", &mut conn);
+    let count = |conn: &mut diesel::sqlite::SqliteConnection| {
+        let expected = expected_feedback(&tournament, &rounds, conn);
+        let n_complete = expected.iter().filter(|f| f.is_complete()).count();
+        (n_complete, expected.len())
+    };
+    // the chair may not give feedback on anybody, so is left out
+    assert_eq!(count(&mut conn), (0, 3));
+
+    diesel::insert_into(feedback_of_teams::table)
+        .values((
+            feedback_of_teams::id.eq("f1"),
+            feedback_of_teams::tournament_id.eq("t"),
+            feedback_of_teams::debate_id.eq("d1"),
+            feedback_of_teams::team_id.eq("prop"),
+            feedback_of_teams::target_judge_id.eq("chair"),
+        ))
+        .execute(&mut conn)
+        .unwrap();
+    assert_eq!(count(&mut conn), (1, 3));
+}
@@ -14,7 +14,8 @@ use crate::{
     state::Conn,
     template::Page,
     tournaments::{
-        Tournament, manage::sidebar::SidebarWrapper, rounds::TournamentRounds,
+        Tournament, feedback::manage::config::FeedbackQuestionKind,
+        manage::sidebar::SidebarWrapper, rounds::TournamentRounds,
     },
     util_resp::{StandardResponse, success},
 };
@@ -56,9 +57,18 @@ pub async fn feedback_table_page(
     let questions = feedback_questions::table
         .filter(feedback_questions::tournament_id.eq(&tournament_id))
         .order_by(feedback_questions::seq.asc())
-        .select((feedback_questions::id, feedback_questions::question))
-        .load::<(String, String)>(&mut *conn)
-        .unwrap();
+        .select((
+            feedback_questions::id,
+            feedback_questions::question,
+            feedback_questions::kind,
+        ))
+        .load::<(String, String, String)>(&mut *conn)
+        .unwrap()
+        .into_iter()
+        .map(|(id, question, kind)| {
+            (id, question, serde_json::from_str(&kind).ok())
+        })
+        .collect();
 
     let page = query.page.unwrap_or(1).max(1);
     let per_page = 128;
@@ -238,7 +248,8 @@ struct FeedbackTableRenderer {
     tournament: Tournament,
     rounds: TournamentRounds,
     all_feedback: Vec<FeedbackDisplayItem>,
-    questions: Vec<(String, String)>,
+    /// `(id, question, kind)`. The kind is used to display the answers.
+    questions: Vec<(String, String, Option<FeedbackQuestionKind>)>,
     page: i64,
 }
 
@@ -260,7 +271,7 @@ impl Renderable for FeedbackTableRenderer {
                             th scope="col" { "Round" }
                             th scope="col" { "From" }
                             th scope="col" { "To (Judge)" }
-                            @for (_, question_text) in &self.questions {
+                            @for (_, question_text, _) in &self.questions {
                                 th scope="col" { (question_text) }
                             }
                             th scope="col" { "ID" }
@@ -273,10 +284,10 @@ impl Renderable for FeedbackTableRenderer {
                                 td { (item.round_name) }
                                 td { (item.source_name) }
                                 td { (item.target_name) }
-                                @for (question_id, _) in &self.questions {
+                                @for (question_id, _, kind) in &self.questions {
                                     td {
                                         @if let Some(answer) = item.answers.get(question_id) {
-                                            (answer)
+                                            (kind.as_ref().map(|kind| kind.display_answer(answer)).unwrap_or_else(|| answer.clone()))
                                         } @else {
                                             span class="text-muted" { "No answer" }
                                         }
@@ -1,8 +1,12 @@
 pub mod manage;
 pub mod public;
+pub mod received;
+pub mod release;
+pub mod requirement;
+pub mod targets;
 
 use diesel::prelude::*;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::schema::{
     answers_of_feedback_from_judges, answers_of_feedback_from_teams,
@@ -21,6 +25,86 @@ pub struct FeedbackQuestion {
     pub seq: i64,
     pub for_judges: bool,
     pub for_teams: bool,
+    pub required: bool,
+    /// A JSON list of [`FeedbackPath`]s. If this is `None`, the question is
+    /// asked on every path.
+    pub paths: Option<String>,
+}
+
+impl FeedbackQuestion {
+    pub fn paths(&self) -> Option<Vec<FeedbackPath>> {
+        self.paths
+            .as_ref()
+            .map(|paths| serde_json::from_str(paths).unwrap())
+    }
+
+    /// Whether this question should be asked of a submitter (a team if
+    /// `from_team` is set, otherwise a judge) giving feedback along `path`.
+    pub fn is_asked(
+        &self,
+        from_team: bool,
+        path: Option<FeedbackPath>,
+    ) -> bool {
+        let for_submitter = if from_team {
+            self.for_teams
+        } else {
+            self.for_judges
+        };
+        for_submitter
+            && match self.paths() {
+                Some(paths) => path.is_some_and(|path| paths.contains(&path)),
+                None => true,
+            }
+    }
+}
+
+/// The relationship between the giver and the recipient of feedback.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackPath {
+    TeamToChair,
+    TeamToPanellist,
+    ChairToPanellist,
+    PanellistToChair,
+    TraineeToChair,
+}
+
+impl FeedbackPath {
+    pub const ALL: [FeedbackPath; 5] = [
+        FeedbackPath::TeamToChair,
+        FeedbackPath::TeamToPanellist,
+        FeedbackPath::ChairToPanellist,
+        FeedbackPath::PanellistToChair,
+        FeedbackPath::TraineeToChair,
+    ];
+
+    /// Determines the path from the status (in `judges_of_debate`) of the
+    /// submitter (`None` for a team) and of the target judge.
+    pub fn between(
+        submitter_status: Option<&str>,
+        target_status: &str,
+    ) -> Option<FeedbackPath> {
+        match (submitter_status, target_status) {
+            (None, "C") => Some(FeedbackPath::TeamToChair),
+            (None, "P") => Some(FeedbackPath::TeamToPanellist),
+            (Some("C"), "P") => Some(FeedbackPath::ChairToPanellist),
+            (Some("P"), "C") => Some(FeedbackPath::PanellistToChair),
+            (Some("T"), "C") => Some(FeedbackPath::TraineeToChair),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for FeedbackPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            FeedbackPath::TeamToChair => "team → chair",
+            FeedbackPath::TeamToPanellist => "team → panellist",
+            FeedbackPath::ChairToPanellist => "chair → panellist",
+            FeedbackPath::PanellistToChair => "panellist → chair",
+            FeedbackPath::TraineeToChair => "trainee → chair",
+        })
+    }
 }
 
 #[derive(
@@ -70,3 +154,19 @@ pub struct FeedbackFromTeamsQuestionAnswer {
     pub question_id: String,
     pub answer: String,
 }
+
+#[cfg(test)]
+#[test]
+fn test_feedback_path_between_statuses() {
+    assert_eq!(
+        FeedbackPath::between(None, "C"),
+        Some(FeedbackPath::TeamToChair)
+    );
+    assert_eq!(
+        FeedbackPath::between(Some("C"), "P"),
+        Some(FeedbackPath::ChairToPanellist)
+    );
+    // nobody gives feedback on trainees
+    assert_eq!(FeedbackPath::between(Some("C"), "T"), None);
+    assert_eq!(FeedbackPath::between(None, "T"), None);
+}
@@ -1,4 +1,4 @@
-use axum::extract::{Form, Path};
+use axum::extract::{Form, Path, Query};
 use std::collections::HashMap;
 
 use diesel::{connection::LoadConnection, prelude::*};
@@ -19,16 +19,26 @@ use crate::{
         Tournament,
         feedback::{
             FeedbackFromJudgesQuestionAnswer, FeedbackFromTeamsQuestionAnswer,
-            FeedbackOfJudge, FeedbackOfTeam, FeedbackQuestion,
+            FeedbackOfJudge, FeedbackOfTeam, FeedbackPath, FeedbackQuestion,
             manage::config::FeedbackQuestionKind,
+            targets::{feedback_targets, panel_of_debate},
         },
         participants::{Judge, Speaker},
-        privateurls::ParticipantKind,
         rounds::{Round, draws::Debate},
     },
-    util_resp::{FailureResponse, StandardResponse, err_not_found, success},
+    util_resp::{
+        FailureResponse, StandardResponse, bad_request, err_not_found, success,
+    },
 };
 
+#[derive(Deserialize)]
+pub struct FeedbackTargetQuery {
+    /// The judge to give feedback on. The questions are only shown once the
+    /// target has been chosen, as which questions are asked depends on the
+    /// role of the target on the panel.
+    target: Option<String>,
+}
+
 pub async fn submit_feedback_page(
     Path((tournament_id, private_url, round_id)): Path<(
         String,
@@ -37,6 +47,7 @@ pub async fn submit_feedback_page(
     )>,
     user: Option<User<true>>,
     mut conn: Conn<true>,
+    Query(query): Query<FeedbackTargetQuery>,
 ) -> StandardResponse {
     let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
     let round = Round::fetch(&tournament_id, &round_id, &mut *conn)?;
@@ -83,25 +94,56 @@ pub async fn submit_feedback_page(
         );
     }
 
-    let targets = if let Some(judge) = &judge {
+    let paths = tournament.feedback_paths(&round);
+    let (debate, targets) = if let Some(judge) = &judge {
         let debate =
             debate_of_judge_in_round(&judge.id, &round.id, &mut *conn)?;
-        let targets =
-            get_feedback_targets_for_judge(&judge.id, &debate.id, &mut *conn);
-        targets
+        let targets = get_feedback_targets_for_judge(
+            &judge.id, &debate.id, &paths, &mut *conn,
+        );
+        (debate, targets)
     } else {
         let speaker = speaker.as_ref().unwrap();
         let debate =
             debate_of_speaker_in_round(&speaker.id, &round.id, &mut *conn)?;
-        let targets = get_feedback_targets_for_speaker(&debate.id, &mut *conn);
-        targets
+        let targets =
+            get_feedback_targets_for_speaker(&debate.id, &paths, &mut *conn);
+        (debate, targets)
     };
 
-    let questions = feedback_questions::table
-        .filter(feedback_questions::tournament_id.eq(&tournament_id))
-        .order_by(feedback_questions::seq.asc())
-        .load::<FeedbackQuestion>(&mut *conn)
-        .unwrap();
+    let target = match &query.target {
+        Some(target_id) => {
+            match targets.iter().find(|target| &target.id == target_id) {
+                Some(target) => Some(target.clone()),
+                None => return err_not_found(),
+            }
+        }
+        None => None,
+    };
+
+    let questions = match &target {
+        Some(target) => {
+            let path = feedback_path(
+                &debate.id,
+                judge.as_ref().map(|judge| judge.id.as_str()),
+                &target.id,
+                &mut *conn,
+            );
+            feedback_questions::table
+                .filter(feedback_questions::tournament_id.eq(&tournament_id))
+                .order_by(feedback_questions::seq.asc())
+                .load::<FeedbackQuestion>(&mut *conn)
+                .unwrap()
+                .into_iter()
+                .filter(|question| question.is_asked(judge.is_none(), path))
+                .map(|question| {
+                    let kind = serde_json::from_str(&question.kind).unwrap();
+                    (question, kind)
+                })
+                .collect()
+        }
+        None => Vec::new(),
+    };
 
     let current_rounds = Round::current_rounds(&tournament_id, &mut *conn);
 
@@ -111,26 +153,27 @@ pub async fn submit_feedback_page(
             .tournament(tournament.clone())
             .current_rounds(current_rounds)
             .body(FeedbackFormRenderer {
+                form_url: format!(
+                    "/tournaments/{}/privateurls/{}/rounds/{}/feedback/submit",
+                    tournament_id, private_url, round_id
+                ),
                 round,
                 targets,
+                target,
                 questions,
-                submitter_kind: if judge.is_some() {
-                    ParticipantKind::Judge
-                } else {
-                    ParticipantKind::Speaker
-                },
-                // tournament,
             })
             .render(),
     )
 }
 
 struct FeedbackFormRenderer {
+    form_url: String,
     round: Round,
     targets: Vec<Judge>,
-    questions: Vec<FeedbackQuestion>,
-    submitter_kind: ParticipantKind,
-    // tournament: Tournament,
+    /// The judge who feedback is being given on, once they have been chosen.
+    target: Option<Judge>,
+    /// The questions which are asked about `target`.
+    questions: Vec<(FeedbackQuestion, FeedbackQuestionKind)>,
 }
 
 impl Renderable for FeedbackFormRenderer {
@@ -143,39 +186,80 @@ impl Renderable for FeedbackFormRenderer {
                 h1 { "Submit feedback" }
                 h2 { (self.round.name) }
 
-                form method="post" {
-                    div class="mb-3" {
-                        label for="target_judge_id" class="form-label" { "Feedback for:" }
-                        select class="form-select" name="target_judge_id" id="target_judge_id" required {
-                            option value="" disabled selected { "Select a judge" }
-                            @for target in &self.targets {
-                                option value=(target.id) { (target.name) }
-                            }
-                        }
+                @if let Some(target) = &self.target {
+                    p {
+                        "Feedback for " strong { (target.name) } " "
+                        a href=(self.form_url) { "(change)" }
                     }
-
-                    @for question in &self.questions {
-                        @let kind: FeedbackQuestionKind = serde_json::from_str(&question.kind).unwrap();
-                        @if (question.for_judges && matches!(self.submitter_kind, ParticipantKind::Judge))
-                            || (question.for_teams && matches!(self.submitter_kind, ParticipantKind::Speaker)) {
-                                div class="mb-3" {
-                                    label for=(question.id) class="form-label" { (question.question) }
-                                    @match kind {
-                                        FeedbackQuestionKind::IntegerScale { .. } => {
-                                            input type="number" class="form-control" name=(question.id) min="1" max="10" required;
+                    form method="post" action=(self.form_url) {
+                        input type="hidden" name="target_judge_id" value=(target.id);
+
+                        @for (question, kind) in &self.questions {
+                            div class="mb-3" {
+                                label for=(question.id) class="form-label" {
+                                    (question.question)
+                                    @if !question.required {
+                                        span class="text-muted" { " (optional)" }
+                                    }
+                                }
+                                @match kind {
+                                    FeedbackQuestionKind::IntegerScale { min, max } => {
+                                        input type="number" class="form-control" id=(question.id) name=(question.id) min=(min.to_string()) max=(max.to_string()) required[question.required];
+                                    }
+                                    FeedbackQuestionKind::Text { .. } => {
+                                        textarea class="form-control" id=(question.id) name=(question.id) rows="3" required[question.required] {}
+                                    }
+                                    FeedbackQuestionKind::Boolean { .. } => {
+                                        div id=(question.id) {
+                                            @for answer in ["yes", "no"] {
+                                                div class="form-check form-check-inline" {
+                                                    input type="radio" class="form-check-input" id=(format!("{}-{answer}", question.id)) name=(question.id) value=(answer) required[question.required];
+                                                    label class="form-check-label" for=(format!("{}-{answer}", question.id)) { (answer) }
+                                                }
+                                            }
                                         }
-                                        FeedbackQuestionKind::Text { .. } => {
-                                            textarea class="form-control" name=(question.id) rows="3" required {}
+                                    }
+                                    FeedbackQuestionKind::SingleChoice { options } => {
+                                        div id=(question.id) {
+                                            @for (i, option) in options.iter().enumerate() {
+                                                div class="form-check" {
+                                                    input type="radio" class="form-check-input" id=(format!("{}-{i}", question.id)) name=(question.id) value=(option) required[question.required];
+                                                    label class="form-check-label" for=(format!("{}-{i}", question.id)) { (option) }
+                                                }
+                                            }
                                         }
-                                        FeedbackQuestionKind::Boolean { .. } => {
-                                            input type="checkbox" class="form-check-input" name=(question.id) required;
+                                    }
+                                    FeedbackQuestionKind::MultipleChoice { options } => {
+                                        div id=(question.id) {
+                                            @for (i, option) in options.iter().enumerate() {
+                                                div class="form-check" {
+                                                    input type="checkbox" class="form-check-input" id=(format!("{}-{i}", question.id)) name=(question.id) value=(option);
+                                                    label class="form-check-label" for=(format!("{}-{i}", question.id)) { (option) }
+                                                }
+                                            }
                                         }
                                     }
                                 }
                             }
-                    }
+                        }
 
-                    button type="submit" class="btn btn-primary" { "Submit Feedback" }
+                        button type="submit" class="btn btn-primary" { "Submit Feedback" }
+                    }
+                } @else if self.targets.is_empty() {
+                    p class="text-muted" { "There is nobody to give feedback on in this round." }
+                } @else {
+                    form method="get" action=(self.form_url) {
+                        div class="mb-3" {
+                            label for="target" class="form-label" { "Feedback for:" }
+                            select class="form-select" name="target" id="target" required {
+                                option value="" disabled selected { "Select a judge" }
+                                @for target in &self.targets {
+                                    option value=(target.id) { (target.name) }
+                                }
+                            }
+                        }
+                        button type="submit" class="btn btn-primary" { "Continue" }
+                    }
                 }
             }
         }
@@ -183,13 +267,6 @@ impl Renderable for FeedbackFormRenderer {
     }
 }
 
-#[derive(Deserialize)]
-pub struct FeedbackSubmissionForm {
-    target_judge_id: String,
-    #[serde(flatten)]
-    answers: HashMap<String, String>,
-}
-
 pub async fn do_submit_feedback(
     Path((tournament_id, private_url, round_id)): Path<(
         String,
@@ -198,7 +275,10 @@ pub async fn do_submit_feedback(
     )>,
     user: Option<User<true>>,
     mut conn: Conn<true>,
-    Form(form): Form<FeedbackSubmissionForm>,
+    // a list of pairs (rather than a struct) is used, because the answer to a
+    // multiple choice question is submitted as several values with the same
+    // name
+    Form(form): Form<Vec<(String, String)>>,
 ) -> StandardResponse {
     let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
     let round = Round::fetch(&tournament_id, &round_id, &mut *conn)?;
@@ -246,20 +326,94 @@ pub async fn do_submit_feedback(
         );
     }
 
-    let (debate, _targets) = if let Some(judge) = &judge {
+    let paths = tournament.feedback_paths(&round);
+    let (debate, targets) = if let Some(judge) = &judge {
         let debate =
             debate_of_judge_in_round(&judge.id, &round.id, &mut *conn)?;
-        let targets =
-            get_feedback_targets_for_judge(&judge.id, &debate.id, &mut *conn);
+        let targets = get_feedback_targets_for_judge(
+            &judge.id, &debate.id, &paths, &mut *conn,
+        );
         (debate, targets)
     } else {
         let speaker = speaker.as_ref().unwrap();
         let debate =
             debate_of_speaker_in_round(&speaker.id, &round.id, &mut *conn)?;
-        let targets = get_feedback_targets_for_speaker(&debate.id, &mut *conn);
+        let targets =
+            get_feedback_targets_for_speaker(&debate.id, &paths, &mut *conn);
         (debate, targets)
     };
 
+    let mut target_judge_id = None;
+    let mut values_of_question: HashMap<String, Vec<String>> = HashMap::new();
+    for (key, value) in form {
+        if key == "target_judge_id" {
+            target_judge_id = Some(value);
+        } else {
+            values_of_question.entry(key).or_default().push(value);
+        }
+    }
+    let Some(target_judge_id) = target_judge_id else {
+        return bad_request(
+            maud! { "Error: no judge was selected to give feedback on." }
+                .render(),
+        );
+    };
+    if !targets.iter().any(|target| target.id == target_judge_id) {
+        return bad_request(
+            maud! { "Error: you may not give feedback on that judge." }
+                .render(),
+        );
+    }
+
+    let path = feedback_path(
+        &debate.id,
+        judge.as_ref().map(|judge| judge.id.as_str()),
+        &target_judge_id,
+        &mut *conn,
+    );
+    let questions = feedback_questions::table
+        .filter(feedback_questions::tournament_id.eq(&tournament_id))
+        .order_by(feedback_questions::seq.asc())
+        .load::<FeedbackQuestion>(&mut *conn)
+        .unwrap();
+
+    let mut answers = Vec::new();
+    let mut errors = Vec::new();
+    for question in questions
+        .iter()
+        .filter(|question| question.is_asked(judge.is_none(), path))
+    {
+        let kind: FeedbackQuestionKind =
+            serde_json::from_str(&question.kind).unwrap();
+        match kind.parse_answer(
+            values_of_question
+                .get(&question.id)
+                .map(Vec::as_slice)
+                .unwrap_or_default(),
+        ) {
+            Ok(Some(answer)) => answers.push((question.id.clone(), answer)),
+            Ok(None) if question.required => errors.push(format!(
+                "\"{}\": an answer is required",
+                question.question
+            )),
+            Ok(None) => (),
+            Err(e) => errors.push(format!("\"{}\": {e}", question.question)),
+        }
+    }
+    if !errors.is_empty() {
+        return bad_request(
+            maud! {
+                p { "Error: your feedback could not be submitted." }
+                ul {
+                    @for error in &errors {
+                        li { (error) }
+                    }
+                }
+            }
+            .render(),
+        );
+    }
+
     conn.transaction::<_, diesel::result::Error, _>(|conn| {
         let feedback_id = Uuid::now_v7().to_string();
 
@@ -270,11 +424,11 @@ pub async fn do_submit_feedback(
                     tournament_id: tournament.id.clone(),
                     debate_id: debate.id.clone(),
                     judge_id: judge.id.clone(),
-                    target_judge_id: form.target_judge_id.clone(),
+                    target_judge_id: target_judge_id.clone(),
                 })
                 .execute(conn)?;
 
-            for (q_id, ans) in &form.answers {
+            for (q_id, ans) in &answers {
                 diesel::insert_into(answers_of_feedback_from_judges::table)
                     .values(FeedbackFromJudgesQuestionAnswer {
                         id: Uuid::now_v7().to_string(),
@@ -297,11 +451,11 @@ pub async fn do_submit_feedback(
                     tournament_id: tournament.id.clone(),
                     debate_id: debate.id.clone(),
                     team_id: team_id,
-                    target_judge_id: form.target_judge_id.clone(),
+                    target_judge_id: target_judge_id.clone(),
                 })
                 .execute(conn)?;
 
-            for (q_id, ans) in &form.answers {
+            for (q_id, ans) in &answers {
                 diesel::insert_into(answers_of_feedback_from_teams::table)
                     .values(FeedbackFromTeamsQuestionAnswer {
                         id: Uuid::now_v7().to_string(),
@@ -333,6 +487,37 @@ pub async fn do_submit_feedback(
     )
 }
 
+/// The status (`C`, `P` or `T`) of a judge on the panel of a debate.
+fn status_on_panel(
+    judge_id: &str,
+    debate_id: &str,
+    conn: &mut impl LoadConnection<Backend = diesel::sqlite::Sqlite>,
+) -> Option<String> {
+    judges_of_debate::table
+        .filter(judges_of_debate::debate_id.eq(debate_id))
+        .filter(judges_of_debate::judge_id.eq(judge_id))
+        .select(judges_of_debate::status)
+        .first::<String>(conn)
+        .optional()
+        .unwrap()
+}
+
+/// The path along which feedback on `target_judge_id` is given, by the judge
+/// `submitter_judge_id` (or by a team, if this is `None`).
+fn feedback_path(
+    debate_id: &str,
+    submitter_judge_id: Option<&str>,
+    target_judge_id: &str,
+    conn: &mut impl LoadConnection<Backend = diesel::sqlite::Sqlite>,
+) -> Option<FeedbackPath> {
+    let target_status = status_on_panel(target_judge_id, debate_id, conn)?;
+    let submitter_status = match submitter_judge_id {
+        Some(judge_id) => Some(status_on_panel(judge_id, debate_id, conn)?),
+        None => None,
+    };
+    FeedbackPath::between(submitter_status.as_deref(), &target_status)
+}
+
 fn debate_of_judge_in_round(
     judge_id: &str,
     round_id: &str,
@@ -395,44 +580,31 @@ fn debate_of_speaker_in_round(
     }
 }
 
+/// The judges on the panel who the judge may give feedback on, given the
+/// feedback paths which are open in the round.
 fn get_feedback_targets_for_judge(
     judge_id: &str,
     debate_id: &str,
+    paths: &[FeedbackPath],
     conn: &mut impl LoadConnection<Backend = diesel::sqlite::Sqlite>,
 ) -> Vec<Judge> {
-    let status = judges_of_debate::table
-        .filter(judges_of_debate::debate_id.eq(debate_id))
-        .filter(judges_of_debate::judge_id.eq(judge_id))
-        .select(judges_of_debate::status)
-        .first::<String>(conn)
-        .unwrap_or_default();
-
-    let _target_status = if status == "c" { "w" } else { "c" };
-
-    judges::table
-        .inner_join(
-            judges_of_debate::table
-                .on(judges_of_debate::judge_id.eq(judges::id)),
-        )
-        .filter(judges_of_debate::debate_id.eq(debate_id))
-        .filter(judges::id.ne(judge_id))
-        .select(judges::all_columns)
-        .load::<Judge>(conn)
-        .unwrap_or_default()
+    let panel = panel_of_debate(debate_id, conn);
+    feedback_targets(Some(judge_id), &panel, paths)
+        .into_iter()
+        .cloned()
+        .collect()
 }
 
+/// The judges on the panel who a team may give feedback on, given the
+/// feedback paths which are open in the round.
 fn get_feedback_targets_for_speaker(
     debate_id: &str,
+    paths: &[FeedbackPath],
     conn: &mut impl LoadConnection<Backend = diesel::sqlite::Sqlite>,
 ) -> Vec<Judge> {
-    judges::table
-        .inner_join(
-            judges_of_debate::table
-                .on(judges_of_debate::judge_id.eq(judges::id)),
-        )
-        .filter(judges_of_debate::debate_id.eq(debate_id))
-        .filter(judges_of_debate::status.eq("c"))
-        .select(judges::all_columns)
-        .load::<Judge>(conn)
-        .unwrap_or_default()
+    let panel = panel_of_debate(debate_id, conn);
+    feedback_targets(None, &panel, paths)
+        .into_iter()
+        .cloned()
+        .collect()
 }
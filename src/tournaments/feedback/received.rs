@@ -0,0 +1,162 @@
+//! The feedback which has been given on a judge.
+
+use std::collections::HashMap;
+
+use diesel::{connection::LoadConnection, prelude::*, sqlite::Sqlite};
+
+use crate::schema::{
+    answers_of_feedback_from_judges, answers_of_feedback_from_teams, debates,
+    feedback_of_judges, feedback_of_teams, judges, rounds, teams,
+};
+
+/// A single piece of feedback given on a judge.
+#[derive(Debug, Clone)]
+pub struct ReceivedFeedback {
+    pub id: String,
+    pub round_id: String,
+    pub round_seq: i64,
+    pub round_name: String,
+    pub debate_id: String,
+    pub from_team: bool,
+    /// The id of the team or judge who gave the feedback.
+    pub source_id: String,
+    pub source_name: String,
+    /// The answer to each question, indexed by question id.
+    pub answers: HashMap<String, String>,
+}
+
+/// Loads the feedback given on the judge, in round order. Where the same team
+/// or judge submitted feedback on the judge more than once in a debate, only
+/// the latest submission is included.
+pub fn received_feedback(
+    tournament_id: &str,
+    judge_id: &str,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> Vec<ReceivedFeedback> {
+    // (feedback_id, source_id, source_name, debate_id, round_id, round_seq,
+    // round_name)
+    type Row = (String, String, String, String, String, i64, String);
+
+    let from_judges = feedback_of_judges::table
+        .inner_join(
+            judges::table.on(judges::id.eq(feedback_of_judges::judge_id)),
+        )
+        .inner_join(
+            debates::table.on(debates::id.eq(feedback_of_judges::debate_id)),
+        )
+        .inner_join(rounds::table.on(rounds::id.eq(debates::round_id)))
+        .filter(feedback_of_judges::tournament_id.eq(tournament_id))
+        .filter(feedback_of_judges::target_judge_id.eq(judge_id))
+        .select((
+            feedback_of_judges::id,
+            feedback_of_judges::judge_id,
+            judges::name,
+            feedback_of_judges::debate_id,
+            rounds::id,
+            rounds::seq,
+            rounds::name,
+        ))
+        .load::<Row>(conn)
+        .unwrap();
+
+    let from_teams = feedback_of_teams::table
+        .inner_join(teams::table.on(teams::id.eq(feedback_of_teams::team_id)))
+        .inner_join(
+            debates::table.on(debates::id.eq(feedback_of_teams::debate_id)),
+        )
+        .inner_join(rounds::table.on(rounds::id.eq(debates::round_id)))
+        .filter(feedback_of_teams::tournament_id.eq(tournament_id))
+        .filter(feedback_of_teams::target_judge_id.eq(judge_id))
+        .select((
+            feedback_of_teams::id,
+            feedback_of_teams::team_id,
+            teams::name,
+            feedback_of_teams::debate_id,
+            rounds::id,
+            rounds::seq,
+            rounds::name,
+        ))
+        .load::<Row>(conn)
+        .unwrap();
+
+    // feedback ids are v7 UUIDs, so the latest submission has the greatest id
+    let mut latest: HashMap<(bool, String, String), (bool, Row)> =
+        HashMap::new();
+    for (from_team, row) in from_judges
+        .into_iter()
+        .map(|row| (false, row))
+        .chain(from_teams.into_iter().map(|row| (true, row)))
+    {
+        let key = (from_team, row.1.clone(), row.3.clone());
+        match latest.get(&key) {
+            Some((_, existing)) if existing.0 >= row.0 => (),
+            _ => {
+                latest.insert(key, (from_team, row));
+            }
+        }
+    }
+
+    let ids: Vec<&str> =
+        latest.values().map(|(_, row)| row.0.as_str()).collect();
+    let mut answers_of: HashMap<String, HashMap<String, String>> =
+        HashMap::new();
+    for (feedback_id, question_id, answer) in
+        answers_of_feedback_from_judges::table
+            .filter(answers_of_feedback_from_judges::feedback_id.eq_any(&ids))
+            .select((
+                answers_of_feedback_from_judges::feedback_id,
+                answers_of_feedback_from_judges::question_id,
+                answers_of_feedback_from_judges::answer,
+            ))
+            .load::<(String, String, String)>(conn)
+            .unwrap()
+            .into_iter()
+            .chain(
+                answers_of_feedback_from_teams::table
+                    .filter(
+                        answers_of_feedback_from_teams::feedback_id
+                            .eq_any(&ids),
+                    )
+                    .select((
+                        answers_of_feedback_from_teams::feedback_id,
+                        answers_of_feedback_from_teams::question_id,
+                        answers_of_feedback_from_teams::answer,
+                    ))
+                    .load::<(String, String, String)>(conn)
+                    .unwrap(),
+            )
+    {
+        answers_of
+            .entry(feedback_id)
+            .or_default()
+            .insert(question_id, answer);
+    }
+
+    let mut feedback: Vec<ReceivedFeedback> = latest
+        .into_values()
+        .map(
+            |(
+                from_team,
+                (id, source_id, source_name, debate_id, round_id, seq, name),
+            )| ReceivedFeedback {
+                answers: answers_of.remove(&id).unwrap_or_default(),
+                id,
+                round_id,
+                round_seq: seq,
+                round_name: name,
+                debate_id,
+                from_team,
+                source_id,
+                source_name,
+            },
+        )
+        .collect();
+    feedback.sort_by(|a, b| {
+        (a.round_seq, &a.round_name, &a.id).cmp(&(
+            b.round_seq,
+            &b.round_name,
+            &b.id,
+        ))
+    });
+    feedback
+}
@@ -0,0 +1,222 @@
+//! The feedback which judges can see about themselves once the adjudication
+//! core has released it (see `release_feedback_to_judges`). Only averages of
+//! the numeric questions and the free-text comments are shown, and nothing
+//! which identifies who gave the feedback (or in which round).
+
+use std::collections::HashSet;
+
+use diesel::{connection::LoadConnection, prelude::*, sqlite::Sqlite};
+use hypertext::prelude::*;
+
+use crate::{
+    schema::{feedback_questions, hidden_feedback_comments},
+    tournaments::feedback::{
+        FeedbackQuestion, manage::config::FeedbackQuestionKind,
+        received::ReceivedFeedback,
+    },
+};
+
+/// The `(feedback id, question id)` of each comment which has been hidden
+/// from the judge it is about.
+pub fn hidden_comments(
+    tournament_id: &str,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> HashSet<(String, String)> {
+    hidden_feedback_comments::table
+        .filter(hidden_feedback_comments::tournament_id.eq(tournament_id))
+        .select((
+            hidden_feedback_comments::feedback_id,
+            hidden_feedback_comments::question_id,
+        ))
+        .load::<(String, String)>(conn)
+        .unwrap()
+        .into_iter()
+        .collect()
+}
+
+/// The questions of the tournament, in order, along with their kinds.
+pub fn questions_with_kinds(
+    tournament_id: &str,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> Vec<(FeedbackQuestion, FeedbackQuestionKind)> {
+    feedback_questions::table
+        .filter(feedback_questions::tournament_id.eq(tournament_id))
+        .order_by(feedback_questions::seq.asc())
+        .load::<FeedbackQuestion>(conn)
+        .unwrap()
+        .into_iter()
+        .map(|question| {
+            let kind = serde_json::from_str(&question.kind).unwrap();
+            (question, kind)
+        })
+        .collect()
+}
+
+/// The released feedback on a single judge, as shown to that judge.
+pub struct ReleasedFeedback<'a> {
+    pub questions: &'a [(FeedbackQuestion, FeedbackQuestionKind)],
+    pub feedback: &'a [ReceivedFeedback],
+    pub hidden: &'a HashSet<(String, String)>,
+}
+
+impl ReleasedFeedback<'_> {
+    /// `(question, mean, number of answers)` for each numeric question which
+    /// has been answered.
+    fn averages(&self) -> Vec<(&str, f64, usize)> {
+        self.questions
+            .iter()
+            .filter_map(|(question, kind)| {
+                let values: Vec<f64> = self
+                    .feedback
+                    .iter()
+                    .filter_map(|f| {
+                        kind.numeric_answer(f.answers.get(&question.id)?)
+                    })
+                    .collect();
+                if values.is_empty() {
+                    return None;
+                }
+                let mean = values.iter().sum::<f64>() / values.len() as f64;
+                Some((question.question.as_str(), mean, values.len()))
+            })
+            .collect()
+    }
+
+    /// `(question, comments)` for each free-text question. The comments are
+    /// sorted, so that they cannot be matched up with the rounds they were
+    /// given in.
+    fn comments(&self) -> Vec<(&str, Vec<&str>)> {
+        self.questions
+            .iter()
+            .filter(|(_, kind)| matches!(kind, FeedbackQuestionKind::Text {}))
+            .filter_map(|(question, _)| {
+                let mut comments: Vec<&str> = self
+                    .feedback
+                    .iter()
+                    .filter(|f| {
+                        !self
+                            .hidden
+                            .contains(&(f.id.clone(), question.id.clone()))
+                    })
+                    .filter_map(|f| {
+                        f.answers
+                            .get(&question.id)
+                            .map(|answer| answer.trim())
+                            .filter(|answer| !answer.is_empty())
+                    })
+                    .collect();
+                if comments.is_empty() {
+                    return None;
+                }
+                comments.sort_unstable();
+                Some((question.question.as_str(), comments))
+            })
+            .collect()
+    }
+}
+
+impl Renderable for ReleasedFeedback<'_> {
+    fn render_to(
+        &self,
+        buffer: &mut hypertext::Buffer<hypertext::context::Node>,
+    ) {
+        let averages = self.averages();
+        let comments = self.comments();
+
+        maud! {
+            @if averages.is_empty() && comments.is_empty() {
+                p class="text-muted" { "No feedback has been received." }
+            }
+            @if !averages.is_empty() {
+                table class="table table-sm mb-4" {
+                    thead {
+                        tr {
+                            th scope="col" { "Question" }
+                            th scope="col" class="text-end" { "Average" }
+                            th scope="col" class="text-end" { "Responses" }
+                        }
+                    }
+                    tbody {
+                        @for (question, mean, n) in &averages {
+                            tr {
+                                td { (question) }
+                                td class="text-end" { (format!("{mean:.2}")) }
+                                td class="text-end" { (n) }
+                            }
+                        }
+                    }
+                }
+            }
+            @for (question, comments) in &comments {
+                h3 class="h6 fw-bold" { (question) }
+                ul class="mb-4" {
+                    @for comment in comments {
+                        li { (comment) }
+                    }
+                }
+            }
+        }
+        .render_to(buffer);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_released_feedback_hides_and_sorts_comments() {
+    use std::collections::HashMap;
+
+    let question = |id: &str, kind: FeedbackQuestionKind| {
+        (
+            FeedbackQuestion {
+                id: id.to_string(),
+                tournament_id: "t".to_string(),
+                question: id.to_string(),
+                kind: serde_json::to_string(&kind).unwrap(),
+                seq: 0,
+                for_judges: true,
+                for_teams: true,
+                required: false,
+                paths: None,
+            },
+            kind,
+        )
+    };
+    let questions = [
+        question(
+            "score",
+            FeedbackQuestionKind::IntegerScale { min: 1, max: 5 },
+        ),
+        question("comments", FeedbackQuestionKind::Text {}),
+    ];
+    let feedback = |id: &str, score: &str, comment: &str| ReceivedFeedback {
+        id: id.to_string(),
+        round_id: "r".to_string(),
+        round_seq: 1,
+        round_name: "Round 1".to_string(),
+        debate_id: "d".to_string(),
+        from_team: true,
+        source_id: "s".to_string(),
+        source_name: "Source".to_string(),
+        answers: HashMap::from([
+            ("score".to_string(), score.to_string()),
+            ("comments".to_string(), comment.to_string()),
+        ]),
+    };
+    let feedback = [
+        feedback("a", "4", "Zealous"),
+        feedback("b", "2", "Rude"),
+        feedback("c", "3", "Clear"),
+    ];
+    let hidden = HashSet::from([("b".to_string(), "comments".to_string())]);
+
+    let released = ReleasedFeedback {
+        questions: &questions,
+        feedback: &feedback,
+        hidden: &hidden,
+    };
+    assert_eq!(released.averages(), vec![("score", 3.0, 3)]);
+    assert_eq!(
+        released.comments(),
+        vec![("comments", vec!["Clear", "Zealous"])]
+    );
+}
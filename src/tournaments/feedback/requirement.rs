@@ -0,0 +1,396 @@
+//! Requiring teams and judges to submit feedback for completed rounds (see
+//! the `feedback_requirement` option of the tournament).
+
+use diesel::{connection::LoadConnection, prelude::*, sqlite::Sqlite};
+use hypertext::prelude::*;
+
+use crate::{
+    schema::{
+        debates, feedback_exemptions, judges_of_debate, rounds, teams_of_debate,
+    },
+    tournaments::{
+        Tournament,
+        feedback::manage::progress::{
+            ExpectedFeedback, expected_feedback_in_debate,
+        },
+        rounds::{Round, draws::Debate},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedbackRequirement {
+    Off,
+    /// Show a warning on the private URL page while feedback is outstanding.
+    Warn,
+    /// As `Warn`, but judges also cannot submit ballots while their feedback
+    /// is outstanding.
+    Block,
+}
+
+impl FeedbackRequirement {
+    pub fn of(tournament: &Tournament) -> Self {
+        match tournament.feedback_requirement.as_str() {
+            "off" => FeedbackRequirement::Off,
+            "warn" => FeedbackRequirement::Warn,
+            "block" => FeedbackRequirement::Block,
+            other => unreachable!("invalid feedback_requirement: {other}"),
+        }
+    }
+}
+
+/// A team or judge who may be required to submit feedback.
+#[derive(Debug, Clone, Copy)]
+pub enum FeedbackSource<'a> {
+    Team(&'a str),
+    Judge(&'a str),
+}
+
+impl FeedbackSource<'_> {
+    fn id(&self) -> &str {
+        match self {
+            FeedbackSource::Team(id) | FeedbackSource::Judge(id) => id,
+        }
+    }
+}
+
+pub fn is_exempt(
+    tournament_id: &str,
+    source: FeedbackSource,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> bool {
+    let query = feedback_exemptions::table
+        .filter(feedback_exemptions::tournament_id.eq(tournament_id));
+    let n = match source {
+        FeedbackSource::Team(id) => query
+            .filter(feedback_exemptions::team_id.eq(id))
+            .count()
+            .get_result::<i64>(conn),
+        FeedbackSource::Judge(id) => query
+            .filter(feedback_exemptions::judge_id.eq(id))
+            .count()
+            .get_result::<i64>(conn),
+    };
+    n.unwrap() > 0
+}
+
+/// The feedback which the team or judge has not yet submitted in the
+/// completed rounds of the tournament. This is always empty if feedback is
+/// not required, or if the team or judge has been exempted.
+pub fn outstanding_feedback(
+    tournament: &Tournament,
+    source: FeedbackSource,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> Vec<ExpectedFeedback> {
+    if FeedbackRequirement::of(tournament) == FeedbackRequirement::Off
+        || is_exempt(&tournament.id, source, conn)
+    {
+        return Vec::new();
+    }
+
+    let completed_rounds = rounds::table
+        .filter(rounds::tournament_id.eq(&tournament.id))
+        .filter(rounds::completed.eq(true))
+        .order_by(rounds::seq.asc())
+        .load::<Round>(conn)
+        .unwrap();
+    let round_ids = completed_rounds
+        .iter()
+        .map(|round| round.id.as_str())
+        .collect::<Vec<_>>();
+
+    let debates_of_source = match source {
+        FeedbackSource::Team(id) => debates::table
+            .filter(debates::round_id.eq_any(&round_ids))
+            .filter(diesel::dsl::exists(
+                teams_of_debate::table
+                    .filter(teams_of_debate::team_id.eq(id))
+                    .filter(teams_of_debate::debate_id.eq(debates::id)),
+            ))
+            .load::<Debate>(conn)
+            .unwrap(),
+        FeedbackSource::Judge(id) => debates::table
+            .filter(debates::round_id.eq_any(&round_ids))
+            .filter(diesel::dsl::exists(
+                judges_of_debate::table
+                    .filter(judges_of_debate::judge_id.eq(id))
+                    .filter(judges_of_debate::debate_id.eq(debates::id)),
+            ))
+            .load::<Debate>(conn)
+            .unwrap(),
+    };
+
+    let mut outstanding = Vec::new();
+    for round in &completed_rounds {
+        for debate in debates_of_source
+            .iter()
+            .filter(|debate| debate.round_id == round.id)
+        {
+            outstanding.extend(
+                expected_feedback_in_debate(tournament, round, debate, conn)
+                    .into_iter()
+                    .filter(|expected| {
+                        expected.source_id == source.id()
+                            && !expected.is_complete()
+                    }),
+            );
+        }
+    }
+    outstanding
+}
+
+/// The warning shown on a private URL page while feedback is outstanding.
+pub struct OutstandingFeedbackAlert<'a> {
+    pub tournament: &'a Tournament,
+    pub private_url: &'a str,
+    pub outstanding: &'a [ExpectedFeedback],
+}
+
+impl Renderable for OutstandingFeedbackAlert<'_> {
+    fn render_to(
+        &self,
+        buffer: &mut hypertext::Buffer<hypertext::context::Node>,
+    ) {
+        let blocks_ballots = FeedbackRequirement::of(self.tournament)
+            == FeedbackRequirement::Block;
+
+        maud! {
+            div class="alert alert-warning mb-5" role="alert" {
+                h2 class="h5 alert-heading" { "Feedback outstanding" }
+                p {
+                    "Please submit your feedback for the following rounds."
+                    @if blocks_ballots {
+                        " Judges cannot submit ballots until they have done so."
+                    }
+                }
+                ul class="mb-0" {
+                    @for expected in self.outstanding {
+                        li {
+                            a href=(format!("/tournaments/{}/privateurls/{}/rounds/{}/feedback/submit", self.tournament.id, self.private_url, expected.round.id)) {
+                                (expected.round.name)
+                            }
+                            ": feedback on "
+                            (expected.missing().iter().map(|judge| judge.name.as_str()).collect::<Vec<_>>().join(", "))
+                        }
+                    }
+                }
+            }
+        }
+        .render_to(buffer);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use diesel::sqlite::SqliteConnection;
+    use diesel_migrations::MigrationHarness;
+
+    use super::*;
+    use crate::schema::{feedback_of_judges, judges, teams, tournaments};
+
+    /// A tournament (with the given `feedback_requirement`) in which the
+    /// panellist and both teams of one completed debate owe feedback on the
+    /// chair. The chair owes no feedback.
+    pub(crate) fn tournament_with_completed_debate(
+        requirement: &str,
+    ) -> (SqliteConnection, Tournament) {
+        let mut conn = SqliteConnection::establish(":memory:").unwrap();
+        conn.run_pending_migrations(crate::MIGRATIONS).unwrap();
+        crate::tournaments::create::insert_tournament(
+            "t", "Test", "T", "test", "user", &mut conn,
+        )
+        .unwrap();
+        diesel::update(tournaments::table.filter(tournaments::id.eq("t")))
+            .set((
+                tournaments::feedback_requirement.eq(requirement),
+                tournaments::prelim_feedback_paths
+                    .eq(r#"["team_to_chair","panellist_to_chair"]"#),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+
+        diesel::insert_into(rounds::table)
+            .values((
+                rounds::id.eq("r1"),
+                rounds::tournament_id.eq("t"),
+                rounds::seq.eq(1),
+                rounds::name.eq("Round 1"),
+                rounds::kind.eq("P"),
+                rounds::completed.eq(true),
+                rounds::draw_status.eq("released_full"),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        diesel::insert_into(debates::table)
+            .values((
+                debates::id.eq("d1"),
+                debates::tournament_id.eq("t"),
+                debates::round_id.eq("r1"),
+                debates::number.eq(1),
+                debates::status.eq("confirmed"),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        for (side, id) in ["prop", "opp"].into_iter().enumerate() {
+            diesel::insert_into(teams::table)
+                .values((
+                    teams::id.eq(id),
+                    teams::tournament_id.eq("t"),
+                    teams::name.eq(id),
+                    teams::number.eq(side as i64 + 1),
+                ))
+                .execute(&mut conn)
+                .unwrap();
+            diesel::insert_into(teams_of_debate::table)
+                .values((
+                    teams_of_debate::id.eq(id),
+                    teams_of_debate::tournament_id.eq("t"),
+                    teams_of_debate::debate_id.eq("d1"),
+                    teams_of_debate::team_id.eq(id),
+                    teams_of_debate::side.eq(side as i64),
+                    teams_of_debate::seq.eq(0),
+                ))
+                .execute(&mut conn)
+                .unwrap();
+        }
+        for (number, (id, status)) in
+            [("chair", "C"), ("panellist", "P")].into_iter().enumerate()
+        {
+            diesel::insert_into(judges::table)
+                .values((
+                    judges::id.eq(id),
+                    judges::tournament_id.eq("t"),
+                    judges::name.eq(id),
+                    judges::email.eq(""),
+                    judges::private_url.eq(id),
+                    judges::number.eq(number as i64 + 1),
+                ))
+                .execute(&mut conn)
+                .unwrap();
+            diesel::insert_into(judges_of_debate::table)
+                .values((
+                    judges_of_debate::id.eq(id),
+                    judges_of_debate::tournament_id.eq("t"),
+                    judges_of_debate::debate_id.eq("d1"),
+                    judges_of_debate::judge_id.eq(id),
+                    judges_of_debate::status.eq(status),
+                ))
+                .execute(&mut conn)
+                .unwrap();
+        }
+
+        let tournament = Tournament::fetch("t", &mut conn).unwrap();
+        (conn, tournament)
+    }
+
+    pub(crate) fn exempt(
+        team_id: Option<&str>,
+        judge_id: Option<&str>,
+        conn: &mut SqliteConnection,
+    ) {
+        diesel::insert_into(feedback_exemptions::table)
+            .values((
+                feedback_exemptions::id.eq("exemption"),
+                feedback_exemptions::tournament_id.eq("t"),
+                feedback_exemptions::team_id.eq(team_id),
+                feedback_exemptions::judge_id.eq(judge_id),
+                feedback_exemptions::created_at
+                    .eq(chrono::Utc::now().naive_utc()),
+            ))
+            .execute(conn)
+            .unwrap();
+    }
+
+    #[test]
+    fn outstanding_feedback_follows_the_requirement() {
+        for requirement in ["warn", "block"] {
+            let (mut conn, tournament) =
+                tournament_with_completed_debate(requirement);
+            let outstanding = outstanding_feedback(
+                &tournament,
+                FeedbackSource::Judge("panellist"),
+                &mut conn,
+            );
+            assert_eq!(outstanding.len(), 1);
+            assert_eq!(outstanding[0].round.id, "r1");
+            assert_eq!(outstanding[0].missing()[0].id, "chair");
+            assert_eq!(
+                outstanding_feedback(
+                    &tournament,
+                    FeedbackSource::Team("prop"),
+                    &mut conn
+                )
+                .len(),
+                1
+            );
+            assert!(
+                outstanding_feedback(
+                    &tournament,
+                    FeedbackSource::Judge("chair"),
+                    &mut conn
+                )
+                .is_empty()
+            );
+        }
+
+        let (mut conn, tournament) = tournament_with_completed_debate("off");
+        assert!(
+            outstanding_feedback(
+                &tournament,
+                FeedbackSource::Judge("panellist"),
+                &mut conn
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn exempt_teams_and_judges_owe_no_feedback() {
+        let (mut conn, tournament) = tournament_with_completed_debate("block");
+        exempt(Some("prop"), None, &mut conn);
+
+        assert!(is_exempt("t", FeedbackSource::Team("prop"), &mut conn));
+        assert!(!is_exempt("t", FeedbackSource::Team("opp"), &mut conn));
+        assert!(!is_exempt("t", FeedbackSource::Judge("prop"), &mut conn));
+        assert!(
+            outstanding_feedback(
+                &tournament,
+                FeedbackSource::Team("prop"),
+                &mut conn
+            )
+            .is_empty()
+        );
+        assert_eq!(
+            outstanding_feedback(
+                &tournament,
+                FeedbackSource::Team("opp"),
+                &mut conn
+            )
+            .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn submitted_feedback_is_not_outstanding() {
+        let (mut conn, tournament) = tournament_with_completed_debate("block");
+        diesel::insert_into(feedback_of_judges::table)
+            .values((
+                feedback_of_judges::id.eq("f1"),
+                feedback_of_judges::tournament_id.eq("t"),
+                feedback_of_judges::debate_id.eq("d1"),
+                feedback_of_judges::judge_id.eq("panellist"),
+                feedback_of_judges::target_judge_id.eq("chair"),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+
+        assert!(
+            outstanding_feedback(
+                &tournament,
+                FeedbackSource::Judge("panellist"),
+                &mut conn
+            )
+            .is_empty()
+        );
+    }
+}
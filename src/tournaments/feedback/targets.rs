@@ -0,0 +1,89 @@
+//! Who may give feedback on whom. This is determined by the status of the
+//! submitter and of the target on the panel, together with the feedback
+//! paths which are open in the round (see [`Tournament::feedback_paths`]).
+//!
+//! [`Tournament::feedback_paths`]: crate::tournaments::Tournament::feedback_paths
+
+use diesel::{connection::LoadConnection, prelude::*, sqlite::Sqlite};
+
+use crate::{
+    schema::{judges, judges_of_debate},
+    tournaments::{feedback::FeedbackPath, participants::Judge},
+};
+
+/// The judges on the panel of the debate, along with their status.
+pub fn panel_of_debate(
+    debate_id: &str,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> Vec<(Judge, String)> {
+    judges::table
+        .inner_join(
+            judges_of_debate::table
+                .on(judges_of_debate::judge_id.eq(judges::id)),
+        )
+        .filter(judges_of_debate::debate_id.eq(debate_id))
+        .select((judges::all_columns, judges_of_debate::status))
+        .load::<(Judge, String)>(conn)
+        .unwrap_or_default()
+}
+
+/// The judges on `panel` who may be given feedback by the judge
+/// `submitter_judge_id`, or by a team if this is `None`.
+pub fn feedback_targets<'a>(
+    submitter_judge_id: Option<&str>,
+    panel: &'a [(Judge, String)],
+    paths: &[FeedbackPath],
+) -> Vec<&'a Judge> {
+    let submitter_status = match submitter_judge_id {
+        Some(judge_id) => {
+            match panel.iter().find(|(judge, _)| judge.id == judge_id) {
+                Some((_, status)) => Some(status.as_str()),
+                // the judge is not on this panel
+                None => return Vec::new(),
+            }
+        }
+        None => None,
+    };
+
+    panel
+        .iter()
+        .filter(|(judge, _)| Some(judge.id.as_str()) != submitter_judge_id)
+        .filter(|(_, target_status)| {
+            FeedbackPath::between(submitter_status, target_status)
+                .is_some_and(|path| paths.contains(&path))
+        })
+        .map(|(judge, _)| judge)
+        .collect()
+}
+
+#[cfg(test)]
+#[test]
+fn test_feedback_targets_follow_open_paths() {
+    let judge = |id: &str| Judge {
+        id: id.to_string(),
+        tournament_id: "t".to_string(),
+        name: id.to_string(),
+        email: String::new(),
+        institution_id: None,
+        private_url: id.to_string(),
+        number: 0,
+    };
+    let panel = vec![
+        (judge("chair"), "C".to_string()),
+        (judge("panellist"), "P".to_string()),
+        (judge("trainee"), "T".to_string()),
+    ];
+    let ids = |targets: Vec<&Judge>| {
+        targets.iter().map(|j| j.id.clone()).collect::<Vec<_>>()
+    };
+    let paths = [FeedbackPath::TeamToChair, FeedbackPath::PanellistToChair];
+
+    assert_eq!(ids(feedback_targets(None, &panel, &paths)), ["chair"]);
+    assert_eq!(
+        ids(feedback_targets(Some("panellist"), &panel, &paths)),
+        ["chair"]
+    );
+    assert!(feedback_targets(Some("chair"), &panel, &paths).is_empty());
+    assert!(feedback_targets(Some("trainee"), &panel, &paths).is_empty());
+    assert!(feedback_targets(Some("someone else"), &panel, &paths).is_empty());
+}
@@ -25,6 +25,8 @@ use crate::{
     template::Page,
     tournaments::{
         Tournament,
+        config::SpeakerMetric,
+        feedback::FeedbackPath,
         manage::sidebar::SidebarWrapper,
         participants::TournamentParticipants,
         rounds::{
@@ -43,6 +45,35 @@ fn default_true() -> bool {
     true
 }
 
+fn default_iron_speech_rule() -> String {
+    "both".to_string()
+}
+
+fn default_ballot_overdue_after_mins() -> i64 {
+    90
+}
+
+fn default_reply_tab_min_replies() -> i64 {
+    1
+}
+
+fn default_speaks_standardisation() -> String {
+    "mean_shift".to_string()
+}
+
+fn default_feedback_requirement() -> String {
+    "off".to_string()
+}
+
+fn default_team_names() -> String {
+    "full".to_string()
+}
+
+fn default_feedback_paths() -> String {
+    r#"["team_to_chair","chair_to_panellist","panellist_to_chair","trainee_to_chair"]"#
+        .to_string()
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 /// This struct is used to marshall the tournament configuration to and from
 /// the TOML format the user supplies.
@@ -78,6 +109,53 @@ pub struct TournamentConfig {
     pub team_standings_metrics: String,
     pub speaker_standings_metrics: String,
     pub exclude_from_speaker_standings_after: Option<i64>,
+    /// One of "both", "highest" or "first". Determines which of the speeches
+    /// count towards the speaker tab when a speaker gives more than one
+    /// substantive speech in the same debate.
+    #[serde(default = "default_iron_speech_rule")]
+    pub iron_speech_rule: String,
+    /// Minutes after the draw is released after which missing ballots are
+    /// shown as overdue.
+    #[serde(default = "default_ballot_overdue_after_mins")]
+    pub ballot_overdue_after_mins: i64,
+    #[serde(default)]
+    pub reply_tab_public: bool,
+    /// Speakers with fewer reply speeches than this are left off the reply
+    /// tab.
+    #[serde(default = "default_reply_tab_min_replies")]
+    pub reply_tab_min_replies: i64,
+    /// One of "mean_shift" or "z_score". Determines how each adjudicator's
+    /// scores are normalised for the standardised speaker score metrics.
+    #[serde(default = "default_speaks_standardisation")]
+    pub speaks_standardisation: String,
+    /// A JSON list of the paths along which feedback may be given in
+    /// preliminary rounds (any of "team_to_chair", "team_to_panellist",
+    /// "chair_to_panellist", "panellist_to_chair" and "trainee_to_chair").
+    #[serde(default = "default_feedback_paths")]
+    pub prelim_feedback_paths: String,
+    /// As `prelim_feedback_paths`, but for elimination rounds.
+    #[serde(default = "default_feedback_paths")]
+    pub elim_feedback_paths: String,
+    /// One of "off", "warn" or "block". Determines whether teams and judges
+    /// are warned about outstanding feedback for completed rounds, and (for
+    /// "block") whether judges may submit ballots while it is outstanding.
+    #[serde(default = "default_feedback_requirement")]
+    pub feedback_requirement: String,
+    /// Whether judges can see the anonymised feedback given on them from
+    /// their private URL pages (usually enabled after the tournament).
+    #[serde(default)]
+    pub release_feedback_to_judges: bool,
+    /// Either "full" or "code". Whether teams are shown on the public draw by
+    /// their full names (including the institution prefix, for teams which
+    /// use it) or by their code names (or numbers, for teams without one).
+    #[serde(default = "default_team_names")]
+    pub draw_team_names: String,
+    /// As `draw_team_names`, but for the public team tabs.
+    #[serde(default = "default_team_names")]
+    pub tab_team_names: String,
+    /// Whether team emoji are shown next to team names on the public draw.
+    #[serde(default)]
+    pub show_team_emoji: bool,
 }
 
 pub fn config_of_tournament(tournament: &Tournament) -> TournamentConfig {
@@ -119,6 +197,18 @@ pub fn config_of_tournament(tournament: &Tournament) -> TournamentConfig {
         require_elim_substantive_speaks: tournament
             .require_elim_substantive_speaks,
         require_elim_speaker_order: tournament.require_elim_speaker_order,
+        iron_speech_rule: tournament.iron_speech_rule.clone(),
+        ballot_overdue_after_mins: tournament.ballot_overdue_after_mins,
+        reply_tab_public: tournament.reply_tab_public,
+        reply_tab_min_replies: tournament.reply_tab_min_replies,
+        speaks_standardisation: tournament.speaks_standardisation.clone(),
+        prelim_feedback_paths: tournament.prelim_feedback_paths.clone(),
+        elim_feedback_paths: tournament.elim_feedback_paths.clone(),
+        feedback_requirement: tournament.feedback_requirement.clone(),
+        release_feedback_to_judges: tournament.release_feedback_to_judges,
+        draw_team_names: tournament.draw_team_names.clone(),
+        tab_team_names: tournament.tab_team_names.clone(),
+        show_team_emoji: tournament.show_team_emoji,
     }
 }
 
@@ -162,6 +252,100 @@ fn parse_tournament_config(
         }).render()).unwrap_err());
     }
 
+    if !["both", "highest", "first"]
+        .contains(&new_config.iron_speech_rule.as_str())
+    {
+        return Err(bad_request(Page::new().user(user.clone()).tournament(tournament.clone()).body(maud! {
+            "Error: `iron_speech_rule` should be one of 'both', 'highest' or 'first'. "
+            "You supplied " (new_config.iron_speech_rule)
+        }).render()).unwrap_err());
+    }
+
+    if let Err(err) = serde_json::from_str::<Vec<SpeakerMetric>>(
+        &new_config.speaker_standings_metrics,
+    ) {
+        return Err(bad_request(Page::new().user(user.clone()).tournament(tournament.clone()).body(maud! {
+            "Error: `speaker_standings_metrics` should be a JSON list containing "
+            "any of \"Avg\", \"StdDev\", \"Total\" or \"StandardisedAvg\" (" (err.to_string()) ")."
+        }).render()).unwrap_err());
+    }
+
+    if !["mean_shift", "z_score"]
+        .contains(&new_config.speaks_standardisation.as_str())
+    {
+        return Err(bad_request(Page::new().user(user.clone()).tournament(tournament.clone()).body(maud! {
+            "Error: `speaks_standardisation` should be one of 'mean_shift' or 'z_score'. "
+            "You supplied " (new_config.speaks_standardisation)
+        }).render()).unwrap_err());
+    }
+
+    for (key, paths) in [
+        ("prelim_feedback_paths", &new_config.prelim_feedback_paths),
+        ("elim_feedback_paths", &new_config.elim_feedback_paths),
+    ] {
+        if let Err(err) = serde_json::from_str::<Vec<FeedbackPath>>(paths) {
+            return Err(bad_request(Page::new().user(user.clone()).tournament(tournament.clone()).body(maud! {
+                "Error: `" (key) "` should be a JSON list containing any of "
+                "\"team_to_chair\", \"team_to_panellist\", \"chair_to_panellist\", "
+                "\"panellist_to_chair\" or \"trainee_to_chair\" (" (err.to_string()) ")."
+            }).render()).unwrap_err());
+        }
+    }
+
+    if !["off", "warn", "block"]
+        .contains(&new_config.feedback_requirement.as_str())
+    {
+        return Err(bad_request(Page::new().user(user.clone()).tournament(tournament.clone()).body(maud! {
+            "Error: `feedback_requirement` should be one of 'off', 'warn' or 'block'. "
+            "You supplied " (new_config.feedback_requirement)
+        }).render()).unwrap_err());
+    }
+
+    for (key, names) in [
+        ("draw_team_names", &new_config.draw_team_names),
+        ("tab_team_names", &new_config.tab_team_names),
+    ] {
+        if !["full", "code"].contains(&names.as_str()) {
+            return Err(bad_request(
+                Page::new()
+                    .user(user.clone())
+                    .tournament(tournament.clone())
+                    .body(maud! {
+                        "Error: `" (key) "` should be one of 'full' or 'code'. "
+                        "You supplied " (names)
+                    })
+                    .render(),
+            )
+            .unwrap_err());
+        }
+    }
+
+    if new_config.ballot_overdue_after_mins < 0 {
+        return Err(bad_request(
+            Page::new()
+                .user(user.clone())
+                .tournament(tournament.clone())
+                .body(maud! {
+                    "Error: `ballot_overdue_after_mins` must not be negative."
+                })
+                .render(),
+        )
+        .unwrap_err());
+    }
+
+    if new_config.reply_tab_min_replies < 0 {
+        return Err(bad_request(
+            Page::new()
+                .user(user.clone())
+                .tournament(tournament.clone())
+                .body(maud! {
+                    "Error: `reply_tab_min_replies` must not be negative."
+                })
+                .render(),
+        )
+        .unwrap_err());
+    }
+
     Ok(new_config)
 }
 
@@ -207,6 +391,18 @@ fn tournament_with_config(
     candidate.require_elim_substantive_speaks =
         config.require_elim_substantive_speaks;
     candidate.require_elim_speaker_order = config.require_elim_speaker_order;
+    candidate.iron_speech_rule = config.iron_speech_rule.clone();
+    candidate.ballot_overdue_after_mins = config.ballot_overdue_after_mins;
+    candidate.reply_tab_public = config.reply_tab_public;
+    candidate.reply_tab_min_replies = config.reply_tab_min_replies;
+    candidate.speaks_standardisation = config.speaks_standardisation.clone();
+    candidate.prelim_feedback_paths = config.prelim_feedback_paths.clone();
+    candidate.elim_feedback_paths = config.elim_feedback_paths.clone();
+    candidate.feedback_requirement = config.feedback_requirement.clone();
+    candidate.release_feedback_to_judges = config.release_feedback_to_judges;
+    candidate.draw_team_names = config.draw_team_names.clone();
+    candidate.tab_team_names = config.tab_team_names.clone();
+    candidate.show_team_emoji = config.show_team_emoji;
     candidate
 }
 
@@ -500,6 +696,10 @@ fn validate_existing_ballots_with_candidate_config(
                 }
             }
 
+            // the judges of a voting panel are expected to disagree
+            if !candidate.round_is_consensus(&round) {
+                continue;
+            }
             let ballot_set_problems =
                 BallotRepr::problems_of_set(&ballots, candidate, &debate);
             for problem in ballot_set_problems {
@@ -640,6 +840,21 @@ fn apply_tournament_config(
             .eq(new_config.require_elim_substantive_speaks),
         tournaments::require_elim_speaker_order
             .eq(new_config.require_elim_speaker_order),
+        tournaments::iron_speech_rule.eq(new_config.iron_speech_rule),
+        tournaments::ballot_overdue_after_mins
+            .eq(new_config.ballot_overdue_after_mins),
+        tournaments::reply_tab_public.eq(new_config.reply_tab_public),
+        tournaments::reply_tab_min_replies.eq(new_config.reply_tab_min_replies),
+        tournaments::speaks_standardisation
+            .eq(new_config.speaks_standardisation),
+        tournaments::prelim_feedback_paths.eq(new_config.prelim_feedback_paths),
+        tournaments::elim_feedback_paths.eq(new_config.elim_feedback_paths),
+        tournaments::feedback_requirement.eq(new_config.feedback_requirement),
+        tournaments::release_feedback_to_judges
+            .eq(new_config.release_feedback_to_judges),
+        tournaments::draw_team_names.eq(new_config.draw_team_names),
+        tournaments::tab_team_names.eq(new_config.tab_team_names),
+        tournaments::show_team_emoji.eq(new_config.show_team_emoji),
     ))
     .execute(conn)?;
     assert_eq!(n, 1);
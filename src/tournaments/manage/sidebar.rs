@@ -9,7 +9,11 @@ pub enum SidebarPage {
     Draw,
     Briefing,
     Ballots,
+    Feedback,
     Results,
+    TeamStandings,
+    SpeakerStandings,
+    ReplyStandings,
 }
 
 pub struct SidebarWrapper<'r, R: Renderable> {
@@ -140,12 +144,38 @@ impl<'r> Renderable for Sidebar<'r> {
                             "Ballots"
                         }
 
+                        a class=(format!("sidebar-stage-tab text-decoration-none {}", if self.active_page == Some(SidebarPage::Feedback) { "sidebar-stage-active" } else { "" }))
+                            href=(format!("/tournaments/{}/rounds/{}/feedback", self.tournament.id, seq)) {
+                            "Feedback"
+                        }
+
                         a class=(format!("sidebar-stage-tab text-decoration-none {}", if self.active_page == Some(SidebarPage::Results) { "sidebar-stage-active" } else { "" }))
                             href=(format!("/tournaments/{}/rounds/{}/results/manage", self.tournament.id, seq)) {
                             "Results"
                         }
                     }
                 }
+
+                div class="round-nav-divider" {}
+
+                nav class="round-stage-nav d-flex align-items-center gap-2" aria-label="Standings" {
+                    a class=(format!("sidebar-stage-tab text-decoration-none {}", if self.active_page == Some(SidebarPage::TeamStandings) { "sidebar-stage-active" } else { "" }))
+                        href=(format!("/tournaments/{}/standings/teams", self.tournament.id)) {
+                        "Team standings"
+                    }
+
+                    a class=(format!("sidebar-stage-tab text-decoration-none {}", if self.active_page == Some(SidebarPage::SpeakerStandings) { "sidebar-stage-active" } else { "" }))
+                        href=(format!("/tournaments/{}/standings/speakers", self.tournament.id)) {
+                        "Speaker standings"
+                    }
+
+                    @if self.tournament.reply_speakers {
+                        a class=(format!("sidebar-stage-tab text-decoration-none {}", if self.active_page == Some(SidebarPage::ReplyStandings) { "sidebar-stage-active" } else { "" }))
+                            href=(format!("/tournaments/{}/standings/replies", self.tournament.id)) {
+                            "Reply standings"
+                        }
+                    }
+                }
             }
         }.render_to(buffer);
     }
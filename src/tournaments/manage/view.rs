@@ -45,7 +45,8 @@ pub async fn admin_view_tournament(
                     (format!("/tournaments/{}/participants", tournament.id).as_str(), "Manage participants"),
                     (format!("/tournaments/{}/participants/privateurls", tournament.id).as_str(), "View private URLs"),
                     (format!("/tournaments/{}/rounds", tournament.id).as_str(), "Manage rounds"),
-                    (format!("/tournaments/{}/feedback/manage", tournament.id).as_str(), "Manage feedback questions")
+                    (format!("/tournaments/{}/feedback/manage", tournament.id).as_str(), "Manage feedback questions"),
+                    (format!("/tournaments/{}/export", tournament.id).as_str(), "Export data")
                 ]);
 
                 @if !active_rounds.is_empty() {
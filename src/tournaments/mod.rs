@@ -4,6 +4,12 @@ pub const WEBSOCKET_SCHEME: &str = "wss://";
 #[cfg(debug_assertions)]
 pub const WEBSOCKET_SCHEME: &str = "ws://";
 
+#[cfg(not(debug_assertions))]
+pub const HTTP_SCHEME: &str = "https://";
+
+#[cfg(debug_assertions)]
+pub const HTTP_SCHEME: &str = "http://";
+
 use diesel::{connection::LoadConnection, prelude::*, sqlite::Sqlite};
 
 use crate::{
@@ -12,15 +18,18 @@ use crate::{
         groups, members_of_group, org, permissions_of_group, tournaments,
     },
     tournaments::{
-        config::{PullupMetric, RankableTeamMetric},
+        config::{PullupMetric, RankableTeamMetric, SpeakerMetric},
+        feedback::FeedbackPath,
         rounds::{Round, ballots::aggregate::BallotAggregationMethod},
     },
     util_resp::{FailureResponse, unauthorized},
 };
 
+pub mod archive;
 pub mod categories;
 pub mod config;
 pub mod create;
+pub mod export;
 pub mod feedback;
 pub mod manage;
 pub mod motions;
@@ -70,6 +79,18 @@ pub struct Tournament {
     pub team_standings_metrics: String,
     pub speaker_standings_metrics: String,
     pub exclude_from_speaker_standings_after: Option<i64>,
+    pub iron_speech_rule: String,
+    pub ballot_overdue_after_mins: i64,
+    pub reply_tab_public: bool,
+    pub reply_tab_min_replies: i64,
+    pub speaks_standardisation: String,
+    pub prelim_feedback_paths: String,
+    pub elim_feedback_paths: String,
+    pub feedback_requirement: String,
+    pub release_feedback_to_judges: bool,
+    pub draw_team_names: String,
+    pub tab_team_names: String,
+    pub show_team_emoji: bool,
 }
 
 pub enum UserRole {
@@ -175,6 +196,14 @@ impl Tournament {
         }
     }
 
+    pub fn round_is_consensus(&self, round: &Round) -> bool {
+        if round.is_elim() {
+            self.elim_is_consensus()
+        } else {
+            self.pool_is_consensus()
+        }
+    }
+
     pub fn round_requires_speaks(&self, round: &Round) -> bool {
         if round.is_elim() {
             self.require_elim_substantive_speaks
@@ -198,41 +227,63 @@ impl Tournament {
             .and_then(rust_decimal::Decimal::from_f32_retain)
     }
 
+    pub fn max_reply_speak(&self) -> Option<rust_decimal::Decimal> {
+        self.reply_speech_max_speak
+            .and_then(rust_decimal::Decimal::from_f32_retain)
+    }
+
+    pub fn min_reply_speak(&self) -> Option<rust_decimal::Decimal> {
+        self.reply_speech_min_speak
+            .and_then(rust_decimal::Decimal::from_f32_retain)
+    }
+
+    /// Note: reply speeches use the same step as substantive speeches (as the
+    /// ballot form does).
     pub fn check_score_valid(
         &self,
         score: rust_decimal::Decimal,
         is_reply: bool,
         speaker_name: String,
     ) -> Result<(), String> {
-        if !is_reply {
-            if let Some(min) = self.min_substantive_speak() {
-                if score < min {
-                    return Err(format!(
-                        "Score of {score} for {speaker_name} is lower than the minimum permissible speak {min}.",
-                    ));
-                }
+        let (min, max, kind) = if is_reply {
+            (
+                self.min_reply_speak(),
+                self.max_reply_speak(),
+                "reply speak",
+            )
+        } else {
+            (
+                self.min_substantive_speak(),
+                self.max_substantive_speak(),
+                "speak",
+            )
+        };
+
+        if let Some(min) = min {
+            if score < min {
+                return Err(format!(
+                    "Score of {score} for {speaker_name} is lower than the minimum permissible {kind} {min}.",
+                ));
             }
+        }
 
-            if let Some(max) = self.max_substantive_speak() {
-                if max < score {
-                    return Err(format!(
-                        "Score of {score} for {speaker_name} is greater than the maximum permissible speak {max}.",
-                    ));
-                }
+        if let Some(max) = max {
+            if max < score {
+                return Err(format!(
+                    "Score of {score} for {speaker_name} is greater than the maximum permissible {kind} {max}.",
+                ));
             }
+        }
 
-            if let Some(step) = self.speak_step() {
-                if step != rust_decimal::Decimal::ZERO
-                    && score % step != rust_decimal::Decimal::ZERO
-                {
-                    return Err(format!(
-                        "Score of {score} for {speaker_name} does not match requirement \
-                         that the score be a multiple of {step}.",
-                    ));
-                }
+        if let Some(step) = self.speak_step() {
+            if step != rust_decimal::Decimal::ZERO
+                && score % step != rust_decimal::Decimal::ZERO
+            {
+                return Err(format!(
+                    "Score of {score} for {speaker_name} does not match requirement \
+                     that the score be a multiple of {step}.",
+                ));
             }
-        } else {
-            todo!("validation for reply speaks")
         }
 
         Ok(())
@@ -246,6 +297,20 @@ impl Tournament {
         serde_json::from_str(&self.team_standings_metrics).unwrap()
     }
 
+    pub fn speaker_metrics(&self) -> Vec<SpeakerMetric> {
+        serde_json::from_str(&self.speaker_standings_metrics).unwrap()
+    }
+
+    /// The paths along which feedback may be given in the round.
+    pub fn feedback_paths(&self, round: &Round) -> Vec<FeedbackPath> {
+        let paths = if round.is_elim() {
+            &self.elim_feedback_paths
+        } else {
+            &self.prelim_feedback_paths
+        };
+        serde_json::from_str(paths).unwrap()
+    }
+
     pub fn fetch(
         id: &str,
         conn: &mut impl LoadConnection<Backend = Sqlite>,
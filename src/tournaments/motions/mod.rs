@@ -1,3 +1,5 @@
+pub mod stats;
+
 use axum::extract::Path;
 use diesel::prelude::*;
 use hypertext::{maud, prelude::*};
@@ -18,6 +20,7 @@ use crate::{
 
 #[derive(Clone)]
 struct MotionsContent<'a> {
+    tournament_id: &'a str,
     motions: &'a Vec<(Motion, String)>,
 }
 
@@ -27,7 +30,12 @@ impl Renderable for MotionsContent<'_> {
         buffer: &mut hypertext::Buffer<hypertext::context::Node>,
     ) {
         maud! {
-            h1 { "Motions" }
+            div class="d-flex align-items-center justify-content-between" {
+                h1 { "Motions" }
+                a href=(format!("/tournaments/{}/motions/statistics", self.tournament_id)) class="btn btn-sm btn-outline-secondary" {
+                    "Statistics"
+                }
+            }
 
             @if self.motions.is_empty() {
                 p class="text-muted" { "No motions have been released yet." }
@@ -110,7 +118,10 @@ pub async fn public_motions_page(
 
     let current_rounds = Round::current_rounds(&tournament_id, &mut *conn);
 
-    let motions_content = MotionsContent { motions: &motions };
+    let motions_content = MotionsContent {
+        tournament_id: &tournament_id,
+        motions: &motions,
+    };
 
     if is_admin {
         success(
@@ -0,0 +1,514 @@
+//! Statistics on how each motion played out (e.g. how often each side won),
+//! which adjudication cores use to check their motions for side bias.
+
+use std::collections::{BTreeMap, HashMap};
+
+use axum::extract::Path;
+use diesel::prelude::*;
+use diesel::{connection::LoadConnection, sqlite::Sqlite};
+use hypertext::{maud, prelude::*};
+
+use crate::{
+    auth::User,
+    schema::{
+        agg_team_results_of_debate, ballots, motions_of_round, teams_of_debate,
+    },
+    state::Conn,
+    template::Page,
+    tournaments::{
+        Tournament,
+        manage::sidebar::SidebarWrapper,
+        rounds::{Motion, Round, TournamentRounds, side_names::name_of_side},
+    },
+    util_resp::{StandardResponse, success},
+};
+
+/// Critical values of the chi-squared distribution at the 5% significance
+/// level, indexed by the number of degrees of freedom minus one.
+const CHI_SQUARED_CRITICAL_VALUES: [f64; 7] =
+    [3.841, 5.991, 7.815, 9.488, 11.070, 12.592, 14.067];
+
+pub struct ChiSquared {
+    pub statistic: f64,
+    pub degrees_of_freedom: usize,
+}
+
+impl ChiSquared {
+    /// Computes Pearson's chi-squared statistic for the hypothesis that every
+    /// category is equally likely (i.e. that no position is favoured). Returns
+    /// `None` if there is nothing to test.
+    fn uniform(observed: &[f64]) -> Option<Self> {
+        let total: f64 = observed.iter().sum();
+        if observed.len() < 2 || total <= 0.0 {
+            return None;
+        }
+
+        let expected = total / observed.len() as f64;
+        let statistic = observed
+            .iter()
+            .map(|o| (o - expected).powi(2) / expected)
+            .sum();
+
+        Some(ChiSquared {
+            statistic,
+            degrees_of_freedom: observed.len() - 1,
+        })
+    }
+
+    /// Whether the statistic indicates a side bias at the 5% significance
+    /// level.
+    pub fn is_significant(&self) -> bool {
+        CHI_SQUARED_CRITICAL_VALUES
+            .get(self.degrees_of_freedom - 1)
+            .is_some_and(|critical| self.statistic > *critical)
+    }
+}
+
+pub struct MotionStats {
+    pub motion: Motion,
+    pub round_name: String,
+    /// Motions of elimination rounds are reported separately, because in
+    /// elimination rounds teams either advance (1 point) or are eliminated
+    /// (0 points), and in BP two teams advance from each debate.
+    pub is_elim: bool,
+    pub n_debates: usize,
+    /// The points earned by the team in each position (i.e. `(side, seq)`)
+    /// in every debate on this motion.
+    pub points_of_position: BTreeMap<(i64, i64), Vec<i64>>,
+    /// The number of debates in which the team in each position took the most
+    /// points (or, in elimination rounds, advanced).
+    pub wins_of_position: BTreeMap<(i64, i64), usize>,
+}
+
+impl MotionStats {
+    /// Computes the statistics of every motion of the given rounds. When
+    /// `only_public` is set, motions which have not been published are
+    /// omitted, as are the results of rounds whose results are not public.
+    pub fn of_rounds(
+        tournament: &Tournament,
+        rounds: &[Round],
+        only_public: bool,
+        conn: &mut impl LoadConnection<Backend = Sqlite>,
+    ) -> Vec<Self> {
+        let motions = motions_of_round::table
+            .filter(motions_of_round::tournament_id.eq(&tournament.id))
+            .order_by(motions_of_round::id.asc())
+            .load::<Motion>(conn)
+            .unwrap();
+
+        // the motion which was debated is recorded on each ballot; later
+        // ballots take precedence
+        let motion_of_debate: HashMap<String, String> = ballots::table
+            .filter(ballots::tournament_id.eq(&tournament.id))
+            .order_by(ballots::submitted_at.asc())
+            .select((ballots::debate_id, ballots::motion_id))
+            .load::<(String, String)>(conn)
+            .unwrap()
+            .into_iter()
+            .collect();
+
+        let position_of_team_in_debate: HashMap<(String, String), (i64, i64)> =
+            teams_of_debate::table
+                .filter(teams_of_debate::tournament_id.eq(&tournament.id))
+                .select((
+                    teams_of_debate::debate_id,
+                    teams_of_debate::team_id,
+                    teams_of_debate::side,
+                    teams_of_debate::seq,
+                ))
+                .load::<(String, String, i64, i64)>(conn)
+                .unwrap()
+                .into_iter()
+                .map(|(debate, team, side, seq)| ((debate, team), (side, seq)))
+                .collect();
+
+        let mut results_of_debate: HashMap<String, Vec<((i64, i64), i64)>> =
+            HashMap::new();
+        for (debate, team, points) in agg_team_results_of_debate::table
+            .filter(
+                agg_team_results_of_debate::tournament_id.eq(&tournament.id),
+            )
+            .select((
+                agg_team_results_of_debate::debate_id,
+                agg_team_results_of_debate::team_id,
+                agg_team_results_of_debate::points,
+            ))
+            .load::<(String, String, Option<i64>)>(conn)
+            .unwrap()
+        {
+            let (Some(points), Some(position)) = (
+                points,
+                position_of_team_in_debate.get(&(debate.clone(), team)),
+            ) else {
+                continue;
+            };
+            results_of_debate
+                .entry(debate)
+                .or_default()
+                .push((*position, points));
+        }
+
+        let positions = (0..tournament.teams_per_side)
+            .flat_map(|seq| [(0, seq), (1, seq)])
+            .collect::<Vec<_>>();
+
+        motions
+            .into_iter()
+            .filter(|motion| !only_public || motion.published_at.is_some())
+            .filter_map(|motion| {
+                let round = rounds.iter().find(|r| r.id == motion.round_id)?;
+                let mut stats = MotionStats {
+                    round_name: round.name.clone(),
+                    is_elim: round.is_elim(),
+                    n_debates: 0,
+                    points_of_position: positions
+                        .iter()
+                        .map(|pos| (*pos, Vec::new()))
+                        .collect(),
+                    wins_of_position: positions
+                        .iter()
+                        .map(|pos| (*pos, 0))
+                        .collect(),
+                    motion,
+                };
+                if only_public && !round.is_results_public() {
+                    return Some(stats);
+                }
+
+                for (debate, results) in &results_of_debate {
+                    if motion_of_debate.get(debate) != Some(&stats.motion.id) {
+                        continue;
+                    }
+                    stats.add_debate(results);
+                }
+
+                Some(stats)
+            })
+            .collect()
+    }
+
+    fn add_debate(&mut self, results: &[((i64, i64), i64)]) {
+        self.n_debates += 1;
+        let max_points = results.iter().map(|(_, points)| *points).max();
+        let is_elim = self.is_elim;
+        let won = |points: i64| {
+            if is_elim {
+                points > 0
+            } else {
+                Some(points) == max_points
+            }
+        };
+        for (position, points) in results {
+            self.points_of_position
+                .entry(*position)
+                .or_default()
+                .push(*points);
+            if won(*points) {
+                *self.wins_of_position.entry(*position).or_default() += 1;
+            }
+        }
+    }
+
+    pub fn average_points(&self, position: (i64, i64)) -> Option<f64> {
+        let points = self.points_of_position.get(&position)?;
+        if points.is_empty() {
+            return None;
+        }
+        Some(points.iter().sum::<i64>() as f64 / points.len() as f64)
+    }
+
+    /// The proportion of debates in which the team in this position took the
+    /// most points.
+    pub fn win_rate(&self, position: (i64, i64)) -> Option<f64> {
+        if self.n_debates == 0 {
+            return None;
+        }
+        Some(
+            *self.wins_of_position.get(&position)? as f64
+                / self.n_debates as f64,
+        )
+    }
+
+    /// The proportion of wins (or, in elimination rounds, of advancing
+    /// places) taken by teams on this side (`0` for proposition, `1` for
+    /// opposition).
+    pub fn win_rate_of_side(&self, side: i64) -> Option<f64> {
+        let total: usize = self.wins_of_position.values().sum();
+        if total == 0 {
+            return None;
+        }
+        let wins: usize = self
+            .wins_of_position
+            .iter()
+            .filter(|((s, _), _)| *s == side)
+            .map(|(_, wins)| *wins)
+            .sum();
+        Some(wins as f64 / total as f64)
+    }
+
+    /// Tests whether the points are spread evenly across the positions.
+    pub fn chi_squared(&self) -> Option<ChiSquared> {
+        ChiSquared::uniform(
+            &self
+                .points_of_position
+                .values()
+                .map(|points| points.iter().sum::<i64>() as f64)
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+struct MotionStatsContent<'a> {
+    tournament: &'a Tournament,
+    stats: &'a [MotionStats],
+}
+
+fn percentage(rate: Option<f64>) -> String {
+    rate.map(|rate| format!("{:.0}%", rate * 100.0))
+        .unwrap_or_else(|| "—".to_string())
+}
+
+impl Renderable for MotionStatsContent<'_> {
+    fn render_to(
+        &self,
+        buffer: &mut hypertext::Buffer<hypertext::context::Node>,
+    ) {
+        let (elim, prelim): (Vec<_>, Vec<_>) =
+            self.stats.iter().partition(|stats| stats.is_elim);
+
+        maud! {
+            a href=(format!("/tournaments/{}/motions", self.tournament.id)) class="btn btn-sm btn-outline-secondary mb-3" {
+                "← Back to Motions"
+            }
+            h1 { "Motion statistics" }
+
+            @if self.stats.is_empty() {
+                p class="text-muted" { "No motions have been released yet." }
+            }
+            @for stats in prelim.iter().copied() {
+                MotionStatsCard tournament=(self.tournament) stats=(stats);
+            }
+            @if !elim.is_empty() {
+                h2 class="h4 mt-5" { "Elimination rounds" }
+                p class="text-muted" {
+                    "These are reported separately from the preliminary rounds, "
+                    "using the proportion of teams in each position which advanced."
+                }
+                @for stats in elim.iter().copied() {
+                    MotionStatsCard tournament=(self.tournament) stats=(stats);
+                }
+            }
+        }
+        .render_to(buffer);
+    }
+}
+
+struct MotionStatsCard<'a> {
+    tournament: &'a Tournament,
+    stats: &'a MotionStats,
+}
+
+impl Renderable for MotionStatsCard<'_> {
+    fn render_to(
+        &self,
+        buffer: &mut hypertext::Buffer<hypertext::context::Node>,
+    ) {
+        let stats = self.stats;
+        maud! {
+            div class="card mb-4" {
+                div class="card-body" {
+                    h2 class="h6 text-muted text-uppercase" { (stats.round_name) }
+                    p class="fw-semibold" { (stats.motion.motion) }
+                    @if stats.n_debates == 0 {
+                        p class="text-muted mb-0" { "No results are available yet." }
+                    } @else {
+                        p class="small text-muted" {
+                            (stats.n_debates) " debate(s). "
+                            @if stats.is_elim {
+                                "Government took " (percentage(stats.win_rate_of_side(0)))
+                                " of the places in the next round, opposition took "
+                                (percentage(stats.win_rate_of_side(1)))
+                            } @else {
+                                "Government won " (percentage(stats.win_rate_of_side(0)))
+                                ", opposition won " (percentage(stats.win_rate_of_side(1)))
+                            }
+                            "."
+                        }
+                        table class="table table-sm" {
+                            thead {
+                                tr {
+                                    th scope="col" { "Position" }
+                                    @if stats.is_elim {
+                                        th scope="col" { "Advanced" }
+                                    } @else {
+                                        th scope="col" { "Average points" }
+                                        th scope="col" { "Win rate" }
+                                    }
+                                }
+                            }
+                            tbody {
+                                @for position in stats.points_of_position.keys() {
+                                    tr {
+                                        td { (name_of_side(self.tournament, position.0, position.1, false)) }
+                                        @if !stats.is_elim {
+                                            td {
+                                                (stats.average_points(*position).map(|avg| format!("{avg:.2}")).unwrap_or_else(|| "—".to_string()))
+                                            }
+                                        }
+                                        td { (percentage(stats.win_rate(*position))) }
+                                    }
+                                }
+                            }
+                        }
+                        @if let Some(chi_squared) = stats.chi_squared() {
+                            p class="small mb-0" {
+                                "χ² = " (format!("{:.2}", chi_squared.statistic))
+                                " (" (chi_squared.degrees_of_freedom) " degree(s) of freedom): "
+                                @if chi_squared.is_significant() {
+                                    span class="text-danger" { "significant side bias at the 5% level." }
+                                } @else {
+                                    "no significant side bias at the 5% level."
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        .render_to(buffer);
+    }
+}
+
+pub async fn motion_statistics_page(
+    Path(tournament_id): Path<String>,
+    user: Option<User<true>>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    let rounds_data =
+        TournamentRounds::fetch(&tournament_id, &mut *conn).unwrap();
+    let all_rounds: Vec<Round> = rounds_data
+        .prelim
+        .iter()
+        .chain(rounds_data.elim.iter())
+        .cloned()
+        .collect();
+
+    let is_admin = if let Some(ref u) = user {
+        tournament
+            .check_user_is_superuser(&u.id, &mut *conn)
+            .is_ok()
+    } else {
+        false
+    };
+
+    let stats =
+        MotionStats::of_rounds(&tournament, &all_rounds, !is_admin, &mut *conn);
+    let current_rounds = Round::current_rounds(&tournament_id, &mut *conn);
+    let content = MotionStatsContent {
+        tournament: &tournament,
+        stats: &stats,
+    };
+
+    if is_admin {
+        success(
+            Page::new()
+                .active_nav(crate::template::ActiveNav::Motions)
+                .user_opt(user)
+                .tournament(tournament.clone())
+                .current_rounds(current_rounds.clone())
+                .body(maud! {
+                    SidebarWrapper tournament=(&tournament) rounds=(&rounds_data) selected_seq=(current_rounds.first().map(|r| r.seq)) active_page=(None) {
+                        (content)
+                    }
+                })
+                .render(),
+        )
+    } else {
+        success(
+            Page::new()
+                .active_nav(crate::template::ActiveNav::Motions)
+                .user_opt(user)
+                .tournament(tournament.clone())
+                .current_rounds(current_rounds)
+                .body(maud! {
+                    div class="container py-5 px-4" {
+                        (content)
+                    }
+                })
+                .render(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChiSquared, MotionStats};
+    use crate::tournaments::rounds::Motion;
+
+    fn stats(is_elim: bool) -> MotionStats {
+        let positions = [(0, 0), (1, 0), (0, 1), (1, 1)];
+        MotionStats {
+            motion: Motion {
+                id: "m".to_string(),
+                tournament_id: "t".to_string(),
+                round_id: "r".to_string(),
+                infoslide: None,
+                motion: "This House would test".to_string(),
+                published_at: None,
+            },
+            round_name: "Round".to_string(),
+            is_elim,
+            n_debates: 0,
+            points_of_position: positions
+                .iter()
+                .map(|pos| (*pos, Vec::new()))
+                .collect(),
+            wins_of_position: positions.iter().map(|pos| (*pos, 0)).collect(),
+        }
+    }
+
+    #[test]
+    fn bp_elims_count_advancing_teams() {
+        let results = [((0, 0), 1), ((1, 0), 0), ((0, 1), 1), ((1, 1), 0)];
+
+        let mut elim = stats(true);
+        elim.add_debate(&results);
+        elim.add_debate(&results);
+        assert_eq!(elim.win_rate((0, 0)), Some(1.0));
+        assert_eq!(elim.win_rate((1, 0)), Some(0.0));
+        assert_eq!(elim.win_rate_of_side(0), Some(1.0));
+
+        // in prelims only the team which took the most points wins
+        let mut prelim = stats(false);
+        prelim.add_debate(&[
+            ((0, 0), 3),
+            ((1, 0), 2),
+            ((0, 1), 1),
+            ((1, 1), 0),
+        ]);
+        assert_eq!(prelim.win_rate((0, 0)), Some(1.0));
+        assert_eq!(prelim.win_rate((1, 0)), Some(0.0));
+        assert_eq!(prelim.win_rate_of_side(1), Some(0.0));
+        assert_eq!(prelim.wins_of_position.values().sum::<usize>(), 1);
+    }
+
+    #[test]
+    fn chi_squared_flags_lopsided_results() {
+        // 30 proposition wins out of 40
+        let lopsided = ChiSquared::uniform(&[30.0, 10.0]).unwrap();
+        assert_eq!(lopsided.degrees_of_freedom, 1);
+        assert!((lopsided.statistic - 10.0).abs() < 1e-9);
+        assert!(lopsided.is_significant());
+
+        let balanced = ChiSquared::uniform(&[22.0, 18.0]).unwrap();
+        assert!(!balanced.is_significant());
+
+        // BP: points by position
+        let bp = ChiSquared::uniform(&[30.0, 30.0, 30.0, 30.0]).unwrap();
+        assert_eq!(bp.degrees_of_freedom, 3);
+        assert_eq!(bp.statistic, 0.0);
+
+        assert!(ChiSquared::uniform(&[0.0, 0.0]).is_none());
+    }
+}
@@ -71,6 +71,24 @@ pub async fn create_teams_page(
 pub struct CreateTeamForm {
     pub name: String,
     pub institution_id: String,
+    #[serde(default)]
+    pub code_name: String,
+    #[serde(default)]
+    pub emoji: String,
+    #[serde(default)]
+    pub use_institution_prefix: bool,
+}
+
+impl CreateTeamForm {
+    /// The code name, or `None` if it was left blank.
+    pub fn code_name(&self) -> Option<&str> {
+        Some(self.code_name.trim()).filter(|code_name| !code_name.is_empty())
+    }
+
+    /// The emoji, or `None` if it was left blank.
+    pub fn emoji(&self) -> Option<&str> {
+        Some(self.emoji.trim()).filter(|emoji| !emoji.is_empty())
+    }
 }
 
 #[tracing::instrument(skip(conn, tx, form))]
@@ -181,6 +199,9 @@ pub async fn do_create_team(
             teams::name.eq(&form.name),
             teams::institution_id.eq(inst.as_ref().map(|inst| inst.id.clone())),
             teams::number.eq(next_number),
+            teams::code_name.eq(form.code_name()),
+            teams::emoji.eq(form.emoji()),
+            teams::use_institution_prefix.eq(form.use_institution_prefix),
         ))
         .execute(&mut *conn)
         .unwrap();
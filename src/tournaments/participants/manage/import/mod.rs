@@ -0,0 +1,948 @@
+//! Bulk import of institutions, teams (with their speakers), judges and rooms
+//! from Tabbycat-style CSV files. The CSV is first validated and previewed,
+//! and then imported in a single transaction.
+
+use std::collections::{HashMap, HashSet};
+
+use axum::{
+    extract::{Extension, Form, Path},
+    response::Redirect,
+};
+use axum_extra::extract::Multipart;
+use diesel::{connection::LoadConnection, prelude::*, sqlite::Sqlite};
+use hypertext::prelude::*;
+use serde::Deserialize;
+use tokio::sync::broadcast::Sender;
+use uuid::Uuid;
+
+use crate::{
+    auth::User,
+    msg::{Msg, MsgContents},
+    schema::{
+        institutions, judges, rooms, speaker_categories, speakers,
+        speakers_of_category, speakers_of_team, team_categories, teams,
+    },
+    state::Conn,
+    template::Page,
+    tournaments::{
+        Tournament,
+        manage::sidebar::SidebarWrapper,
+        rounds::{Round, TournamentRounds},
+        snapshots::take_snapshot,
+        standings::compute::refresh_saved_team_standings,
+    },
+    util_resp::{StandardResponse, bad_request, see_other_ok, success},
+};
+
+pub mod rows;
+
+use rows::{InstitutionRow, JudgeRow, RoomRow, TeamRow};
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportKind {
+    Institutions,
+    Teams,
+    Judges,
+    Rooms,
+}
+
+impl ImportKind {
+    const ALL: [ImportKind; 4] = [
+        ImportKind::Institutions,
+        ImportKind::Teams,
+        ImportKind::Judges,
+        ImportKind::Rooms,
+    ];
+
+    fn form_value(&self) -> &'static str {
+        match self {
+            ImportKind::Institutions => "institutions",
+            ImportKind::Teams => "teams",
+            ImportKind::Judges => "judges",
+            ImportKind::Rooms => "rooms",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            ImportKind::Institutions => "Institutions",
+            ImportKind::Teams => "Teams",
+            ImportKind::Judges => "Judges",
+            ImportKind::Rooms => "Rooms",
+        }
+    }
+
+    /// The columns which the CSV file should have.
+    fn columns(&self) -> &'static str {
+        match self {
+            ImportKind::Institutions => {
+                "full_name, short_code (and optionally region)"
+            }
+            ImportKind::Teams => {
+                "full_name, institution, categories, and speaker1_name, \
+                 speaker1_email, speaker1_categories, speaker2_name, ... for \
+                 each speaker"
+            }
+            ImportKind::Judges => "name, email, institution",
+            ImportKind::Rooms => "name, priority",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ImportRecord {
+    Institution(InstitutionRow),
+    Team(TeamRow),
+    Judge(JudgeRow),
+    Room(RoomRow),
+}
+
+impl ImportRecord {
+    /// A short description of the row, shown in the preview.
+    fn describe(&self) -> String {
+        match self {
+            ImportRecord::Institution(row) => {
+                format!("{} ({})", row.full_name, row.short_code)
+            }
+            ImportRecord::Team(row) => {
+                let speakers = row
+                    .speakers
+                    .iter()
+                    .map(|speaker| match &speaker.email {
+                        Some(email) => format!("{} <{email}>", speaker.name),
+                        None => speaker.name.clone(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                match &row.institution {
+                    Some(institution) => {
+                        format!("{} ({institution}): {speakers}", row.full_name)
+                    }
+                    None => format!("{}: {speakers}", row.full_name),
+                }
+            }
+            ImportRecord::Judge(row) => {
+                let mut description = row.name.clone();
+                if let Some(email) = &row.email {
+                    description.push_str(&format!(" <{email}>"));
+                }
+                if let Some(institution) = &row.institution {
+                    description.push_str(&format!(" ({institution})"));
+                }
+                description
+            }
+            ImportRecord::Room(row) => {
+                format!("{} (priority {})", row.name, row.priority)
+            }
+        }
+    }
+}
+
+/// A row of the CSV file, along with any problems which prevent it from
+/// being imported.
+#[derive(Debug)]
+struct ImportRow {
+    /// The line of the file the row is on.
+    line: u64,
+    /// `None` if the row could not be read at all.
+    record: Option<ImportRecord>,
+    errors: Vec<String>,
+    /// Columns which were read but will not be imported, so that the user
+    /// knows they have been left out.
+    warnings: Vec<String>,
+}
+
+/// What already exists in the tournament, which the imported rows are
+/// checked against.
+struct Existing {
+    /// The id of each institution, indexed by its lowercased name and code.
+    institutions: HashMap<String, String>,
+    /// The lowercased names of the existing institutions, teams, judges or
+    /// rooms (whichever is being imported).
+    names: HashSet<String>,
+}
+
+impl Existing {
+    fn load(
+        tournament_id: &str,
+        kind: ImportKind,
+        conn: &mut impl LoadConnection<Backend = Sqlite>,
+    ) -> Self {
+        let all_institutions = institutions::table
+            .filter(institutions::tournament_id.eq(tournament_id))
+            .select((institutions::id, institutions::name, institutions::code))
+            .load::<(String, String, String)>(conn)
+            .unwrap();
+        let mut institutions = HashMap::new();
+        for (id, _, code) in &all_institutions {
+            institutions.insert(code.to_lowercase(), id.clone());
+        }
+        // names take precedence over codes
+        for (id, name, _) in &all_institutions {
+            institutions.insert(name.to_lowercase(), id.clone());
+        }
+
+        let names = match kind {
+            ImportKind::Institutions => all_institutions
+                .into_iter()
+                .map(|(_, name, _)| name)
+                .collect(),
+            ImportKind::Teams => teams::table
+                .filter(teams::tournament_id.eq(tournament_id))
+                .select(teams::name)
+                .load::<String>(conn)
+                .unwrap(),
+            ImportKind::Judges => judges::table
+                .filter(judges::tournament_id.eq(tournament_id))
+                .select(judges::name)
+                .load::<String>(conn)
+                .unwrap(),
+            ImportKind::Rooms => rooms::table
+                .filter(rooms::tournament_id.eq(tournament_id))
+                .select(rooms::name)
+                .load::<String>(conn)
+                .unwrap(),
+        }
+        .into_iter()
+        .map(|name| name.trim().to_lowercase())
+        .collect();
+
+        Existing {
+            institutions,
+            names,
+        }
+    }
+
+    fn institution_id(&self, institution: &Option<String>) -> Option<&str> {
+        let institution = institution.as_deref()?.trim();
+        self.institutions
+            .get(&institution.to_lowercase())
+            .map(String::as_str)
+    }
+}
+
+fn is_blank(value: &Option<String>) -> bool {
+    value.as_deref().is_none_or(|value| value.trim().is_empty())
+}
+
+fn nonblank(value: &Option<String>) -> Option<&str> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+fn check_email(email: &Option<String>, whose: &str, errors: &mut Vec<String>) {
+    match email.as_deref().map(str::trim) {
+        None | Some("") => errors.push(format!("{whose} has no email address")),
+        Some(email) if !email.contains('@') => errors
+            .push(format!("{whose} has an invalid email address ({email})")),
+        Some(_) => (),
+    }
+}
+
+fn check_institution(
+    institution: &Option<String>,
+    existing: &Existing,
+    errors: &mut Vec<String>,
+) {
+    if !is_blank(institution) && existing.institution_id(institution).is_none()
+    {
+        errors.push(format!(
+            "the institution {} does not exist (import it first)",
+            institution.as_deref().unwrap().trim()
+        ));
+    }
+}
+
+/// The columns of a judge row which are read, but which have no equivalent
+/// here and so are not imported.
+fn unsupported_judge_columns(row: &JudgeRow) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if row.is_ca || row.is_ia {
+        warnings.push(
+            "is_ca and is_ia are not imported (add adjudication core members \
+             as users of the tournament instead)"
+                .to_string(),
+        );
+    }
+    if let Some(base_score) = row.base_score {
+        warnings.push(format!("the base_score ({base_score}) is not imported"));
+    }
+    if !row.institution_clashes.is_empty() {
+        warnings.push(format!(
+            "the institution_clashes ({}) are not imported (add clashes \
+             from the judge's page instead)",
+            row.institution_clashes.join(", ")
+        ));
+    }
+    if !row.availability.is_empty() {
+        warnings.push(
+            "the availability is not imported (set it for each round instead)"
+                .to_string(),
+        );
+    }
+    warnings
+}
+
+/// Reads and validates every row of the CSV file. Returns an error if the
+/// file itself cannot be read.
+fn parse_rows(
+    kind: ImportKind,
+    csv: &str,
+    existing: &Existing,
+) -> Result<Vec<ImportRow>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(csv.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("could not read the header row: {e}"))?
+        .clone();
+
+    let mut rows = Vec::new();
+    // the lowercased names in the file so far, and the line each was on
+    let mut seen: HashMap<String, u64> = HashMap::new();
+
+    for result in reader.records() {
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => {
+                rows.push(ImportRow {
+                    line: e.position().map(|p| p.line()).unwrap_or_default(),
+                    record: None,
+                    errors: vec![e.to_string()],
+                    warnings: Vec::new(),
+                });
+                continue;
+            }
+        };
+        let line = record.position().map(|p| p.line()).unwrap_or_default();
+
+        let parsed = match kind {
+            ImportKind::Institutions => record
+                .deserialize::<InstitutionRow>(Some(&headers))
+                .map(ImportRecord::Institution),
+            ImportKind::Teams => record
+                .deserialize::<TeamRow>(Some(&headers))
+                .map(ImportRecord::Team),
+            ImportKind::Judges => record
+                .deserialize::<JudgeRow>(Some(&headers))
+                .map(ImportRecord::Judge),
+            ImportKind::Rooms => record
+                .deserialize::<RoomRow>(Some(&headers))
+                .map(ImportRecord::Room),
+        };
+        let record = match parsed {
+            Ok(record) => record,
+            Err(e) => {
+                rows.push(ImportRow {
+                    line,
+                    record: None,
+                    errors: vec![e.to_string()],
+                    warnings: Vec::new(),
+                });
+                continue;
+            }
+        };
+
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        let name = match &record {
+            ImportRecord::Institution(row) => {
+                if row.short_code.trim().is_empty() {
+                    errors.push("the short code is missing".to_string());
+                }
+                &row.full_name
+            }
+            ImportRecord::Team(row) => {
+                check_institution(&row.institution, existing, &mut errors);
+                if row.speakers.is_empty() {
+                    errors.push("the team has no speakers".to_string());
+                }
+                for speaker in &row.speakers {
+                    check_email(
+                        &speaker.email,
+                        &format!("the speaker {}", speaker.name),
+                        &mut errors,
+                    );
+                }
+                &row.full_name
+            }
+            ImportRecord::Judge(row) => {
+                check_institution(&row.institution, existing, &mut errors);
+                check_email(&row.email, "the judge", &mut errors);
+                warnings = unsupported_judge_columns(row);
+                &row.name
+            }
+            ImportRecord::Room(row) => &row.name,
+        };
+        let key = name.trim().to_lowercase();
+        if key.is_empty() {
+            errors.push("the name is missing".to_string());
+        } else if existing.names.contains(&key) {
+            errors.push(format!("{} already exists in this tournament", name));
+        } else if let Some(other_line) = seen.get(&key) {
+            errors.push(format!(
+                "{name} is a duplicate of the row on line {other_line}"
+            ));
+        } else {
+            seen.insert(key, line);
+        }
+
+        rows.push(ImportRow {
+            line,
+            record: Some(record),
+            errors,
+            warnings,
+        });
+    }
+
+    Ok(rows)
+}
+
+/// Returns the ID of the speaker category with the given name, creating it
+/// (along with a team category containing teams made up entirely of speakers
+/// in it) if it does not already exist.
+pub fn get_or_create_speaker_category(
+    conn: &mut SqliteConnection,
+    tournament_id: &str,
+    name: &str,
+) -> QueryResult<String> {
+    if let Some(id) = speaker_categories::table
+        .filter(speaker_categories::tournament_id.eq(tournament_id))
+        .filter(speaker_categories::name.eq(name))
+        .select(speaker_categories::id)
+        .first::<String>(conn)
+        .optional()?
+    {
+        return Ok(id);
+    }
+
+    let id = Uuid::now_v7().to_string();
+    diesel::insert_into(speaker_categories::table)
+        .values((
+            speaker_categories::id.eq(&id),
+            speaker_categories::tournament_id.eq(tournament_id),
+            speaker_categories::name.eq(name),
+        ))
+        .execute(conn)?;
+    diesel::insert_into(team_categories::table)
+        .values((
+            team_categories::id.eq(Uuid::now_v7().to_string()),
+            team_categories::tournament_id.eq(tournament_id),
+            team_categories::name.eq(name),
+            team_categories::speaker_category_id.eq(&id),
+            team_categories::rule.eq("all"),
+        ))
+        .execute(conn)?;
+
+    Ok(id)
+}
+
+/// Inserts the (already validated) records.
+fn insert_records(
+    tournament_id: &str,
+    records: Vec<ImportRecord>,
+    existing: &Existing,
+    conn: &mut SqliteConnection,
+) -> QueryResult<()> {
+    let next_team_number = teams::table
+        .filter(teams::tournament_id.eq(tournament_id))
+        .select(diesel::dsl::max(teams::number))
+        .first::<Option<i64>>(conn)?
+        .unwrap_or(0)
+        + 1;
+    let next_judge_number = judges::table
+        .filter(judges::tournament_id.eq(tournament_id))
+        .select(diesel::dsl::max(judges::number))
+        .first::<Option<i64>>(conn)?
+        .unwrap_or(0)
+        + 1;
+    let next_room_number = rooms::table
+        .filter(rooms::tournament_id.eq(tournament_id))
+        .select(diesel::dsl::max(rooms::number))
+        .first::<Option<i64>>(conn)?
+        .unwrap_or(0)
+        + 1;
+
+    for (i, record) in records.into_iter().enumerate() {
+        let i = i as i64;
+        match record {
+            ImportRecord::Institution(row) => {
+                diesel::insert_into(institutions::table)
+                    .values((
+                        institutions::id.eq(Uuid::now_v7().to_string()),
+                        institutions::tournament_id.eq(tournament_id),
+                        institutions::name.eq(row.full_name.trim()),
+                        institutions::code.eq(row.short_code.trim()),
+                    ))
+                    .execute(conn)?;
+            }
+            ImportRecord::Team(row) => {
+                let team_id = Uuid::now_v7().to_string();
+                diesel::insert_into(teams::table)
+                    .values((
+                        teams::id.eq(&team_id),
+                        teams::tournament_id.eq(tournament_id),
+                        teams::name.eq(row.full_name.trim()),
+                        teams::institution_id
+                            .eq(existing.institution_id(&row.institution)),
+                        teams::number.eq(next_team_number + i),
+                        teams::code_name.eq(nonblank(&row.code_name)),
+                        teams::emoji.eq(nonblank(&row.emoji)),
+                        teams::use_institution_prefix
+                            .eq(row.use_institution_prefix),
+                    ))
+                    .execute(conn)?;
+
+                for speaker in &row.speakers {
+                    let speaker_id = Uuid::now_v7().to_string();
+                    diesel::insert_into(speakers::table)
+                        .values((
+                            speakers::id.eq(&speaker_id),
+                            speakers::tournament_id.eq(tournament_id),
+                            speakers::name.eq(&speaker.name),
+                            speakers::email.eq(speaker
+                                .email
+                                .as_deref()
+                                .unwrap_or_default()
+                                .trim()),
+                            speakers::private_url
+                                .eq(Uuid::new_v4().to_string()),
+                        ))
+                        .execute(conn)?;
+                    diesel::insert_into(speakers_of_team::table)
+                        .values((
+                            speakers_of_team::id.eq(Uuid::now_v7().to_string()),
+                            speakers_of_team::team_id.eq(&team_id),
+                            speakers_of_team::speaker_id.eq(&speaker_id),
+                        ))
+                        .execute(conn)?;
+
+                    // Tabbycat team categories (e.g. "ESL") apply to every
+                    // speaker on the team
+                    for category in
+                        speaker.categories.iter().chain(row.categories.iter())
+                    {
+                        let category_id = get_or_create_speaker_category(
+                            conn,
+                            tournament_id,
+                            category.trim(),
+                        )?;
+                        diesel::insert_or_ignore_into(
+                            speakers_of_category::table,
+                        )
+                        .values((
+                            speakers_of_category::id
+                                .eq(Uuid::now_v7().to_string()),
+                            speakers_of_category::tournament_id
+                                .eq(tournament_id),
+                            speakers_of_category::category_id.eq(&category_id),
+                            speakers_of_category::speaker_id.eq(&speaker_id),
+                        ))
+                        .execute(conn)?;
+                    }
+                }
+            }
+            ImportRecord::Judge(row) => {
+                diesel::insert_into(judges::table)
+                    .values((
+                        judges::id.eq(Uuid::now_v7().to_string()),
+                        judges::tournament_id.eq(tournament_id),
+                        judges::name.eq(row.name.trim()),
+                        judges::email.eq(row
+                            .email
+                            .as_deref()
+                            .unwrap_or_default()
+                            .trim()),
+                        judges::institution_id
+                            .eq(existing.institution_id(&row.institution)),
+                        judges::private_url.eq(Uuid::new_v4().to_string()),
+                        judges::number.eq(next_judge_number + i),
+                    ))
+                    .execute(conn)?;
+            }
+            ImportRecord::Room(row) => {
+                diesel::insert_into(rooms::table)
+                    .values((
+                        rooms::id.eq(Uuid::now_v7().to_string()),
+                        rooms::tournament_id.eq(tournament_id),
+                        rooms::name.eq(row.name.trim()),
+                        rooms::priority.eq(row.priority),
+                        rooms::number.eq(next_room_number + i),
+                    ))
+                    .execute(conn)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+pub struct ImportForm {
+    kind: ImportKind,
+    csv: String,
+}
+
+struct ImportPageRenderer<'a> {
+    tournament: &'a Tournament,
+    rounds: &'a TournamentRounds,
+    form: Option<&'a ImportForm>,
+    /// The validated rows (if a preview was requested), or the reason the
+    /// file could not be read.
+    preview: Option<Result<Vec<ImportRow>, String>>,
+}
+
+impl Renderable for ImportPageRenderer<'_> {
+    fn render_to(
+        &self,
+        buffer: &mut hypertext::Buffer<hypertext::context::Node>,
+    ) {
+        let kind = self.form.map(|form| form.kind);
+        let csv = self.form.map(|form| form.csv.as_str()).unwrap_or_default();
+        let n_problems = match &self.preview {
+            Some(Ok(rows)) => {
+                rows.iter().filter(|row| !row.errors.is_empty()).count()
+            }
+            _ => 0,
+        };
+
+        maud! {
+            SidebarWrapper tournament=(self.tournament) rounds=(self.rounds) active_page=(None) selected_seq=(None) {
+                h1 { "Import participants" }
+                p class="text-muted" {
+                    "Upload a Tabbycat-style CSV file, or paste its contents "
+                    "(including the header row) below. Institutions should be imported before the teams "
+                    "and judges which belong to them."
+                }
+                ul class="text-muted small" {
+                    @for kind in ImportKind::ALL {
+                        li { strong { (kind.name()) } ": " (kind.columns()) }
+                    }
+                }
+
+                form method="post" action=(format!("/tournaments/{}/participants/import/preview", self.tournament.id)) enctype="multipart/form-data" class="mb-5" {
+                    div class="mb-3" {
+                        label for="kind" class="form-label" { "Import" }
+                        select class="form-select" id="kind" name="kind" required {
+                            @for option in ImportKind::ALL {
+                                option value=(option.form_value()) selected[kind == Some(option)] { (option.name()) }
+                            }
+                        }
+                    }
+                    div class="mb-3" {
+                        label for="file" class="form-label" { "CSV file" }
+                        input type="file" class="form-control" id="file" name="file" accept=".csv,text/csv";
+                        div class="form-text" { "If a file is chosen, it is used instead of the text below." }
+                    }
+                    div class="mb-3" {
+                        label for="csv" class="form-label" { "CSV" }
+                        textarea class="form-control font-monospace" id="csv" name="csv" rows="12" { (csv) }
+                    }
+                    button type="submit" class="btn btn-outline-primary" { "Preview" }
+                }
+
+                @match &self.preview {
+                    Some(Err(e)) => {
+                        div class="alert alert-danger" { "Error: " (e) }
+                    }
+                    Some(Ok(rows)) => {
+                        h2 class="h4" { "Preview" }
+                        @if rows.is_empty() {
+                            p class="text-muted" { "The file has no rows." }
+                        } @else {
+                            table class="table table-sm" {
+                                thead {
+                                    tr {
+                                        th scope="col" { "Line" }
+                                        th scope="col" { "Row" }
+                                        th scope="col" { "Problems" }
+                                    }
+                                }
+                                tbody {
+                                    @for row in rows {
+                                        tr class=(if !row.errors.is_empty() { "table-danger" } else if !row.warnings.is_empty() { "table-warning" } else { "" }) {
+                                            td { (row.line) }
+                                            td {
+                                                @if let Some(record) = &row.record {
+                                                    (record.describe())
+                                                } @else {
+                                                    span class="text-muted" { "—" }
+                                                }
+                                            }
+                                            td {
+                                                @if row.errors.is_empty() && row.warnings.is_empty() {
+                                                    span class="text-success" { "OK" }
+                                                } @else {
+                                                    ul class="mb-0" {
+                                                        @for error in &row.errors {
+                                                            li { (error) }
+                                                        }
+                                                        @for warning in &row.warnings {
+                                                            li class="text-body-secondary" { "Warning: " (warning) }
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                            @if n_problems > 0 {
+                                p class="text-danger" {
+                                    (n_problems) " rows have problems. Fix them and preview the file again to import it."
+                                }
+                            } @else if let Some(form) = self.form {
+                                form method="post" action=(format!("/tournaments/{}/participants/import/commit", self.tournament.id)) {
+                                    input type="hidden" name="kind" value=(form.kind.form_value());
+                                    textarea name="csv" hidden { (form.csv) }
+                                    button type="submit" class="btn btn-primary" {
+                                        "Import " (rows.len()) " " (form.kind.name().to_lowercase())
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    None => {}
+                }
+            }
+        }
+        .render_to(buffer);
+    }
+}
+
+pub async fn import_participants_page(
+    Path(tid): Path<String>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tid, &mut *conn)?;
+    tournament.check_user_has_permission(
+        &user.id,
+        crate::permission::Permission::ManageParticipants,
+        &mut *conn,
+    )?;
+    let rounds = TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+    let current_rounds = Round::current_rounds(&tid, &mut *conn);
+
+    success(
+        Page::new()
+            .active_nav(crate::template::ActiveNav::Participants)
+            .user(user)
+            .tournament(tournament.clone())
+            .current_rounds(current_rounds)
+            .body(ImportPageRenderer {
+                tournament: &tournament,
+                rounds: &rounds,
+                form: None,
+                preview: None,
+            })
+            .render(),
+    )
+}
+
+/// Reads the preview form, which is sent as `multipart/form-data` so that a
+/// CSV file can be uploaded. An uploaded file takes precedence over any text
+/// pasted into the form.
+async fn read_import_form(
+    mut multipart: Multipart,
+) -> Result<ImportForm, String> {
+    let mut kind = None;
+    let mut csv = String::new();
+    let mut file = None;
+
+    while let Some(field) =
+        multipart.next_field().await.map_err(|e| e.to_string())?
+    {
+        let name = field.name().unwrap_or_default().to_string();
+        match name.as_str() {
+            "kind" => {
+                let value = field.text().await.map_err(|e| e.to_string())?;
+                kind = ImportKind::ALL
+                    .into_iter()
+                    .find(|kind| kind.form_value() == value);
+            }
+            "csv" => {
+                csv = field.text().await.map_err(|e| e.to_string())?;
+            }
+            "file" => {
+                let bytes = field.bytes().await.map_err(|e| e.to_string())?;
+                // Browsers send an empty part when no file has been chosen.
+                if !bytes.is_empty() {
+                    file = Some(String::from_utf8(bytes.to_vec()).map_err(
+                        |_| "the uploaded file is not valid UTF-8".to_string(),
+                    )?);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let kind = kind.ok_or_else(|| "no import type was selected".to_string())?;
+    let csv = file.unwrap_or(csv);
+    if csv.trim().is_empty() {
+        return Err("choose a CSV file or paste its contents".to_string());
+    }
+    Ok(ImportForm { kind, csv })
+}
+
+pub async fn do_preview_import(
+    Path(tid): Path<String>,
+    user: User<true>,
+    mut conn: Conn<true>,
+    multipart: Multipart,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tid, &mut *conn)?;
+    tournament.check_user_has_permission(
+        &user.id,
+        crate::permission::Permission::ManageParticipants,
+        &mut *conn,
+    )?;
+    let rounds = TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+
+    let form = match read_import_form(multipart).await {
+        Ok(form) => form,
+        Err(e) => {
+            return bad_request(
+                Page::new()
+                    .user(user)
+                    .tournament(tournament.clone())
+                    .body(maud! {
+                        SidebarWrapper tournament=(&tournament) rounds=(&rounds) active_page=(None) selected_seq=(None) {
+                            h1 { "Import participants" }
+                            div class="alert alert-danger" { "Error: " (e) }
+                            a href=(format!("/tournaments/{}/participants/import", tournament.id)) class="btn btn-outline-primary" { "Back" }
+                        }
+                    })
+                    .render(),
+            );
+        }
+    };
+
+    let existing = Existing::load(&tournament.id, form.kind, &mut *conn);
+    let preview = parse_rows(form.kind, &form.csv, &existing);
+
+    let current_rounds = Round::current_rounds(&tid, &mut *conn);
+
+    success(
+        Page::new()
+            .active_nav(crate::template::ActiveNav::Participants)
+            .user(user)
+            .tournament(tournament.clone())
+            .current_rounds(current_rounds)
+            .body(ImportPageRenderer {
+                tournament: &tournament,
+                rounds: &rounds,
+                form: Some(&form),
+                preview: Some(preview),
+            })
+            .render(),
+    )
+}
+
+pub async fn do_commit_import(
+    Path(tid): Path<String>,
+    user: User<true>,
+    Extension(tx): Extension<Sender<Msg>>,
+    mut conn: Conn<true>,
+    Form(form): Form<ImportForm>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tid, &mut *conn)?;
+    tournament.check_user_has_permission(
+        &user.id,
+        crate::permission::Permission::ManageParticipants,
+        &mut *conn,
+    )?;
+
+    // the tournament may have changed since the preview, so everything is
+    // checked again
+    let existing = Existing::load(&tournament.id, form.kind, &mut *conn);
+    let rows = match parse_rows(form.kind, &form.csv, &existing) {
+        Ok(rows) => rows,
+        Err(e) => {
+            return bad_request(maud! { "Error: " (e) }.render());
+        }
+    };
+    if rows.iter().any(|row| !row.errors.is_empty()) {
+        return bad_request(
+            maud! {
+                "Error: some rows have problems. Preview the file again to see them."
+            }
+            .render(),
+        );
+    }
+    let records = rows.into_iter().filter_map(|row| row.record).collect();
+
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        insert_records(&tournament.id, records, &existing, conn)?;
+        refresh_saved_team_standings(&tournament.id, conn)
+    })?;
+    take_snapshot(&tournament.id, &mut *conn);
+
+    let _ = tx.send(Msg {
+        tournament: tournament.clone(),
+        inner: MsgContents::ParticipantsUpdate,
+    });
+
+    see_other_ok(Redirect::to(&format!(
+        "/tournaments/{}/participants",
+        tournament.id
+    )))
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_rows_reports_problems() {
+    let existing = Existing {
+        institutions: HashMap::from([("arnor".to_string(), "i1".to_string())]),
+        names: HashSet::from(["existing".to_string()]),
+    };
+
+    let rows = parse_rows(
+        ImportKind::Judges,
+        "name,email,institution\n\
+         Alice,alice@example.com,Arnor\n\
+         Bob,,Arnor\n\
+         Carol,carol@example.com,Gondor\n\
+         alice,alice2@example.com,\n\
+         Existing,existing@example.com,\n",
+        &existing,
+    )
+    .unwrap();
+    let errors: Vec<(u64, usize)> = rows
+        .iter()
+        .map(|row| (row.line, row.errors.len()))
+        .collect();
+    assert_eq!(errors, vec![(2, 0), (3, 1), (4, 1), (5, 1), (6, 1)]);
+    assert!(rows[1].errors[0].contains("no email"));
+    assert!(rows[2].errors[0].contains("Gondor"));
+    assert!(rows[3].errors[0].contains("line 2"));
+    assert!(rows[4].errors[0].contains("already exists"));
+    assert!(rows.iter().all(|row| row.warnings.is_empty()));
+
+    let judges = parse_rows(
+        ImportKind::Judges,
+        "name,email,institution,is_ca,base_score,institution_clashes\n\
+         Dan,dan@example.com,Arnor,true,4.5,\"Arnor,Gondor\"\n",
+        &existing,
+    )
+    .unwrap();
+    assert!(judges[0].errors.is_empty());
+    assert_eq!(judges[0].warnings.len(), 3);
+    assert!(judges[0].warnings[2].contains("Arnor, Gondor"));
+
+    let teams = parse_rows(
+        ImportKind::Teams,
+        "full_name,institution,speaker1_name,speaker1_email,speaker2_name,speaker2_email\n\
+         Arnor A,Arnor,Ann,ann@example.com,Ben,\n",
+        &existing,
+    )
+    .unwrap();
+    assert_eq!(
+        teams[0].errors,
+        vec!["the speaker Ben has no email address"]
+    );
+}
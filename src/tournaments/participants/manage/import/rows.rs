@@ -0,0 +1,189 @@
+//! The rows of Tabbycat-style CSV files (as accepted by Tabbycat's
+//! `importtournament` command).
+
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use serde::{
+    Deserialize, Deserializer,
+    de::{self, Unexpected},
+};
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct InstitutionRow {
+    pub region: Option<String>,
+    // TODO: warn when this is >20 characters (Tabbycat currently applies
+    // this restriction) to aid with debugging
+    pub short_code: String,
+    pub full_name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RoomRow {
+    pub name: String,
+    pub priority: i64,
+}
+
+fn ret_false() -> bool {
+    false
+}
+
+fn tags_deserialize<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let str_sequence = String::deserialize(deserializer)?;
+    Ok(str_sequence
+        .split(',')
+        .map(|item| item.to_owned())
+        .filter(|item| !item.is_empty())
+        .collect())
+}
+
+fn bool_from_str<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match String::deserialize(deserializer)?.to_lowercase().trim() {
+        "t" | "true" | "1" | "on" | "y" | "yes" => Ok(true),
+        "f" | "false" | "0" | "off" | "n" | "no" | "" => Ok(false),
+        other => Err(de::Error::invalid_value(
+            Unexpected::Str(other),
+            &"Must be truthy (t, true, 1, on, y, yes) or falsey (f, false, 0, off, n, no)",
+        )),
+    }
+}
+
+fn not_true() -> bool {
+    false
+}
+
+// todo: team institution clashes
+#[derive(Deserialize, Debug, Clone)]
+pub struct TeamRow {
+    pub full_name: String,
+    /// If not supplied, we truncate the full name.
+    pub short_name: Option<String>,
+    #[serde(deserialize_with = "tags_deserialize", default = "Vec::new")]
+    pub categories: Vec<String>,
+    pub code_name: Option<String>,
+    pub institution: Option<String>,
+    pub seed: Option<u32>,
+    pub emoji: Option<String>,
+    #[serde(deserialize_with = "bool_from_str", default = "not_true")]
+    pub use_institution_prefix: bool,
+    #[serde(flatten, deserialize_with = "deserialize_fields_to_vec")]
+    pub speakers: Vec<Speaker>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Clash {
+    pub object_1: String,
+    pub object_2: String,
+}
+
+/// Collects the `speaker<n>_<field>` columns into a list of speakers (in
+/// order of `n`). Speakers whose columns are all blank are left out.
+fn deserialize_fields_to_vec<'de, D>(
+    deserializer: D,
+) -> Result<Vec<Speaker>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let map: HashMap<String, String> = HashMap::deserialize(deserializer)?;
+    let mut buckets: HashMap<u8, HashMap<String, String>> = HashMap::new();
+    for (key, value) in map.iter() {
+        if let Some(rest) = key.strip_prefix("speaker") {
+            let Some((number, field_name)) = rest.split_once('_') else {
+                return Err(de::Error::custom(format!(
+                    "the column `{key}` should be named like `speaker1_name`"
+                )));
+            };
+            let number = number.trim().parse::<u8>().map_err(|_| {
+                de::Error::custom(format!(
+                    "the column `{key}` should be named like `speaker1_name`"
+                ))
+            })?;
+            buckets
+                .entry(number)
+                .or_default()
+                .insert(field_name.to_string(), value.clone());
+        }
+    }
+
+    buckets
+        .into_iter()
+        .sorted_by_key(|(t, _)| *t)
+        .filter(|(_, map)| !map.values().all(|value| value.trim().is_empty()))
+        .map(|(number, map)| {
+            let nonempty = |field: &str| {
+                map.get(field)
+                    .map(|value| value.trim())
+                    .filter(|value| !value.is_empty())
+                    .map(|value| value.to_string())
+            };
+            Ok(Speaker {
+                name: nonempty("name").ok_or_else(|| {
+                    de::Error::custom(format!("speaker {number} has no name"))
+                })?,
+                categories: map
+                    .get("categories")
+                    .map(|t| {
+                        t.split(',')
+                            .map(|x| x.trim().to_string())
+                            .filter(|t| !t.is_empty())
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default(),
+                email: nonempty("email"),
+                phone: nonempty("phone"),
+                anonymous: map
+                    .get("anonymous")
+                    .map(|t| t.trim().eq_ignore_ascii_case("true"))
+                    .unwrap_or(false),
+                code_name: nonempty("code_name"),
+                url_key: nonempty("url_key"),
+                gender: nonempty("gender").map(|gender| {
+                    match gender.to_lowercase().as_str() {
+                        "male" => "M".to_string(),
+                        "female" => "F".to_string(),
+                        "other" => "O".to_string(),
+                        _ => gender,
+                    }
+                }),
+                pronoun: nonempty("pronoun"),
+            })
+        })
+        .collect()
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Speaker {
+    pub name: String,
+    pub categories: Vec<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub anonymous: bool,
+    pub code_name: Option<String>,
+    pub url_key: Option<String>,
+    // todo: validate correct
+    pub gender: Option<String>,
+    // todo: validate length
+    pub pronoun: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct JudgeRow {
+    pub name: String,
+    pub institution: Option<String>,
+    #[serde(deserialize_with = "tags_deserialize", default = "Vec::new")]
+    pub institution_clashes: Vec<String>,
+    pub email: Option<String>,
+    #[serde(deserialize_with = "bool_from_str", default = "ret_false")]
+    pub is_ca: bool,
+    #[serde(deserialize_with = "bool_from_str", default = "ret_false")]
+    pub is_ia: bool,
+    pub base_score: Option<f64>,
+    #[serde(deserialize_with = "tags_deserialize", default = "Vec::new")]
+    pub availability: Vec<String>,
+}
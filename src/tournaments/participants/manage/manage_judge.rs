@@ -71,6 +71,9 @@ pub async fn edit_judge_details_page(
             .user(user)
             .tournament(tournament)
             .body(maud! {
+                a href=(format!("/tournaments/{}/judges/{}/feedback", tournament_id, judge.id)) class="btn btn-sm btn-outline-secondary mb-3" {
+                    "View feedback on this judge"
+                }
                 form method="post" {
                   div class="mb-3" {
                     label for="judgeName" class="form-label" { "Name of judge" }
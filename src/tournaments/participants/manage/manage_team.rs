@@ -141,7 +141,11 @@ pub async fn edit_team_details_page(
 
     let rounds = TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
 
-    let form = TeamForm::new(&institution_selector).with_team_name(&team.name);
+    let form = TeamForm::new(&institution_selector)
+        .with_team_name(&team.name)
+        .with_code_name(team.code_name.as_deref())
+        .with_emoji(team.emoji.as_deref())
+        .with_institution_prefix(team.use_institution_prefix);
 
     success(
         Page::new()
@@ -240,6 +244,9 @@ pub async fn do_edit_team_details(
         .set((
             teams::name.eq(&form.name),
             teams::institution_id.eq(inst.map(|t| t.id)),
+            teams::code_name.eq(form.code_name()),
+            teams::emoji.eq(form.emoji()),
+            teams::use_institution_prefix.eq(form.use_institution_prefix),
         ))
         .execute(&mut *conn)
         .unwrap();
@@ -36,6 +36,7 @@ pub mod create_judge;
 pub mod create_speaker;
 pub mod create_team;
 pub mod gen_private_url;
+pub mod import;
 pub mod institution_selector;
 pub mod manage_judge;
 pub mod manage_private_urls;
@@ -158,6 +159,7 @@ impl Renderable for ParticipantsTable {
                                         td class="text-end py-4" {
                                             div class="d-flex justify-content-end gap-2" {
                                                 a href=(format!("/tournaments/{}/participants/judge/{}/constraints", self.0.id, judge.id)) class="btn btn-sm btn-outline-dark" { "Constraints" }
+                                                a href=(format!("/tournaments/{}/judges/{}/feedback", self.0.id, judge.id)) class="btn btn-sm btn-outline-dark" { "Feedback" }
                                                 a href=(format!("/tournaments/{}/judges/{}/edit", self.0.id, judge.id)) class="btn btn-sm btn-outline-dark" { "Edit" }
                                             }
                                         }
@@ -208,8 +210,13 @@ pub async fn manage_tournament_participants_impl(
                 .current_rounds(current_rounds.clone())
                 .body(maud! {
                     SidebarWrapper tournament=(&tournament) rounds=(&rounds) selected_seq=(current_rounds.first().map(|r| r.seq)) active_page=(None) {
-                        h1 {
-                            "Participants"
+                        div class="d-flex justify-content-between align-items-center" {
+                            h1 {
+                                "Participants"
+                            }
+                            a href=(format!("/tournaments/{}/participants/import", tournament.id)) class="btn btn-outline-dark btn-sm" {
+                                "Import from CSV"
+                            }
                         }
 
                         (table)
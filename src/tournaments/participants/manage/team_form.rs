@@ -4,6 +4,9 @@ use super::institution_selector::InstitutionSelector;
 
 pub struct TeamForm<'a> {
     team_name: Option<&'a str>,
+    code_name: Option<&'a str>,
+    emoji: Option<&'a str>,
+    use_institution_prefix: bool,
     institution_selector: &'a InstitutionSelector<'a>,
 }
 
@@ -11,6 +14,9 @@ impl<'a> TeamForm<'a> {
     pub fn new(institution_selector: &'a InstitutionSelector) -> Self {
         Self {
             team_name: None,
+            code_name: None,
+            emoji: None,
+            use_institution_prefix: true,
             institution_selector,
         }
     }
@@ -19,6 +25,21 @@ impl<'a> TeamForm<'a> {
         self.team_name = Some(team_name);
         self
     }
+
+    pub fn with_code_name(mut self, code_name: Option<&'a str>) -> Self {
+        self.code_name = code_name;
+        self
+    }
+
+    pub fn with_emoji(mut self, emoji: Option<&'a str>) -> Self {
+        self.emoji = emoji;
+        self
+    }
+
+    pub fn with_institution_prefix(mut self, use_prefix: bool) -> Self {
+        self.use_institution_prefix = use_prefix;
+        self
+    }
 }
 
 impl<'a> Renderable for TeamForm<'a> {
@@ -34,11 +55,47 @@ impl<'a> Renderable for TeamForm<'a> {
                     name="name"
                     value=(self.team_name.unwrap_or(""));
                 div id="teamNameHelp" class="form-text" {
-                    "The team name. Please note that this will be prefixed with"
-                    " the institution name (if an institution is selected)."
+                    "The team name. If \"Prefix with institution code\" is"
+                    " ticked, this will be prefixed with the code of the"
+                    " institution (if an institution is selected)."
                 }
             }
             (self.institution_selector)
+            div class="mb-3 form-check" {
+                input
+                    type="checkbox"
+                    class="form-check-input"
+                    id="useInstitutionPrefix"
+                    name="use_institution_prefix"
+                    value="true"
+                    checked[self.use_institution_prefix];
+                label for="useInstitutionPrefix" class="form-check-label" {
+                    "Prefix with institution code"
+                }
+            }
+            div class="mb-3" {
+                label for="codeName" class="form-label" { "Code name" }
+                input
+                    type="text"
+                    class="form-control"
+                    id="codeName"
+                    aria-describedby="codeNameHelp"
+                    name="code_name"
+                    value=(self.code_name.unwrap_or(""));
+                div id="codeNameHelp" class="form-text" {
+                    "Optional. Shown instead of the team name on public pages"
+                    " which are configured to use code names."
+                }
+            }
+            div class="mb-3" {
+                label for="emoji" class="form-label" { "Emoji" }
+                input
+                    type="text"
+                    class="form-control"
+                    id="emoji"
+                    name="emoji"
+                    value=(self.emoji.unwrap_or(""));
+            }
         }
         .render_to(buffer);
     }
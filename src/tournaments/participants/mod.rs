@@ -9,7 +9,7 @@ use crate::{
         institutions, judges, judges_of_debate, speakers, speakers_of_team,
         teams,
     },
-    tournaments::teams::Team,
+    tournaments::teams::{Team, TeamNameKind},
     util_resp::{FailureResponse, err_not_found},
 };
 
@@ -113,6 +113,18 @@ pub struct DebateJudge {
     pub status: String,
 }
 
+impl DebateJudge {
+    /// The name of the judge's position on the panel, e.g. "Chair".
+    pub fn role(&self) -> &'static str {
+        match self.status.as_str() {
+            "C" => "Chair",
+            "P" => "Panelist",
+            "T" => "Trainee",
+            _ => "Judge",
+        }
+    }
+}
+
 #[derive(Queryable, Serialize, Deserialize, Clone, Debug)]
 pub struct Institution {
     id: String,
@@ -219,13 +231,22 @@ impl TournamentParticipants {
         }
     }
 
+    /// The name of the team as shown to the tab team.
     pub fn canonical_name_of_team(&self, team: &Team) -> String {
+        team.full_name(self.institution_code_of_team(team))
+    }
+
+    pub fn display_name_of_team(
+        &self,
+        team: &Team,
+        kind: TeamNameKind,
+    ) -> String {
+        team.display_name(kind, self.institution_code_of_team(team))
+    }
+
+    fn institution_code_of_team(&self, team: &Team) -> Option<&str> {
         team.institution_id
             .as_ref()
-            .map(|inst| {
-                let code = &self.institutions.get(inst).unwrap().code;
-                format!("{code} {}", team.name)
-            })
-            .unwrap_or_else(|| team.name.clone())
+            .map(|inst| self.institutions.get(inst).unwrap().code.as_str())
     }
 }
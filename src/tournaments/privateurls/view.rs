@@ -13,6 +13,16 @@ use crate::{
     template::Page,
     tournaments::{
         Tournament,
+        feedback::{
+            received::received_feedback,
+            release::{
+                ReleasedFeedback, hidden_comments, questions_with_kinds,
+            },
+            requirement::{
+                FeedbackRequirement, FeedbackSource, OutstandingFeedbackAlert,
+                outstanding_feedback,
+            },
+        },
         participants::{Judge, Participant, Speaker},
         rounds::{
             Round, TournamentRounds,
@@ -53,6 +63,22 @@ fn private_url_page_of_speaker(
 ) -> StandardResponse {
     let current_rounds = Round::current_rounds(&tournament.id, conn);
 
+    // a speaker may (rarely) be a member of more than one team
+    let outstanding = speakers_of_team::table
+        .filter(speakers_of_team::speaker_id.eq(&speaker.id))
+        .select(speakers_of_team::team_id)
+        .load::<String>(conn)
+        .unwrap()
+        .iter()
+        .flat_map(|team_id| {
+            outstanding_feedback(
+                &tournament,
+                FeedbackSource::Team(team_id),
+                conn,
+            )
+        })
+        .collect_vec();
+
     let current_debate_info = if !current_rounds.is_empty() {
         // todo: should make sure that teams can only ever be allocated to ONE
         // round per sequence number
@@ -143,6 +169,10 @@ fn private_url_page_of_speaker(
                         }
                     }
 
+                    @if !outstanding.is_empty() {
+                        OutstandingFeedbackAlert tournament=(&tournament) private_url=(&speaker.private_url) outstanding=(&outstanding);
+                    }
+
                     @if let Some(ref debate_info) = current_debate_info {
                         section class="mb-5" {
                             (debate_info)
@@ -176,6 +206,14 @@ fn private_url_page_of_judge(
         None
     };
 
+    let outstanding = outstanding_feedback(
+        &tournament,
+        FeedbackSource::Judge(&judge.id),
+        conn,
+    );
+    let ballots_blocked = !outstanding.is_empty()
+        && FeedbackRequirement::of(&tournament) == FeedbackRequirement::Block;
+
     let current_debate_info = if !current_rounds.is_empty() {
         let judge_debate = judges_of_debate::table
             .filter(judges_of_debate::judge_id.eq(&judge.id))
@@ -235,9 +273,18 @@ fn private_url_page_of_judge(
                                 }
                             }
                         }
-                        a href=(format!("/tournaments/{}/privateurls/{}/rounds/{}/submit", tournament_id, judge_private_url, round_id))
-                          class="btn btn-dark btn-lg mt-3" {
-                            "Submit Ballot"
+                        @if ballots_blocked {
+                            button type="button" class="btn btn-dark btn-lg mt-3" disabled {
+                                "Submit Ballot"
+                            }
+                            p class="text-muted small mt-2 mb-0" {
+                                "You must submit your outstanding feedback before you can submit a ballot."
+                            }
+                        } @else {
+                            a href=(format!("/tournaments/{}/privateurls/{}/rounds/{}/submit", tournament_id, judge_private_url, round_id))
+                              class="btn btn-dark btn-lg mt-3" {
+                                "Submit Ballot"
+                            }
                         }
                     }
                 }
@@ -249,6 +296,14 @@ fn private_url_page_of_judge(
         None
     };
 
+    let released_feedback = tournament.release_feedback_to_judges.then(|| {
+        (
+            questions_with_kinds(&tournament.id, conn),
+            received_feedback(&tournament.id, &judge.id, conn),
+            hidden_comments(&tournament.id, conn),
+        )
+    });
+
     let all_current_rounds = Round::current_rounds(&tournament.id, conn);
 
     success(
@@ -270,6 +325,10 @@ fn private_url_page_of_judge(
                         }
                     }
 
+                    @if !outstanding.is_empty() {
+                        OutstandingFeedbackAlert tournament=(&tournament) private_url=(&judge.private_url) outstanding=(&outstanding);
+                    }
+
                     @if let Some(ref debate_info) = current_debate_info {
                         section class="mb-5" {
                             (debate_info)
@@ -292,6 +351,20 @@ fn private_url_page_of_judge(
                             }
                         }
                     }
+
+                    @if let Some((questions, feedback, hidden)) = &released_feedback {
+                        section class="mb-5" {
+                            h2 class="h4 text-uppercase fw-bold text-secondary mb-4" {
+                                "Feedback on your judging"
+                            }
+                            p class="text-muted" {
+                                "This is a summary of the feedback you received from "
+                                "teams and other judges. To keep feedback anonymous, it "
+                                "does not say who gave it or in which round."
+                            }
+                            ReleasedFeedback questions=(questions) feedback=(feedback) hidden=(hidden);
+                        }
+                    }
                 }
             })
             .render(),
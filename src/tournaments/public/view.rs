@@ -4,7 +4,11 @@ use crate::{
     auth::User,
     state::Conn,
     template::Page,
-    tournaments::{Tournament, rounds::Round},
+    tournaments::{
+        Tournament,
+        categories::{SpeakerCategory, TeamCategory},
+        rounds::Round,
+    },
     util_resp::{StandardResponse, success},
 };
 
@@ -22,6 +26,10 @@ pub async fn public_tournament_page(
     let current_rounds = Round::current_rounds(tournament_id, &mut *conn);
 
     let grouped_rounds = all_rounds.all_grouped_by_seq();
+    let speaker_categories =
+        SpeakerCategory::of_tournament(tournament_id, &mut *conn);
+    let team_categories =
+        TeamCategory::of_tournament(tournament_id, &mut *conn);
 
     success(
         Page::new()
@@ -107,12 +115,62 @@ pub async fn public_tournament_page(
                                     span class="material-icons text-muted me-3" { "leaderboard" }
                                     div class="flex-grow-1" {
                                         div class="fw-semibold text-dark" { "Standings" }
-                                        div class="small text-muted" { "Team and speaker tab" }
+                                        div class="small text-muted" { "Team tab" }
                                     }
                                     span class="material-icons text-muted fs-6" { "chevron_right" }
                                 }
                             }
 
+                            @if tournament.speaker_tab_public {
+                                a href=(format!("/tournaments/{}/tab/speaker", tournament.id))
+                                  class="list-group-item list-group-item-action py-3 d-flex align-items-center" {
+                                    span class="material-icons text-muted me-3" { "record_voice_over" }
+                                    div class="flex-grow-1" {
+                                        div class="fw-semibold text-dark" { "Speaker Tab" }
+                                        div class="small text-muted" { "Rankings of individual speakers" }
+                                    }
+                                    span class="material-icons text-muted fs-6" { "chevron_right" }
+                                }
+                            }
+
+                            @if tournament.reply_tab_public {
+                                a href=(format!("/tournaments/{}/tab/replies", tournament.id))
+                                  class="list-group-item list-group-item-action py-3 d-flex align-items-center" {
+                                    span class="material-icons text-muted me-3" { "record_voice_over" }
+                                    div class="flex-grow-1" {
+                                        div class="fw-semibold text-dark" { "Reply Tab" }
+                                        div class="small text-muted" { "Rankings of reply speakers" }
+                                    }
+                                    span class="material-icons text-muted fs-6" { "chevron_right" }
+                                }
+                            }
+
+                            @if tournament.team_tab_public {
+                                @for category in &team_categories {
+                                    a href=(format!("/tournaments/{}/tab/team/{}", tournament.id, category.id))
+                                      class="list-group-item list-group-item-action py-3 d-flex align-items-center" {
+                                        span class="material-icons text-muted me-3" { "leaderboard" }
+                                        div class="flex-grow-1" {
+                                            div class="fw-semibold text-dark" { (category.name) " Team Tab" }
+                                        }
+                                        span class="material-icons text-muted fs-6" { "chevron_right" }
+                                    }
+                                }
+                            }
+
+                            @if tournament.speaker_tab_public {
+                                @for category in &speaker_categories {
+                                    a href=(format!("/tournaments/{}/tab/speaker/{}", tournament.id, category.id))
+                                      class="list-group-item list-group-item-action py-3 d-flex align-items-center" {
+                                        span class="material-icons text-muted me-3" { "record_voice_over" }
+                                        div class="flex-grow-1" {
+                                            div class="fw-semibold text-dark" { (category.name) " Speaker Tab" }
+                                        }
+                                        span class="material-icons text-muted fs-6" { "chevron_right" }
+                                    }
+                                }
+                            }
+
                             a href=(format!("/tournaments/{}/motions", tournament.id))
                               class="list-group-item list-group-item-action py-3 d-flex align-items-center" {
                                 span class="material-icons text-muted me-3" { "article" }
@@ -1,6 +1,7 @@
 use std::collections::HashSet;
 
 use diesel::{connection::LoadConnection, prelude::*, sqlite::Sqlite};
+use itertools::Itertools;
 use rust_decimal::{Decimal, prelude::FromPrimitive};
 use uuid::Uuid;
 
@@ -12,7 +13,8 @@ use crate::{
     },
 };
 
-#[derive(Queryable)]
+#[derive(Queryable, Insertable)]
+#[diesel(table_name = agg_speaker_results_of_debate)]
 pub struct TournamentDebateSpeakerResult {
     pub id: String,
     pub tournament_id: String,
@@ -21,6 +23,7 @@ pub struct TournamentDebateSpeakerResult {
     pub team_id: String,
     pub position: i64,
     pub score: Option<f32>,
+    pub counts_for_speaker_tab: bool,
 }
 
 #[derive(Queryable)]
@@ -61,20 +64,20 @@ pub fn aggregate_ballot_set(
         BallotAggregationMethod::Individual
     };
 
-    for a in ballots {
-        for b in ballots {
-            assert!(a.is_isomorphic(b, tournament, debate))
-        }
-    }
-
     match method {
         BallotAggregationMethod::Consensus => {
+            for a in ballots {
+                for b in ballots {
+                    assert!(a.is_isomorphic(b, tournament, debate))
+                }
+            }
             let canonical = &ballots[0];
             if is_elim {
                 aggregate_consensus_elimination(canonical, debate, conn);
             } else {
                 aggregate_consensus_prelim(
                     canonical,
+                    tournament,
                     tournament.round_requires_speaks(&round),
                     conn,
                 );
@@ -92,11 +95,12 @@ pub fn aggregate_ballot_set(
             insert_two_team_results(ballots, debate, did_prop_win, conn);
 
             if !is_elim && tournament.round_requires_speaks(&round) {
-                let speaker_points = compute_averaged_speaker_scores(
+                let mut speaker_points = compute_averaged_speaker_scores(
                     ballots,
                     &ballots[0].metadata.tournament_id,
                     &ballots[0].metadata.debate_id,
                 );
+                apply_iron_speech_rule(tournament, &mut speaker_points);
 
                 diesel::insert_into(agg_speaker_results_of_debate::table)
                     .values(speaker_points)
@@ -109,6 +113,7 @@ pub fn aggregate_ballot_set(
 
 fn aggregate_consensus_prelim(
     canonical: &BallotRepr,
+    tournament: &Tournament,
     requires_speaks: bool,
     conn: &mut impl LoadConnection<Backend = Sqlite>,
 ) {
@@ -134,27 +139,21 @@ fn aggregate_consensus_prelim(
         .unwrap();
 
     if requires_speaks {
-        let speaker_points: Vec<_> = canonical
+        let mut speaker_points: Vec<_> = canonical
             .scores
             .iter()
-            .map(|score| {
-                (
-                    agg_speaker_results_of_debate::id
-                        .eq(Uuid::now_v7().to_string()),
-                    agg_speaker_results_of_debate::tournament_id
-                        .eq(canonical.metadata.tournament_id.clone()),
-                    agg_speaker_results_of_debate::debate_id
-                        .eq(canonical.metadata.debate_id.clone()),
-                    agg_speaker_results_of_debate::speaker_id
-                        .eq(score.speaker_id.clone()),
-                    agg_speaker_results_of_debate::team_id
-                        .eq(score.team_id.clone()),
-                    agg_speaker_results_of_debate::position
-                        .eq(score.speaker_position),
-                    agg_speaker_results_of_debate::score.eq(score.score),
-                )
+            .map(|score| TournamentDebateSpeakerResult {
+                id: Uuid::now_v7().to_string(),
+                tournament_id: canonical.metadata.tournament_id.clone(),
+                debate_id: canonical.metadata.debate_id.clone(),
+                speaker_id: score.speaker_id.clone(),
+                team_id: score.team_id.clone(),
+                position: score.speaker_position,
+                score: score.score,
+                counts_for_speaker_tab: true,
             })
             .collect();
+        apply_iron_speech_rule(tournament, &mut speaker_points);
 
         if !speaker_points.is_empty() {
             diesel::insert_into(agg_speaker_results_of_debate::table)
@@ -290,15 +289,7 @@ fn compute_averaged_speaker_scores(
     ballots: &[BallotRepr],
     tournament_id: &str,
     debate_id: &str,
-) -> Vec<(
-    diesel::dsl::Eq<agg_speaker_results_of_debate::id, String>,
-    diesel::dsl::Eq<agg_speaker_results_of_debate::tournament_id, String>,
-    diesel::dsl::Eq<agg_speaker_results_of_debate::debate_id, String>,
-    diesel::dsl::Eq<agg_speaker_results_of_debate::speaker_id, String>,
-    diesel::dsl::Eq<agg_speaker_results_of_debate::team_id, String>,
-    diesel::dsl::Eq<agg_speaker_results_of_debate::position, i64>,
-    diesel::dsl::Eq<agg_speaker_results_of_debate::score, Option<f32>>,
-)> {
+) -> Vec<TournamentDebateSpeakerResult> {
     let mut speaker_points = Vec::new();
 
     for score in &ballots[0].scores {
@@ -328,22 +319,83 @@ fn compute_averaged_speaker_scores(
         let avg = sum / Decimal::from_usize(ballots.len()).unwrap();
         let avg_f32: f32 = avg.round_dp(2).try_into().unwrap();
 
-        speaker_points.push((
-            agg_speaker_results_of_debate::id.eq(Uuid::now_v7().to_string()),
-            agg_speaker_results_of_debate::tournament_id
-                .eq(tournament_id.to_string()),
-            agg_speaker_results_of_debate::debate_id.eq(debate_id.to_string()),
-            agg_speaker_results_of_debate::speaker_id
-                .eq(score.speaker_id.clone()),
-            agg_speaker_results_of_debate::team_id.eq(score.team_id.clone()),
-            agg_speaker_results_of_debate::position.eq(score.speaker_position),
-            agg_speaker_results_of_debate::score.eq(Some(avg_f32)),
-        ));
+        speaker_points.push(TournamentDebateSpeakerResult {
+            id: Uuid::now_v7().to_string(),
+            tournament_id: tournament_id.to_string(),
+            debate_id: debate_id.to_string(),
+            speaker_id: score.speaker_id.clone(),
+            team_id: score.team_id.clone(),
+            position: score.speaker_position,
+            score: Some(avg_f32),
+            counts_for_speaker_tab: true,
+        });
     }
 
     speaker_points
 }
 
+/// When a speaker gives more than one substantive speech in a debate (e.g.
+/// because their team was a speaker short) the tournament's
+/// `iron_speech_rule` determines which of those speeches count towards the
+/// speaker tab. Reply speeches are never affected.
+pub fn apply_iron_speech_rule(
+    tournament: &Tournament,
+    results: &mut [TournamentDebateSpeakerResult],
+) {
+    apply_iron_speech_rule_to(
+        &tournament.iron_speech_rule,
+        tournament.substantive_speakers,
+        results,
+    );
+}
+
+fn apply_iron_speech_rule_to(
+    rule: &str,
+    substantive_speakers: i64,
+    results: &mut [TournamentDebateSpeakerResult],
+) {
+    let is_substantive = |result: &TournamentDebateSpeakerResult| -> bool {
+        result.position < substantive_speakers
+    };
+
+    let speakers_with_multiple_speeches = results
+        .iter()
+        .filter(|result| is_substantive(result))
+        .map(|result| (result.team_id.clone(), result.speaker_id.clone()))
+        .duplicates()
+        .collect::<HashSet<_>>();
+
+    for (team_id, speaker_id) in speakers_with_multiple_speeches {
+        let speeches = results
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| {
+                is_substantive(result)
+                    && result.team_id == team_id
+                    && result.speaker_id == speaker_id
+            })
+            .map(|(i, result)| (i, result.position, result.score))
+            .collect::<Vec<_>>();
+
+        let counted = match rule {
+            "both" => continue,
+            "first" => speeches.iter().min_by_key(|(_, position, _)| *position),
+            "highest" => speeches.iter().max_by(|a, b| {
+                a.2.unwrap_or(f32::MIN)
+                    .total_cmp(&b.2.unwrap_or(f32::MIN))
+                    // prefer the earlier speech when scores are tied
+                    .then(b.1.cmp(&a.1))
+            }),
+            other => unreachable!("invalid iron_speech_rule: {other}"),
+        }
+        .map(|(i, _, _)| *i);
+
+        for (i, _, _) in &speeches {
+            results[*i].counts_for_speaker_tab = Some(*i) == counted;
+        }
+    }
+}
+
 fn side_judge_voted_for_in_2_team_format(
     debate: &DebateRepr,
     ballot: &BallotRepr,
@@ -363,3 +415,50 @@ fn side_judge_voted_for_in_2_team_format(
         .expect("Winning team not found in debate")
         .side
 }
+
+#[cfg(test)]
+#[test]
+fn test_iron_speech_rule_picks_counted_speeches() {
+    // speaker "a" gives the first and third substantive speeches and the
+    // reply (position 3, with three substantive speakers)
+    let results = || {
+        [
+            ("a", 0, 74.0),
+            ("b", 1, 76.0),
+            ("a", 2, 77.0),
+            ("a", 3, 38.0),
+        ]
+        .into_iter()
+        .map(|(speaker, position, score)| TournamentDebateSpeakerResult {
+            id: position.to_string(),
+            tournament_id: "t".to_string(),
+            debate_id: "d".to_string(),
+            speaker_id: speaker.to_string(),
+            team_id: "team".to_string(),
+            position,
+            score: Some(score),
+            counts_for_speaker_tab: true,
+        })
+        .collect::<Vec<_>>()
+    };
+    let counted = |rule: &str| {
+        let mut results = results();
+        apply_iron_speech_rule_to(rule, 3, &mut results);
+        results
+            .iter()
+            .map(|result| result.counts_for_speaker_tab)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(counted("both"), vec![true, true, true, true]);
+    assert_eq!(counted("first"), vec![true, true, false, true]);
+    assert_eq!(counted("highest"), vec![false, true, true, true]);
+
+    // ties go to the earlier speech
+    let mut tied = results();
+    tied[2].score = Some(74.0);
+    apply_iron_speech_rule_to("highest", 3, &mut tied);
+    assert!(tied[0].counts_for_speaker_tab);
+    assert!(!tied[2].counts_for_speaker_tab);
+    assert!(tied[3].counts_for_speaker_tab);
+}
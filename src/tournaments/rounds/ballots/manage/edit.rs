@@ -1,14 +1,16 @@
 use crate::tournaments::rounds::ballots::form::QsForm;
-use axum::extract::Path;
+use axum::extract::{Extension, Path};
 use axum::response::Redirect;
 use chrono::Utc;
 use diesel::{connection::LoadConnection, sqlite::Sqlite};
 use hypertext::{Renderable, maud, prelude::*};
 use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::Sender;
 use uuid::Uuid;
 
 use crate::{
     auth::User,
+    msg::Msg,
     state::Conn,
     template::Page,
     tournaments::{
@@ -17,8 +19,8 @@ use crate::{
         rounds::{
             Round, TournamentRounds,
             ballots::{
-                BallotMetadata, BallotRepr, form::fields_of_single_ballot_form,
-                update_debate_status,
+                BallotMetadata, BallotRepr, ballot_update_msg,
+                form::fields_of_single_ballot_form, update_debate_status,
             },
             draws::DebateRepr,
         },
@@ -26,7 +28,7 @@ use crate::{
     util_resp::{StandardResponse, err_not_found, see_other_ok, success},
 };
 
-fn redirect_to_ballot_set(
+pub(super) fn redirect_to_ballot_set(
     tournament_id: &str,
     debate_id: &str,
 ) -> StandardResponse {
@@ -36,7 +38,7 @@ fn redirect_to_ballot_set(
     )))
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 /// Ballot form used for judges to submit ballots, and for tab directors to
 /// edit them behind the scenes.
 ///
@@ -57,7 +59,7 @@ pub struct BallotFormSingleSpeakerEntry {
     pub score: Option<f32>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct BallotFormSingleTeamEntry {
     pub speakers: Vec<BallotFormSingleSpeakerEntry>,
     pub points: Option<usize>,
@@ -146,7 +148,7 @@ impl BallotForm {
     prior_version,
     conn
 ))]
-fn build_edit_ballot(
+pub(super) fn build_edit_ballot(
     form: BallotForm,
     tournament: &Tournament,
     round: &Round,
@@ -279,6 +281,7 @@ pub async fn edit_ballot_page(
 pub async fn do_edit_ballot(
     Path((tournament_id, debate_id, judge_id)): Path<(String, String, String)>,
     user: User<true>,
+    Extension(tx): Extension<Sender<Msg>>,
     mut conn: Conn<true>,
     QsForm(form): QsForm<BallotForm>,
 ) -> StandardResponse {
@@ -346,7 +349,8 @@ pub async fn do_edit_ballot(
 
     // Refresh debate repr and update status
     let debate_repr = DebateRepr::fetch(&debate_id, &mut *conn);
-    update_debate_status(&debate_repr, &tournament, &mut *conn);
+    let status = update_debate_status(&debate_repr, &tournament, &mut *conn);
+    let _ = tx.send(ballot_update_msg(&tournament, &debate_id, status));
 
     see_other_ok(Redirect::to(&format!(
         "/tournaments/{}/debates/{}/ballots",
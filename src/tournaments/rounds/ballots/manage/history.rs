@@ -0,0 +1,368 @@
+//! The version history of the ballots of a debate, used to answer appeals
+//! once results have been released.
+
+use axum::extract::{Extension, Path};
+use axum::response::Redirect;
+use diesel::{connection::LoadConnection, sqlite::Sqlite};
+use hypertext::prelude::*;
+use itertools::Itertools;
+use tokio::sync::broadcast::Sender;
+
+use crate::{
+    auth::User,
+    msg::Msg,
+    state::Conn,
+    template::Page,
+    tournaments::{
+        Tournament,
+        manage::sidebar::{SidebarPage, SidebarWrapper},
+        rounds::{
+            Round, TournamentRounds,
+            ballots::{
+                BallotRepr, ballot_update_msg,
+                manage::{
+                    resolve::{comparison_rows, copy_ballot_for_judge},
+                    view_ballot_set::editor_names,
+                },
+                update_debate_status,
+            },
+            draws::DebateRepr,
+        },
+    },
+    util_resp::{
+        StandardResponse, bad_request, err_not_found, see_other_ok, success,
+    },
+};
+
+/// A field which changed between two consecutive versions of a ballot.
+struct FieldChange {
+    label: String,
+    before: String,
+    after: String,
+}
+
+/// Computes the fields which differ between `before` and `after`.
+fn diff_of_versions(
+    before: &BallotRepr,
+    after: &BallotRepr,
+    tournament: &Tournament,
+    debate: &DebateRepr,
+) -> Vec<FieldChange> {
+    let pair = [before.clone(), after.clone()];
+
+    comparison_rows(&pair, tournament, debate)
+        .into_iter()
+        .filter(|row| row.disagrees())
+        .map(|mut row| {
+            let after = row.values.pop().unwrap();
+            let before = row.values.pop().unwrap();
+            FieldChange {
+                label: row.label,
+                before,
+                after,
+            }
+        })
+        .collect()
+}
+
+#[tracing::instrument(skip(conn))]
+/// Lists every version of every ballot submitted for a debate, along with
+/// who made each change and what it changed.
+pub async fn ballot_history_page(
+    Path((tournament_id, debate_id)): Path<(String, String)>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let all_rounds =
+        TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+    let debate = match DebateRepr::try_fetch(&debate_id, &mut *conn) {
+        Ok(debate) if debate.debate.tournament_id == tournament.id => debate,
+        _ => return err_not_found(),
+    };
+    let round =
+        Round::fetch(&tournament_id, &debate.debate.round_id, &mut *conn)?;
+
+    let history = debate
+        .ballot_history(&mut *conn)
+        .into_iter()
+        .into_group_map_by(|b| b.metadata.judge_id.clone());
+
+    let editor_names = editor_names(history.values().flatten(), &mut *conn);
+
+    // (judge_id, versions in ascending order, changes relative to the
+    // previous version)
+    let timelines = debate
+        .judges_of_debate
+        .iter()
+        .filter_map(|dj| {
+            history.get(&dj.judge_id).map(|ballots| {
+                let versions = ballots
+                    .iter()
+                    .sorted_by_key(|b| b.metadata.version)
+                    .collect_vec();
+                let diffs = versions
+                    .iter()
+                    .enumerate()
+                    .map(|(i, ballot)| {
+                        if i == 0 {
+                            Vec::new()
+                        } else {
+                            diff_of_versions(
+                                versions[i - 1],
+                                ballot,
+                                &tournament,
+                                &debate,
+                            )
+                        }
+                    })
+                    .collect_vec();
+                (dj.judge_id.clone(), versions, diffs)
+            })
+        })
+        .collect_vec();
+
+    success(
+        Page::new()
+            .user(user)
+            .tournament(tournament.clone())
+            .body(maud! {
+                SidebarWrapper
+                    rounds=(&all_rounds)
+                    tournament=(&tournament)
+                    active_page=(Some(SidebarPage::Ballots))
+                    selected_seq=(Some(round.seq))
+                {
+                    div class="container py-5" style="max-width: 900px;" {
+                        header class="mb-5" {
+                            a href=(format!("/tournaments/{}/debates/{}/ballots", tournament.id, debate.debate.id)) class="btn btn-sm btn-outline-secondary mb-3" {
+                                "← Back to Ballot Overview"
+                            }
+                            h1 class="display-4 fw-bold mb-2" {
+                                "Ballot History for Debate " (debate.debate.number)
+                            }
+                            @if round.completed {
+                                div class="alert alert-warning mt-3" {
+                                    "This round has already been completed. After "
+                                    "reverting a ballot, mark the round as incomplete "
+                                    "and then complete it again to recompute the results."
+                                }
+                            }
+                        }
+
+                        @if timelines.is_empty() {
+                            p class="text-muted" { "No ballots have been submitted for this debate." }
+                        }
+
+                        @for (judge_id, versions, diffs) in &timelines {
+                            @let judge = debate.judges.get(judge_id).unwrap();
+                            @let latest_version = versions.last().unwrap().metadata.version;
+
+                            div class="card mb-4" {
+                                div class="card-header bg-light" {
+                                    h2 class="h5 mb-0" { (judge.name) " (" (debate.judge_role(judge_id)) ")" }
+                                }
+                                ul class="list-group list-group-flush" {
+                                    @for (i, (ballot, diff)) in versions.iter().zip(diffs.iter()).enumerate().rev() {
+                                        li class="list-group-item p-3" {
+                                            div class="d-flex justify-content-between align-items-start" {
+                                                div {
+                                                    h3 class="h6 mb-1" {
+                                                        "Version " (ballot.metadata.version)
+                                                        @if ballot.metadata.version == latest_version {
+                                                            span class="badge bg-dark ms-2" { "Current" }
+                                                        }
+                                                    }
+                                                    small class="text-muted" {
+                                                        (ballot.metadata.submitted_at.format("%Y-%m-%d %H:%M:%S").to_string())
+                                                        " — "
+                                                        @if let Some(editor_id) = &ballot.metadata.editor_id {
+                                                            "edited by "
+                                                            (editor_names.get(editor_id).map(|s| s.as_str()).unwrap_or("unknown"))
+                                                        } @else {
+                                                            "submitted by the judge via their private URL"
+                                                        }
+                                                        @if let Some(change) = &ballot.metadata.change {
+                                                            " — " (change)
+                                                        }
+                                                    }
+                                                }
+                                                div class="d-flex gap-2" {
+                                                    a href=(format!("/tournaments/{}/debates/{}/ballots/{}/view", tournament.id, debate.debate.id, ballot.metadata.id)) class="btn btn-sm btn-outline-secondary" {
+                                                        "View"
+                                                    }
+                                                    @if ballot.metadata.version != latest_version {
+                                                        form method="post" action=(format!("/tournaments/{}/debates/{}/ballots/{}/revert", tournament.id, debate.debate.id, ballot.metadata.id)) {
+                                                            button type="submit" class="btn btn-sm btn-outline-danger" {
+                                                                "Revert to this version"
+                                                            }
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                            @if i > 0 {
+                                                @if diff.is_empty() {
+                                                    p class="small text-muted mt-2 mb-0" { "No fields changed." }
+                                                } @else {
+                                                    table class="table table-sm mt-2 mb-0" {
+                                                        thead {
+                                                            tr {
+                                                                th { "Field" }
+                                                                th { "Before" }
+                                                                th { "After" }
+                                                            }
+                                                        }
+                                                        tbody {
+                                                            @for change in diff {
+                                                                tr {
+                                                                    td { (change.label) }
+                                                                    td class="text-danger" { (change.before) }
+                                                                    td class="text-success" { (change.after) }
+                                                                }
+                                                            }
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+            .render(),
+    )
+}
+
+/// Saves a copy of `target` as the version after `latest` (the newest version
+/// of the same judge's ballot).
+fn revert_ballot(
+    target: &BallotRepr,
+    latest: &BallotRepr,
+    editor_id: &str,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> BallotRepr {
+    let reverted = copy_ballot_for_judge(
+        target,
+        &target.metadata.judge_id,
+        latest.metadata.version + 1,
+        format!("Reverted to version {}", target.metadata.version),
+        editor_id,
+    );
+    reverted.insert(conn);
+    reverted
+}
+
+#[tracing::instrument(skip(conn, tx))]
+/// Restores an earlier version of a judge's ballot, by saving a copy of it as
+/// the newest version.
+pub async fn do_revert_ballot(
+    Path((tournament_id, debate_id, ballot_id)): Path<(String, String, String)>,
+    user: User<true>,
+    Extension(tx): Extension<Sender<Msg>>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let debate = match DebateRepr::try_fetch(&debate_id, &mut *conn) {
+        Ok(debate) if debate.debate.tournament_id == tournament.id => debate,
+        _ => return err_not_found(),
+    };
+
+    let target = match debate
+        .ballot_history(&mut *conn)
+        .into_iter()
+        .find(|b| b.metadata.id == ballot_id)
+    {
+        Some(ballot) => ballot,
+        None => return err_not_found(),
+    };
+
+    let latest = debate
+        .latest_ballots(&mut *conn)
+        .into_iter()
+        .find(|b| b.metadata.judge_id == target.metadata.judge_id)
+        .expect("a judge with a ballot in the history has a latest ballot");
+
+    if latest.metadata.id == target.metadata.id {
+        return bad_request(
+            maud! {
+                "This is already the current version of the ballot."
+            }
+            .render(),
+        );
+    }
+
+    revert_ballot(&target, &latest, &user.id, &mut *conn);
+
+    let debate = DebateRepr::fetch(&debate_id, &mut *conn);
+    let status = update_debate_status(&debate, &tournament, &mut *conn);
+    let _ = tx.send(ballot_update_msg(&tournament, &debate_id, status));
+
+    see_other_ok(Redirect::to(&format!(
+        "/tournaments/{}/debates/{}/ballots/history",
+        tournament_id, debate_id
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::{diff_of_versions, revert_ballot};
+    use crate::tournaments::rounds::ballots::manage::resolve::tests::{
+        ballot, debate_with_panel,
+    };
+
+    #[test]
+    fn diff_lists_the_fields_which_changed() {
+        let (_, tournament, _, debate) = debate_with_panel();
+        let before = ballot("chair", 75.0, 74.0);
+        let after = ballot("chair", 74.0, 75.0);
+
+        let changes = diff_of_versions(&before, &after, &tournament, &debate);
+        assert_eq!(
+            changes
+                .iter()
+                .map(|c| (c.before.as_str(), c.after.as_str()))
+                .collect_vec(),
+            [("75", "74"), ("1", "0"), ("74", "75"), ("0", "1")]
+        );
+        assert!(changes[0].label.ends_with("(score)"));
+        assert!(changes[1].label.ends_with("(points)"));
+
+        assert!(
+            diff_of_versions(&before, &before, &tournament, &debate).is_empty()
+        );
+    }
+
+    #[test]
+    fn reverting_saves_the_old_version_as_the_newest() {
+        let (mut conn, tournament, _, debate) = debate_with_panel();
+        let original = ballot("chair", 75.0, 74.0);
+        original.insert(&mut conn);
+        let mut edited = ballot("chair", 74.0, 75.0);
+        edited.metadata.version = 1;
+        edited.insert(&mut conn);
+
+        revert_ballot(&original, &edited, "admin", &mut conn);
+
+        let latest = debate.latest_ballots(&mut conn);
+        assert_eq!(latest.len(), 1);
+        let reverted = &latest[0];
+        assert_eq!(reverted.metadata.version, 2);
+        assert_eq!(
+            reverted.metadata.change.as_deref(),
+            Some("Reverted to version 0")
+        );
+        assert!(reverted.is_isomorphic(&original, &tournament, &debate));
+        assert!(
+            diff_of_versions(&original, reverted, &tournament, &debate)
+                .is_empty()
+        );
+        assert_eq!(debate.ballot_history(&mut conn).len(), 3);
+    }
+}
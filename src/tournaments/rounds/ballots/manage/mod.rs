@@ -1,3 +1,6 @@
 pub mod edit;
+pub mod history;
 pub mod overview;
+pub mod resolve;
+pub mod substitutes;
 pub mod view_ballot_set;
@@ -1,16 +1,26 @@
-use axum::extract::Path;
-use diesel::prelude::*;
-use hypertext::prelude::*;
-use itertools::Itertools;
+use axum::{
+    extract::{
+        Extension, Path,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    response::IntoResponse,
+};
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use diesel::{connection::LoadConnection, prelude::*, sqlite::Sqlite};
+use hypertext::{Renderable, prelude::*};
+use itertools::{Either, Itertools};
+use tokio::{sync::broadcast::Sender, task::spawn_blocking};
 
 use crate::{
     auth::User,
-    schema::{debates, rounds},
-    state::Conn,
+    msg::{Msg, MsgContents},
+    schema::debates,
+    state::{Conn, DbPool},
     template::Page,
     tournaments::{
         Tournament,
         manage::sidebar::SidebarWrapper,
+        participants::Judge,
         rounds::{
             Round, TournamentRounds,
             ballots::BallotRepr,
@@ -20,63 +30,151 @@ use crate::{
     util_resp::{StandardResponse, err_not_found, success},
 };
 
-pub async fn admin_ballot_of_seq_overview(
-    Path((tournament_id, round_seq)): Path<(String, i64)>,
-    user: User<true>,
-    mut conn: Conn<true>,
-) -> StandardResponse {
-    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
-    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+/// The live-updating part of the ballot overview for a round (i.e. everything
+/// below the page heading).
+pub struct BallotOverview {
+    tournament: Tournament,
+    round_seq: i64,
+    rounds: Vec<Round>,
+    ballot_sets: Vec<(DebateRepr, Vec<BallotRepr>)>,
+    now: NaiveDateTime,
+}
 
-    let all_rounds =
-        TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+impl BallotOverview {
+    pub fn load(
+        tournament: &Tournament,
+        round_seq: i64,
+        conn: &mut impl LoadConnection<Backend = Sqlite>,
+    ) -> Option<Self> {
+        let rounds = Round::of_seq(round_seq, &tournament.id, conn);
+        if rounds.is_empty() {
+            return None;
+        }
 
-    let rounds = rounds::table
-        .filter(rounds::seq.eq(round_seq))
-        .load::<Round>(&mut *conn)
-        .unwrap();
+        let debates = debates::table
+            .filter(
+                debates::round_id
+                    .eq_any(rounds.iter().map(|r| r.id.clone()).collect_vec()),
+            )
+            .order_by((debates::round_id.asc(), debates::number.asc()))
+            .load::<Debate>(conn)
+            .unwrap();
 
-    if rounds.is_empty() {
-        return err_not_found();
-    }
+        let ballot_sets = debates
+            .iter()
+            .map(|debate| {
+                let debate_repr = DebateRepr::fetch(&debate.id, conn);
+                let ballots = debate_repr.latest_ballots(conn);
+                (debate_repr, ballots)
+            })
+            .collect_vec();
 
-    let debates = debates::table
-        .inner_join(
-            rounds::table.on(rounds::seq
-                .eq(round_seq)
-                .and(debates::round_id.eq(rounds::id))),
-        )
-        .order_by((rounds::id.asc(), debates::number.asc()))
-        .select(debates::all_columns)
-        .load::<Debate>(&mut *conn)
-        .unwrap();
-
-    let ballot_sets: Vec<(DebateRepr, Vec<BallotRepr>)> = debates
-        .iter()
-        .map(|debate| {
-            let debate_repr = DebateRepr::fetch(&debate.id, &mut *conn);
-            let ballots = debate_repr.latest_ballots(&mut *conn);
-            (debate_repr, ballots)
+        Some(Self {
+            tournament: tournament.clone(),
+            round_seq,
+            rounds,
+            ballot_sets,
+            now: Utc::now().naive_utc(),
         })
-        .collect_vec();
+    }
 
-    let html = {
-        let tournament = tournament.clone();
-        maud! {
-            SidebarWrapper rounds=(&all_rounds) tournament=(&tournament)
-                active_page=(Some(crate::tournaments::manage::sidebar::SidebarPage::Ballots))
-                selected_seq=(Some(round_seq)) {
+    /// The time at which the round started. We treat the release of the draw
+    /// (or of the motions, if this happens separately) as the start of the
+    /// round.
+    fn started_at(&self) -> Option<NaiveDateTime> {
+        self.rounds
+            .iter()
+            .filter_map(|r| r.motions_released_at.or(r.draw_released_at))
+            .min()
+    }
 
-                div class="mb-4 pb-3 border-bottom border-2 border-dark" {
-                    h1 class="mb-2" { "Ballot Status" }
-                    p class="text-muted mb-0" { (rounds[0].name) }
-                }
+    /// Non-trainee judges who have not submitted a ballot, although the
+    /// round started more than `ballot_overdue_after_mins` minutes ago.
+    fn overdue_judges(&self) -> Vec<(&DebateRepr, &Judge)> {
+        let overdue = match self.started_at() {
+            Some(start) => {
+                self.now - start
+                    >= TimeDelta::minutes(
+                        self.tournament.ballot_overdue_after_mins,
+                    )
+            }
+            None => false,
+        };
+        if !overdue {
+            return Vec::new();
+        }
+
+        self.ballot_sets
+            .iter()
+            .filter(|(debate, _)| {
+                !self
+                    .rounds
+                    .iter()
+                    .find(|r| r.id == debate.debate.round_id)
+                    .unwrap()
+                    .completed
+            })
+            .flat_map(|(debate, ballots)| {
+                debate
+                    .judges_of_debate
+                    .iter()
+                    .filter(|dj| dj.status != "T")
+                    .filter(|dj| {
+                        !ballots
+                            .iter()
+                            .any(|b| b.metadata.judge_id == dj.judge_id)
+                    })
+                    .map(move |dj| (debate, &debate.judges[&dj.judge_id]))
+            })
+            .collect()
+    }
+
+    /// The number of debates whose ballot set has the given status.
+    fn count_of_status(&self, status: &str) -> usize {
+        self.ballot_sets
+            .iter()
+            .filter(|(debate, _)| debate.debate.status == status)
+            .count()
+    }
+
+    /// The number of ballots submitted, and the number expected (one from
+    /// every judge on every panel).
+    fn ballot_counts(&self) -> (usize, usize) {
+        let submitted = self
+            .ballot_sets
+            .iter()
+            .map(|(_, ballots)| ballots.len())
+            .sum();
+        let expected = self
+            .ballot_sets
+            .iter()
+            .map(|(debate, _)| debate.judges_of_debate.len())
+            .sum();
+        (submitted, expected)
+    }
+}
+
+fn format_elapsed(elapsed: TimeDelta) -> String {
+    let mins = elapsed.num_minutes().max(0);
+    format!("{}h {:02}m", mins / 60, mins % 60)
+}
+
+impl Renderable for BallotOverview {
+    fn render_to(
+        &self,
+        buffer: &mut hypertext::Buffer<hypertext::context::Node>,
+    ) {
+        let tournament = &self.tournament;
+        let rounds = &self.rounds;
+        let ballot_sets = &self.ballot_sets;
+        let started_at = self.started_at();
+        let overdue_judges = self.overdue_judges();
+        let (total_ballots, total_expected) = self.ballot_counts();
+
+        maud! {
+            div id="ballotOverview" hx-ext="ws" hx-swap-oob="morphdom"
+                "ws-connect"=(format!("/tournaments/{}/rounds/{}/ballots/ws", tournament.id, self.round_seq)) {
 
-                @let total_ballots = ballot_sets.iter()
-                    .map(|(_, ballots)| ballots.len()).sum::<usize>();
-                @let total_expected = ballot_sets.iter()
-                    .map(|(debate, _)| debate.judges_of_debate.len())
-                    .sum::<usize>();
                 @let completion_rate = if total_expected > 0 {
                     (total_ballots as f64 / total_expected as f64 * 100.0) as i32
                 } else {
@@ -113,8 +211,73 @@ pub async fn admin_ballot_of_seq_overview(
                             div class="fs-3 fw-bold" { (completion_rate) "%" }
                         }
                     }
+                    div class="col-md-3" {
+                        div class="p-3 border border-dark rounded-3" {
+                            div class="text-uppercase small fw-bold text-muted mb-1"
+                                style="letter-spacing: 1px;" {
+                                "Confirmed"
+                            }
+                            div class="fs-3 fw-bold" { (self.count_of_status("confirmed")) }
+                        }
+                    }
+                    div class="col-md-3" {
+                        div class="p-3 border border-dark rounded-3" {
+                            div class="text-uppercase small fw-bold text-muted mb-1"
+                                style="letter-spacing: 1px;" {
+                                "Conflicts"
+                            }
+                            div class="fs-3 fw-bold" { (self.count_of_status("conflict")) }
+                        }
+                    }
+                    div class="col-md-3" {
+                        div class="p-3 border border-dark rounded-3" {
+                            div class="text-uppercase small fw-bold text-muted mb-1"
+                                style="letter-spacing: 1px;" {
+                                "Awaiting Ballots"
+                            }
+                            div class="fs-3 fw-bold" { (self.count_of_status("draft")) }
+                        }
+                    }
+                    div class="col-md-3" {
+                        div class="p-3 border border-dark rounded-3" {
+                            div class="text-uppercase small fw-bold text-muted mb-1"
+                                style="letter-spacing: 1px;" {
+                                "Since Round Started"
+                            }
+                            div class="fs-3 fw-bold" {
+                                @match started_at {
+                                    Some(start) => {
+                                        (format_elapsed(self.now - start))
+                                    },
+                                    None => {
+                                        "Not started"
+                                    },
+                                }
+                            }
+                        }
+                    }
                 }
 
+                @if !overdue_judges.is_empty() {
+                    div class="alert alert-danger mb-4" {
+                        h2 class="h6 fw-bold" {
+                            "Overdue ballots (more than "
+                            (tournament.ballot_overdue_after_mins)
+                            " minutes since the round started)"
+                        }
+                        ul class="mb-0" {
+                            @for (debate, judge) in &overdue_judges {
+                                li {
+                                    (judge.name) " (debate " (debate.debate.number) ") — "
+                                    a href=(format!("/tournaments/{}/privateurls/{}", tournament.id, judge.private_url))
+                                        class="alert-link" {
+                                        "private URL"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
                 div class="table-responsive" {
                     table class="table table-hover table-borderless align-middle" {
                         thead class="border-bottom border-dark" {
@@ -293,13 +456,172 @@ pub async fn admin_ballot_of_seq_overview(
                 }
             }
         }
+        .render_to(buffer);
+    }
+}
+
+pub async fn admin_ballot_of_seq_overview(
+    Path((tournament_id, round_seq)): Path<(String, i64)>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+
+    let all_rounds =
+        TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+
+    let overview =
+        match BallotOverview::load(&tournament, round_seq, &mut *conn) {
+            Some(overview) => overview,
+            None => return err_not_found(),
+        };
+
+    let html = {
+        let tournament = tournament.clone();
+        maud! {
+            SidebarWrapper rounds=(&all_rounds) tournament=(&tournament)
+                active_page=(Some(crate::tournaments::manage::sidebar::SidebarPage::Ballots))
+                selected_seq=(Some(round_seq)) {
+
+                div class="mb-4 pb-3 border-bottom border-2 border-dark" {
+                    h1 class="mb-2" { "Ballot Status" }
+                    p class="text-muted mb-0" { (overview.rounds[0].name) }
+                }
+
+                (overview)
+            }
+        }
     };
 
     success(
         Page::new()
             .user(user)
             .tournament(tournament.clone())
+            .extra_head(maud! {
+                script src="https://cdn.jsdelivr.net/npm/htmx-ext-ws@2.0.2" crossorigin="anonymous" {
+                }
+            })
             .body(html)
             .render(),
     )
 }
+
+pub async fn ballot_overview_updates(
+    Path((tournament_id, round_seq)): Path<(String, i64)>,
+    ws: WebSocketUpgrade,
+    Extension(pool): Extension<DbPool>,
+    Extension(tx): Extension<Sender<Msg>>,
+    user: User<false>,
+) -> impl IntoResponse {
+    let pool1 = pool.clone();
+    let mut conn = spawn_blocking(move || pool1.get().unwrap()).await.unwrap();
+
+    let tournament = match Tournament::fetch(&tournament_id, &mut conn) {
+        Ok(t) => t,
+        Err(_) => return axum::http::StatusCode::NOT_FOUND.into_response(),
+    };
+
+    if tournament
+        .check_user_is_superuser(&user.id, &mut conn)
+        .is_err()
+    {
+        return axum::http::StatusCode::FORBIDDEN.into_response();
+    }
+
+    if Round::of_seq(round_seq, &tournament.id, &mut conn).is_empty() {
+        return axum::http::StatusCode::NOT_FOUND.into_response();
+    }
+
+    ws.on_upgrade(move |socket| {
+        handle_socket(socket, pool, tx, round_seq, tournament)
+    })
+}
+
+async fn handle_socket(
+    mut socket: WebSocket,
+    pool: DbPool,
+    tx: Sender<Msg>,
+    round_seq: i64,
+    tournament: Tournament,
+) {
+    let mut rx = tx.subscribe();
+
+    loop {
+        let msg = tokio::select! {
+            msg = rx.recv() => Either::Left(msg),
+            msg = socket.recv() => Either::Right(msg),
+        };
+
+        match msg {
+            Either::Left(Ok(msg)) => {
+                if msg.tournament.id == tournament.id
+                    && matches!(
+                        msg.inner,
+                        MsgContents::BallotSubmitted(_)
+                            | MsgContents::BallotConflict(_)
+                            | MsgContents::BallotConfirmed(_)
+                            | MsgContents::DrawUpdated(_)
+                    )
+                {
+                    let pool1 = pool.clone();
+                    let tournament = tournament.clone();
+
+                    let html = spawn_blocking(move || {
+                        let mut conn = pool1.get().unwrap();
+                        BallotOverview::load(&tournament, round_seq, &mut *conn)
+                            .map(|overview| overview.render().into_inner())
+                    })
+                    .await
+                    .unwrap();
+
+                    let Some(html) = html else {
+                        break;
+                    };
+
+                    if socket.send(Message::Text(html)).await.is_err() {
+                        break;
+                    }
+                }
+            }
+            Either::Right(Some(Ok(Message::Close(_))))
+            | Either::Right(None) => {
+                break;
+            }
+            Either::Right(Some(Err(_))) => {
+                break;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tournaments::rounds::ballots::manage::resolve::tests::{
+        ballot, debate_with_panel,
+    };
+
+    #[test]
+    fn overview_counts_pending_and_confirmed_ballot_sets() {
+        let (mut conn, tournament, _, _) = debate_with_panel();
+        ballot("chair", 75.0, 74.0).insert(&mut conn);
+        ballot("p1", 75.0, 74.0).insert(&mut conn);
+
+        let overview = BallotOverview::load(&tournament, 1, &mut conn).unwrap();
+        assert_eq!(overview.ballot_counts(), (2, 3));
+        assert_eq!(overview.count_of_status("draft"), 1);
+        assert_eq!(overview.count_of_status("confirmed"), 0);
+
+        diesel::update(debates::table.filter(debates::id.eq("d1")))
+            .set(debates::status.eq("confirmed"))
+            .execute(&mut conn)
+            .unwrap();
+        let overview = BallotOverview::load(&tournament, 1, &mut conn).unwrap();
+        assert_eq!(overview.count_of_status("draft"), 0);
+        assert_eq!(overview.count_of_status("confirmed"), 1);
+
+        assert!(BallotOverview::load(&tournament, 2, &mut conn).is_none());
+    }
+}
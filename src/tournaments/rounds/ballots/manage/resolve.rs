@@ -0,0 +1,947 @@
+//! Tools for resolving conflicts between the ballots submitted by the judges
+//! on a panel.
+//!
+//! The conflict view lines up the latest ballot from each judge next to each
+//! other, and highlights every field on which they disagree. From there the
+//! tab director can either accept one judge's ballot as canonical (which
+//! copies it over the ballots of the other judges) or edit a merged ballot,
+//! which is then saved as a new version for every judge on the panel.
+
+use axum::extract::{Extension, Path};
+use axum::response::Redirect;
+use chrono::Utc;
+use diesel::{connection::LoadConnection, sqlite::Sqlite};
+use hypertext::prelude::*;
+use itertools::Itertools;
+use tokio::sync::broadcast::Sender;
+use uuid::Uuid;
+
+use crate::{
+    auth::User,
+    msg::Msg,
+    state::Conn,
+    template::Page,
+    tournaments::{
+        Tournament,
+        manage::sidebar::{SidebarPage, SidebarWrapper},
+        rounds::{
+            Round, TournamentRounds,
+            ballots::{
+                BallotMetadata, BallotRepr, BallotScore, BallotTeamRank,
+                ballot_update_msg,
+                form::{QsForm, fields_of_single_ballot_form},
+                manage::edit::{
+                    BallotForm, build_edit_ballot, redirect_to_ballot_set,
+                },
+                update_debate_status,
+            },
+            draws::DebateRepr,
+            side_names::name_of_side,
+        },
+    },
+    util_resp::{
+        FailureResponse, StandardResponse, bad_request, err_not_found,
+        see_other_ok, success,
+    },
+};
+
+/// A single row of the side-by-side comparison, containing the value of one
+/// field on each of the ballots being compared.
+pub(super) struct ComparisonRow {
+    pub(super) label: String,
+    pub(super) values: Vec<String>,
+}
+
+impl ComparisonRow {
+    pub(super) fn disagrees(&self) -> bool {
+        self.values.iter().unique().count() > 1
+    }
+}
+
+/// Produces the rows of the comparison table, in the order the fields appear
+/// on the ballot (motion, then each team's speakers followed by its points).
+pub(super) fn comparison_rows(
+    ballots: &[BallotRepr],
+    tournament: &Tournament,
+    debate: &DebateRepr,
+) -> Vec<ComparisonRow> {
+    let mut rows = Vec::new();
+
+    if debate.motions.len() > 1 {
+        rows.push(ComparisonRow {
+            label: "Motion".to_string(),
+            values: ballots
+                .iter()
+                .map(|ballot| {
+                    debate
+                        .motions
+                        .get(&ballot.metadata.motion_id)
+                        .map(|m| m.motion.clone())
+                        .unwrap_or_else(|| "unknown motion".to_string())
+                })
+                .collect(),
+        });
+    }
+
+    let n_positions = ballots
+        .iter()
+        .flat_map(|ballot| ballot.scores.iter().map(|s| s.speaker_position))
+        .max()
+        .map(|max| max + 1)
+        .unwrap_or(0);
+
+    for debate_team in debate
+        .teams_of_debate
+        .iter()
+        .sorted_by_key(|dt| (dt.seq, dt.side))
+    {
+        let short_name =
+            name_of_side(tournament, debate_team.side, debate_team.seq, true);
+        let speakers = debate.speakers_of_team.get(&debate_team.team_id);
+
+        for position in 0..n_positions {
+            let position_name = tournament.speaker_position_name(
+                debate_team.side,
+                debate_team.seq,
+                position,
+            );
+
+            let scores = ballots
+                .iter()
+                .map(|ballot| {
+                    ballot
+                        .scores_of_team(&debate_team.team_id)
+                        .into_iter()
+                        .find(|score| score.speaker_position == position)
+                })
+                .collect_vec();
+
+            rows.push(ComparisonRow {
+                label: format!("{position_name} (speaker)"),
+                values: scores
+                    .iter()
+                    .map(|score| match score {
+                        Some(score) => speakers
+                            .and_then(|speakers| {
+                                speakers
+                                    .iter()
+                                    .find(|s| s.id == score.speaker_id)
+                            })
+                            .map(|s| s.name.clone())
+                            .unwrap_or_else(|| "unknown speaker".to_string()),
+                        None => "-".to_string(),
+                    })
+                    .collect(),
+            });
+
+            if scores.iter().flatten().any(|score| score.score.is_some()) {
+                rows.push(ComparisonRow {
+                    label: format!("{position_name} (score)"),
+                    values: scores
+                        .iter()
+                        .map(|score| {
+                            score
+                                .as_ref()
+                                .and_then(|score| score.score)
+                                .map(|s| s.to_string())
+                                .unwrap_or_else(|| "-".to_string())
+                        })
+                        .collect(),
+                });
+            }
+        }
+
+        rows.push(ComparisonRow {
+            label: format!("{short_name} (points)"),
+            values: ballots
+                .iter()
+                .map(|ballot| {
+                    ballot
+                        .team_ranks
+                        .iter()
+                        .find(|tr| tr.team_id == debate_team.team_id)
+                        .map(|tr| tr.points.to_string())
+                        .unwrap_or_else(|| "-".to_string())
+                })
+                .collect(),
+        });
+    }
+
+    rows
+}
+
+#[tracing::instrument(skip(conn))]
+/// Displays the latest ballots of every judge in a debate side-by-side, with
+/// the fields that disagree highlighted.
+pub async fn resolve_ballot_conflict_page(
+    Path((tournament_id, debate_id)): Path<(String, String)>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let all_rounds =
+        TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+    let debate = match DebateRepr::try_fetch(&debate_id, &mut *conn) {
+        Ok(debate) if debate.debate.tournament_id == tournament.id => debate,
+        _ => return err_not_found(),
+    };
+    let round =
+        Round::fetch(&tournament_id, &debate.debate.round_id, &mut *conn)?;
+
+    let ballots = debate
+        .latest_ballots(&mut *conn)
+        .into_iter()
+        .sorted_by_key(|ballot| {
+            debate
+                .judges_of_debate
+                .iter()
+                .position(|dj| dj.judge_id == ballot.metadata.judge_id)
+        })
+        .collect_vec();
+
+    let rows = comparison_rows(&ballots, &tournament, &debate);
+    let n_disagreements = rows.iter().filter(|row| row.disagrees()).count();
+
+    success(
+        Page::new()
+            .user(user)
+            .tournament(tournament.clone())
+            .body(maud! {
+                SidebarWrapper
+                    rounds=(&all_rounds)
+                    tournament=(&tournament)
+                    active_page=(Some(SidebarPage::Ballots))
+                    selected_seq=(Some(round.seq))
+                {
+                    div class="container py-5" {
+                        header class="mb-5" {
+                            a href=(format!("/tournaments/{}/debates/{}/ballots", tournament.id, debate.debate.id)) class="btn btn-sm btn-outline-secondary mb-3" {
+                                "← Back to Ballot Overview"
+                            }
+                            h1 class="display-4 fw-bold mb-2" {
+                                "Resolve Ballots for Debate " (debate.debate.number)
+                            }
+                            @if ballots.len() < 2 {
+                                p class="text-muted" {
+                                    "At least two ballots are needed before they can be compared."
+                                }
+                            } @else if n_disagreements == 0 {
+                                p class="text-muted" {
+                                    "All submitted ballots agree."
+                                }
+                            } @else {
+                                p class="text-muted" {
+                                    "The ballots disagree on " (n_disagreements) " field(s), which are highlighted below."
+                                }
+                            }
+                        }
+
+                        @if !ballots.is_empty() {
+                            div class="table-responsive" {
+                                table class="table table-bordered align-middle" {
+                                    thead {
+                                        tr {
+                                            th scope="col" { "Field" }
+                                            @for ballot in &ballots {
+                                                @let judge = debate.judges.get(&ballot.metadata.judge_id).unwrap();
+                                                th scope="col" {
+                                                    (judge.name) " (" (debate.judge_role(&judge.id)) ")"
+                                                    br;
+                                                    small class="text-muted fw-normal" {
+                                                        "Version " (ballot.metadata.version)
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                    tbody {
+                                        @for row in &rows {
+                                            @let disagrees = row.disagrees();
+                                            tr class=(if disagrees { "table-warning" } else { "" }) {
+                                                th scope="row" { (row.label) }
+                                                @for value in &row.values {
+                                                    td {
+                                                        @if disagrees {
+                                                            strong { (value) }
+                                                        } @else {
+                                                            (value)
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        tr {
+                                            td {}
+                                            @for ballot in &ballots {
+                                                td {
+                                                    form method="post" action=(format!("/tournaments/{}/debates/{}/ballots/{}/accept", tournament.id, debate.debate.id, ballot.metadata.id)) class="mb-2" {
+                                                        button type="submit" class="btn btn-sm btn-dark" disabled=(ballots.len() < 2) {
+                                                            "Accept this ballot"
+                                                        }
+                                                    }
+                                                    a href=(format!("/tournaments/{}/debates/{}/ballots/{}/merge", tournament.id, debate.debate.id, ballot.metadata.id)) class="btn btn-sm btn-outline-dark" {
+                                                        "Edit merged ballot"
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+            .render(),
+    )
+}
+
+/// Creates a copy of `ballot` attributed to `judge_id`, as the next version of
+/// that judge's ballot.
+pub(super) fn copy_ballot_for_judge(
+    ballot: &BallotRepr,
+    judge_id: &str,
+    version: i64,
+    change: String,
+    editor_id: &str,
+) -> BallotRepr {
+    let ballot_id = Uuid::now_v7().to_string();
+
+    BallotRepr {
+        metadata: BallotMetadata {
+            id: ballot_id.clone(),
+            tournament_id: ballot.metadata.tournament_id.clone(),
+            debate_id: ballot.metadata.debate_id.clone(),
+            judge_id: judge_id.to_string(),
+            submitted_at: Utc::now().naive_utc(),
+            motion_id: ballot.metadata.motion_id.clone(),
+            version,
+            change: Some(change),
+            editor_id: Some(editor_id.to_string()),
+        },
+        scores: ballot
+            .scores
+            .iter()
+            .map(|score| BallotScore {
+                id: Uuid::now_v7().to_string(),
+                ballot_id: ballot_id.clone(),
+                ..score.clone()
+            })
+            .collect(),
+        team_ranks: ballot
+            .team_ranks
+            .iter()
+            .map(|rank| BallotTeamRank {
+                id: Uuid::now_v7().to_string(),
+                ballot_id: ballot_id.clone(),
+                ..rank.clone()
+            })
+            .collect(),
+    }
+}
+
+fn round_completed_error() -> StandardResponse {
+    bad_request(
+        maud! {
+            "This round has already been completed. Mark it as incomplete "
+            "before resolving its ballots."
+        }
+        .render(),
+    )
+}
+
+/// Saves a copy of `accepted` as the next version of the ballot of every
+/// other judge in `latest_ballots` who disagrees with it.
+pub(super) fn accept_ballot(
+    accepted: &BallotRepr,
+    latest_ballots: &[BallotRepr],
+    tournament: &Tournament,
+    debate: &DebateRepr,
+    editor_id: &str,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) {
+    let accepted_judge =
+        debate.judges.get(&accepted.metadata.judge_id).unwrap();
+
+    for other in latest_ballots {
+        if other.metadata.judge_id == accepted.metadata.judge_id
+            || other.is_isomorphic(accepted, tournament, debate)
+        {
+            continue;
+        }
+
+        copy_ballot_for_judge(
+            accepted,
+            &other.metadata.judge_id,
+            other.metadata.version + 1,
+            format!("Accepted ballot of {}", accepted_judge.name),
+            editor_id,
+        )
+        .insert(conn);
+    }
+}
+
+/// Saves `form` as the next version of the ballot of every judge in
+/// `latest_ballots`. Nothing is saved unless the form is valid for all of
+/// them.
+pub(super) fn merge_ballots(
+    form: &BallotForm,
+    latest_ballots: &[BallotRepr],
+    tournament: &Tournament,
+    round: &Round,
+    debate: &DebateRepr,
+    editor_id: &str,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> Result<(), FailureResponse> {
+    let participants =
+        crate::tournaments::participants::TournamentParticipants::load(
+            &tournament.id,
+            conn,
+        );
+
+    let mut merged = Vec::new();
+    for prior in latest_ballots {
+        let new_metadata = BallotMetadata {
+            id: Uuid::now_v7().to_string(),
+            tournament_id: tournament.id.clone(),
+            debate_id: debate.debate.id.clone(),
+            judge_id: prior.metadata.judge_id.clone(),
+            submitted_at: Utc::now().naive_utc(),
+            motion_id: form.motion_id.clone(),
+            version: 0, // Set later by builder based on prior_version
+            change: Some("Merged ballot".to_string()),
+            editor_id: Some(editor_id.to_string()),
+        };
+
+        merged.push(build_edit_ballot(
+            form.clone(),
+            tournament,
+            round,
+            debate,
+            &participants,
+            new_metadata,
+            prior.metadata.version + 1,
+            prior.metadata.version,
+            true,
+            conn,
+        )?);
+    }
+
+    for repr in &merged {
+        repr.insert(conn);
+    }
+    Ok(())
+}
+
+#[tracing::instrument(skip(conn, tx))]
+/// Accepts the given ballot as canonical, by saving a copy of it as a new
+/// version of every other judge's ballot in the same debate.
+pub async fn do_accept_ballot(
+    Path((tournament_id, debate_id, ballot_id)): Path<(String, String, String)>,
+    user: User<true>,
+    Extension(tx): Extension<Sender<Msg>>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let debate = match DebateRepr::try_fetch(&debate_id, &mut *conn) {
+        Ok(debate) if debate.debate.tournament_id == tournament.id => debate,
+        _ => return err_not_found(),
+    };
+    let round =
+        Round::fetch(&tournament_id, &debate.debate.round_id, &mut *conn)?;
+    if round.draw_status != "released_full" {
+        return redirect_to_ballot_set(&tournament_id, &debate_id);
+    }
+    if round.completed {
+        return round_completed_error();
+    }
+
+    let latest_ballots = debate.latest_ballots(&mut *conn);
+    let accepted =
+        match latest_ballots
+            .iter()
+            .find(|ballot| ballot.metadata.id == ballot_id)
+        {
+            Some(ballot) => ballot,
+            // Only the latest version of a judge's ballot may be accepted, as
+            // otherwise the admin is probably looking at stale data.
+            None => {
+                return bad_request(maud! {
+                "This ballot has since been superseded by a newer version. "
+                "Please go back and reload the page."
+            }
+            .render());
+            }
+        };
+
+    accept_ballot(
+        accepted,
+        &latest_ballots,
+        &tournament,
+        &debate,
+        &user.id,
+        &mut *conn,
+    );
+
+    let debate = DebateRepr::fetch(&debate_id, &mut *conn);
+    let status = update_debate_status(&debate, &tournament, &mut *conn);
+    let _ = tx.send(ballot_update_msg(&tournament, &debate_id, status));
+
+    see_other_ok(Redirect::to(&format!(
+        "/tournaments/{}/debates/{}/ballots",
+        tournament_id, debate_id
+    )))
+}
+
+#[tracing::instrument(skip(conn))]
+/// Displays the ballot edit form, pre-filled with the given ballot. Saving the
+/// form replaces the ballot of every judge who has submitted one.
+pub async fn merge_ballots_page(
+    Path((tournament_id, debate_id, ballot_id)): Path<(String, String, String)>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let all_rounds =
+        TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+    let debate = match DebateRepr::try_fetch(&debate_id, &mut *conn) {
+        Ok(debate) if debate.debate.tournament_id == tournament.id => debate,
+        _ => return err_not_found(),
+    };
+    let round =
+        Round::fetch(&tournament_id, &debate.debate.round_id, &mut *conn)?;
+
+    let latest_ballots = debate.latest_ballots(&mut *conn);
+    let base = match latest_ballots
+        .iter()
+        .find(|ballot| ballot.metadata.id == ballot_id)
+    {
+        Some(ballot) => ballot,
+        None => return err_not_found(),
+    };
+
+    let form_data = BallotForm::from_repr(base, &debate, &tournament);
+    let form_fields = fields_of_single_ballot_form(
+        &tournament,
+        &round,
+        &debate,
+        Some(&form_data),
+    );
+
+    let judge_names = latest_ballots
+        .iter()
+        .map(|ballot| {
+            debate
+                .judges
+                .get(&ballot.metadata.judge_id)
+                .unwrap()
+                .name
+                .clone()
+        })
+        .join(", ");
+
+    success(
+        Page::new()
+            .user(user)
+            .tournament(tournament.clone())
+            .body(maud! {
+                SidebarWrapper
+                    rounds=(&all_rounds)
+                    tournament=(&tournament)
+                    active_page=(Some(SidebarPage::Ballots))
+                    selected_seq=(Some(round.seq))
+                {
+                    div class="container py-5" style="max-width: 800px;" {
+                        header class="mb-5" {
+                            h1 class="display-4 fw-bold mb-3" { "Edit Merged Ballot" }
+                            h2 class="h4 text-muted mb-3" { "Debate " (debate.debate.number) }
+                            p class="text-muted" {
+                                "Saving this ballot will replace the ballots of: " (judge_names)
+                            }
+                        }
+
+                        form method="post" {
+                            (form_fields)
+
+                            button type="submit" class="btn btn-dark btn-lg mt-4" {
+                                "Save Merged Ballot"
+                            }
+                            a href=(format!("/tournaments/{}/debates/{}/ballots/resolve", tournament_id, debate_id)) class="btn btn-outline-secondary btn-lg mt-4 ms-2" {
+                                "Cancel"
+                            }
+                        }
+                    }
+                }
+            })
+            .render(),
+    )
+}
+
+#[tracing::instrument(skip(conn, tx, form))]
+pub async fn do_merge_ballots(
+    Path((tournament_id, debate_id, ballot_id)): Path<(String, String, String)>,
+    user: User<true>,
+    Extension(tx): Extension<Sender<Msg>>,
+    mut conn: Conn<true>,
+    QsForm(form): QsForm<BallotForm>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let debate = match DebateRepr::try_fetch(&debate_id, &mut *conn) {
+        Ok(debate) if debate.debate.tournament_id == tournament.id => debate,
+        _ => return err_not_found(),
+    };
+    let round =
+        Round::fetch(&tournament_id, &debate.debate.round_id, &mut *conn)?;
+    if round.draw_status != "released_full" {
+        return redirect_to_ballot_set(&tournament_id, &debate_id);
+    }
+    if round.completed {
+        return round_completed_error();
+    }
+
+    let latest_ballots = debate.latest_ballots(&mut *conn);
+    let base =
+        match latest_ballots
+            .iter()
+            .find(|ballot| ballot.metadata.id == ballot_id)
+        {
+            Some(ballot) => ballot,
+            None => {
+                return bad_request(maud! {
+                "This ballot has since been superseded by a newer version. "
+                "Please go back and reload the page."
+            }
+            .render());
+            }
+        };
+
+    // The form was generated from `base`, so this detects edits which were
+    // made to the ballot while the merged ballot was being edited.
+    if form.expected_version != base.metadata.version + 1 {
+        return bad_request(
+            maud! {
+                "The ballot has been modified since you started editing. "
+                "Please reload the page to see the latest version."
+            }
+            .render(),
+        );
+    }
+
+    merge_ballots(
+        &form,
+        &latest_ballots,
+        &tournament,
+        &round,
+        &debate,
+        &user.id,
+        &mut *conn,
+    )?;
+
+    let debate = DebateRepr::fetch(&debate_id, &mut *conn);
+    let status = update_debate_status(&debate, &tournament, &mut *conn);
+    let _ = tx.send(ballot_update_msg(&tournament, &debate_id, status));
+
+    see_other_ok(Redirect::to(&format!(
+        "/tournaments/{}/debates/{}/ballots",
+        tournament_id, debate_id
+    )))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use diesel::{prelude::*, sqlite::SqliteConnection};
+    use diesel_migrations::MigrationHarness;
+
+    use super::*;
+    use crate::schema::{
+        debates, judges, judges_of_debate, motions_of_round, rounds, speakers,
+        speakers_of_team, teams, teams_of_debate, tournaments,
+    };
+
+    /// A debate between teams "a" and "b" (with one speaker each), judged by
+    /// a chair and two panellists.
+    pub(crate) fn debate_with_panel()
+    -> (SqliteConnection, Tournament, Round, DebateRepr) {
+        let mut conn = SqliteConnection::establish(":memory:").unwrap();
+        conn.run_pending_migrations(crate::MIGRATIONS).unwrap();
+        crate::tournaments::create::insert_tournament(
+            "t", "Test", "T", "test", "user", &mut conn,
+        )
+        .unwrap();
+        diesel::update(tournaments::table.filter(tournaments::id.eq("t")))
+            .set((
+                tournaments::teams_per_side.eq(1),
+                tournaments::substantive_speakers.eq(1),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        diesel::insert_into(rounds::table)
+            .values((
+                rounds::id.eq("r1"),
+                rounds::tournament_id.eq("t"),
+                rounds::seq.eq(1),
+                rounds::name.eq("Round 1"),
+                rounds::kind.eq("P"),
+                rounds::completed.eq(false),
+                rounds::draw_status.eq("released_full"),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        diesel::insert_into(motions_of_round::table)
+            .values((
+                motions_of_round::id.eq("m1"),
+                motions_of_round::tournament_id.eq("t"),
+                motions_of_round::round_id.eq("r1"),
+                motions_of_round::motion.eq("This House would test"),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        diesel::insert_into(debates::table)
+            .values((
+                debates::id.eq("d1"),
+                debates::tournament_id.eq("t"),
+                debates::round_id.eq("r1"),
+                debates::number.eq(1),
+                debates::status.eq("draft"),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        for (side, team) in ["a", "b"].into_iter().enumerate() {
+            let speaker = format!("s{team}");
+            diesel::insert_into(teams::table)
+                .values((
+                    teams::id.eq(team),
+                    teams::tournament_id.eq("t"),
+                    teams::name.eq(team),
+                    teams::number.eq(side as i64 + 1),
+                ))
+                .execute(&mut conn)
+                .unwrap();
+            diesel::insert_into(teams_of_debate::table)
+                .values((
+                    teams_of_debate::id.eq(team),
+                    teams_of_debate::tournament_id.eq("t"),
+                    teams_of_debate::debate_id.eq("d1"),
+                    teams_of_debate::team_id.eq(team),
+                    teams_of_debate::side.eq(side as i64),
+                    teams_of_debate::seq.eq(0),
+                ))
+                .execute(&mut conn)
+                .unwrap();
+            diesel::insert_into(speakers::table)
+                .values((
+                    speakers::id.eq(&speaker),
+                    speakers::tournament_id.eq("t"),
+                    speakers::name.eq(&speaker),
+                    speakers::email.eq(""),
+                    speakers::private_url.eq(&speaker),
+                ))
+                .execute(&mut conn)
+                .unwrap();
+            diesel::insert_into(speakers_of_team::table)
+                .values((
+                    speakers_of_team::id.eq(&speaker),
+                    speakers_of_team::team_id.eq(team),
+                    speakers_of_team::speaker_id.eq(&speaker),
+                ))
+                .execute(&mut conn)
+                .unwrap();
+        }
+        for (number, (judge, status)) in
+            [("chair", "C"), ("p1", "P"), ("p2", "P")]
+                .into_iter()
+                .enumerate()
+        {
+            diesel::insert_into(judges::table)
+                .values((
+                    judges::id.eq(judge),
+                    judges::tournament_id.eq("t"),
+                    judges::name.eq(judge),
+                    judges::email.eq(""),
+                    judges::private_url.eq(judge),
+                    judges::number.eq(number as i64 + 1),
+                ))
+                .execute(&mut conn)
+                .unwrap();
+            diesel::insert_into(judges_of_debate::table)
+                .values((
+                    judges_of_debate::id.eq(judge),
+                    judges_of_debate::tournament_id.eq("t"),
+                    judges_of_debate::debate_id.eq("d1"),
+                    judges_of_debate::judge_id.eq(judge),
+                    judges_of_debate::status.eq(status),
+                ))
+                .execute(&mut conn)
+                .unwrap();
+        }
+
+        let tournament = Tournament::fetch("t", &mut conn).unwrap();
+        let round = Round::fetch("t", "r1", &mut conn).unwrap();
+        let debate = DebateRepr::fetch("d1", &mut conn);
+        (conn, tournament, round, debate)
+    }
+
+    pub(crate) fn ballot(
+        judge: &str,
+        score_of_a: f32,
+        score_of_b: f32,
+    ) -> BallotRepr {
+        let id = Uuid::now_v7().to_string();
+        let metadata = BallotMetadata {
+            id: id.clone(),
+            tournament_id: "t".to_string(),
+            debate_id: "d1".to_string(),
+            judge_id: judge.to_string(),
+            submitted_at: Utc::now().naive_utc(),
+            motion_id: "m1".to_string(),
+            version: 0,
+            change: None,
+            editor_id: None,
+        };
+        let teams =
+            [("a", score_of_a, score_of_b), ("b", score_of_b, score_of_a)];
+        BallotRepr {
+            metadata,
+            scores: teams
+                .iter()
+                .map(|(team, score, _)| BallotScore {
+                    id: Uuid::now_v7().to_string(),
+                    tournament_id: "t".to_string(),
+                    ballot_id: id.clone(),
+                    team_id: team.to_string(),
+                    speaker_id: format!("s{team}"),
+                    speaker_position: 0,
+                    score: Some(*score),
+                })
+                .collect(),
+            team_ranks: teams
+                .iter()
+                .map(|(team, score, opponent_score)| BallotTeamRank {
+                    id: Uuid::now_v7().to_string(),
+                    tournament_id: "t".to_string(),
+                    ballot_id: id.clone(),
+                    team_id: team.to_string(),
+                    points: (score > opponent_score) as i64,
+                })
+                .collect(),
+        }
+    }
+
+    /// The chair and the first panellist give the debate to "a", and the
+    /// second panellist gives it to "b".
+    fn submit_split_decision(conn: &mut SqliteConnection) {
+        ballot("chair", 75.0, 74.0).insert(conn);
+        ballot("p1", 75.0, 74.0).insert(conn);
+        ballot("p2", 73.0, 76.0).insert(conn);
+    }
+
+    fn latest_of(
+        judge: &str,
+        debate: &DebateRepr,
+        conn: &mut SqliteConnection,
+    ) -> BallotRepr {
+        debate
+            .latest_ballots(conn)
+            .into_iter()
+            .find(|ballot| ballot.metadata.judge_id == judge)
+            .unwrap()
+    }
+
+    #[test]
+    fn accepting_a_ballot_overwrites_only_the_judges_who_disagree() {
+        let (mut conn, tournament, _, debate) = debate_with_panel();
+        submit_split_decision(&mut conn);
+        assert_eq!(
+            update_debate_status(&debate, &tournament, &mut conn),
+            "conflict"
+        );
+
+        let latest = debate.latest_ballots(&mut conn);
+        let chair = latest
+            .iter()
+            .find(|ballot| ballot.metadata.judge_id == "chair")
+            .unwrap();
+        accept_ballot(chair, &latest, &tournament, &debate, "admin", &mut conn);
+
+        let p1 = latest_of("p1", &debate, &mut conn);
+        assert_eq!(p1.metadata.version, 0);
+        let p2 = latest_of("p2", &debate, &mut conn);
+        assert_eq!(p2.metadata.version, 1);
+        assert_eq!(
+            p2.metadata.change.as_deref(),
+            Some("Accepted ballot of chair")
+        );
+        assert_eq!(p2.metadata.editor_id.as_deref(), Some("admin"));
+        assert!(p2.is_isomorphic(chair, &tournament, &debate));
+        assert_eq!(
+            update_debate_status(&debate, &tournament, &mut conn),
+            "confirmed"
+        );
+    }
+
+    #[test]
+    fn merging_ballots_saves_a_new_version_for_every_judge() {
+        let (mut conn, tournament, round, debate) = debate_with_panel();
+        submit_split_decision(&mut conn);
+
+        let latest = debate.latest_ballots(&mut conn);
+        let p2 = latest_of("p2", &debate, &mut conn);
+        let form = BallotForm::from_repr(&p2, &debate, &tournament);
+        merge_ballots(
+            &form,
+            &latest,
+            &tournament,
+            &round,
+            &debate,
+            "admin",
+            &mut conn,
+        )
+        .unwrap();
+
+        for judge in ["chair", "p1", "p2"] {
+            let merged = latest_of(judge, &debate, &mut conn);
+            assert_eq!(merged.metadata.version, 1);
+            assert_eq!(
+                merged.metadata.change.as_deref(),
+                Some("Merged ballot")
+            );
+            assert_eq!(merged.points_of_team("b"), 1);
+        }
+        assert_eq!(
+            update_debate_status(&debate, &tournament, &mut conn),
+            "confirmed"
+        );
+    }
+
+    #[test]
+    fn an_invalid_merged_ballot_is_not_saved_for_anyone() {
+        let (mut conn, tournament, round, debate) = debate_with_panel();
+        submit_split_decision(&mut conn);
+
+        let latest = debate.latest_ballots(&mut conn);
+        let mut form = BallotForm::from_repr(
+            &latest_of("p2", &debate, &mut conn),
+            &debate,
+            &tournament,
+        );
+        form.motion_id = "not a motion".to_string();
+        assert!(
+            merge_ballots(
+                &form,
+                &latest,
+                &tournament,
+                &round,
+                &debate,
+                "admin",
+                &mut conn
+            )
+            .is_err()
+        );
+
+        assert_eq!(debate.ballot_history(&mut conn).len(), 3);
+    }
+}
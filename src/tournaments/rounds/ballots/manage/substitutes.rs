@@ -0,0 +1,326 @@
+//! Substitute speakers, who speak for a team in a single debate without
+//! becoming a permanent member of that team.
+
+use axum::{
+    extract::{Form, Path},
+    response::Redirect,
+};
+use diesel::{prelude::*, result::DatabaseErrorKind};
+use hypertext::prelude::*;
+use itertools::Itertools;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    auth::User,
+    schema::{speakers, speakers_of_team, substitute_speakers_of_debate},
+    state::Conn,
+    template::Page,
+    tournaments::{
+        Tournament,
+        manage::sidebar::{SidebarPage, SidebarWrapper},
+        participants::{
+            Speaker, manage::gen_private_url::get_unique_private_url,
+        },
+        rounds::{
+            Round, TournamentRounds, draws::DebateRepr,
+            side_names::name_of_side,
+        },
+    },
+    util_resp::{
+        StandardResponse, bad_request, err_not_found, see_other_ok, success,
+    },
+};
+
+#[tracing::instrument(skip(conn))]
+pub async fn manage_substitutes_page(
+    Path((tournament_id, debate_id)): Path<(String, String)>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let all_rounds =
+        TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+    let debate = match DebateRepr::try_fetch(&debate_id, &mut *conn) {
+        Ok(debate) if debate.debate.tournament_id == tournament.id => debate,
+        _ => return err_not_found(),
+    };
+    let round =
+        Round::fetch(&tournament_id, &debate.debate.round_id, &mut *conn)?;
+
+    success(
+        Page::new()
+            .user(user)
+            .tournament(tournament.clone())
+            .body(maud! {
+                SidebarWrapper
+                    rounds=(&all_rounds)
+                    tournament=(&tournament)
+                    active_page=(Some(SidebarPage::Ballots))
+                    selected_seq=(Some(round.seq))
+                {
+                    div class="container py-5" style="max-width: 800px;" {
+                        header class="mb-5" {
+                            a href=(format!("/tournaments/{}/debates/{}/ballots", tournament.id, debate.debate.id)) class="btn btn-sm btn-outline-secondary mb-3" {
+                                "← Back to Ballot Overview"
+                            }
+                            h1 class="display-4 fw-bold mb-2" {
+                                "Substitutes for Debate " (debate.debate.number)
+                            }
+                            p class="text-muted" {
+                                "Substitutes can be selected on the ballot for this debate "
+                                "only. They are not added to the team."
+                            }
+                        }
+
+                        @for debate_team in debate.teams_of_debate.iter().sorted_by_key(|dt| (dt.seq, dt.side)) {
+                            @let team = debate.teams.get(&debate_team.team_id).unwrap();
+                            @let substitutes = debate
+                                .speakers_of_team
+                                .get(&debate_team.team_id)
+                                .map(|speakers| {
+                                    speakers
+                                        .iter()
+                                        .filter(|s| debate.substitute_ids.contains(&s.id))
+                                        .collect_vec()
+                                })
+                                .unwrap_or_default();
+
+                            div class="card mb-4" {
+                                div class="card-header bg-light" {
+                                    h2 class="h5 mb-0" {
+                                        (team.name) " (" (name_of_side(&tournament, debate_team.side, debate_team.seq, true)) ")"
+                                    }
+                                }
+                                ul class="list-group list-group-flush" {
+                                    @if substitutes.is_empty() {
+                                        li class="list-group-item text-muted" { "No substitutes." }
+                                    }
+                                    @for speaker in &substitutes {
+                                        li class="list-group-item d-flex justify-content-between align-items-center" {
+                                            (speaker.name)
+                                            form method="post" action=(format!("/tournaments/{}/debates/{}/substitutes/{}/delete", tournament.id, debate.debate.id, speaker.id)) {
+                                                button type="submit" class="btn btn-sm btn-outline-danger" { "Remove" }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+
+                        h2 class="h4 mt-5" { "Add a substitute" }
+                        form method="post" {
+                            div class="mb-3" {
+                                label for="team_id" class="form-label" { "Team" }
+                                select class="form-select" id="team_id" name="team_id" required {
+                                    @for debate_team in debate.teams_of_debate.iter().sorted_by_key(|dt| (dt.seq, dt.side)) {
+                                        option value=(debate_team.team_id) {
+                                            (debate.teams.get(&debate_team.team_id).unwrap().name)
+                                        }
+                                    }
+                                }
+                            }
+                            div class="mb-3" {
+                                label for="name" class="form-label" { "Name" }
+                                input type="text" class="form-control" id="name" name="name" required;
+                                div class="form-text" {
+                                    "If a speaker with this name is already registered, they will be used as the substitute."
+                                }
+                            }
+                            div class="mb-3" {
+                                label for="email" class="form-label" { "Email (optional)" }
+                                input type="email" class="form-control" id="email" name="email";
+                            }
+                            button type="submit" class="btn btn-primary" { "Add Substitute" }
+                        }
+                    }
+                }
+            })
+            .render(),
+    )
+}
+
+#[derive(Deserialize)]
+pub struct AddSubstituteForm {
+    team_id: String,
+    name: String,
+    #[serde(default)]
+    email: String,
+}
+
+#[tracing::instrument(skip(conn, form))]
+pub async fn do_add_substitute(
+    Path((tournament_id, debate_id)): Path<(String, String)>,
+    user: User<true>,
+    mut conn: Conn<true>,
+    Form(form): Form<AddSubstituteForm>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let debate = match DebateRepr::try_fetch(&debate_id, &mut *conn) {
+        Ok(debate) if debate.debate.tournament_id == tournament.id => debate,
+        _ => return err_not_found(),
+    };
+
+    if !debate
+        .teams_of_debate
+        .iter()
+        .any(|dt| dt.team_id == form.team_id)
+    {
+        return err_not_found();
+    }
+
+    let name = form.name.trim();
+    if name.is_empty() || name.len() > 128 {
+        return bad_request(
+            maud! {
+                "Error: the name must be between 1 and 128 characters long."
+            }
+            .render(),
+        );
+    }
+
+    let existing = speakers::table
+        .filter(speakers::tournament_id.eq(&tournament.id))
+        .filter(speakers::name.eq(name))
+        .first::<Speaker>(&mut *conn)
+        .optional()?;
+
+    let speaker_id = match existing {
+        Some(speaker) => {
+            if debate
+                .speakers_of_team
+                .values()
+                .flatten()
+                .any(|s| s.id == speaker.id)
+            {
+                return bad_request(
+                    maud! {
+                        "Error: " (speaker.name) " is already speaking in this debate."
+                    }
+                    .render(),
+                );
+            }
+            // otherwise their speeches would be credited to a team they are
+            // not registered with
+            let on_another_team = diesel::dsl::select(diesel::dsl::exists(
+                speakers_of_team::table
+                    .filter(speakers_of_team::speaker_id.eq(&speaker.id))
+                    .filter(speakers_of_team::team_id.ne(&form.team_id)),
+            ))
+            .get_result::<bool>(&mut *conn)?;
+            if on_another_team {
+                return bad_request(
+                    maud! {
+                        "Error: " (speaker.name) " is a member of another team, "
+                        "so cannot substitute for this one."
+                    }
+                    .render(),
+                );
+            }
+            speaker.id
+        }
+        None => {
+            let speaker_id = Uuid::now_v7().to_string();
+            let private_url =
+                get_unique_private_url(&tournament.id, &mut *conn);
+            let res = diesel::insert_into(speakers::table)
+                .values((
+                    speakers::id.eq(&speaker_id),
+                    speakers::tournament_id.eq(&tournament.id),
+                    speakers::name.eq(name),
+                    speakers::email.eq(form.email.trim()),
+                    speakers::private_url.eq(private_url),
+                ))
+                .execute(&mut *conn);
+            match res {
+                Ok(n) => assert_eq!(n, 1),
+                Err(diesel::result::Error::DatabaseError(
+                    DatabaseErrorKind::UniqueViolation,
+                    _,
+                )) => {
+                    return bad_request(
+                        maud! {
+                            "Error: a speaker with that name already exists."
+                        }
+                        .render(),
+                    );
+                }
+                Err(e) => return Err(e.into()),
+            }
+            speaker_id
+        }
+    };
+
+    let n = diesel::insert_into(substitute_speakers_of_debate::table)
+        .values((
+            substitute_speakers_of_debate::id.eq(Uuid::now_v7().to_string()),
+            substitute_speakers_of_debate::tournament_id.eq(&tournament.id),
+            substitute_speakers_of_debate::debate_id.eq(&debate.debate.id),
+            substitute_speakers_of_debate::team_id.eq(&form.team_id),
+            substitute_speakers_of_debate::speaker_id.eq(&speaker_id),
+        ))
+        .execute(&mut *conn)?;
+    assert_eq!(n, 1);
+
+    see_other_ok(Redirect::to(&format!(
+        "/tournaments/{}/debates/{}/substitutes",
+        tournament_id, debate_id
+    )))
+}
+
+#[tracing::instrument(skip(conn))]
+pub async fn do_remove_substitute(
+    Path((tournament_id, debate_id, speaker_id)): Path<(
+        String,
+        String,
+        String,
+    )>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let debate = match DebateRepr::try_fetch(&debate_id, &mut *conn) {
+        Ok(debate) if debate.debate.tournament_id == tournament.id => debate,
+        _ => return err_not_found(),
+    };
+
+    // Removing a substitute who is on a current ballot would leave that
+    // ballot referring to a speaker who is no longer part of the debate.
+    let on_ballot = debate
+        .latest_ballots(&mut *conn)
+        .iter()
+        .flat_map(|ballot| ballot.scores.iter())
+        .any(|score| score.speaker_id == speaker_id);
+
+    if on_ballot {
+        return bad_request(
+            maud! {
+                "Error: this substitute has been recorded on a ballot for this "
+                "debate, so cannot be removed. Edit the ballot first."
+            }
+            .render(),
+        );
+    }
+
+    let n = diesel::delete(
+        substitute_speakers_of_debate::table
+            .filter(
+                substitute_speakers_of_debate::tournament_id.eq(&tournament.id),
+            )
+            .filter(substitute_speakers_of_debate::debate_id.eq(&debate_id))
+            .filter(substitute_speakers_of_debate::speaker_id.eq(&speaker_id)),
+    )
+    .execute(&mut *conn)?;
+
+    if n == 0 {
+        return err_not_found();
+    }
+
+    see_other_ok(Redirect::to(&format!(
+        "/tournaments/{}/debates/{}/substitutes",
+        tournament_id, debate_id
+    )))
+}
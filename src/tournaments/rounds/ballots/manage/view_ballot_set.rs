@@ -1,11 +1,12 @@
 use axum::extract::Path;
-use diesel::prelude::*;
+use diesel::{connection::LoadConnection, prelude::*, sqlite::Sqlite};
 use hypertext::prelude::*;
 use itertools::Itertools;
 use std::collections::HashMap;
 
 use crate::{
     auth::User,
+    schema::users,
     state::Conn,
     template::Page,
     tournaments::{
@@ -16,6 +17,30 @@ use crate::{
     util_resp::{StandardResponse, success},
 };
 
+/// Looks up the usernames of the admins who edited any of the `ballots`
+/// (ballots submitted by judges have no editor).
+pub(super) fn editor_names<'a>(
+    ballots: impl IntoIterator<Item = &'a BallotRepr>,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> HashMap<String, String> {
+    let editor_ids = ballots
+        .into_iter()
+        .filter_map(|b| b.metadata.editor_id.clone())
+        .unique()
+        .collect_vec();
+    if editor_ids.is_empty() {
+        return HashMap::new();
+    }
+
+    users::table
+        .filter(users::id.eq_any(&editor_ids))
+        .select((users::id, users::username))
+        .load::<(String, String)>(conn)
+        .unwrap()
+        .into_iter()
+        .collect()
+}
+
 #[tracing::instrument(skip(conn))]
 /// Displays the ballot set for an individual debate.
 pub async fn view_ballot_set_page(
@@ -54,25 +79,8 @@ pub async fn view_ballot_set_page(
         .into_iter()
         .into_group_map_by(|b| b.metadata.judge_id.clone());
 
-    // Look up editor usernames for ballots submitted via admin override
-    let editor_ids: Vec<String> = grouped_history
-        .values()
-        .flatten()
-        .filter_map(|b| b.metadata.editor_id.clone())
-        .collect::<std::collections::HashSet<_>>()
-        .into_iter()
-        .collect();
-    let editor_names: HashMap<String, String> = if !editor_ids.is_empty() {
-        crate::schema::users::table
-            .filter(crate::schema::users::id.eq_any(&editor_ids))
-            .select((crate::schema::users::id, crate::schema::users::username))
-            .load::<(String, String)>(&mut *conn)
-            .unwrap_or_default()
-            .into_iter()
-            .collect()
-    } else {
-        HashMap::new()
-    };
+    let editor_names =
+        editor_names(grouped_history.values().flatten(), &mut *conn);
 
     success(
         Page::new()
@@ -94,6 +102,9 @@ pub async fn view_ballot_set_page(
                             h1 class="display-4 fw-bold mb-3" {
                                 "Ballots for Debate " (debate.debate.number)
                             }
+                            a href=(format!("/tournaments/{}/debates/{}/substitutes", tournament.id, debate.debate.id)) class="btn btn-sm btn-outline-secondary" {
+                                "Manage Substitutes"
+                            }
                         }
 
                         @if !missing_ballots.is_empty() {
@@ -102,16 +113,7 @@ pub async fn view_ballot_set_page(
                                 p { "The following judges have not yet submitted their ballots:" }
                                 ul class="mb-0" {
                                     @for (_, judge) in &missing_ballots {
-                                        @let judge_role = debate.judges_of_debate
-                                            .iter()
-                                            .find(|dj| dj.judge_id == judge.id)
-                                            .map(|dj| match dj.status.as_str() {
-                                                "C" => "Chair",
-                                                "P" => "Panelist",
-                                                "T" => "Trainee",
-                                                _ => "Judge",
-                                            })
-                                            .unwrap_or("Judge");
+                                        @let judge_role = debate.judge_role(&judge.id);
                                         li { (judge.name) " (" (judge_role) ")" }
                                     }
                                 }
@@ -127,6 +129,9 @@ pub async fn view_ballot_set_page(
                                         li { (problem) }
                                     }
                                 }
+                                a href=(format!("/tournaments/{}/debates/{}/ballots/resolve", tournament.id, debate.debate.id)) class="btn btn-danger mt-3" {
+                                    "Resolve Conflicts"
+                                }
                             }
                         }
 
@@ -135,16 +140,7 @@ pub async fn view_ballot_set_page(
 
                         @for ballot in &ballots {
                             @let judge = debate.judges.get(&ballot.ballot().judge_id).unwrap();
-                            @let judge_role = debate.judges_of_debate
-                                .iter()
-                                .find(|dj| dj.judge_id == ballot.ballot().judge_id)
-                                .map(|dj| match dj.status.as_str() {
-                                    "C" => "Chair",
-                                    "P" => "Panelist",
-                                    "T" => "Trainee",
-                                    _ => "Judge",
-                                })
-                                .unwrap_or("Judge");
+                            @let judge_role = debate.judge_role(&ballot.ballot().judge_id);
 
                             section class="mb-5" {
                                 div class="d-flex justify-content-between align-items-center mb-4" {
@@ -231,16 +227,7 @@ pub async fn view_ballot_set_page(
                         }
 
                         @for (_, judge) in &missing_ballots {
-                            @let judge_role = debate.judges_of_debate
-                                .iter()
-                                .find(|dj| dj.judge_id == judge.id)
-                                .map(|dj| match dj.status.as_str() {
-                                    "C" => "Chair",
-                                    "P" => "Panelist",
-                                    "T" => "Trainee",
-                                    _ => "Judge",
-                                })
-                                .unwrap_or("Judge");
+                            @let judge_role = debate.judge_role(&judge.id);
 
                             section class="mb-5" {
                                 div class="d-flex justify-content-between align-items-center mb-4" {
@@ -263,19 +250,13 @@ pub async fn view_ballot_set_page(
 
                         h2 class="display-6 fw-bold mb-4" { "History of Ballots" }
                         p class="text-muted mb-4" { "This section displays the full timeline of ballots submitted for this debate by every judge." }
+                        a href=(format!("/tournaments/{}/debates/{}/ballots/history", tournament.id, debate.debate.id)) class="btn btn-outline-dark mb-4" {
+                            "View Changes Between Versions"
+                        }
 
                         @for (judge_id, judge_ballots) in &grouped_history {
                             @let judge = debate.judges.get(judge_id).unwrap();
-                            @let judge_role = debate.judges_of_debate
-                                .iter()
-                                .find(|dj| &dj.judge_id == judge_id)
-                                .map(|dj| match dj.status.as_str() {
-                                    "C" => "Chair",
-                                    "P" => "Panelist",
-                                    "T" => "Trainee",
-                                    _ => "Judge",
-                                })
-                                .unwrap_or("Judge");
+                            @let judge_role = debate.judge_role(judge_id);
 
                             div class="card mb-4" {
                                 div class="card-header bg-light" {
@@ -329,17 +310,7 @@ pub async fn view_single_ballot_page(
 
     let ballot = BallotRepr::fetch(&ballot_id, &mut *conn);
     let judge = debate.judges.get(&ballot.ballot().judge_id).unwrap();
-    let judge_role = debate
-        .judges_of_debate
-        .iter()
-        .find(|dj| dj.judge_id == ballot.ballot().judge_id)
-        .map(|dj| match dj.status.as_str() {
-            "C" => "Chair",
-            "P" => "Panelist",
-            "T" => "Trainee",
-            _ => "Judge",
-        })
-        .unwrap_or("Judge");
+    let judge_role = debate.judge_role(&ballot.ballot().judge_id);
 
     success(
         Page::new()
@@ -13,6 +13,7 @@ use itertools::Itertools;
 use rust_decimal::Decimal;
 
 use crate::{
+    msg::{Msg, MsgContents},
     schema::{ballots, speaker_scores_of_ballot, team_ranks_of_ballot},
     tournaments::{
         Tournament,
@@ -25,7 +26,7 @@ pub mod form;
 pub mod manage;
 pub mod public;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BallotRepr {
     pub metadata: BallotMetadata,
     pub scores: Vec<BallotScore>,
@@ -360,11 +361,13 @@ impl BallotRepr {
 /// - If all non-trainee judges have submitted but there are conflicts, the
 ///   status is set to `conflict`.
 /// - Otherwise the status is set to `draft`.
+///
+/// Returns the new status.
 pub fn update_debate_status(
     debate: &DebateRepr,
     tournament: &Tournament,
     conn: &mut impl LoadConnection<Backend = Sqlite>,
-) {
+) -> &'static str {
     let ballots = debate.latest_ballots(conn);
 
     let non_trainee_judges: Vec<_> = debate
@@ -399,6 +402,26 @@ pub fn update_debate_status(
     .set(crate::schema::debates::status.eq(status))
     .execute(conn)
     .unwrap();
+
+    status
+}
+
+/// The message to broadcast after a ballot for a debate has been submitted or
+/// changed, given the status returned by [`update_debate_status`].
+pub fn ballot_update_msg(
+    tournament: &Tournament,
+    debate_id: &str,
+    status: &str,
+) -> Msg {
+    let debate_id = debate_id.to_string();
+    Msg {
+        tournament: tournament.clone(),
+        inner: match status {
+            "confirmed" => MsgContents::BallotConfirmed(debate_id),
+            "conflict" => MsgContents::BallotConflict(debate_id),
+            _ => MsgContents::BallotSubmitted(debate_id),
+        },
+    }
 }
 
 #[derive(Queryable, Debug, Clone)]
@@ -617,6 +640,47 @@ impl<'a> BallotBuilder<'a> {
             );
         }
 
+        let team_speakers = self.debate.speakers_of_team.get(&dt.team_id);
+        for (speaker_id, _) in &speakers {
+            // empty IDs are submitted for teams without registered speakers
+            if speaker_id.is_empty() {
+                continue;
+            }
+            if !team_speakers
+                .is_some_and(|team| team.iter().any(|s| &s.id == speaker_id))
+            {
+                return Err(
+                    "Error: a speaker on the ballot is not a member of (or \
+                     substituting for) their team"
+                        .into(),
+                );
+            }
+        }
+
+        // A speaker may give two substantive speeches when their team is a
+        // speaker short (an "iron-person" speech), but no more than that.
+        let substantive = self.tournament.substantive_speakers as usize;
+        if let Some(speaker_id) = speakers
+            .iter()
+            .take(substantive)
+            .map(|(id, _)| id)
+            .filter(|id| !id.is_empty())
+            .counts()
+            .into_iter()
+            .find(|(_, n)| *n > 2)
+            .map(|(id, _)| id)
+        {
+            let name = self
+                .participants
+                .speakers
+                .get(speaker_id)
+                .map(|s| s.name.clone())
+                .unwrap_or_default();
+            return Err(format!(
+                "Error: {name} cannot give more than two substantive speeches"
+            ));
+        }
+
         for (j, (speaker_id, score)) in speakers.into_iter().enumerate() {
             self.scores.push(BallotScore {
                 id: Uuid::now_v7().to_string(),
@@ -764,3 +828,46 @@ fn build_team_ranks_from_advancing(
         })
         .collect()
 }
+
+#[cfg(test)]
+#[test]
+fn test_reply_speeches_are_checked_against_the_reply_range() {
+    use diesel::sqlite::SqliteConnection;
+    use diesel_migrations::MigrationHarness;
+
+    use crate::{schema::tournaments, tournaments::create::insert_tournament};
+
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+    conn.run_pending_migrations(crate::MIGRATIONS).unwrap();
+    insert_tournament("t", "Reply Open", "RO", "reply-open", "u", &mut conn)
+        .unwrap();
+    diesel::update(tournaments::table.filter(tournaments::id.eq("t")))
+        .set((
+            tournaments::teams_per_side.eq(1),
+            tournaments::substantive_speakers.eq(3),
+            tournaments::reply_speakers.eq(true),
+            tournaments::substantive_speech_min_speak.eq(Some(60.0)),
+            tournaments::substantive_speech_max_speak.eq(Some(80.0)),
+            tournaments::substantive_speech_step.eq(Some(0.5)),
+            tournaments::reply_speech_min_speak.eq(Some(30.0)),
+            tournaments::reply_speech_max_speak.eq(Some(40.0)),
+        ))
+        .execute(&mut conn)
+        .unwrap();
+    let tournament = Tournament::fetch("t", &mut conn).unwrap();
+    let participants = TournamentParticipants::load("t", &mut conn);
+
+    let ballot_with_reply = |reply: f32| {
+        BallotTeamSpeakersBuilder::new(&tournament, &participants, true, true)
+            .add_speaker("s1", Some(75.0))?
+            .add_speaker("s2", Some(74.5))?
+            .add_speaker("s3", Some(73.0))?
+            .add_speaker("s1", Some(reply))?
+            .build()
+    };
+
+    assert_eq!(ballot_with_reply(37.5).unwrap()[3].1, Some(37.5));
+    assert!(ballot_with_reply(75.0).unwrap_err().contains("maximum"));
+    assert!(ballot_with_reply(25.0).unwrap_err().contains("minimum"));
+    assert!(ballot_with_reply(37.25).unwrap_err().contains("multiple"));
+}
@@ -1,24 +1,30 @@
 use crate::tournaments::rounds::ballots::form::QsForm;
-use axum::extract::Path;
+use axum::extract::{Extension, Path};
 use axum::response::Redirect;
 use chrono::Utc;
 use diesel::{connection::LoadConnection, prelude::*, sqlite::Sqlite};
 use hypertext::{Renderable, maud, prelude::*};
+use tokio::sync::broadcast::Sender;
 use uuid::Uuid;
 
 use crate::{
     auth::User,
+    msg::Msg,
     schema::{ballots, debates, judges_of_debate, rounds},
     state::Conn,
     template::Page,
     tournaments::{
         Tournament,
+        feedback::requirement::{
+            FeedbackRequirement, FeedbackSource, outstanding_feedback,
+        },
         participants::{Judge, TournamentParticipants},
         rounds::{
             Round,
             ballots::{
-                BallotMetadata, BallotRepr, form::fields_of_single_ballot_form,
-                manage::edit::BallotForm, update_debate_status,
+                BallotMetadata, BallotRepr, ballot_update_msg,
+                form::fields_of_single_ballot_form, manage::edit::BallotForm,
+                update_debate_status,
             },
             draws::{Debate, DebateRepr},
         },
@@ -61,6 +67,7 @@ pub async fn submit_ballot_page(
         &tournament,
         &round,
     )?;
+    check_feedback_submitted(user.clone(), &mut *conn, &tournament, &judge)?;
 
     let debate = debate_of_judge_in_round(&judge.id, &round.id, &mut *conn)?;
     let debate_repr = DebateRepr::fetch(&debate.id, &mut *conn);
@@ -139,6 +146,44 @@ fn check_round_not_completed(
     Ok(())
 }
 
+/// If the tournament blocks ballots until feedback is submitted, checks that
+/// the judge has no outstanding feedback.
+fn check_feedback_submitted(
+    user: Option<User<true>>,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+    tournament: &Tournament,
+    judge: &Judge,
+) -> Result<(), FailureResponse> {
+    if FeedbackRequirement::of(tournament) != FeedbackRequirement::Block {
+        return Ok(());
+    }
+    let outstanding = outstanding_feedback(
+        tournament,
+        FeedbackSource::Judge(&judge.id),
+        conn,
+    );
+    if !outstanding.is_empty() {
+        let rounds = outstanding
+            .iter()
+            .map(|expected| expected.round.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let current_rounds = Round::current_rounds(&tournament.id, conn);
+        return Err(bad_request(
+            Page::new()
+                .tournament(tournament.clone())
+                .user_opt(user)
+                .current_rounds(current_rounds)
+                .body(maud! {
+                    ErrorAlert msg = (format!("Error: you must submit your feedback for {rounds} before you can submit a ballot."));
+                })
+                .render(),
+        )
+        .unwrap_err());
+    }
+    Ok(())
+}
+
 fn check_round_released(
     tournament_id: &String,
     user: Option<User<true>>,
@@ -218,7 +263,7 @@ fn build_submit_ballot(
 // TODO: it would be nice to display the errors inline. However, this is more
 // programming effort, so currently we collate a list of problems and then
 // display a list of problems at the top of the page.
-#[tracing::instrument(skip(conn))]
+#[tracing::instrument(skip(conn, tx))]
 pub async fn do_submit_ballot(
     Path((tournament_id, private_url, round_id)): Path<(
         String,
@@ -226,6 +271,7 @@ pub async fn do_submit_ballot(
         String,
     )>,
     user: Option<User<true>>,
+    Extension(tx): Extension<Sender<Msg>>,
     mut conn: Conn<true>,
     QsForm(form): QsForm<BallotForm>,
 ) -> StandardResponse {
@@ -256,6 +302,7 @@ pub async fn do_submit_ballot(
         &tournament,
         &round,
     )?;
+    check_feedback_submitted(user.clone(), &mut *conn, &tournament, &judge)?;
 
     let debate = debate_of_judge_in_round(&judge.id, &round.id, &mut *conn)?;
     let debate_repr = DebateRepr::fetch(&debate.id, &mut *conn);
@@ -304,7 +351,8 @@ pub async fn do_submit_ballot(
 
     // Refresh debate repr so we pick up the newly inserted ballot
     let debate_repr = DebateRepr::fetch(&debate.id, &mut *conn);
-    update_debate_status(&debate_repr, &tournament, &mut *conn);
+    let status = update_debate_status(&debate_repr, &tournament, &mut *conn);
+    let _ = tx.send(ballot_update_msg(&tournament, &debate.id, status));
 
     see_other_ok(Redirect::to(&format!(
         "/tournaments/{}/privateurls/{}",
@@ -335,3 +383,36 @@ fn debate_of_judge_in_round(
         None => err_not_found().map(|_| unreachable!()),
     }
 }
+
+#[cfg(test)]
+#[test]
+fn test_outstanding_feedback_only_blocks_ballots_in_block_mode() {
+    use crate::tournaments::feedback::requirement::tests::{
+        exempt, tournament_with_completed_debate,
+    };
+
+    let (mut conn, tournament) = tournament_with_completed_debate("warn");
+    let panellist = Judge::of_private_url("panellist", "t", &mut conn).unwrap();
+    assert!(
+        check_feedback_submitted(None, &mut conn, &tournament, &panellist)
+            .is_ok()
+    );
+
+    let (mut conn, tournament) = tournament_with_completed_debate("block");
+    let panellist = Judge::of_private_url("panellist", "t", &mut conn).unwrap();
+    let chair = Judge::of_private_url("chair", "t", &mut conn).unwrap();
+    assert!(
+        check_feedback_submitted(None, &mut conn, &tournament, &panellist)
+            .is_err()
+    );
+    // the chair owes no feedback in this round
+    assert!(
+        check_feedback_submitted(None, &mut conn, &tournament, &chair).is_ok()
+    );
+
+    exempt(None, Some("panellist"), &mut conn);
+    assert!(
+        check_feedback_submitted(None, &mut conn, &tournament, &panellist)
+            .is_ok()
+    );
+}
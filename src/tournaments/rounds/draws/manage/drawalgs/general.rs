@@ -9,7 +9,7 @@ use rand::Rng;
 use rust_decimal::prelude::ToPrimitive;
 
 use crate::tournaments::{
-    config::{PullupMetric, RankableTeamMetric, UnrankableTeamMetric},
+    config::{PullupMetric, UnrankableTeamMetric},
     rounds::draws::manage::drawalgs::{DrawInput, MakeDrawError},
     standings::compute::history::TeamHistory,
     teams::Team,
@@ -339,14 +339,13 @@ pub fn make_draw(
                                 });
                             }
                             PullupMetric::LowestDsSpeaks => {
-                                let sub_penalty = -standings
-                                    .get_ranked_metric_of_team(
-                                        &team.id,
-                                        RankableTeamMetric::AverageTotalSpeakerScore
-                                    )
-                                    .to_f64()
-                                    .unwrap();
-                                penalty += sub_penalty;
+                                // teams which have faced weaker opponents
+                                // are preferred
+                                let ds_speaks = standings.pullup_metrics.get(&(
+                                    team.id.clone(),
+                                    UnrankableTeamMetric::DrawStrengthBySpeaks,
+                                )).unwrap();
+                                penalty += ds_speaks.to_f64().unwrap();
                             }
                         }
                     }
@@ -9,6 +9,7 @@ use crate::schema::motions_of_round;
 use crate::schema::rooms as schema_rooms;
 use crate::schema::speakers;
 use crate::schema::speakers_of_team;
+use crate::schema::substitute_speakers_of_debate;
 use crate::schema::teams;
 use crate::schema::teams_of_debate;
 use crate::tournaments::participants::DebateJudge;
@@ -77,7 +78,12 @@ pub struct DebateRepr {
     // todo: teams and speakers should be placed in a separate struct (we can
     // also load the private URLs as part of this struct)
     pub teams: HashMap<String, Team>,
+    /// Includes any substitutes who are speaking for the team in this debate
+    /// (see `substitute_ids`).
     pub speakers_of_team: HashMap<String, Vec<Speaker>>,
+    /// The IDs of speakers who are substituting for a team in this debate
+    /// only.
+    pub substitute_ids: Vec<String>,
     pub judges_of_debate: Vec<DebateJudge>,
     pub judges: HashMap<String, Judge>,
     pub motions: HashMap<String, Motion>,
@@ -146,6 +152,20 @@ impl DebateRepr {
             .select((speakers_of_team::team_id, speakers_of_team::speaker_id))
             .load::<(String, String)>(&mut *conn)?;
 
+        let substitutes = substitute_speakers_of_debate::table
+            .filter(substitute_speakers_of_debate::debate_id.eq(&debate.id))
+            .select((
+                substitute_speakers_of_debate::team_id,
+                substitute_speakers_of_debate::speaker_id,
+            ))
+            .load::<(String, String)>(&mut *conn)?;
+        let substitute_ids =
+            substitutes.iter().map(|(_, id)| id.clone()).collect_vec();
+        let speakers_of_team = speakers_of_team
+            .into_iter()
+            .chain(substitutes)
+            .collect_vec();
+
         let speakers = speakers::table
             .filter(
                 speakers::id.eq_any(
@@ -220,6 +240,7 @@ impl DebateRepr {
                 }
                 map
             },
+            substitute_ids,
             judges_of_debate,
             judges,
             motions,
@@ -265,6 +286,15 @@ impl DebateRepr {
             .collect()
     }
 
+    /// The position of the judge on the panel (see [`DebateJudge::role`]).
+    pub fn judge_role(&self, judge_id: &str) -> &'static str {
+        self.judges_of_debate
+            .iter()
+            .find(|dj| dj.judge_id == judge_id)
+            .map(DebateJudge::role)
+            .unwrap_or("Judge")
+    }
+
     pub fn team_of_side_and_seq(&self, side: i64, seq: i64) -> &DebateTeam {
         self.teams_of_debate
             .iter()
@@ -1,18 +1,15 @@
-use std::collections::HashMap;
-
 use axum::extract::Path;
-use diesel::prelude::*;
 use hypertext::prelude::*;
 
 use crate::{
     auth::User,
-    schema::teams,
     state::Conn,
     template::Page,
     tournaments::{
         Tournament,
+        participants::TournamentParticipants,
         rounds::{Round, draws::RoundDrawRepr},
-        teams::Team,
+        teams::TeamNameKind,
     },
     util_resp::{StandardResponse, success},
 };
@@ -44,13 +41,8 @@ pub async fn view_active_draw_page(
         .map(|round| RoundDrawRepr::of_round(round.clone(), &mut *conn))
         .collect::<Vec<_>>();
 
-    let teams = teams::table
-        .filter(teams::tournament_id.eq(&tournament_id))
-        .load::<Team>(&mut *conn)
-        .unwrap()
-        .into_iter()
-        .map(|t| (t.id.clone(), t))
-        .collect::<HashMap<_, _>>();
+    let participants = TournamentParticipants::load(&tournament_id, &mut *conn);
+    let name_kind = TeamNameKind::of_draw(&tournament);
 
     let current_rounds = Round::current_rounds(&tournament_id, &mut *conn);
 
@@ -121,9 +113,27 @@ pub async fn view_active_draw_page(
                                                         th scope="row" class="ps-3 text-secondary fw-normal" { (i + 1) }
                                                         @for debate_team in &debate.teams_of_debate {
                                                             td {
-                                                                a href = (format!("/tournaments/{tournament_id}/teams/{}", &debate_team.team_id)) 
-                                                                  class="text-decoration-none fw-semibold text-dark" {
-                                                                    (teams.get(&debate_team.team_id).map(|t| t.name.as_str()).unwrap_or("Unknown Team"))
+                                                                @let team = participants.teams.get(&debate_team.team_id);
+                                                                @let emoji = team.and_then(|team| team.emoji.as_ref()).filter(|_| tournament.show_team_emoji);
+                                                                @let name = team
+                                                                    .map(|team| participants.display_name_of_team(team, name_kind))
+                                                                    .unwrap_or_else(|| "Unknown Team".to_string());
+                                                                // the team page would reveal who is behind a code name
+                                                                @if name_kind == TeamNameKind::Code {
+                                                                    span class="fw-semibold text-dark" {
+                                                                        @if let Some(emoji) = emoji {
+                                                                            span class="me-1" { (emoji) }
+                                                                        }
+                                                                        (name)
+                                                                    }
+                                                                } @else {
+                                                                    a href = (format!("/tournaments/{tournament_id}/teams/{}", &debate_team.team_id))
+                                                                      class="text-decoration-none fw-semibold text-dark" {
+                                                                        @if let Some(emoji) = emoji {
+                                                                            span class="me-1" { (emoji) }
+                                                                        }
+                                                                        (name)
+                                                                    }
                                                                 }
                                                             }
                                                         }
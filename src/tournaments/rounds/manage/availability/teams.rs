@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use axum::{
     Extension, Form,
@@ -25,6 +25,7 @@ use crate::{
     template::Page,
     tournaments::{
         Tournament,
+        categories::{BreakCategory, team::is_eligible_to_break},
         manage::sidebar::SidebarWrapper,
         participants::TournamentParticipants,
         rounds::{Round, TournamentRounds},
@@ -40,6 +41,8 @@ pub struct ManageAvailabilityTable<'r> {
     rounds: &'r [Round],
     teams: &'r [Team],
     teams_and_availability: &'r HashMap<(String, String), bool>,
+    /// See [`eligible_teams_of_rounds`].
+    eligible_teams: &'r HashMap<String, Option<HashSet<String>>>,
 }
 
 impl<'r> Renderable for ManageAvailabilityTable<'r> {
@@ -78,6 +81,7 @@ impl<'r> Renderable for ManageAvailabilityTable<'r> {
                             }
                             @for round in self.rounds {
                                 @let available = self.teams_and_availability.get(&(team.id.clone(), round.id.clone())).unwrap_or(&false);
+                                @let eligible = is_eligible_to_break(self.eligible_teams[&round.id].as_ref(), &team.id);
                                 td {
                                     form method="post"
                                          action=(format!("/tournaments/{}/rounds/{}/update_team_eligibility", self.tournament_id, round.id)) {
@@ -95,6 +99,9 @@ impl<'r> Renderable for ManageAvailabilityTable<'r> {
                                             input type="submit" value=""
                                                 style="left: 0; height: 100%; opacity: 0; position: absolute; top: 0; width: 100%";
                                         }
+                                        @if !eligible {
+                                            span class="badge text-bg-secondary ms-1" { "Not eligible" }
+                                        }
                                     }
                                 }
                             }
@@ -106,6 +113,26 @@ impl<'r> Renderable for ManageAvailabilityTable<'r> {
     }
 }
 
+/// The teams which can be checked in for each round (indexed by round id),
+/// or `None` if any team can be. Only the teams eligible to break in an
+/// elimination round's break category can take part in it.
+fn eligible_teams_of_rounds(
+    rounds: &[Round],
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> HashMap<String, Option<HashSet<String>>> {
+    rounds
+        .iter()
+        .map(|round| {
+            let eligible = round.break_category().and_then(|category_id| {
+                BreakCategory::fetch(category_id, &round.tournament_id, conn)
+                    .ok()?
+                    .eligible_team_ids(conn)
+            });
+            (round.id.clone(), eligible)
+        })
+        .collect()
+}
+
 fn get_teams_and_availability(
     tournament_id: &str,
     round_seq: usize,
@@ -157,6 +184,7 @@ pub async fn view_team_availability(
 
     let teams_and_availability =
         get_teams_and_availability(&tournament_id, round_seq, &mut *conn);
+    let eligible_teams = eligible_teams_of_rounds(&selected_rounds, &mut *conn);
 
     success(
         Page::new()
@@ -202,7 +230,8 @@ pub async fn view_team_availability(
                         tournament_id=(&tournament_id)
                         rounds=(&selected_rounds)
                         teams=(&teams)
-                        teams_and_availability=(&teams_and_availability);
+                        teams_and_availability=(&teams_and_availability)
+                        eligible_teams=(&eligible_teams);
                 }
             })
             .render(),
@@ -305,11 +334,15 @@ async fn handle_socket(
                         &mut *conn,
                     );
 
+                    let eligible_teams =
+                        eligible_teams_of_rounds(&rounds, &mut *conn);
+
                     let table = ManageAvailabilityTable {
                         tournament_id: &tournament_id,
                         rounds: &rounds,
                         teams: &teams,
                         teams_and_availability: &teams_and_availability,
+                        eligible_teams: &eligible_teams,
                     };
 
                     table.render().into_inner()
@@ -387,8 +420,14 @@ pub async fn update_eligibility_for_all(
             // todo: there is a more efficient way to do this
             let participants =
                 TournamentParticipants::load(&tournament.id, &mut *conn);
+            let eligible_teams = eligible_teams_of_rounds(
+                std::slice::from_ref(&round),
+                &mut *conn,
+            );
 
-            for (_, team) in participants.teams {
+            for (_, team) in participants.teams.into_iter().filter(|(id, _)| {
+                is_eligible_to_break(eligible_teams[&round.id].as_ref(), id)
+            }) {
                 let n = diesel::insert_into(team_availability::table)
                     .values((
                         team_availability::id.eq(Uuid::now_v7().to_string()),
@@ -519,6 +558,25 @@ pub async fn update_team_availability_for_round(
         );
     }
 
+    if available_bool {
+        let eligible_teams =
+            eligible_teams_of_rounds(std::slice::from_ref(&round), &mut *conn);
+        if !is_eligible_to_break(eligible_teams[&round.id].as_ref(), &team.id) {
+            return bad_request(
+                Page::new()
+                    .user(user)
+                    .tournament(tournament)
+                    .body(maud! {
+                        p {
+                            "Error: " (team.name) " is not eligible to break "
+                            "in the break category of " (round.name) "."
+                        }
+                    })
+                    .render(),
+            );
+        }
+    }
+
     // TODO: check that a team can't be allocated to multiple concurrent rounds
 
     let n = diesel::insert_into(team_availability::table)
@@ -69,10 +69,11 @@ pub async fn create_new_round(
                     } @else {
                         li class="list-group-item" {
                             p {
-                                "To create elimination rounds, please first set up break categories"
+                                "To create elimination rounds, please first "
+                                a href=(format!("/tournaments/{tid}/categories")) {
+                                    "set up break categories"
+                                }
                                 " (e.g. open, esl, etc)"
-                                // todo: link to these break categories (also
-                                // always link to "create new" break category)
                             }
                         }
                     }
@@ -69,6 +69,12 @@ pub async fn view_draws_page(
                         a href=(format!("/tournaments/{}/rounds/draws/edit?{}", &tournament.id, rounds_in_seq.iter().map(|r| format!("rounds={}", r.id)).join("&"))) class="btn btn-primary" {
                             "Edit Draw"
                         }
+                        a href=(format!("/tournaments/{}/rounds/{}/print/ballots", &tournament.id, round_seq)) class="btn btn-outline-dark" {
+                            "Print Ballots"
+                        }
+                        a href=(format!("/tournaments/{}/rounds/{}/print/feedback", &tournament.id, round_seq)) class="btn btn-outline-dark" {
+                            "Print Feedback Forms"
+                        }
                     }
 
                     @for (round, draw) in &rounds_with_draws {
@@ -28,6 +28,7 @@ pub mod draw_edit;
 pub mod draw_view;
 pub mod edit;
 pub mod motions;
+pub mod print;
 pub mod results;
 pub mod setup;
 pub mod view;
@@ -0,0 +1,471 @@
+//! Printable ballot and feedback sheets, for venues where judges cannot
+//! reliably submit their ballots online.
+//!
+//! These pages deliberately do not use [`Page`](crate::template::Page), as the
+//! navigation bars would otherwise be printed on every sheet. Each debate is
+//! printed on its own page.
+
+use axum::extract::Path;
+use diesel::prelude::*;
+use hypertext::{Raw, prelude::*};
+use itertools::Itertools;
+
+use crate::{
+    auth::User,
+    schema::feedback_questions,
+    state::Conn,
+    tournaments::{
+        Tournament,
+        feedback::{FeedbackQuestion, manage::config::FeedbackQuestionKind},
+        rounds::{
+            Round,
+            draws::{DebateRepr, RoundDrawRepr},
+            side_names::name_of_side,
+        },
+    },
+    util_resp::{FailureResponse, StandardResponse, success},
+};
+
+const PRINT_STYLES: &str = r#"
+    body { background: white; }
+    .sheet { padding: 1.5rem; page-break-after: always; break-after: page; }
+    .sheet:last-child { page-break-after: auto; break-after: auto; }
+    .write-in { border-bottom: 1px solid black; min-width: 8rem; display: inline-block; }
+    .score-box { border: 1px solid black; width: 5rem; height: 2rem; }
+    .tick-box { border: 1px solid black; width: 1rem; height: 1rem; display: inline-block; vertical-align: middle; }
+    .text-answer { border: 1px solid black; height: 6rem; }
+    @media print { .no-print { display: none; } }
+"#;
+
+/// Wraps the sheets in a bare HTML document.
+struct PrintDocument<'a, R: Renderable> {
+    title: &'a str,
+    sheets: R,
+}
+
+impl<R: Renderable> Renderable for PrintDocument<'_, R> {
+    fn render_to(
+        &self,
+        buffer: &mut hypertext::Buffer<hypertext::context::Node>,
+    ) {
+        maud! {
+            (Raw::dangerously_create("<!DOCTYPE html>"))
+            html {
+                head {
+                    title { (self.title) }
+                    meta charset="utf-8";
+                    link rel="stylesheet" href="/style.css";
+                    style { (Raw::dangerously_create(PRINT_STYLES)) }
+                }
+                body {
+                    div class="no-print p-3 border-bottom" {
+                        button type="button" class="btn btn-dark" onclick="window.print()" {
+                            "Print"
+                        }
+                    }
+                    (self.sheets)
+                }
+            }
+        }
+        .render_to(buffer)
+    }
+}
+
+/// Loads the draws of all the rounds with the given sequence number, returning
+/// an error response if none of the rounds has a draw.
+fn draws_of_seq(
+    tournament: &Tournament,
+    round_seq: i64,
+    conn: &mut impl diesel::connection::LoadConnection<
+        Backend = diesel::sqlite::Sqlite,
+    >,
+) -> Result<Vec<RoundDrawRepr>, FailureResponse> {
+    let rounds = Round::of_seq(round_seq, &tournament.id, conn);
+    if rounds.is_empty() {
+        return Err(FailureResponse::NotFound(()));
+    }
+
+    let draws = rounds
+        .into_iter()
+        .filter(|round| round.draw_status != "none")
+        .map(|round| RoundDrawRepr::of_round(round, conn))
+        .collect_vec();
+
+    if draws.is_empty() {
+        return Err(FailureResponse::BadRequest(
+            maud! {
+                "There is no draw for these rounds yet, so there is nothing to print."
+            }
+            .render(),
+        ));
+    }
+
+    Ok(draws)
+}
+
+fn room_name(debate: &DebateRepr) -> String {
+    debate
+        .room
+        .as_ref()
+        .map(|room| room.name.clone())
+        .unwrap_or_else(|| "No room".to_string())
+}
+
+/// Describes the permitted range of speaker scores, e.g. "70 to 80, in steps
+/// of 0.5".
+fn score_range_description(
+    min: Option<f32>,
+    max: Option<f32>,
+    step: Option<f32>,
+) -> Option<String> {
+    let range = match (min, max) {
+        (Some(min), Some(max)) => format!("{min} to {max}"),
+        (Some(min), None) => format!("at least {min}"),
+        (None, Some(max)) => format!("at most {max}"),
+        (None, None) => return None,
+    };
+    Some(match step {
+        Some(step) => format!("{range}, in steps of {step}"),
+        None => range,
+    })
+}
+
+#[tracing::instrument(skip(conn))]
+/// Produces one pre-filled paper ballot for every debate (and therefore every
+/// room) in the rounds with the given sequence number.
+pub async fn print_ballots_page(
+    Path((tournament_id, round_seq)): Path<(String, i64)>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+
+    let draws = draws_of_seq(&tournament, round_seq, &mut *conn)?;
+
+    let substantive_range = score_range_description(
+        tournament.substantive_speech_min_speak,
+        tournament.substantive_speech_max_speak,
+        tournament.substantive_speech_step,
+    );
+    let reply_range = score_range_description(
+        tournament.reply_speech_min_speak,
+        tournament.reply_speech_max_speak,
+        tournament.substantive_speech_step,
+    );
+
+    let sheets = maud! {
+        @for draw in &draws {
+            @let records_positions = tournament.round_requires_speaker_order(&draw.round);
+            @let records_scores = tournament.round_requires_speaks(&draw.round);
+            @let n_positions = tournament.substantive_speakers
+                + if tournament.reply_speakers { 1 } else { 0 };
+
+            @for debate in draw.debates.iter().sorted_by_key(|d| d.debate.number) {
+                div class="sheet" {
+                    div class="d-flex justify-content-between border-bottom border-dark mb-3" {
+                        h1 class="h4" { (tournament.name) " — " (draw.round.name) }
+                        h2 class="h4" { "Debate " (debate.debate.number) " — " (room_name(debate)) }
+                    }
+
+                    p class="mb-1" {
+                        strong { "Panel: " }
+                        (debate.judges_of_debate.iter().map(|dj| {
+                            format!(
+                                "{} ({})",
+                                debate.judges.get(&dj.judge_id).unwrap().name,
+                                dj.role()
+                            )
+                        }).join(", "))
+                    }
+                    p class="mb-1" { strong { "Judge completing this ballot: " } span class="write-in" {} }
+
+                    @if debate.motions.len() == 1 {
+                        p class="mb-1" { strong { "Motion: " } (debate.motions.values().next().unwrap().motion) }
+                    } @else if debate.motions.len() > 1 {
+                        p class="mb-1" { strong { "Motion (circle one):" } }
+                        ul {
+                            @for motion in debate.motions.values() {
+                                li { (motion.motion) }
+                            }
+                        }
+                    }
+
+                    @if records_scores {
+                        @if let Some(range) = &substantive_range {
+                            p class="mb-1" { strong { "Substantive speeches: " } (range) }
+                        }
+                        @if tournament.reply_speakers {
+                            @if let Some(range) = &reply_range {
+                                p class="mb-1" { strong { "Reply speeches: " } (range) }
+                            }
+                        }
+                    }
+
+                    div class="row mt-3" {
+                        @for debate_team in debate.teams_of_debate.iter().sorted_by_key(|dt| (dt.seq, dt.side)) {
+                            @let team = debate.teams.get(&debate_team.team_id).unwrap();
+                            @let speakers = debate.speakers_of_team.get(&debate_team.team_id);
+
+                            div class="col-6 mb-3" {
+                                h3 class="h5 mb-1" {
+                                    (name_of_side(&tournament, debate_team.side, debate_team.seq, false))
+                                    ": " (team.name)
+                                }
+                                @if let Some(speakers) = speakers {
+                                    p class="small text-muted mb-2" {
+                                        "Speakers: " (speakers.iter().map(|s| s.name.as_str()).join(", "))
+                                    }
+                                }
+
+                                @if records_positions {
+                                    table class="table table-bordered table-sm" {
+                                        thead {
+                                            tr {
+                                                th { "Position" }
+                                                th { "Speaker" }
+                                                @if records_scores {
+                                                    th { "Score" }
+                                                }
+                                            }
+                                        }
+                                        tbody {
+                                            @for position in 0..n_positions {
+                                                tr {
+                                                    td { (tournament.speaker_position_name(debate_team.side, debate_team.seq, position)) }
+                                                    td {}
+                                                    @if records_scores {
+                                                        td class="score-box" {}
+                                                    }
+                                                }
+                                            }
+                                            @if records_scores {
+                                                tr {
+                                                    td colspan="2" { strong { "Total" } }
+                                                    td class="score-box" {}
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+
+                                @if draw.round.is_elim() {
+                                    p { span class="tick-box" {} " Advancing" }
+                                } @else if !records_scores {
+                                    p { "Rank: " span class="write-in" {} }
+                                }
+                            }
+                        }
+                    }
+
+                    div class="mt-4" {
+                        "Signature: " span class="write-in" style="min-width: 16rem;" {}
+                    }
+                }
+            }
+        }
+    };
+
+    success(
+        PrintDocument {
+            title: &format!("Ballots — {}", tournament.name),
+            sheets,
+        }
+        .render(),
+    )
+}
+
+#[tracing::instrument(skip(conn))]
+/// Produces one paper feedback form for every debate in the rounds with the
+/// given sequence number, listing the tournament's feedback questions.
+pub async fn print_feedback_page(
+    Path((tournament_id, round_seq)): Path<(String, i64)>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+
+    let draws = draws_of_seq(&tournament, round_seq, &mut *conn)?;
+
+    let questions = feedback_questions::table
+        .filter(feedback_questions::tournament_id.eq(&tournament.id))
+        .order_by(feedback_questions::seq.asc())
+        .load::<FeedbackQuestion>(&mut *conn)
+        .unwrap()
+        .into_iter()
+        .map(|question| {
+            let kind: FeedbackQuestionKind =
+                serde_json::from_str(&question.kind).unwrap();
+            (question, kind)
+        })
+        .collect_vec();
+
+    let sheets = maud! {
+        @for draw in &draws {
+            @for debate in draw.debates.iter().sorted_by_key(|d| d.debate.number) {
+                div class="sheet" {
+                    div class="d-flex justify-content-between border-bottom border-dark mb-3" {
+                        h1 class="h4" { (tournament.name) " — " (draw.round.name) " Feedback" }
+                        h2 class="h4" { "Debate " (debate.debate.number) " — " (room_name(debate)) }
+                    }
+
+                    p class="mb-1" { strong { "Submitted by (tick one):" } }
+                    ul class="list-unstyled" {
+                        @for debate_team in debate.teams_of_debate.iter().sorted_by_key(|dt| (dt.seq, dt.side)) {
+                            li {
+                                span class="tick-box" {} " "
+                                (debate.teams.get(&debate_team.team_id).unwrap().name)
+                                " (" (name_of_side(&tournament, debate_team.side, debate_team.seq, true)) ")"
+                            }
+                        }
+                        @for dj in &debate.judges_of_debate {
+                            li {
+                                span class="tick-box" {} " "
+                                (debate.judges.get(&dj.judge_id).unwrap().name)
+                                " (" (dj.role()) ")"
+                            }
+                        }
+                    }
+
+                    p class="mb-1" { strong { "Feedback on (tick one):" } }
+                    ul class="list-unstyled" {
+                        @for dj in &debate.judges_of_debate {
+                            li {
+                                span class="tick-box" {} " "
+                                (debate.judges.get(&dj.judge_id).unwrap().name)
+                                " (" (dj.role()) ")"
+                            }
+                        }
+                    }
+
+                    @for (question, kind) in &questions {
+                        div class="mb-3" {
+                            p class="mb-1" {
+                                strong { (question.question) }
+                                @if !question.for_teams {
+                                    " (judges only)"
+                                } @else if !question.for_judges {
+                                    " (teams only)"
+                                }
+                                @if let Some(paths) = question.paths() {
+                                    " (only " (paths.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")) ")"
+                                }
+                                @if matches!(kind, FeedbackQuestionKind::MultipleChoice { .. }) {
+                                    " (tick all that apply)"
+                                }
+                            }
+                            @match kind {
+                                FeedbackQuestionKind::IntegerScale { min, max } => {
+                                    div class="d-flex gap-3" {
+                                        @for value in *min..=*max {
+                                            span { span class="tick-box" {} " " (value) }
+                                        }
+                                    }
+                                }
+                                FeedbackQuestionKind::Text {} => {
+                                    div class="text-answer" {}
+                                }
+                                FeedbackQuestionKind::Boolean {} => {
+                                    div class="d-flex gap-3" {
+                                        span { span class="tick-box" {} " Yes" }
+                                        span { span class="tick-box" {} " No" }
+                                    }
+                                }
+                                FeedbackQuestionKind::SingleChoice { options }
+                                | FeedbackQuestionKind::MultipleChoice { options } => {
+                                    div class="d-flex flex-wrap gap-3" {
+                                        @for option in options {
+                                            span { span class="tick-box" {} " " (option) }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    success(
+        PrintDocument {
+            title: &format!("Feedback forms — {}", tournament.name),
+            sheets,
+        }
+        .render(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel::sqlite::SqliteConnection;
+
+    use super::*;
+    use crate::{
+        schema::rounds,
+        tournaments::feedback::requirement::tests::tournament_with_completed_debate,
+    };
+
+    fn insert_round(
+        id: &str,
+        seq: i64,
+        draw_status: &str,
+        conn: &mut SqliteConnection,
+    ) {
+        diesel::insert_into(rounds::table)
+            .values((
+                rounds::id.eq(id),
+                rounds::tournament_id.eq("t"),
+                rounds::seq.eq(seq),
+                rounds::name.eq(id),
+                rounds::kind.eq("P"),
+                rounds::completed.eq(false),
+                rounds::draw_status.eq(draw_status),
+            ))
+            .execute(conn)
+            .unwrap();
+    }
+
+    #[test]
+    fn sheets_cover_every_drawn_round_of_the_seq() {
+        let (mut conn, tournament) = tournament_with_completed_debate("off");
+        // r1 (seq 1, with debate d1) comes from the fixture
+        insert_round("r1b", 1, "none", &mut conn);
+        insert_round("r2", 2, "none", &mut conn);
+
+        let draws = draws_of_seq(&tournament, 1, &mut conn).unwrap();
+        assert_eq!(draws.len(), 1);
+        assert_eq!(draws[0].round.id, "r1");
+        assert_eq!(
+            draws[0]
+                .debates
+                .iter()
+                .map(|d| d.debate.id.as_str())
+                .collect_vec(),
+            vec!["d1"]
+        );
+
+        assert!(matches!(
+            draws_of_seq(&tournament, 2, &mut conn),
+            Err(FailureResponse::BadRequest(_))
+        ));
+        assert!(matches!(
+            draws_of_seq(&tournament, 3, &mut conn),
+            Err(FailureResponse::NotFound(()))
+        ));
+    }
+
+    #[test]
+    fn score_ranges_are_described_in_words() {
+        assert_eq!(
+            score_range_description(Some(70.0), Some(80.0), Some(0.5)).unwrap(),
+            "70 to 80, in steps of 0.5"
+        );
+        assert_eq!(
+            score_range_description(None, Some(80.0), None).unwrap(),
+            "at most 80"
+        );
+        assert_eq!(score_range_description(None, None, Some(1.0)), None);
+    }
+}
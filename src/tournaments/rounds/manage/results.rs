@@ -20,7 +20,10 @@ use crate::{
             ballots::{BallotRepr, aggregate::aggregate_ballot_set},
             draws::{DebateRepr, RoundDrawRepr},
         },
-        standings::compute::refresh_saved_team_standings,
+        standings::compute::{
+            refresh_saved_team_standings,
+            speakers::refresh_saved_speaker_standings,
+        },
     },
     util_resp::{
         StandardResponse, bad_request, err_not_found, see_other_ok, success,
@@ -296,6 +299,7 @@ pub async fn set_round_completed(
     }
 
     refresh_saved_team_standings(&tournament_id, &mut *conn).unwrap();
+    refresh_saved_speaker_standings(&tournament_id, &mut *conn).unwrap();
 
     see_other_ok(Redirect::to(&format!(
         "/tournaments/{}/rounds/{}/results/manage",
@@ -170,7 +170,7 @@ pub async fn view_results_page(
                 }
                 tbody {
                     @for debate in &data.draw_repr.debates {
-                        tr {
+                        tr id=(format!("debate-{}", debate.debate.id)) {
                             td class="align-middle" {
                                 @if let Some(room) = &debate.room {
                                     (room.name)
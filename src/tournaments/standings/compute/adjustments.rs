@@ -0,0 +1,165 @@
+//! Manual adjustments to the standings (e.g. a point deduction for a late
+//! team, or a change to a speaker's scores following an equity ruling). These
+//! are stored separately from the ballots, and added to the computed metrics
+//! whenever the standings are computed.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::{connection::LoadConnection, sqlite::Sqlite};
+use rust_decimal::Decimal;
+
+use crate::schema::{rounds, standings_adjustments};
+use crate::tournaments::config::{RankableTeamMetric, SpeakerMetric};
+
+#[derive(Queryable, Clone)]
+pub struct StandingsAdjustment {
+    pub id: String,
+    pub tournament_id: String,
+    pub team_id: Option<String>,
+    pub speaker_id: Option<String>,
+    /// The serialized metric (see [`RankableTeamMetric`] and
+    /// [`SpeakerMetric`]).
+    pub metric: String,
+    pub delta: f32,
+    pub reason: String,
+    pub author_id: String,
+    pub created_at: NaiveDateTime,
+    /// The sequence number of the round after which this adjustment takes
+    /// effect (`0` if it applies from the start of the tournament).
+    pub from_seq: i64,
+}
+
+impl StandingsAdjustment {
+    /// Returns every adjustment made in the tournament, most recent first.
+    pub fn of_tournament(
+        tid: &str,
+        conn: &mut impl LoadConnection<Backend = Sqlite>,
+    ) -> Vec<Self> {
+        standings_adjustments::table
+            .filter(standings_adjustments::tournament_id.eq(tid))
+            .order_by(standings_adjustments::created_at.desc())
+            .load::<StandingsAdjustment>(conn)
+            .unwrap()
+    }
+
+    pub fn delta(&self) -> Decimal {
+        Decimal::from_f32_retain(self.delta)
+            .unwrap_or_else(|| {
+                panic!("could not convert `{}` to decimal", self.delta)
+            })
+            .round_dp(2)
+    }
+
+    pub fn team_metric(&self) -> Option<RankableTeamMetric> {
+        self.team_id.as_ref()?;
+        serde_json::from_str(&self.metric).ok()
+    }
+
+    pub fn speaker_metric(&self) -> Option<SpeakerMetric> {
+        self.speaker_id.as_ref()?;
+        serde_json::from_str(&self.metric).ok()
+    }
+}
+
+/// The sequence number of the latest completed round (or `0` if no round has
+/// been completed yet). Adjustments which take effect after a later round are
+/// not yet in effect.
+pub fn latest_completed_seq(
+    tid: &str,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> i64 {
+    rounds::table
+        .filter(rounds::tournament_id.eq(tid))
+        .filter(rounds::completed.eq(true))
+        .select(diesel::dsl::max(rounds::seq))
+        .first::<Option<i64>>(conn)
+        .unwrap()
+        .unwrap_or(0)
+}
+
+/// The adjustments to team metrics which had taken effect after the round
+/// with sequence number `max_seq`, as `(team_id, metric, delta)`.
+pub fn team_adjustments(
+    tid: &str,
+    max_seq: i64,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> Vec<(String, RankableTeamMetric, Decimal)> {
+    StandingsAdjustment::of_tournament(tid, conn)
+        .into_iter()
+        .filter(|adjustment| adjustment.from_seq <= max_seq)
+        .filter_map(|adjustment| {
+            let metric = adjustment.team_metric()?;
+            Some((adjustment.team_id.clone()?, metric, adjustment.delta()))
+        })
+        .collect()
+}
+
+/// As [`team_adjustments`], but for speaker metrics.
+pub fn speaker_adjustments(
+    tid: &str,
+    max_seq: i64,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> Vec<(String, SpeakerMetric, Decimal)> {
+    StandingsAdjustment::of_tournament(tid, conn)
+        .into_iter()
+        .filter(|adjustment| adjustment.from_seq <= max_seq)
+        .filter_map(|adjustment| {
+            let metric = adjustment.speaker_metric()?;
+            Some((adjustment.speaker_id.clone()?, metric, adjustment.delta()))
+        })
+        .collect()
+}
+
+/// Adds each adjustment to the corresponding metric. Adjustments to metrics
+/// which are not in use (or to teams/speakers who are not in the standings)
+/// are ignored.
+pub fn apply_adjustments<M: PartialEq>(
+    metrics_of: &mut HashMap<String, Vec<(M, Decimal)>>,
+    adjustments: &[(String, M, Decimal)],
+) {
+    for (id, metric, delta) in adjustments {
+        if let Some((_, value)) = metrics_of
+            .get_mut(id)
+            .and_then(|metrics| metrics.iter_mut().find(|(m, _)| m == metric))
+        {
+            *value += *delta;
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_adjustments_only_apply_to_metrics_in_use() {
+    let mut metrics_of = HashMap::from([(
+        "a".to_string(),
+        vec![
+            (RankableTeamMetric::Wins, Decimal::from(4)),
+            (RankableTeamMetric::TotalSpeakerScore, Decimal::from(300)),
+        ],
+    )]);
+
+    apply_adjustments(
+        &mut metrics_of,
+        &[
+            ("a".to_string(), RankableTeamMetric::Wins, Decimal::from(-1)),
+            ("a".to_string(), RankableTeamMetric::Wins, Decimal::from(-1)),
+            (
+                "a".to_string(),
+                RankableTeamMetric::Ballots,
+                Decimal::from(5),
+            ),
+            ("b".to_string(), RankableTeamMetric::Wins, Decimal::from(1)),
+        ],
+    );
+
+    assert_eq!(
+        metrics_of["a"],
+        vec![
+            (RankableTeamMetric::Wins, Decimal::from(2)),
+            (RankableTeamMetric::TotalSpeakerScore, Decimal::from(300)),
+        ]
+    );
+    assert!(!metrics_of.contains_key("b"));
+}
@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+use diesel::{connection::LoadConnection, sqlite::Sqlite};
+use rust_decimal::Decimal;
+
+use crate::schema::{
+    agg_speaker_results_of_debate, agg_team_results_of_debate, debates,
+    teams_of_debate,
+};
+use crate::tournaments::participants::TournamentParticipants;
+use crate::tournaments::rounds::{Round, TournamentRounds};
+use crate::tournaments::teams::TeamNameKind;
+
+/// How a team fared in a single debate.
+pub struct TeamRoundResult {
+    pub debate_id: String,
+    pub side: i64,
+    pub seq: i64,
+    pub points: Option<i64>,
+    /// The names (of the kind shown on the tab) of the other teams in the
+    /// debate.
+    pub opponents: Vec<String>,
+    /// The sum of the team's speaker scores (including replies), if any were
+    /// recorded.
+    pub speaks: Option<Decimal>,
+}
+
+/// The result of every team in each preliminary round, as shown on the team
+/// tab.
+pub struct TeamTabBreakdown {
+    /// The rounds which are shown, in order.
+    pub rounds: Vec<Round>,
+    /// Indexed by `(team_id, round_id)`.
+    pub results: HashMap<(String, String), TeamRoundResult>,
+}
+
+impl TeamTabBreakdown {
+    /// Loads the results of the completed preliminary rounds. When
+    /// `only_published` is set, rounds whose results have not been published
+    /// are left out. Opponents are named using `name_kind`.
+    pub fn load(
+        tid: &str,
+        only_published: bool,
+        participants: &TournamentParticipants,
+        name_kind: TeamNameKind,
+        conn: &mut impl LoadConnection<Backend = Sqlite>,
+    ) -> Self {
+        let rounds: Vec<Round> = TournamentRounds::fetch(tid, conn)
+            .unwrap()
+            .prelim
+            .into_iter()
+            .filter(|round| round.completed)
+            .filter(|round| !only_published || round.is_results_public())
+            .collect();
+        let round_ids: Vec<&str> =
+            rounds.iter().map(|round| round.id.as_str()).collect();
+
+        let team_names: HashMap<&str, String> = participants
+            .teams
+            .iter()
+            .map(|(id, team)| {
+                (
+                    id.as_str(),
+                    participants.display_name_of_team(team, name_kind),
+                )
+            })
+            .collect();
+
+        let appearances = teams_of_debate::table
+            .inner_join(
+                debates::table.on(debates::id.eq(teams_of_debate::debate_id)),
+            )
+            .filter(debates::round_id.eq_any(&round_ids))
+            .select((
+                debates::id,
+                debates::round_id,
+                teams_of_debate::team_id,
+                teams_of_debate::side,
+                teams_of_debate::seq,
+            ))
+            .load::<(String, String, String, i64, i64)>(conn)
+            .unwrap();
+
+        let points: HashMap<(String, String), Option<i64>> =
+            agg_team_results_of_debate::table
+                .filter(agg_team_results_of_debate::tournament_id.eq(tid))
+                .select((
+                    agg_team_results_of_debate::debate_id,
+                    agg_team_results_of_debate::team_id,
+                    agg_team_results_of_debate::points,
+                ))
+                .load::<(String, String, Option<i64>)>(conn)
+                .unwrap()
+                .into_iter()
+                .map(|(debate, team, points)| ((debate, team), points))
+                .collect();
+
+        let mut speaks: HashMap<(String, String), Decimal> = HashMap::new();
+        for (debate, team, score) in agg_speaker_results_of_debate::table
+            .filter(agg_speaker_results_of_debate::tournament_id.eq(tid))
+            .select((
+                agg_speaker_results_of_debate::debate_id,
+                agg_speaker_results_of_debate::team_id,
+                agg_speaker_results_of_debate::score,
+            ))
+            .load::<(String, String, Option<f32>)>(conn)
+            .unwrap()
+        {
+            if let Some(score) = score.and_then(Decimal::from_f32_retain) {
+                *speaks.entry((debate, team)).or_default() += score;
+            }
+        }
+
+        let mut teams_of_debate: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (debate, _, team, _, _) in &appearances {
+            teams_of_debate
+                .entry(debate.as_str())
+                .or_default()
+                .push(team.as_str());
+        }
+
+        let results = appearances
+            .iter()
+            .map(|(debate, round, team, side, seq)| {
+                let key = (debate.clone(), team.clone());
+                (
+                    (team.clone(), round.clone()),
+                    TeamRoundResult {
+                        debate_id: debate.clone(),
+                        side: *side,
+                        seq: *seq,
+                        points: points.get(&key).copied().flatten(),
+                        opponents: teams_of_debate[debate.as_str()]
+                            .iter()
+                            .filter(|other| **other != team.as_str())
+                            .map(|other| {
+                                team_names
+                                    .get(*other)
+                                    .cloned()
+                                    .unwrap_or_default()
+                            })
+                            .collect(),
+                        speaks: speaks.get(&key).map(|s| s.round_dp(2)),
+                    },
+                )
+            })
+            .collect();
+
+        Self { rounds, results }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_breakdown_leaves_out_incomplete_and_unpublished_rounds() {
+    use diesel::sqlite::SqliteConnection;
+    use diesel_migrations::MigrationHarness;
+
+    use crate::schema::{rounds, teams};
+
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+    conn.run_pending_migrations(crate::MIGRATIONS).unwrap();
+    crate::tournaments::create::insert_tournament(
+        "t", "Test", "T", "test", "user", &mut conn,
+    )
+    .unwrap();
+    for (number, team) in ["a", "b"].into_iter().enumerate() {
+        diesel::insert_into(teams::table)
+            .values((
+                teams::id.eq(team),
+                teams::tournament_id.eq("t"),
+                teams::name.eq(team),
+                teams::number.eq(number as i64 + 1),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+    }
+    // (round, completed, published)
+    for (seq, (round, completed, published)) in [
+        ("published", true, true),
+        ("unpublished", true, false),
+        ("incomplete", false, false),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        diesel::insert_into(rounds::table)
+            .values((
+                rounds::id.eq(round),
+                rounds::tournament_id.eq("t"),
+                rounds::seq.eq(seq as i64 + 1),
+                rounds::name.eq(round),
+                rounds::kind.eq("P"),
+                rounds::completed.eq(completed),
+                rounds::draw_status.eq("released_full"),
+                rounds::results_published_at
+                    .eq(published.then(|| chrono::Utc::now().naive_utc())),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        diesel::insert_into(debates::table)
+            .values((
+                debates::id.eq(round),
+                debates::tournament_id.eq("t"),
+                debates::round_id.eq(round),
+                debates::number.eq(1),
+                debates::status.eq("confirmed"),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        for (side, team) in ["a", "b"].into_iter().enumerate() {
+            diesel::insert_into(teams_of_debate::table)
+                .values((
+                    teams_of_debate::id.eq(format!("{round}-{team}")),
+                    teams_of_debate::tournament_id.eq("t"),
+                    teams_of_debate::debate_id.eq(round),
+                    teams_of_debate::team_id.eq(team),
+                    teams_of_debate::side.eq(side as i64),
+                    teams_of_debate::seq.eq(0),
+                ))
+                .execute(&mut conn)
+                .unwrap();
+        }
+    }
+    let participants = TournamentParticipants::load("t", &mut conn);
+    let round_ids = |breakdown: &TeamTabBreakdown| {
+        breakdown
+            .rounds
+            .iter()
+            .map(|round| round.id.clone())
+            .collect::<Vec<_>>()
+    };
+
+    let public = TeamTabBreakdown::load(
+        "t",
+        true,
+        &participants,
+        TeamNameKind::Full,
+        &mut conn,
+    );
+    assert_eq!(round_ids(&public), ["published"]);
+    assert_eq!(
+        public.results[&("a".to_string(), "published".to_string())].opponents,
+        ["b"]
+    );
+    assert!(
+        !public
+            .results
+            .contains_key(&("a".to_string(), "unpublished".to_string()))
+    );
+    assert!(
+        !public
+            .results
+            .contains_key(&("a".to_string(), "incomplete".to_string()))
+    );
+
+    let admin = TeamTabBreakdown::load(
+        "t",
+        false,
+        &participants,
+        TeamNameKind::Full,
+        &mut conn,
+    );
+    assert_eq!(round_ids(&admin), ["published", "unpublished"]);
+    assert!(
+        !admin
+            .results
+            .contains_key(&("a".to_string(), "incomplete".to_string()))
+    );
+}
@@ -9,7 +9,7 @@ use diesel::prelude::*;
 use rust_decimal::Decimal;
 
 pub fn atss(
-    (tid, tss): (&str, HashMap<String, rust_decimal::Decimal>),
+    (tid, max_seq, tss): (&str, i64, HashMap<String, rust_decimal::Decimal>),
     conn: &mut impl diesel::connection::LoadConnection<
         Backend = diesel::sqlite::Sqlite,
     >,
@@ -21,7 +21,8 @@ pub fn atss(
                 rounds::tournament_id
                     .eq(tid)
                     .and(rounds::kind.eq("P"))
-                    .and(rounds::completed.eq(true)),
+                    .and(rounds::completed.eq(true))
+                    .and(rounds::seq.le(max_seq)),
             )
             .inner_join(teams::table.on(diesel::dsl::exists(
                 agg_team_results_of_debate::table.filter(
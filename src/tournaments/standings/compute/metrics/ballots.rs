@@ -5,7 +5,7 @@ use diesel::prelude::*;
 use crate::schema::{ballots, debates, rounds, team_ranks_of_ballot, teams};
 
 pub fn ballot_points_of_team(
-    (tid,): (&str,),
+    (tid, max_seq): (&str, i64),
     conn: &mut impl diesel::connection::LoadConnection<
         Backend = diesel::sqlite::Sqlite,
     >,
@@ -17,6 +17,7 @@ pub fn ballot_points_of_team(
         .filter(rounds::tournament_id.eq(tid))
         .filter(rounds::kind.eq("P"))
         .filter(rounds::completed.eq(true))
+        .filter(rounds::seq.le(max_seq))
         .select((
             ballots::debate_id,
             ballots::judge_id,
@@ -1,20 +1,26 @@
 use std::collections::HashMap;
 
 use diesel::prelude::*;
-use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, prelude::ToPrimitive};
 
-use crate::schema::{debates, rounds, teams_of_debate};
+use crate::schema::{debates, rounds, teams, teams_of_debate};
+use crate::tournaments::standings::compute::metrics::tss::total_speaker_score_of_team;
 
-pub fn draw_strength_of_teams(
-    (tid, team_points): (&str, HashMap<String, rust_decimal::Decimal>),
+/// Returns the teams which took part in each debate of the completed
+/// preliminary rounds of the tournament, up to and including those with
+/// sequence number `max_seq` (as a map from debate IDs to team IDs).
+fn teams_of_completed_prelim_debates(
+    tid: &str,
+    max_seq: i64,
     conn: &mut impl diesel::connection::LoadConnection<
         Backend = diesel::sqlite::Sqlite,
     >,
-) -> HashMap<String, i64> {
+) -> HashMap<String, Vec<String>> {
     let teams_of_debate: Vec<(String, String)> = debates::table
         .inner_join(rounds::table.on(rounds::id.eq(debates::round_id)))
         .filter(rounds::completed.eq(true).and(rounds::kind.eq("P")))
         .filter(rounds::tournament_id.eq(tid))
+        .filter(rounds::seq.le(max_seq))
         .inner_join(
             teams_of_debate::table
                 .on(teams_of_debate::debate_id.eq(debates::id)),
@@ -22,7 +28,7 @@ pub fn draw_strength_of_teams(
         .select((debates::id, teams_of_debate::team_id))
         .load::<(String, String)>(conn)
         .unwrap();
-    let teams_of_debate = teams_of_debate.into_iter().fold(
+    teams_of_debate.into_iter().fold(
         HashMap::new(),
         |mut map, (debate, team)| {
             map.entry(debate)
@@ -33,7 +39,20 @@ pub fn draw_strength_of_teams(
 
             map
         },
-    );
+    )
+}
+
+pub fn draw_strength_of_teams(
+    (tid, max_seq, team_points): (
+        &str,
+        i64,
+        HashMap<String, rust_decimal::Decimal>,
+    ),
+    conn: &mut impl diesel::connection::LoadConnection<
+        Backend = diesel::sqlite::Sqlite,
+    >,
+) -> HashMap<String, i64> {
+    let teams_of_debate = teams_of_completed_prelim_debates(tid, max_seq, conn);
 
     let mut ds: HashMap<String, i64> =
         team_points.keys().map(|team| (team.clone(), 0)).collect();
@@ -59,3 +78,311 @@ pub fn draw_strength_of_teams(
 
     ds
 }
+
+/// The total speaker scores (over the completed preliminary rounds) of every
+/// team faced by each team in the completed preliminary rounds. A team which
+/// faces the same opponent twice has that opponent's speaks listed twice.
+fn speaks_of_opponents_of_teams(
+    (tid, max_seq): (&str, i64),
+    conn: &mut impl diesel::connection::LoadConnection<
+        Backend = diesel::sqlite::Sqlite,
+    >,
+) -> HashMap<String, Vec<Decimal>> {
+    let tss = total_speaker_score_of_team((tid, max_seq), conn);
+    let teams_of_debate = teams_of_completed_prelim_debates(tid, max_seq, conn);
+
+    let mut opponents: HashMap<String, Vec<Decimal>> = teams::table
+        .filter(teams::tournament_id.eq(tid))
+        .select(teams::id)
+        .load::<String>(conn)
+        .unwrap()
+        .into_iter()
+        .map(|team| (team, Vec::new()))
+        .collect();
+
+    for (_, teams) in teams_of_debate {
+        for team_a in &teams {
+            for team_b in &teams {
+                if team_a == team_b {
+                    continue;
+                }
+
+                // teams for which no speaks have been recorded (e.g. in elim
+                // rounds without speaks) contribute nothing
+                let speaks = tss.get(team_b).copied().unwrap_or(Decimal::ZERO);
+                opponents.entry(team_a.clone()).or_default().push(speaks);
+            }
+        }
+    }
+
+    opponents
+}
+
+/// Computes the draw strength of each team by speaks, i.e. the sum of the
+/// total speaker scores (over the completed preliminary rounds) of every team
+/// faced in the completed preliminary rounds. A team which faces the same
+/// opponent twice counts that opponent's speaks twice.
+pub fn draw_strength_by_speaks_of_teams(
+    (tid, max_seq): (&str, i64),
+    conn: &mut impl diesel::connection::LoadConnection<
+        Backend = diesel::sqlite::Sqlite,
+    >,
+) -> HashMap<String, Decimal> {
+    speaks_of_opponents_of_teams((tid, max_seq), conn)
+        .into_iter()
+        .map(|(team, speaks)| (team, speaks.into_iter().sum()))
+        .collect()
+}
+
+/// As [`draw_strength_by_speaks_of_teams`], but averaged over the opponents
+/// faced (so that teams which have faced fewer opponents, e.g. because of a
+/// bye or a swing team, are not penalised). Teams which have not faced anyone
+/// have an average of zero.
+pub fn average_draw_strength_by_speaks_of_teams(
+    (tid, max_seq): (&str, i64),
+    conn: &mut impl diesel::connection::LoadConnection<
+        Backend = diesel::sqlite::Sqlite,
+    >,
+) -> HashMap<String, Decimal> {
+    speaks_of_opponents_of_teams((tid, max_seq), conn)
+        .into_iter()
+        .map(|(team, speaks)| {
+            let average = if speaks.is_empty() {
+                Decimal::ZERO
+            } else {
+                speaks.iter().sum::<Decimal>() / Decimal::from(speaks.len())
+            };
+            (team, average)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel::prelude::*;
+    use diesel::sqlite::SqliteConnection;
+    use diesel_migrations::MigrationHarness;
+    use rust_decimal::Decimal;
+
+    use super::{
+        average_draw_strength_by_speaks_of_teams,
+        draw_strength_by_speaks_of_teams,
+    };
+    use crate::schema::{
+        agg_speaker_results_of_debate, debates, rounds, teams, teams_of_debate,
+    };
+
+    const TID: &str = "tournament";
+
+    fn test_conn() -> SqliteConnection {
+        let mut conn = SqliteConnection::establish(":memory:").unwrap();
+        conn.run_pending_migrations(crate::MIGRATIONS).unwrap();
+        conn
+    }
+
+    fn insert_round(
+        conn: &mut SqliteConnection,
+        id: &str,
+        seq: i64,
+        completed: bool,
+    ) {
+        diesel::insert_into(rounds::table)
+            .values((
+                rounds::id.eq(id),
+                rounds::tournament_id.eq(TID),
+                rounds::seq.eq(seq),
+                rounds::name.eq(id),
+                rounds::kind.eq("P"),
+                rounds::completed.eq(completed),
+                rounds::draw_status.eq("released_full"),
+            ))
+            .execute(conn)
+            .unwrap();
+    }
+
+    fn insert_team(conn: &mut SqliteConnection, id: &str) {
+        diesel::insert_into(teams::table)
+            .values((
+                teams::id.eq(id),
+                teams::tournament_id.eq(TID),
+                teams::name.eq(id),
+                teams::number.eq(0_i64),
+            ))
+            .execute(conn)
+            .unwrap();
+    }
+
+    /// Inserts a debate between `teams`, in which each team's (only)
+    /// speaker receives the given score.
+    fn insert_debate(
+        conn: &mut SqliteConnection,
+        id: &str,
+        round_id: &str,
+        teams: &[(&str, f32)],
+    ) {
+        diesel::insert_into(debates::table)
+            .values((
+                debates::id.eq(id),
+                debates::tournament_id.eq(TID),
+                debates::round_id.eq(round_id),
+                debates::number.eq(0_i64),
+                debates::status.eq("confirmed"),
+            ))
+            .execute(conn)
+            .unwrap();
+
+        for (side, (team, score)) in teams.iter().enumerate() {
+            diesel::insert_into(teams_of_debate::table)
+                .values((
+                    teams_of_debate::id.eq(format!("{id}-{team}")),
+                    teams_of_debate::tournament_id.eq(TID),
+                    teams_of_debate::debate_id.eq(id),
+                    teams_of_debate::team_id.eq(team),
+                    teams_of_debate::side.eq(side as i64),
+                    teams_of_debate::seq.eq(0_i64),
+                ))
+                .execute(conn)
+                .unwrap();
+
+            diesel::insert_into(agg_speaker_results_of_debate::table)
+                .values((
+                    agg_speaker_results_of_debate::id
+                        .eq(format!("{id}-{team}")),
+                    agg_speaker_results_of_debate::tournament_id.eq(TID),
+                    agg_speaker_results_of_debate::debate_id.eq(id),
+                    agg_speaker_results_of_debate::speaker_id
+                        .eq(format!("{team}-speaker")),
+                    agg_speaker_results_of_debate::team_id.eq(team),
+                    agg_speaker_results_of_debate::position.eq(0_i64),
+                    agg_speaker_results_of_debate::score.eq(Some(*score)),
+                ))
+                .execute(conn)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn draw_strength_by_speaks_sums_opponents_total_speaks() {
+        let mut conn = test_conn();
+        insert_round(&mut conn, "r1", 1, true);
+        insert_round(&mut conn, "r2", 2, true);
+        for team in ["a", "b", "c", "d"] {
+            insert_team(&mut conn, team);
+        }
+
+        insert_debate(&mut conn, "d1", "r1", &[("a", 70.0), ("b", 75.0)]);
+        insert_debate(&mut conn, "d2", "r1", &[("c", 80.0), ("d", 72.0)]);
+        insert_debate(&mut conn, "d3", "r2", &[("a", 71.0), ("c", 78.0)]);
+        insert_debate(&mut conn, "d4", "r2", &[("b", 74.0), ("d", 73.0)]);
+
+        let ds = draw_strength_by_speaks_of_teams((TID, i64::MAX), &mut conn);
+
+        // total speaks: a = 141, b = 149, c = 158, d = 145
+        assert_eq!(ds["a"], Decimal::from(149 + 158));
+        assert_eq!(ds["b"], Decimal::from(141 + 145));
+        assert_eq!(ds["c"], Decimal::from(145 + 141));
+        assert_eq!(ds["d"], Decimal::from(158 + 149));
+    }
+
+    #[test]
+    fn draw_strength_by_speaks_ignores_incomplete_rounds() {
+        let mut conn = test_conn();
+        insert_round(&mut conn, "r1", 1, true);
+        insert_round(&mut conn, "r2", 2, false);
+        for team in ["a", "b", "c"] {
+            insert_team(&mut conn, team);
+        }
+
+        insert_debate(&mut conn, "d1", "r1", &[("a", 70.0), ("b", 75.0)]);
+        insert_debate(&mut conn, "d2", "r2", &[("a", 71.0), ("c", 78.0)]);
+
+        let ds = draw_strength_by_speaks_of_teams((TID, i64::MAX), &mut conn);
+
+        assert_eq!(ds["a"], Decimal::from(75));
+        assert_eq!(ds["b"], Decimal::from(70));
+        // c only debated in the incomplete round
+        assert_eq!(ds["c"], Decimal::ZERO);
+    }
+
+    #[test]
+    fn draw_strength_by_speaks_as_of_earlier_round() {
+        let mut conn = test_conn();
+        insert_round(&mut conn, "r1", 1, true);
+        insert_round(&mut conn, "r2", 2, true);
+        for team in ["a", "b", "c"] {
+            insert_team(&mut conn, team);
+        }
+
+        insert_debate(&mut conn, "d1", "r1", &[("a", 70.0), ("b", 75.0)]);
+        insert_debate(&mut conn, "d2", "r2", &[("a", 71.0), ("c", 78.0)]);
+
+        let ds = draw_strength_by_speaks_of_teams((TID, 1), &mut conn);
+
+        assert_eq!(ds["a"], Decimal::from(75));
+        assert_eq!(ds["b"], Decimal::from(70));
+        assert_eq!(ds["c"], Decimal::ZERO);
+    }
+
+    #[test]
+    fn average_draw_strength_by_speaks_divides_by_opponents_faced() {
+        let mut conn = test_conn();
+        insert_round(&mut conn, "r1", 1, true);
+        insert_round(&mut conn, "r2", 2, true);
+        for team in ["a", "b", "c", "d"] {
+            insert_team(&mut conn, team);
+        }
+
+        insert_debate(&mut conn, "d1", "r1", &[("a", 70.0), ("b", 75.0)]);
+        insert_debate(&mut conn, "d2", "r1", &[("c", 80.0), ("d", 72.0)]);
+        // d has a bye in the second round
+        insert_debate(&mut conn, "d3", "r2", &[("a", 71.0), ("c", 78.0)]);
+
+        let ds = average_draw_strength_by_speaks_of_teams(
+            (TID, i64::MAX),
+            &mut conn,
+        );
+
+        // total speaks: a = 141, b = 75, c = 158, d = 72
+        assert_eq!(ds["a"], Decimal::from(75 + 158) / Decimal::from(2));
+        assert_eq!(ds["b"], Decimal::from(141));
+        assert_eq!(ds["c"], Decimal::from(72 + 141) / Decimal::from(2));
+        assert_eq!(ds["d"], Decimal::from(158));
+    }
+
+    #[test]
+    fn lowest_ds_speaks_pullups_use_draw_strength_by_speaks() {
+        use crate::schema::tournaments;
+        use crate::tournaments::config::UnrankableTeamMetric;
+        use crate::tournaments::create::insert_tournament;
+        use crate::tournaments::standings::compute::TeamStandings;
+
+        let mut conn = test_conn();
+        insert_tournament(TID, "DS Open", "DSO", "ds-open", "u", &mut conn)
+            .unwrap();
+        diesel::update(tournaments::table.filter(tournaments::id.eq(TID)))
+            .set((
+                tournaments::team_standings_metrics
+                    .eq("[\"draw_strength_by_speaks\",\
+                     \"avg_draw_strength_by_speaks\"]"),
+                tournaments::pullup_metrics.eq("[\"lowest_ds_speaks\"]"),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        insert_round(&mut conn, "r1", 1, true);
+        for team in ["a", "b"] {
+            insert_team(&mut conn, team);
+        }
+        insert_debate(&mut conn, "d1", "r1", &[("a", 70.0), ("b", 75.0)]);
+
+        let standings = TeamStandings::recompute(TID, &mut conn);
+
+        let ds = |team: &str| {
+            standings.pullup_metrics[&(
+                team.to_string(),
+                UnrankableTeamMetric::DrawStrengthBySpeaks,
+            )]
+        };
+        assert_eq!(ds("a"), Decimal::from(75));
+        assert_eq!(ds("b"), Decimal::from(70));
+    }
+}
@@ -7,12 +7,16 @@ pub mod ballots;
 pub mod draw_strength;
 pub mod n_times_specific_result;
 pub mod points;
+pub mod standardised;
 pub mod tss;
 
+/// Joins the completed preliminary rounds with a sequence number of at most
+/// `max_seq` (pass `i64::MAX` to include every completed round).
 #[diesel::dsl::auto_type]
-pub fn completed_preliminary_rounds() -> _ {
+pub fn completed_preliminary_rounds(max_seq: i64) -> _ {
     rounds::table.on(rounds::kind
         .eq("P")
         .and(rounds::completed.eq(true))
-        .and(rounds::draw_status.eq("released_full")))
+        .and(rounds::draw_status.eq("released_full"))
+        .and(rounds::seq.le(max_seq)))
 }
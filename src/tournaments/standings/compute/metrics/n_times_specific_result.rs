@@ -5,7 +5,7 @@ use diesel::prelude::*;
 use crate::schema::{agg_team_results_of_debate, debates, rounds, teams};
 
 pub fn times_team_achieved_p_points(
-    (p, tid): (u8, &str),
+    (p, tid, max_seq): (u8, &str, i64),
     conn: &mut impl diesel::connection::LoadConnection<
         Backend = diesel::sqlite::Sqlite,
     >,
@@ -19,7 +19,8 @@ pub fn times_team_achieved_p_points(
                         rounds::table.on(rounds::id
                             .eq(debates::round_id)
                             .and(rounds::kind.eq("P"))
-                            .and(rounds::completed.eq(true))),
+                            .and(rounds::completed.eq(true))
+                            .and(rounds::seq.le(max_seq))),
                     ),
                 )
                 .on(agg_team_results_of_debate::team_id.eq(teams::id).and(
@@ -8,7 +8,7 @@ use crate::{
 };
 
 pub fn points_of_team(
-    (tid,): (&str,),
+    (tid, max_seq): (&str, i64),
     conn: &mut impl diesel::connection::LoadConnection<
         Backend = diesel::sqlite::Sqlite,
     >,
@@ -22,6 +22,7 @@ pub fn points_of_team(
         .filter(rounds::tournament_id.eq(tid))
         .filter(rounds::kind.eq("P"))
         .filter(rounds::completed.eq(true))
+        .filter(rounds::seq.le(max_seq))
         .select((
             agg_team_results_of_debate::team_id,
             agg_team_results_of_debate::points,
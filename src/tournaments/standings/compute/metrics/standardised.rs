@@ -0,0 +1,237 @@
+//! Speaker scores which have been standardised per adjudicator. Some
+//! adjudicators consistently score higher (or lower) than others, which
+//! favours the speakers who happen to be judged by them; standardising each
+//! adjudicator's scores against the whole tournament removes this effect.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+
+use crate::schema::{
+    ballots, debates, rounds, speaker_scores_of_ballot, teams,
+};
+use crate::tournaments::Tournament;
+use crate::tournaments::standings::compute::metrics::completed_preliminary_rounds;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Standardisation {
+    /// Shifts each adjudicator's scores so that their mean matches the mean of
+    /// all scores.
+    MeanShift,
+    /// Converts each adjudicator's scores to z-scores, which are then mapped
+    /// back onto the mean and standard deviation of all scores.
+    ZScore,
+}
+
+impl Standardisation {
+    pub fn of_tournament(tournament: &Tournament) -> Self {
+        match tournament.speaks_standardisation.as_str() {
+            "z_score" => Standardisation::ZScore,
+            _ => Standardisation::MeanShift,
+        }
+    }
+}
+
+/// Returns the (population) mean and standard deviation.
+fn mean_and_std_dev(scores: impl Iterator<Item = f64> + Clone) -> (f64, f64) {
+    let n = scores.clone().count() as f64;
+    let mean = scores.clone().sum::<f64>() / n;
+    let variance = scores.map(|s| (s - mean).powi(2)).sum::<f64>() / n;
+    (mean, variance.sqrt())
+}
+
+/// Standardises the scores given by each adjudicator (the first element of
+/// each tuple) and then averages the standardised scores awarded to each
+/// speech (the second element).
+fn standardise<K: Hash + Eq + Clone>(
+    scores: &[(String, K, f64)],
+    method: Standardisation,
+) -> HashMap<K, f64> {
+    if scores.is_empty() {
+        return HashMap::new();
+    }
+
+    let (mean, std_dev) =
+        mean_and_std_dev(scores.iter().map(|(_, _, score)| *score));
+
+    let mut scores_of_judge: HashMap<&str, Vec<f64>> = HashMap::new();
+    for (judge, _, score) in scores {
+        scores_of_judge.entry(judge).or_default().push(*score);
+    }
+    let stats_of_judge: HashMap<&str, (f64, f64)> = scores_of_judge
+        .into_iter()
+        .map(|(judge, scores)| {
+            (judge, mean_and_std_dev(scores.iter().copied()))
+        })
+        .collect();
+
+    let mut standardised: HashMap<K, (f64, usize)> = HashMap::new();
+    for (judge, speech, score) in scores {
+        let (judge_mean, judge_std_dev) = stats_of_judge[judge.as_str()];
+        let value = match method {
+            // a z-score is meaningless if the adjudicator gave every speech
+            // the same score, so we fall back to shifting the mean
+            Standardisation::ZScore if judge_std_dev > 0.0 => {
+                (score - judge_mean) / judge_std_dev * std_dev + mean
+            }
+            Standardisation::ZScore | Standardisation::MeanShift => {
+                score - judge_mean + mean
+            }
+        };
+        let entry = standardised.entry(speech.clone()).or_default();
+        entry.0 += value;
+        entry.1 += 1;
+    }
+
+    standardised
+        .into_iter()
+        .map(|(speech, (total, n))| (speech, total / n as f64))
+        .collect()
+}
+
+/// A substantive speech, identified by `(debate_id, team_id, speaker_id,
+/// position)`.
+pub type Speech = (String, String, String, i64);
+
+/// Computes the standardised score of every substantive speech given in the
+/// completed preliminary rounds up to (and including) `max_seq`. Only the
+/// latest version of each adjudicator's ballot is used.
+pub fn standardised_speeches(
+    tournament: &Tournament,
+    max_seq: i64,
+    conn: &mut impl diesel::connection::LoadConnection<
+        Backend = diesel::sqlite::Sqlite,
+    >,
+) -> HashMap<Speech, f64> {
+    let rows = speaker_scores_of_ballot::table
+        .inner_join(
+            ballots::table
+                .on(ballots::id.eq(speaker_scores_of_ballot::ballot_id)),
+        )
+        .inner_join(debates::table.on(debates::id.eq(ballots::debate_id)))
+        .inner_join(completed_preliminary_rounds(max_seq))
+        .filter(rounds::id.eq(debates::round_id))
+        .filter(rounds::tournament_id.eq(&tournament.id))
+        .filter(
+            speaker_scores_of_ballot::speaker_position
+                .lt(tournament.substantive_speakers),
+        )
+        .select((
+            ballots::debate_id,
+            ballots::judge_id,
+            ballots::version,
+            speaker_scores_of_ballot::team_id,
+            speaker_scores_of_ballot::speaker_id,
+            speaker_scores_of_ballot::speaker_position,
+            speaker_scores_of_ballot::score,
+        ))
+        .load::<(String, String, i64, String, String, i64, Option<f32>)>(conn)
+        .unwrap();
+
+    let mut latest_version: HashMap<(&str, &str), i64> = HashMap::new();
+    for (debate, judge, version, ..) in &rows {
+        let latest = latest_version
+            .entry((debate.as_str(), judge.as_str()))
+            .or_insert(*version);
+        *latest = (*latest).max(*version);
+    }
+
+    let scores = rows
+        .iter()
+        .filter(|(debate, judge, version, ..)| {
+            latest_version[&(debate.as_str(), judge.as_str())] == *version
+        })
+        .filter_map(|(debate, judge, _, team, speaker, position, score)| {
+            score.map(|score| {
+                (
+                    judge.clone(),
+                    (debate.clone(), team.clone(), speaker.clone(), *position),
+                    score as f64,
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+
+    standardise(&scores, Standardisation::of_tournament(tournament))
+}
+
+fn to_decimal(value: f64) -> Decimal {
+    Decimal::from_f64_retain(value)
+        .unwrap_or_else(|| panic!("could not convert `{value}` to decimal"))
+        .round_dp(2)
+}
+
+/// The average (over the debates a team has taken part in) of the team's total
+/// standardised speaker score.
+pub fn standardised_atss(
+    (tournament, max_seq): (&Tournament, i64),
+    conn: &mut impl diesel::connection::LoadConnection<
+        Backend = diesel::sqlite::Sqlite,
+    >,
+) -> HashMap<String, Decimal> {
+    let mut total_of_team_in_debate: HashMap<(String, String), f64> =
+        HashMap::new();
+    for ((debate, team, _, _), score) in
+        standardised_speeches(tournament, max_seq, conn)
+    {
+        *total_of_team_in_debate.entry((team, debate)).or_default() += score;
+    }
+
+    let mut totals_of_team: HashMap<String, Vec<f64>> = HashMap::new();
+    for ((team, _), total) in total_of_team_in_debate {
+        totals_of_team.entry(team).or_default().push(total);
+    }
+
+    teams::table
+        .filter(teams::tournament_id.eq(&tournament.id))
+        .select(teams::id)
+        .load::<String>(conn)
+        .unwrap()
+        .into_iter()
+        .map(|team| {
+            let value = match totals_of_team.get(&team) {
+                Some(totals) => {
+                    to_decimal(totals.iter().sum::<f64>() / totals.len() as f64)
+                }
+                None => Decimal::ZERO,
+            };
+            (team, value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Standardisation, standardise};
+
+    fn scores() -> Vec<(String, &'static str, f64)> {
+        // judge "h" scores everyone 2 points higher than judge "l"
+        vec![
+            ("h".to_string(), "a", 78.0),
+            ("h".to_string(), "b", 76.0),
+            ("l".to_string(), "c", 76.0),
+            ("l".to_string(), "d", 74.0),
+        ]
+    }
+
+    #[test]
+    fn test_mean_shift_removes_judge_bias() {
+        let standardised = standardise(&scores(), Standardisation::MeanShift);
+        assert_eq!(standardised["a"], standardised["c"]);
+        assert_eq!(standardised["b"], standardised["d"]);
+        assert_eq!(standardised["a"], 77.0);
+    }
+
+    #[test]
+    fn test_z_score_falls_back_when_judge_has_no_spread() {
+        let mut scores = scores();
+        scores.push(("f".to_string(), "e", 70.0));
+        scores.push(("f".to_string(), "f", 70.0));
+        let standardised = standardise(&scores, Standardisation::ZScore);
+        assert_eq!(standardised["a"], standardised["c"]);
+        // the mean of all scores is 74
+        assert_eq!(standardised["e"], 74.0);
+    }
+}
@@ -11,7 +11,7 @@ use crate::{
 use diesel::{dsl, prelude::*};
 
 pub fn total_speaker_score_of_team(
-    (tid,): (&str,),
+    (tid, max_seq): (&str, i64),
     conn: &mut impl diesel::connection::LoadConnection<
         Backend = diesel::sqlite::Sqlite,
     >,
@@ -19,7 +19,7 @@ pub fn total_speaker_score_of_team(
     teams::table
         .filter(teams::tournament_id.eq(tid))
         // for all completed preliminary rounds
-        .inner_join(completed_preliminary_rounds())
+        .inner_join(completed_preliminary_rounds(max_seq))
         .inner_join(debates::table.on(debates::round_id.eq(rounds::id)))
         .inner_join(
             agg_speaker_results_of_debate::table.on(
@@ -12,17 +12,29 @@ use crate::tournaments::Tournament;
 use crate::tournaments::config::{
     PullupMetric, RankableTeamMetric, UnrankableTeamMetric,
 };
+use crate::tournaments::participants::TournamentParticipants;
+use crate::tournaments::standings::compute::adjustments::{
+    apply_adjustments, latest_completed_seq, team_adjustments,
+};
 use crate::tournaments::standings::compute::metrics::atss;
 use crate::tournaments::standings::compute::metrics::ballots::ballot_points_of_team;
-use crate::tournaments::standings::compute::metrics::draw_strength::draw_strength_of_teams;
+use crate::tournaments::standings::compute::metrics::draw_strength::{
+    average_draw_strength_by_speaks_of_teams, draw_strength_by_speaks_of_teams,
+    draw_strength_of_teams,
+};
 use crate::tournaments::standings::compute::metrics::n_times_specific_result::times_team_achieved_p_points;
 use crate::tournaments::standings::compute::metrics::points::points_of_team;
+use crate::tournaments::standings::compute::metrics::standardised::standardised_atss;
 use crate::tournaments::standings::compute::metrics::tss::total_speaker_score_of_team;
-use crate::tournaments::teams::Team;
+use crate::tournaments::teams::{Team, TeamNameKind};
 use rust_decimal::Decimal;
 
+pub mod adjustments;
+pub mod breakdown;
 pub mod history;
 pub mod metrics;
+pub mod replies;
+pub mod speakers;
 
 #[derive(Serialize, Deserialize)]
 #[serde(untagged)]
@@ -45,6 +57,71 @@ pub struct TeamStandings {
     /// Stores the actual rank of each team (i.e. the position this team is at:
     /// the number of teams which outperformed this team, plus one).
     pub rank_of_team: HashMap<String, i64>,
+    /// Explains why each team is ranked where it is relative to the team
+    /// directly above it in rank order. Indexed by the id of the lower team;
+    /// the first team has no entry.
+    pub tie_break_with_team_above: HashMap<String, TieBreak>,
+}
+
+/// What separates two teams which are adjacent in rank order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TieBreak {
+    pub above: String,
+    pub below: String,
+    /// The first metric on which the teams differ, and by how much the team
+    /// above leads on it. `None` if the teams are tied on every metric.
+    pub separated_by: Option<(RankableTeamMetric, Decimal)>,
+}
+
+impl TieBreak {
+    pub fn explain(&self, name_of_team_above: &str) -> String {
+        match &self.separated_by {
+            Some((metric, margin)) => format!(
+                "Ranked below {name_of_team_above} on {metric} (by {})",
+                margin.normalize()
+            ),
+            None => {
+                format!("Tied with {name_of_team_above} on every metric")
+            }
+        }
+    }
+}
+
+/// Works out which metric separates each pair of adjacent teams.
+fn tie_breaks(
+    teams_in_rank_order: &[Vec<Team>],
+    ranked_metrics_of_team: &HashMap<
+        String,
+        Vec<(RankableTeamMetric, Decimal)>,
+    >,
+) -> HashMap<String, TieBreak> {
+    teams_in_rank_order
+        .iter()
+        .flatten()
+        .tuple_windows()
+        .map(|(above, below)| {
+            let separated_by = ranked_metrics_of_team
+                .get(&above.id)
+                .into_iter()
+                .flatten()
+                .zip(
+                    ranked_metrics_of_team.get(&below.id).into_iter().flatten(),
+                )
+                .sorted_by_key(|((metric, _), _)| {
+                    RankableTeamMetric::sort_order_for_comp(metric)
+                })
+                .find(|((_, a), (_, b))| a != b)
+                .map(|((metric, a), (_, b))| (*metric, (a - b).round_dp(2)));
+            (
+                below.id.clone(),
+                TieBreak {
+                    above: above.id.clone(),
+                    below: below.id.clone(),
+                    separated_by,
+                },
+            )
+        })
+        .collect()
 }
 
 impl TeamStandings {
@@ -66,9 +143,40 @@ impl TeamStandings {
             .unwrap()
     }
 
+    /// Describes why the given team is ranked below the team directly above it,
+    /// if there is one. The team above is referred to by its `kind` of name.
+    pub fn explain_tie_break(
+        &self,
+        team_id: &str,
+        participants: &TournamentParticipants,
+        kind: TeamNameKind,
+    ) -> Option<String> {
+        let tie_break = self.tie_break_with_team_above.get(team_id)?;
+        let above = self
+            .teams_in_rank_order
+            .iter()
+            .flatten()
+            .find(|team| team.id == tie_break.above)?;
+        Some(tie_break.explain(&participants.display_name_of_team(above, kind)))
+    }
+
+    /// Computes the current standings, i.e. those after the latest completed
+    /// round.
     pub fn recompute(
         tid: &str,
         conn: &mut impl LoadConnection<Backend = Sqlite>,
+    ) -> Self {
+        let max_seq = latest_completed_seq(tid, conn);
+        Self::recompute_up_to(tid, max_seq, conn)
+    }
+
+    /// Computes the standings as they stood after the round with sequence
+    /// number `max_seq` (i.e. only using the results of completed rounds up to
+    /// and including that one).
+    pub fn recompute_up_to(
+        tid: &str,
+        max_seq: i64,
+        conn: &mut impl LoadConnection<Backend = Sqlite>,
     ) -> Self {
         let tournament = tournaments::table
             .filter(tournaments::id.eq(tid))
@@ -93,10 +201,8 @@ impl TeamStandings {
                     | PullupMetric::Random => false,
                     // these always needs to be manually computed
                     PullupMetric::FewerPreviousPullups
-                    | PullupMetric::LowestDsRank => true,
-                    PullupMetric::LowestDsSpeaks => metrics.iter().any(|m| {
-                        matches!(m, RankableTeamMetric::DrawStrengthBySpeaks)
-                    }),
+                    | PullupMetric::LowestDsRank
+                    | PullupMetric::LowestDsSpeaks => true,
                 })
         };
 
@@ -108,18 +214,20 @@ impl TeamStandings {
         for metric in &metrics {
             let val2merge: HashMap<String, rust_decimal::Decimal> = match metric
             {
-                RankableTeamMetric::Wins => points_of_team((tid,), conn)
-                    .into_iter()
-                    .map(|(k, v)| (k, rust_decimal::Decimal::from(v)))
-                    .collect(),
+                RankableTeamMetric::Wins => {
+                    points_of_team((tid, max_seq), conn)
+                        .into_iter()
+                        .map(|(k, v)| (k, rust_decimal::Decimal::from(v)))
+                        .collect()
+                }
                 RankableTeamMetric::NTimesAchieved(t) => {
-                    times_team_achieved_p_points((*t, tid), conn)
+                    times_team_achieved_p_points((*t, tid, max_seq), conn)
                         .into_iter()
                         .map(|(k, v)| (k, rust_decimal::Decimal::from(v)))
                         .collect()
                 }
                 RankableTeamMetric::TotalSpeakerScore => {
-                    total_speaker_score_of_team((tid,), conn)
+                    total_speaker_score_of_team((tid, max_seq), conn)
                 }
                 RankableTeamMetric::DrawStrengthByWins => {
                     // todo: store metrics separately
@@ -141,7 +249,7 @@ impl TeamStandings {
                             )
                         })
                         .collect();
-                    draw_strength_of_teams((tid, team_points), conn)
+                    draw_strength_of_teams((tid, max_seq, team_points), conn)
                         .into_iter()
                         .map(|(k, v)| (k, rust_decimal::Decimal::from(v)))
                         .collect()
@@ -165,15 +273,26 @@ impl TeamStandings {
                             )
                         })
                         .collect();
-                    atss::atss((tid, tss), conn)
+                    atss::atss((tid, max_seq, tss), conn)
                 }
                 RankableTeamMetric::Ballots => {
-                    ballot_points_of_team((tid,), conn)
+                    ballot_points_of_team((tid, max_seq), conn)
                         .into_iter()
                         .map(|(k, v)| (k, rust_decimal::Decimal::from(v)))
                         .collect()
                 }
-                RankableTeamMetric::DrawStrengthBySpeaks => todo!(),
+                RankableTeamMetric::DrawStrengthBySpeaks => {
+                    draw_strength_by_speaks_of_teams((tid, max_seq), conn)
+                }
+                RankableTeamMetric::AverageDrawStrengthBySpeaks => {
+                    average_draw_strength_by_speaks_of_teams(
+                        (tid, max_seq),
+                        conn,
+                    )
+                }
+                RankableTeamMetric::StandardisedAverageTotalSpeakerScore => {
+                    standardised_atss((&tournament, max_seq), conn)
+                }
             };
 
             for (k, v) in val2merge {
@@ -188,6 +307,14 @@ impl TeamStandings {
             }
         }
 
+        // note: adjustments are applied after all the metrics are computed, so
+        // e.g. a point deduction does not affect the draw strength of the
+        // penalised team's opponents
+        apply_adjustments(
+            &mut ranked_metrics_of_team,
+            &team_adjustments(tid, max_seq, conn),
+        );
+
         let mut teams = teams::table
             .filter(teams::tournament_id.eq(tid))
             .load::<Team>(conn)
@@ -217,14 +344,26 @@ impl TeamStandings {
             .collect::<Vec<_>>();
 
         let pullup_metrics = {
-            let map = HashMap::new();
+            let mut map = HashMap::new();
 
             for metric in pullup_metrics_to_compute_and_save {
                 match metric {
                     PullupMetric::FewerPreviousPullups => todo!(),
                     PullupMetric::LowestDsRank => todo!(),
-                    // todo: need to first implement ATSS metric
-                    PullupMetric::LowestDsSpeaks => todo!(),
+                    PullupMetric::LowestDsSpeaks => {
+                        for (team, value) in draw_strength_by_speaks_of_teams(
+                            (tid, max_seq),
+                            conn,
+                        ) {
+                            map.insert(
+                                (
+                                    team,
+                                    UnrankableTeamMetric::DrawStrengthBySpeaks,
+                                ),
+                                value,
+                            );
+                        }
+                    }
                     _ => unreachable!(),
                 };
             }
@@ -259,12 +398,16 @@ impl TeamStandings {
             map
         };
 
+        let tie_break_with_team_above =
+            tie_breaks(&teams_grouped_by_rank, &ranked_metrics_of_team);
+
         Self {
             metrics,
             ranked_metrics_of_team,
             teams_in_rank_order: teams_grouped_by_rank,
             pullup_metrics,
             rank_of_team,
+            tie_break_with_team_above,
         }
     }
 
@@ -302,7 +445,7 @@ impl TeamStandings {
 
         let grouped =
             rankings.clone().into_iter().chunk_by(|(_team, rank)| *rank);
-        let ranked = grouped
+        let ranked: Vec<Vec<Team>> = grouped
             .into_iter()
             .map(|(_rank, team)| {
                 team.into_iter()
@@ -360,9 +503,12 @@ impl TeamStandings {
             })
         });
 
+        let tie_break_with_team_above = tie_breaks(&ranked, &metrics_of_team);
+
         Self {
             metrics,
             ranked_metrics_of_team: metrics_of_team,
+            tie_break_with_team_above,
             teams_in_rank_order: ranked,
             pullup_metrics: non_ranking_metrics,
             rank_of_team: rankings.into_iter().collect::<HashMap<_, _>>(),
@@ -481,3 +627,153 @@ pub fn refresh_saved_team_standings(
 ) -> Result<(), diesel::result::Error> {
     TeamStandings::recompute(tid, conn).save(tid, conn)
 }
+
+/// Restricts a ranking (in which tied entries share a list) to the entries for
+/// which `include` holds, and re-ranks them. This is used for the tabs of
+/// speaker and team categories, where e.g. the best ESL speaker is ranked
+/// first even if they are tenth overall.
+///
+/// Returns each group of tied entries alongside their rank within the subset.
+pub fn rank_subset<T>(
+    ranked: &[Vec<T>],
+    include: impl Fn(&T) -> bool,
+) -> Vec<(i64, Vec<&T>)> {
+    let mut subset = Vec::new();
+    let mut rank = 1;
+    for group in ranked {
+        let included: Vec<&T> = group.iter().filter(|t| include(t)).collect();
+        if included.is_empty() {
+            continue;
+        }
+        let n = included.len() as i64;
+        subset.push((rank, included));
+        rank += n;
+    }
+    subset
+}
+
+#[cfg(test)]
+#[test]
+fn test_rank_subset_reranks_included_entries() {
+    let ranked = vec![vec![1, 2], vec![3], vec![4, 5, 6], vec![7]];
+    let odd = rank_subset(&ranked, |n| n % 2 == 1);
+    assert_eq!(
+        odd,
+        vec![(1, vec![&1]), (2, vec![&3]), (3, vec![&5]), (4, vec![&7])]
+    );
+
+    let some = rank_subset(&ranked, |n| [1, 2, 4, 5, 7].contains(n));
+    assert_eq!(
+        some,
+        vec![(1, vec![&1, &2]), (3, vec![&4, &5]), (5, vec![&7])]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_tie_breaks_name_the_first_differing_metric() {
+    let team = |id: &str| Team {
+        id: id.to_string(),
+        tournament_id: "t".to_string(),
+        name: id.to_string(),
+        institution_id: None,
+        number: 0,
+        code_name: None,
+        emoji: None,
+        use_institution_prefix: false,
+    };
+    let metrics = |wins: i64, speaks: i64| {
+        vec![
+            (RankableTeamMetric::Wins, Decimal::from(wins)),
+            (RankableTeamMetric::TotalSpeakerScore, Decimal::from(speaks)),
+        ]
+    };
+    let ranked = vec![vec![team("a")], vec![team("b"), team("c")]];
+    let metrics_of_team = HashMap::from([
+        ("a".to_string(), metrics(3, 150)),
+        ("b".to_string(), metrics(3, 148)),
+        ("c".to_string(), metrics(3, 148)),
+    ]);
+
+    let breaks = tie_breaks(&ranked, &metrics_of_team);
+    assert!(!breaks.contains_key("a"));
+    assert_eq!(
+        breaks["b"].separated_by,
+        Some((RankableTeamMetric::TotalSpeakerScore, Decimal::from(2)))
+    );
+    assert_eq!(breaks["c"].above, "b");
+    assert_eq!(breaks["c"].separated_by, None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_adjustments_for_future_rounds_wait_until_the_round_is_completed() {
+    use diesel::sqlite::SqliteConnection;
+    use diesel_migrations::MigrationHarness;
+
+    use crate::schema::{rounds, standings_adjustments};
+
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+    conn.run_pending_migrations(crate::MIGRATIONS).unwrap();
+    crate::tournaments::create::insert_tournament(
+        "t", "Test", "T", "test", "user", &mut conn,
+    )
+    .unwrap();
+    diesel::update(tournaments::table.filter(tournaments::id.eq("t")))
+        .set(tournaments::team_standings_metrics.eq(r#"["wins"]"#))
+        .execute(&mut conn)
+        .unwrap();
+    for (id, seq, completed) in [("r1", 1, true), ("r2", 2, false)] {
+        diesel::insert_into(rounds::table)
+            .values((
+                rounds::id.eq(id),
+                rounds::tournament_id.eq("t"),
+                rounds::seq.eq(seq),
+                rounds::name.eq(id),
+                rounds::kind.eq("P"),
+                rounds::completed.eq(completed),
+                rounds::draw_status.eq("released_full"),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+    }
+    diesel::insert_into(teams::table)
+        .values((
+            teams::id.eq("a"),
+            teams::tournament_id.eq("t"),
+            teams::name.eq("a"),
+            teams::number.eq(1),
+        ))
+        .execute(&mut conn)
+        .unwrap();
+    diesel::insert_into(standings_adjustments::table)
+        .values((
+            standings_adjustments::id.eq("adjustment"),
+            standings_adjustments::tournament_id.eq("t"),
+            standings_adjustments::team_id.eq("a"),
+            standings_adjustments::metric
+                .eq(serde_json::to_string(&RankableTeamMetric::Wins).unwrap()),
+            standings_adjustments::delta.eq(1.0),
+            standings_adjustments::reason.eq("test"),
+            standings_adjustments::author_id.eq("user"),
+            standings_adjustments::created_at
+                .eq(chrono::Utc::now().naive_utc()),
+            standings_adjustments::from_seq.eq(2),
+        ))
+        .execute(&mut conn)
+        .unwrap();
+
+    let wins = |standings: TeamStandings| {
+        standings.get_ranked_metric_of_team("a", RankableTeamMetric::Wins)
+    };
+    assert_eq!(
+        wins(TeamStandings::recompute("t", &mut conn)),
+        Decimal::ZERO
+    );
+
+    diesel::update(rounds::table.filter(rounds::id.eq("r2")))
+        .set(rounds::completed.eq(true))
+        .execute(&mut conn)
+        .unwrap();
+    assert_eq!(wins(TeamStandings::recompute("t", &mut conn)), Decimal::ONE);
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+use diesel::{connection::LoadConnection, sqlite::Sqlite};
+use itertools::Itertools;
+use rust_decimal::Decimal;
+
+use crate::schema::{agg_speaker_results_of_debate, debates, rounds, speakers};
+use crate::tournaments::Tournament;
+use crate::tournaments::participants::Speaker;
+use crate::tournaments::standings::compute::metrics::completed_preliminary_rounds;
+use crate::tournaments::standings::compute::speakers::team_of_speaker;
+
+/// Ranks speakers by their average reply speech score (over the completed
+/// preliminary rounds). Unlike [`super::speakers::SpeakerStandings`], these are
+/// cheap to compute and so are not saved.
+pub struct ReplyStandings {
+    /// The average reply score of each ranked speaker, along with the number
+    /// of replies they gave.
+    pub replies_of_speaker: HashMap<String, (Decimal, usize)>,
+    /// Speakers who are tied occupy the same list.
+    pub speakers_in_rank_order: Vec<Vec<Speaker>>,
+    pub rank_of_speaker: HashMap<String, i64>,
+    /// The name of the team each speaker belongs to.
+    pub team_of_speaker: HashMap<String, String>,
+}
+
+/// Ranks the speakers who gave at least `min_replies` replies by their average
+/// reply score. Speakers with the same average share a rank.
+fn rank_reply_speakers(
+    speakers: Vec<Speaker>,
+    scores_of_speaker: &HashMap<String, Vec<f32>>,
+    min_replies: usize,
+) -> (
+    HashMap<String, (Decimal, usize)>,
+    Vec<Vec<Speaker>>,
+    HashMap<String, i64>,
+) {
+    let replies_of_speaker: HashMap<String, (Decimal, usize)> =
+        scores_of_speaker
+            .iter()
+            .filter(|(_, scores)| {
+                !scores.is_empty() && scores.len() >= min_replies
+            })
+            .map(|(speaker, scores)| {
+                let avg = scores.iter().map(|s| *s as f64).sum::<f64>()
+                    / scores.len() as f64;
+                (
+                    speaker.clone(),
+                    (
+                        Decimal::from_f64_retain(avg).unwrap().round_dp(2),
+                        scores.len(),
+                    ),
+                )
+            })
+            .collect();
+
+    let key = |speaker: &Speaker| {
+        std::cmp::Reverse(replies_of_speaker[&speaker.id].0)
+    };
+    let speakers_in_rank_order = speakers
+        .into_iter()
+        .filter(|speaker| replies_of_speaker.contains_key(&speaker.id))
+        .sorted_by_cached_key(|speaker| (key(speaker), speaker.name.clone()))
+        .chunk_by(key)
+        .into_iter()
+        .map(|(_key, chunk)| chunk.collect_vec())
+        .collect_vec();
+
+    let mut rank_of_speaker = HashMap::new();
+    let mut n = 1;
+    for speakers_of_rank in &speakers_in_rank_order {
+        for speaker in speakers_of_rank {
+            rank_of_speaker.insert(speaker.id.clone(), n as i64);
+        }
+        n += speakers_of_rank.len();
+    }
+
+    (replies_of_speaker, speakers_in_rank_order, rank_of_speaker)
+}
+
+impl ReplyStandings {
+    pub fn compute(
+        tournament: &Tournament,
+        conn: &mut impl LoadConnection<Backend = Sqlite>,
+    ) -> Self {
+        let mut scores_of_speaker: HashMap<String, Vec<f32>> = HashMap::new();
+        if tournament.reply_speakers {
+            // reply speeches are recorded after the substantive speeches
+            for (speaker_id, score) in agg_speaker_results_of_debate::table
+                .filter(
+                    agg_speaker_results_of_debate::tournament_id
+                        .eq(&tournament.id),
+                )
+                .filter(
+                    agg_speaker_results_of_debate::position
+                        .eq(tournament.substantive_speakers),
+                )
+                .inner_join(debates::table.on(
+                    debates::id.eq(agg_speaker_results_of_debate::debate_id),
+                ))
+                .inner_join(completed_preliminary_rounds(i64::MAX))
+                .filter(rounds::id.eq(debates::round_id))
+                .select((
+                    agg_speaker_results_of_debate::speaker_id,
+                    agg_speaker_results_of_debate::score,
+                ))
+                .load::<(String, Option<f32>)>(conn)
+                .unwrap()
+            {
+                if let Some(score) = score {
+                    scores_of_speaker
+                        .entry(speaker_id)
+                        .or_default()
+                        .push(score);
+                }
+            }
+        }
+
+        let speakers = speakers::table
+            .filter(speakers::tournament_id.eq(&tournament.id))
+            .load::<Speaker>(conn)
+            .unwrap();
+
+        let (replies_of_speaker, speakers_in_rank_order, rank_of_speaker) =
+            rank_reply_speakers(
+                speakers,
+                &scores_of_speaker,
+                tournament.reply_tab_min_replies as usize,
+            );
+
+        Self {
+            replies_of_speaker,
+            speakers_in_rank_order,
+            rank_of_speaker,
+            team_of_speaker: team_of_speaker(&tournament.id, conn),
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_reply_tab_requires_minimum_replies() {
+    let speaker = |id: &str| Speaker {
+        id: id.to_string(),
+        tournament_id: "t".to_string(),
+        name: id.to_string(),
+        email: String::new(),
+        private_url: id.to_string(),
+    };
+    let scores: HashMap<String, Vec<f32>> = HashMap::from([
+        ("a".to_string(), vec![38.0, 39.0]),
+        ("b".to_string(), vec![40.0]),
+        ("c".to_string(), vec![37.0, 40.0]),
+        ("d".to_string(), vec![36.0, 36.0]),
+    ]);
+
+    let (replies, ranked, rank_of_speaker) = rank_reply_speakers(
+        vec![speaker("a"), speaker("b"), speaker("c"), speaker("d")],
+        &scores,
+        2,
+    );
+
+    // b only gave a single reply
+    assert!(!replies.contains_key("b"));
+    assert_eq!(ranked.len(), 2);
+    assert_eq!(rank_of_speaker["a"], 1);
+    assert_eq!(rank_of_speaker["c"], 1);
+    assert_eq!(rank_of_speaker["d"], 3);
+    assert_eq!(replies["a"], (Decimal::new(385, 1), 2));
+}
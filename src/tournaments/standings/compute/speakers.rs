@@ -0,0 +1,581 @@
+use std::collections::{HashMap, HashSet};
+
+use diesel::prelude::*;
+use diesel::{connection::LoadConnection, sqlite::Sqlite};
+use itertools::Itertools;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use uuid::Uuid;
+
+use crate::schema::{
+    agg_speaker_results_of_debate, debates, institutions, rounds,
+    speaker_metrics, speaker_standings, speakers, speakers_of_team, teams,
+    tournaments,
+};
+use crate::tournaments::Tournament;
+use crate::tournaments::config::SpeakerMetric;
+use crate::tournaments::participants::Speaker;
+use crate::tournaments::standings::compute::adjustments::{
+    apply_adjustments, latest_completed_seq, speaker_adjustments,
+};
+use crate::tournaments::standings::compute::metrics::completed_preliminary_rounds;
+use crate::tournaments::standings::compute::metrics::standardised::standardised_speeches;
+use crate::tournaments::teams::Team;
+
+/// The speaker counterpart to [`super::TeamStandings`].
+pub struct SpeakerStandings {
+    pub metrics: Vec<SpeakerMetric>,
+    pub metrics_of_speaker: HashMap<String, Vec<(SpeakerMetric, Decimal)>>,
+    /// Stores the speakers in rank order. Speakers who are tied occupy the
+    /// same list.
+    pub speakers_in_rank_order: Vec<Vec<Speaker>>,
+    pub rank_of_speaker: HashMap<String, i64>,
+    /// The name of the team each speaker belongs to.
+    pub team_of_speaker: HashMap<String, String>,
+}
+
+/// Computes the value of `metric` for the given (non-empty) set of scores.
+fn compute_metric(metric: SpeakerMetric, scores: &[f32]) -> Decimal {
+    debug_assert!(!scores.is_empty());
+
+    let n = scores.len() as f64;
+    let total = scores.iter().map(|s| *s as f64).sum::<f64>();
+
+    let value = match metric {
+        SpeakerMetric::Total => total,
+        // the caller supplies the standardised scores for this metric
+        SpeakerMetric::Avg | SpeakerMetric::StandardisedAvg => total / n,
+        SpeakerMetric::StdDev => {
+            let mean = total / n;
+            (scores
+                .iter()
+                .map(|s| (*s as f64 - mean).powi(2))
+                .sum::<f64>()
+                / n)
+                .sqrt()
+        }
+    };
+
+    Decimal::from_f64_retain(value)
+        .unwrap_or_else(|| panic!("could not convert `{value}` to decimal"))
+        .round_dp(2)
+}
+
+/// The key used to sort speakers (in descending order).
+fn sort_key(metrics: &[(SpeakerMetric, Decimal)]) -> Vec<Decimal> {
+    metrics
+        .iter()
+        .map(|(metric, value)| match metric {
+            // a lower standard deviation is better
+            SpeakerMetric::StdDev => -*value,
+            SpeakerMetric::Avg
+            | SpeakerMetric::Total
+            | SpeakerMetric::StandardisedAvg => *value,
+        })
+        .collect()
+}
+
+/// Ranks speakers, such that tied speakers share a rank (e.g. two speakers on
+/// =1 are followed by a speaker on 3).
+fn rank_speakers(
+    mut speakers: Vec<Speaker>,
+    metrics_of_speaker: &HashMap<String, Vec<(SpeakerMetric, Decimal)>>,
+) -> (Vec<Vec<Speaker>>, HashMap<String, i64>) {
+    let key = |speaker: &Speaker| {
+        std::cmp::Reverse(sort_key(&metrics_of_speaker[&speaker.id]))
+    };
+    speakers.sort_by_cached_key(|speaker| (key(speaker), speaker.name.clone()));
+
+    let grouped = speakers
+        .into_iter()
+        .chunk_by(key)
+        .into_iter()
+        .map(|(_key, chunk)| chunk.collect_vec())
+        .collect_vec();
+
+    let mut rank_of_speaker = HashMap::new();
+    let mut n = 1;
+    for speakers_of_rank in &grouped {
+        for speaker in speakers_of_rank {
+            rank_of_speaker.insert(speaker.id.clone(), n as i64);
+        }
+        n += speakers_of_rank.len();
+    }
+
+    (grouped, rank_of_speaker)
+}
+
+pub(super) fn team_of_speaker(
+    tid: &str,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> HashMap<String, String> {
+    let institution_codes: HashMap<String, String> = institutions::table
+        .filter(institutions::tournament_id.eq(tid))
+        .select((institutions::id, institutions::code))
+        .load::<(String, String)>(conn)
+        .unwrap()
+        .into_iter()
+        .collect();
+
+    speakers_of_team::table
+        .inner_join(teams::table.on(teams::id.eq(speakers_of_team::team_id)))
+        .filter(teams::tournament_id.eq(tid))
+        .select((speakers_of_team::speaker_id, teams::all_columns))
+        .load::<(String, Team)>(conn)
+        .unwrap()
+        .into_iter()
+        .map(|(speaker_id, team)| {
+            let code = team
+                .institution_id
+                .as_ref()
+                .and_then(|inst| institution_codes.get(inst))
+                .map(String::as_str);
+            (speaker_id, team.full_name(code))
+        })
+        .collect()
+}
+
+impl SpeakerStandings {
+    pub fn recompute(
+        tid: &str,
+        conn: &mut impl LoadConnection<Backend = Sqlite>,
+    ) -> Self {
+        let max_seq = latest_completed_seq(tid, conn);
+        Self::recompute_up_to(tid, max_seq, conn)
+    }
+
+    /// Computes the standings as they stood after the round with sequence
+    /// number `max_seq`.
+    pub fn recompute_up_to(
+        tid: &str,
+        max_seq: i64,
+        conn: &mut impl LoadConnection<Backend = Sqlite>,
+    ) -> Self {
+        let tournament = tournaments::table
+            .filter(tournaments::id.eq(tid))
+            .first::<Tournament>(conn)
+            .unwrap();
+        let metrics = tournament.speaker_metrics();
+
+        // concurrent rounds share a seq, and each speaker can only speak in
+        // one of them
+        let n_completed_rounds = rounds::table
+            .filter(rounds::tournament_id.eq(tid))
+            .filter(rounds::kind.eq("P"))
+            .filter(rounds::completed.eq(true))
+            .filter(rounds::draw_status.eq("released_full"))
+            .filter(rounds::seq.le(max_seq))
+            .select(diesel::dsl::count_distinct(rounds::seq))
+            .get_result::<i64>(conn)
+            .unwrap();
+
+        // (speaker_id, round seq, score, debate_id, team_id, position) for
+        // every substantive speech which counts towards the speaker tab
+        let speeches = agg_speaker_results_of_debate::table
+            .filter(agg_speaker_results_of_debate::tournament_id.eq(tid))
+            .filter(
+                agg_speaker_results_of_debate::counts_for_speaker_tab.eq(true),
+            )
+            .filter(
+                agg_speaker_results_of_debate::position
+                    .lt(tournament.substantive_speakers),
+            )
+            .inner_join(
+                debates::table
+                    .on(debates::id
+                        .eq(agg_speaker_results_of_debate::debate_id)),
+            )
+            .inner_join(completed_preliminary_rounds(max_seq))
+            .filter(rounds::id.eq(debates::round_id))
+            .select((
+                agg_speaker_results_of_debate::speaker_id,
+                rounds::seq,
+                agg_speaker_results_of_debate::score,
+                agg_speaker_results_of_debate::debate_id,
+                agg_speaker_results_of_debate::team_id,
+                agg_speaker_results_of_debate::position,
+            ))
+            .load::<(String, i64, Option<f32>, String, String, i64)>(conn)
+            .unwrap();
+
+        let standardised = if metrics.contains(&SpeakerMetric::StandardisedAvg)
+        {
+            standardised_speeches(&tournament, max_seq, conn)
+        } else {
+            HashMap::new()
+        };
+
+        let mut scores_of_speaker: HashMap<String, Vec<f32>> = HashMap::new();
+        let mut standardised_scores_of_speaker: HashMap<String, Vec<f32>> =
+            HashMap::new();
+        let mut seqs_of_speaker: HashMap<String, HashSet<i64>> = HashMap::new();
+        for (speaker_id, seq, score, debate_id, team_id, position) in speeches {
+            if let Some(score) = score {
+                scores_of_speaker
+                    .entry(speaker_id.clone())
+                    .or_default()
+                    .push(score);
+            }
+            if let Some(score) = standardised.get(&(
+                debate_id,
+                team_id,
+                speaker_id.clone(),
+                position,
+            )) {
+                standardised_scores_of_speaker
+                    .entry(speaker_id.clone())
+                    .or_default()
+                    .push(*score as f32);
+            }
+            seqs_of_speaker.entry(speaker_id).or_default().insert(seq);
+        }
+
+        // Speakers who have missed more than this many rounds are omitted
+        // from the tab. A negative value means that nobody is omitted.
+        let max_missed = tournament
+            .exclude_from_speaker_standings_after
+            .filter(|n| *n >= 0);
+
+        let speakers = speakers::table
+            .filter(speakers::tournament_id.eq(tid))
+            .load::<Speaker>(conn)
+            .unwrap()
+            .into_iter()
+            .filter(|speaker| scores_of_speaker.contains_key(&speaker.id))
+            .filter(|speaker| match max_missed {
+                Some(max_missed) => {
+                    let attended = seqs_of_speaker[&speaker.id].len() as i64;
+                    n_completed_rounds - attended <= max_missed
+                }
+                None => true,
+            })
+            .collect_vec();
+
+        let mut metrics_of_speaker: HashMap<_, _> = speakers
+            .iter()
+            .map(|speaker| {
+                let scores = &scores_of_speaker[&speaker.id];
+                (
+                    speaker.id.clone(),
+                    metrics
+                        .iter()
+                        .map(|metric| match metric {
+                            SpeakerMetric::StandardisedAvg => (
+                                *metric,
+                                standardised_scores_of_speaker
+                                    .get(&speaker.id)
+                                    .map(|scores| {
+                                        compute_metric(*metric, scores)
+                                    })
+                                    .unwrap_or_default(),
+                            ),
+                            _ => (*metric, compute_metric(*metric, scores)),
+                        })
+                        .collect_vec(),
+                )
+            })
+            .collect();
+
+        apply_adjustments(
+            &mut metrics_of_speaker,
+            &speaker_adjustments(tid, max_seq, conn),
+        );
+
+        let (speakers_in_rank_order, rank_of_speaker) =
+            rank_speakers(speakers, &metrics_of_speaker);
+
+        Self {
+            metrics,
+            metrics_of_speaker,
+            speakers_in_rank_order,
+            rank_of_speaker,
+            team_of_speaker: team_of_speaker(tid, conn),
+        }
+    }
+
+    /// Load the standings from the database. These are saved whenever a round
+    /// is completed (see [`refresh_saved_speaker_standings`]).
+    pub fn fetch(
+        tid: &str,
+        conn: &mut impl LoadConnection<Backend = Sqlite>,
+    ) -> Self {
+        let tournament = tournaments::table
+            .find(tid)
+            .first::<Tournament>(conn)
+            .unwrap();
+        let metrics = tournament.speaker_metrics();
+
+        let speakers: HashMap<_, _> = speakers::table
+            .filter(speakers::tournament_id.eq(tid))
+            .load::<Speaker>(conn)
+            .unwrap()
+            .into_iter()
+            .map(|speaker| (speaker.id.clone(), speaker))
+            .collect();
+
+        let rankings = speaker_standings::table
+            .filter(speaker_standings::tournament_id.eq(tid))
+            .select((speaker_standings::speaker_id, speaker_standings::rank))
+            .order_by(speaker_standings::rank.asc())
+            .load::<(String, i64)>(conn)
+            .unwrap();
+
+        let speakers_in_rank_order = rankings
+            .iter()
+            .chunk_by(|(_speaker, rank)| *rank)
+            .into_iter()
+            .map(|(_rank, chunk)| {
+                chunk
+                    .map(|(speaker, _rank)| speakers[speaker].clone())
+                    .sorted_by(|a, b| a.name.cmp(&b.name))
+                    .collect_vec()
+            })
+            .collect_vec();
+
+        let mut metrics_of_speaker: HashMap<String, Vec<_>> = HashMap::new();
+        for (speaker, kind, value) in speaker_metrics::table
+            .filter(speaker_metrics::tournament_id.eq(tid))
+            .select((
+                speaker_metrics::speaker_id,
+                speaker_metrics::metric_kind,
+                speaker_metrics::metric_value,
+            ))
+            .load::<(String, String, f32)>(conn)
+            .unwrap()
+        {
+            let kind: SpeakerMetric = serde_json::from_str(&kind).unwrap();
+            if metrics.contains(&kind) {
+                metrics_of_speaker.entry(speaker).or_default().push((
+                    kind,
+                    Decimal::from_f32_retain(value).unwrap().round_dp(2),
+                ));
+            }
+        }
+        for metrics_of_speaker in metrics_of_speaker.values_mut() {
+            metrics_of_speaker.sort_by_key(|(kind, _)| {
+                metrics.iter().position(|needle| needle == kind).unwrap()
+            });
+        }
+
+        Self {
+            metrics,
+            metrics_of_speaker,
+            speakers_in_rank_order,
+            rank_of_speaker: rankings.into_iter().collect(),
+            team_of_speaker: team_of_speaker(tid, conn),
+        }
+    }
+
+    pub fn save(
+        &self,
+        tid: &str,
+        conn: &mut impl LoadConnection<Backend = Sqlite>,
+    ) -> Result<(), diesel::result::Error> {
+        diesel::delete(
+            speaker_metrics::table
+                .filter(speaker_metrics::tournament_id.eq(tid)),
+        )
+        .execute(conn)?;
+        diesel::delete(
+            speaker_standings::table
+                .filter(speaker_standings::tournament_id.eq(tid)),
+        )
+        .execute(conn)?;
+
+        let metric_records = self
+            .metrics_of_speaker
+            .iter()
+            .flat_map(|(speaker, metrics)| {
+                metrics.iter().map(move |(kind, value)| {
+                    (
+                        speaker_metrics::id.eq(Uuid::now_v7().to_string()),
+                        speaker_metrics::tournament_id.eq(tid),
+                        speaker_metrics::speaker_id.eq(speaker),
+                        speaker_metrics::metric_kind
+                            .eq(serde_json::to_string(kind).unwrap()),
+                        speaker_metrics::metric_value
+                            .eq(value.to_f32().unwrap()),
+                    )
+                })
+            })
+            .collect_vec();
+        if !metric_records.is_empty() {
+            diesel::insert_into(speaker_metrics::table)
+                .values(metric_records)
+                .execute(conn)?;
+        }
+
+        let standing_records = self
+            .rank_of_speaker
+            .iter()
+            .map(|(speaker, rank)| {
+                (
+                    speaker_standings::id.eq(Uuid::now_v7().to_string()),
+                    speaker_standings::tournament_id.eq(tid),
+                    speaker_standings::speaker_id.eq(speaker),
+                    speaker_standings::rank.eq(rank),
+                )
+            })
+            .collect_vec();
+        if !standing_records.is_empty() {
+            diesel::insert_into(speaker_standings::table)
+                .values(standing_records)
+                .execute(conn)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn refresh_saved_speaker_standings(
+    tid: &str,
+    conn: &mut impl LoadConnection<Backend = Sqlite>,
+) -> Result<(), diesel::result::Error> {
+    SpeakerStandings::recompute(tid, conn).save(tid, conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rust_decimal::Decimal;
+
+    use super::{compute_metric, rank_speakers};
+    use crate::tournaments::{config::SpeakerMetric, participants::Speaker};
+
+    fn speaker(id: &str) -> Speaker {
+        Speaker {
+            id: id.to_string(),
+            tournament_id: "t".to_string(),
+            name: id.to_string(),
+            email: String::new(),
+            private_url: id.to_string(),
+        }
+    }
+
+    #[test]
+    fn lower_std_dev_breaks_ties_and_equal_speakers_share_rank() {
+        let scores: HashMap<&str, Vec<f32>> = HashMap::from([
+            ("a", vec![75.0, 75.0]),
+            ("b", vec![74.0, 76.0]),
+            ("c", vec![75.0, 75.0]),
+            ("d", vec![80.0, 80.0]),
+        ]);
+        let metrics = [SpeakerMetric::Avg, SpeakerMetric::StdDev];
+        let metrics_of_speaker = scores
+            .iter()
+            .map(|(id, scores)| {
+                (
+                    id.to_string(),
+                    metrics
+                        .iter()
+                        .map(|m| (*m, compute_metric(*m, scores)))
+                        .collect(),
+                )
+            })
+            .collect();
+
+        let (ranked, rank_of_speaker) = rank_speakers(
+            vec![speaker("a"), speaker("b"), speaker("c"), speaker("d")],
+            &metrics_of_speaker,
+        );
+
+        assert_eq!(ranked.len(), 3);
+        assert_eq!(rank_of_speaker["d"], 1);
+        assert_eq!(rank_of_speaker["a"], 2);
+        assert_eq!(rank_of_speaker["c"], 2);
+        assert_eq!(rank_of_speaker["b"], 4);
+        assert_eq!(
+            compute_metric(SpeakerMetric::StdDev, &scores["b"]),
+            Decimal::ONE
+        );
+    }
+
+    #[test]
+    fn concurrent_rounds_count_once_towards_attendance() {
+        use diesel::{prelude::*, sqlite::SqliteConnection};
+        use diesel_migrations::MigrationHarness;
+
+        use super::SpeakerStandings;
+        use crate::schema::{
+            agg_speaker_results_of_debate, debates, rounds, speakers,
+            tournaments,
+        };
+
+        let mut conn = SqliteConnection::establish(":memory:").unwrap();
+        conn.run_pending_migrations(crate::MIGRATIONS).unwrap();
+        crate::tournaments::create::insert_tournament(
+            "t", "Test", "T", "test", "user", &mut conn,
+        )
+        .unwrap();
+        // speakers who miss a single round are left out of the tab
+        diesel::update(tournaments::table.filter(tournaments::id.eq("t")))
+            .set(tournaments::exclude_from_speaker_standings_after.eq(0))
+            .execute(&mut conn)
+            .unwrap();
+
+        // "a" and "b" speak in concurrent rounds, and only "a" speaks in
+        // round 2
+        for (round, seq, speaker) in
+            [("r1a", 1, "a"), ("r1b", 1, "b"), ("r2", 2, "a")]
+        {
+            diesel::insert_into(rounds::table)
+                .values((
+                    rounds::id.eq(round),
+                    rounds::tournament_id.eq("t"),
+                    rounds::seq.eq(seq),
+                    rounds::name.eq(round),
+                    rounds::kind.eq("P"),
+                    rounds::completed.eq(true),
+                    rounds::draw_status.eq("released_full"),
+                ))
+                .execute(&mut conn)
+                .unwrap();
+            diesel::insert_into(debates::table)
+                .values((
+                    debates::id.eq(round),
+                    debates::tournament_id.eq("t"),
+                    debates::round_id.eq(round),
+                    debates::number.eq(1),
+                    debates::status.eq("confirmed"),
+                ))
+                .execute(&mut conn)
+                .unwrap();
+            diesel::insert_into(agg_speaker_results_of_debate::table)
+                .values((
+                    agg_speaker_results_of_debate::id.eq(round),
+                    agg_speaker_results_of_debate::tournament_id.eq("t"),
+                    agg_speaker_results_of_debate::debate_id.eq(round),
+                    agg_speaker_results_of_debate::speaker_id.eq(speaker),
+                    agg_speaker_results_of_debate::team_id.eq("team"),
+                    agg_speaker_results_of_debate::position.eq(0),
+                    agg_speaker_results_of_debate::score.eq(Some(75.0)),
+                    agg_speaker_results_of_debate::counts_for_speaker_tab
+                        .eq(true),
+                ))
+                .execute(&mut conn)
+                .unwrap();
+        }
+        for id in ["a", "b"] {
+            diesel::insert_into(speakers::table)
+                .values((
+                    speakers::id.eq(id),
+                    speakers::tournament_id.eq("t"),
+                    speakers::name.eq(id),
+                    speakers::email.eq(""),
+                    speakers::private_url.eq(id),
+                ))
+                .execute(&mut conn)
+                .unwrap();
+        }
+
+        let after_round_1 =
+            SpeakerStandings::recompute_up_to("t", 1, &mut conn);
+        assert!(after_round_1.rank_of_speaker.contains_key("a"));
+        assert!(after_round_1.rank_of_speaker.contains_key("b"));
+
+        let after_round_2 = SpeakerStandings::recompute("t", &mut conn);
+        assert!(after_round_2.rank_of_speaker.contains_key("a"));
+        assert!(!after_round_2.rank_of_speaker.contains_key("b"));
+    }
+}
@@ -0,0 +1,435 @@
+//! Manual adjustments to the standings, along with the audit list of every
+//! adjustment which has been made.
+
+use std::collections::{BTreeMap, HashMap};
+
+use axum::{
+    extract::{Form, Path},
+    response::Redirect,
+};
+use chrono::Utc;
+use diesel::prelude::*;
+use hypertext::prelude::*;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    auth::User,
+    schema::{speakers, standings_adjustments, teams, users},
+    state::Conn,
+    template::Page,
+    tournaments::{
+        Tournament,
+        config::{RankableTeamMetric, SpeakerMetric},
+        manage::sidebar::SidebarWrapper,
+        participants::Speaker,
+        rounds::{Round, TournamentRounds},
+        standings::compute::{
+            adjustments::StandingsAdjustment, refresh_saved_team_standings,
+            speakers::refresh_saved_speaker_standings,
+        },
+        teams::Team,
+    },
+    util_resp::{
+        StandardResponse, bad_request, err_not_found, see_other_ok, success,
+    },
+};
+
+pub async fn admin_standings_adjustments_page(
+    Path(tournament_id): Path<String>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let rounds = TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+
+    let teams = teams::table
+        .filter(teams::tournament_id.eq(&tournament.id))
+        .order_by(teams::name.asc())
+        .load::<Team>(&mut *conn)?;
+    let speakers = speakers::table
+        .filter(speakers::tournament_id.eq(&tournament.id))
+        .order_by(speakers::name.asc())
+        .load::<Speaker>(&mut *conn)?;
+    let name_of_team: HashMap<&str, &str> = teams
+        .iter()
+        .map(|team| (team.id.as_str(), team.name.as_str()))
+        .collect();
+    let name_of_speaker: HashMap<&str, &str> = speakers
+        .iter()
+        .map(|speaker| (speaker.id.as_str(), speaker.name.as_str()))
+        .collect();
+
+    let adjustments =
+        StandingsAdjustment::of_tournament(&tournament.id, &mut *conn);
+    let username_of_user: HashMap<String, String> = users::table
+        .filter(
+            users::id.eq_any(
+                adjustments
+                    .iter()
+                    .map(|adjustment| adjustment.author_id.as_str())
+                    .collect::<Vec<_>>(),
+            ),
+        )
+        .select((users::id, users::username))
+        .load::<(String, String)>(&mut *conn)?
+        .into_iter()
+        .collect();
+
+    let team_metrics = tournament.metrics();
+    let speaker_metrics = tournament.speaker_metrics();
+    // the name of the adjusted metric of each adjustment, and whether it is
+    // currently used to rank teams/speakers
+    let metric_of_adjustment: HashMap<&str, (String, bool)> = adjustments
+        .iter()
+        .filter_map(|adjustment| {
+            let metric =
+                match (adjustment.team_metric(), adjustment.speaker_metric()) {
+                    (Some(metric), _) => {
+                        (metric.to_string(), team_metrics.contains(&metric))
+                    }
+                    (_, Some(metric)) => {
+                        (metric.to_string(), speaker_metrics.contains(&metric))
+                    }
+                    _ => return None,
+                };
+            Some((adjustment.id.as_str(), metric))
+        })
+        .collect();
+
+    // adjustments take effect after a round, defaulting to the latest
+    // completed one (so that they apply to the current standings, but not to
+    // the historical standings as they stood after earlier rounds)
+    let round_seqs = round_seqs(&rounds);
+    let latest_completed_seq = rounds
+        .prelim
+        .iter()
+        .chain(rounds.elim.iter())
+        .filter(|round| round.completed)
+        .map(|round| round.seq)
+        .max()
+        .unwrap_or(0);
+
+    let current_rounds = Round::current_rounds(&tournament_id, &mut *conn);
+
+    success(
+        Page::new()
+            .user(user)
+            .tournament(tournament.clone())
+            .current_rounds(current_rounds)
+            .body(maud! {
+                SidebarWrapper tournament=(&tournament) rounds=(&rounds) active_page=(None) selected_seq=(None) {
+                    h1 { "Standings adjustments" }
+                    p class="text-muted" {
+                        "Adjustments are added to the computed metrics of a team "
+                        "or speaker (e.g. a delta of -1 on #points deducts a "
+                        "point). Adjustments cannot be deleted; to undo one, add "
+                        "another with the opposite delta."
+                    }
+
+                    div class="row g-4 mb-5" {
+                        div class="col-md-6" {
+                            h2 class="h5" { "Adjust a team" }
+                            form method="post" action=(format!("/tournaments/{}/standings/adjustments/team", tournament.id)) class="d-flex flex-column gap-2" {
+                                select class="form-select" name="team_id" required {
+                                    @for team in &teams {
+                                        option value=(team.id) { (team.name) }
+                                    }
+                                }
+                                select class="form-select" name="metric" required {
+                                    @for metric in &team_metrics {
+                                        option value=(serde_json::to_string(metric).unwrap()) { (metric.to_string()) }
+                                    }
+                                }
+                                input type="number" step="0.01" class="form-control" name="delta" placeholder="Delta (e.g. -1)" required;
+                                select class="form-select" name="from_seq" required {
+                                    option value="0" selected[latest_completed_seq == 0] { "From the start of the tournament" }
+                                    @for (seq, name) in &round_seqs {
+                                        option value=(seq) selected[*seq == latest_completed_seq] { "From after " (name) }
+                                    }
+                                }
+                                input type="text" class="form-control" name="reason" placeholder="Reason" required;
+                                button type="submit" class="btn btn-primary" { "Add team adjustment" }
+                            }
+                        }
+                        div class="col-md-6" {
+                            h2 class="h5" { "Adjust a speaker" }
+                            form method="post" action=(format!("/tournaments/{}/standings/adjustments/speaker", tournament.id)) class="d-flex flex-column gap-2" {
+                                select class="form-select" name="speaker_id" required {
+                                    @for speaker in &speakers {
+                                        option value=(speaker.id) { (speaker.name) }
+                                    }
+                                }
+                                select class="form-select" name="metric" required {
+                                    @for metric in &speaker_metrics {
+                                        option value=(serde_json::to_string(metric).unwrap()) { (metric.to_string()) }
+                                    }
+                                }
+                                input type="number" step="0.01" class="form-control" name="delta" placeholder="Delta (e.g. -1.5)" required;
+                                select class="form-select" name="from_seq" required {
+                                    option value="0" selected[latest_completed_seq == 0] { "From the start of the tournament" }
+                                    @for (seq, name) in &round_seqs {
+                                        option value=(seq) selected[*seq == latest_completed_seq] { "From after " (name) }
+                                    }
+                                }
+                                input type="text" class="form-control" name="reason" placeholder="Reason" required;
+                                button type="submit" class="btn btn-primary" { "Add speaker adjustment" }
+                            }
+                        }
+                    }
+
+                    h2 class="h4" { "Audit log" }
+                    @if adjustments.is_empty() {
+                        p class="text-muted" { "No adjustments have been made." }
+                    } @else {
+                        table class="table table-sm" {
+                            thead {
+                                tr {
+                                    th scope="col" { "When" }
+                                    th scope="col" { "By" }
+                                    th scope="col" { "Team or speaker" }
+                                    th scope="col" { "Metric" }
+                                    th scope="col" { "Delta" }
+                                    th scope="col" { "From" }
+                                    th scope="col" { "Reason" }
+                                }
+                            }
+                            tbody {
+                                @for adjustment in &adjustments {
+                                    tr {
+                                        td { (adjustment.created_at.format("%Y-%m-%d %H:%M").to_string()) }
+                                        td {
+                                            (username_of_user.get(&adjustment.author_id).map(String::as_str).unwrap_or("—"))
+                                        }
+                                        td {
+                                            @if let Some(team_id) = &adjustment.team_id {
+                                                (name_of_team.get(team_id.as_str()).copied().unwrap_or("—"))
+                                                span class="badge bg-secondary ms-2" { "team" }
+                                            } @else if let Some(speaker_id) = &adjustment.speaker_id {
+                                                (name_of_speaker.get(speaker_id.as_str()).copied().unwrap_or("—"))
+                                                span class="badge bg-secondary ms-2" { "speaker" }
+                                            }
+                                        }
+                                        td {
+                                            @if let Some((name, in_use)) = metric_of_adjustment.get(adjustment.id.as_str()) {
+                                                (name)
+                                                @if !in_use {
+                                                    span class="badge bg-warning text-dark ms-2" title="This metric is not currently used, so the adjustment has no effect." { "not in use" }
+                                                }
+                                            } @else {
+                                                (adjustment.metric)
+                                            }
+                                        }
+                                        td { (adjustment.delta().normalize().to_string()) }
+                                        td {
+                                            @if adjustment.from_seq == 0 {
+                                                "Start"
+                                            } @else {
+                                                "After " (round_seqs.get(&adjustment.from_seq).map(String::as_str).unwrap_or("—"))
+                                            }
+                                        }
+                                        td { (adjustment.reason) }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+            .render(),
+    )
+}
+
+#[derive(Deserialize)]
+pub struct AdjustmentForm {
+    #[serde(default)]
+    team_id: String,
+    #[serde(default)]
+    speaker_id: String,
+    metric: String,
+    delta: f32,
+    reason: String,
+    #[serde(default)]
+    from_seq: i64,
+}
+
+/// The name(s) of the round(s) with each sequence number.
+fn round_seqs(rounds: &TournamentRounds) -> BTreeMap<i64, String> {
+    let mut names: BTreeMap<i64, Vec<&str>> = BTreeMap::new();
+    for round in rounds.prelim.iter().chain(rounds.elim.iter()) {
+        names.entry(round.seq).or_default().push(&round.name);
+    }
+    names
+        .into_iter()
+        .map(|(seq, names)| (seq, names.join(", ")))
+        .collect()
+}
+
+fn validate_adjustment(
+    form: &AdjustmentForm,
+    rounds: &TournamentRounds,
+) -> Result<(), &'static str> {
+    if !form.delta.is_finite() || form.delta == 0.0 {
+        return Err("Error: the delta must be a non-zero number.");
+    }
+    if form.from_seq != 0 && !round_seqs(rounds).contains_key(&form.from_seq) {
+        return Err("Error: that round does not exist.");
+    }
+    if form.reason.trim().is_empty() || form.reason.len() > 512 {
+        return Err(
+            "Error: the reason must be between 1 and 512 characters long.",
+        );
+    }
+    Ok(())
+}
+
+fn insert_adjustment(
+    tournament: &Tournament,
+    author_id: &str,
+    (team_id, speaker_id): (Option<&str>, Option<&str>),
+    form: &AdjustmentForm,
+    conn: &mut SqliteConnection,
+) -> Result<(), diesel::result::Error> {
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        diesel::insert_into(standings_adjustments::table)
+            .values((
+                standings_adjustments::id.eq(Uuid::now_v7().to_string()),
+                standings_adjustments::tournament_id.eq(&tournament.id),
+                standings_adjustments::team_id.eq(team_id),
+                standings_adjustments::speaker_id.eq(speaker_id),
+                standings_adjustments::metric.eq(&form.metric),
+                standings_adjustments::delta.eq(form.delta),
+                standings_adjustments::reason.eq(form.reason.trim()),
+                standings_adjustments::author_id.eq(author_id),
+                standings_adjustments::created_at.eq(Utc::now().naive_utc()),
+                standings_adjustments::from_seq.eq(form.from_seq),
+            ))
+            .execute(conn)?;
+
+        refresh_saved_team_standings(&tournament.id, conn)?;
+        refresh_saved_speaker_standings(&tournament.id, conn)
+    })
+}
+
+fn adjustment_error(
+    user: &User<true>,
+    tournament: &Tournament,
+    message: &str,
+) -> StandardResponse {
+    bad_request(
+        Page::new()
+            .user(user.clone())
+            .tournament(tournament.clone())
+            .body(maud! {
+                p { (message) }
+                a href=(format!("/tournaments/{}/standings/adjustments", tournament.id)) {
+                    "Back to standings adjustments"
+                }
+            })
+            .render(),
+    )
+}
+
+fn redirect_to_adjustments(tournament_id: &str) -> StandardResponse {
+    see_other_ok(Redirect::to(&format!(
+        "/tournaments/{tournament_id}/standings/adjustments"
+    )))
+}
+
+pub async fn do_create_team_adjustment(
+    Path(tournament_id): Path<String>,
+    user: User<true>,
+    mut conn: Conn<true>,
+    Form(form): Form<AdjustmentForm>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let team = Team::fetch(&form.team_id, &tournament.id, &mut *conn)?;
+
+    let rounds = TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+    if let Err(e) = validate_adjustment(&form, &rounds) {
+        return adjustment_error(&user, &tournament, e);
+    }
+
+    let metric = match serde_json::from_str::<RankableTeamMetric>(&form.metric)
+    {
+        Ok(metric) if tournament.metrics().contains(&metric) => metric,
+        _ => {
+            return adjustment_error(
+                &user,
+                &tournament,
+                "Error: that metric is not used to rank teams.",
+            );
+        }
+    };
+    // these metrics are counts, so a fractional adjustment makes no sense
+    // (and would break e.g. the pairing of teams by points)
+    if matches!(
+        metric,
+        RankableTeamMetric::Wins
+            | RankableTeamMetric::Ballots
+            | RankableTeamMetric::NTimesAchieved(_)
+    ) && form.delta.fract() != 0.0
+    {
+        return adjustment_error(
+            &user,
+            &tournament,
+            &format!("Error: adjustments to {metric} must be whole numbers."),
+        );
+    }
+
+    insert_adjustment(
+        &tournament,
+        &user.id,
+        (Some(&team.id), None),
+        &form,
+        &mut *conn,
+    )?;
+
+    redirect_to_adjustments(&tournament.id)
+}
+
+pub async fn do_create_speaker_adjustment(
+    Path(tournament_id): Path<String>,
+    user: User<true>,
+    mut conn: Conn<true>,
+    Form(form): Form<AdjustmentForm>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let Some(speaker) = speakers::table
+        .filter(speakers::tournament_id.eq(&tournament.id))
+        .filter(speakers::id.eq(&form.speaker_id))
+        .first::<Speaker>(&mut *conn)
+        .optional()?
+    else {
+        return err_not_found();
+    };
+
+    let rounds = TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+    if let Err(e) = validate_adjustment(&form, &rounds) {
+        return adjustment_error(&user, &tournament, e);
+    }
+
+    match serde_json::from_str::<SpeakerMetric>(&form.metric) {
+        Ok(metric) if tournament.speaker_metrics().contains(&metric) => (),
+        _ => {
+            return adjustment_error(
+                &user,
+                &tournament,
+                "Error: that metric is not used to rank speakers.",
+            );
+        }
+    }
+
+    insert_adjustment(
+        &tournament,
+        &user.id,
+        (None, Some(&speaker.id)),
+        &form,
+        &mut *conn,
+    )?;
+
+    redirect_to_adjustments(&tournament.id)
+}
@@ -0,0 +1,107 @@
+use axum::extract::Path;
+use hypertext::{Renderable, maud, prelude::*};
+
+use crate::{
+    auth::User,
+    state::Conn,
+    template::Page,
+    tournaments::{
+        Tournament,
+        manage::sidebar::{SidebarPage, SidebarWrapper},
+        rounds::TournamentRounds,
+        standings::compute::replies::ReplyStandings,
+    },
+    util_resp::{StandardResponse, success},
+};
+
+pub async fn admin_view_reply_standings(
+    Path(tournament_id): Path<String>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let rounds = TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+
+    let standings = ReplyStandings::compute(&tournament, &mut *conn);
+
+    let current_rounds = crate::tournaments::rounds::Round::current_rounds(
+        &tournament_id,
+        &mut *conn,
+    );
+
+    success(
+        Page::new()
+            .user(user)
+            .tournament(tournament.clone())
+            .current_rounds(current_rounds)
+            .body(maud! {
+                SidebarWrapper tournament=(&tournament) rounds=(&rounds) active_page=(Some(SidebarPage::ReplyStandings)) selected_seq=(None) {
+                    @if !tournament.reply_speakers {
+                        div class="alert alert-warning" {
+                            "This tournament does not have reply speeches."
+                        }
+                    }
+                    @if !tournament.reply_tab_public {
+                        div class="alert alert-info" {
+                            "The reply tab is not currently public."
+                        }
+                    }
+                    p class="text-muted" {
+                        "Only speakers who have given at least "
+                        (tournament.reply_tab_min_replies)
+                        " reply speech(es) are ranked."
+                    }
+                    table class="table" {
+                        thead {
+                            tr {
+                                th scope="col" {
+                                    "Rank"
+                                }
+                                td {
+                                    "Speaker name"
+                                }
+                                td {
+                                    "Team"
+                                }
+                                td {
+                                    "Replies"
+                                }
+                                td {
+                                    "Average"
+                                }
+                            }
+                        }
+                        tbody {
+                            @for speakers in &standings.speakers_in_rank_order {
+                                @for speaker in speakers {
+                                    @let (avg, n) = standings.replies_of_speaker[&speaker.id];
+                                    tr {
+                                        th scope="col" {
+                                            @if speakers.len() > 1 {
+                                                 "="
+                                            }
+                                            (standings.rank_of_speaker[&speaker.id])
+                                        }
+                                        td {
+                                            (speaker.name)
+                                        }
+                                        td {
+                                            (standings.team_of_speaker.get(&speaker.id).map(|s| s.as_str()).unwrap_or("—"))
+                                        }
+                                        td {
+                                            (n)
+                                        }
+                                        td {
+                                            (avg.to_string())
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+            .render(),
+    )
+}
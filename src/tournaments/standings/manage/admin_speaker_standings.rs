@@ -0,0 +1,97 @@
+use axum::extract::Path;
+use hypertext::{Renderable, maud, prelude::*};
+
+use crate::{
+    auth::User,
+    state::Conn,
+    template::Page,
+    tournaments::{
+        Tournament,
+        manage::sidebar::{SidebarPage, SidebarWrapper},
+        rounds::TournamentRounds,
+        standings::compute::speakers::SpeakerStandings,
+    },
+    util_resp::{StandardResponse, success},
+};
+
+pub async fn admin_view_speaker_standings(
+    Path(tournament_id): Path<String>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let rounds = TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+
+    let standings = SpeakerStandings::recompute(&tournament_id, &mut *conn);
+
+    let current_rounds = crate::tournaments::rounds::Round::current_rounds(
+        &tournament_id,
+        &mut *conn,
+    );
+
+    success(
+        Page::new()
+            .user(user)
+            .tournament(tournament.clone())
+            .current_rounds(current_rounds)
+            .body(maud! {
+                SidebarWrapper tournament=(&tournament) rounds=(&rounds) active_page=(Some(SidebarPage::SpeakerStandings)) selected_seq=(None) {
+                    a href=(format!("/tournaments/{}/standings/adjustments", tournament.id)) class="btn btn-sm btn-outline-secondary mb-3" {
+                        "Adjustments"
+                    }
+                    @if !tournament.speaker_tab_public {
+                        div class="alert alert-info" {
+                            "The speaker tab is not currently public."
+                        }
+                    }
+                    table class="table" {
+                        thead {
+                            tr {
+                                th scope="col" {
+                                    "Rank"
+                                }
+                                td {
+                                    "Speaker name"
+                                }
+                                td {
+                                    "Team"
+                                }
+                                @for metric in &standings.metrics {
+                                    td {
+                                        (metric.to_string())
+                                    }
+                                }
+                            }
+                        }
+                        tbody {
+                            @for speakers in &standings.speakers_in_rank_order {
+                                @for speaker in speakers {
+                                    tr {
+                                        th scope="col" {
+                                            @if speakers.len() > 1 {
+                                                 "="
+                                            }
+                                            (standings.rank_of_speaker[&speaker.id])
+                                        }
+                                        td {
+                                            (speaker.name)
+                                        }
+                                        td {
+                                            (standings.team_of_speaker.get(&speaker.id).map(|s| s.as_str()).unwrap_or("—"))
+                                        }
+                                        @for (_, metric_value) in &standings.metrics_of_speaker[&speaker.id] {
+                                            td {
+                                                (metric_value.to_string())
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+            .render(),
+    )
+}
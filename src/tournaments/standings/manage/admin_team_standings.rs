@@ -6,8 +6,12 @@ use crate::{
     state::Conn,
     template::Page,
     tournaments::{
-        Tournament, manage::sidebar::SidebarWrapper, rounds::TournamentRounds,
+        Tournament,
+        manage::sidebar::{SidebarPage, SidebarWrapper},
+        participants::TournamentParticipants,
+        rounds::TournamentRounds,
         standings::compute::TeamStandings,
+        teams::TeamNameKind,
     },
     util_resp::{StandardResponse, success},
 };
@@ -22,6 +26,7 @@ pub async fn admin_view_team_standings(
     let rounds = TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
 
     let standings = TeamStandings::recompute(&tournament_id, &mut *conn);
+    let participants = TournamentParticipants::load(&tournament_id, &mut *conn);
 
     let current_rounds = crate::tournaments::rounds::Round::current_rounds(
         &tournament_id,
@@ -34,7 +39,16 @@ pub async fn admin_view_team_standings(
             .tournament(tournament.clone())
             .current_rounds(current_rounds)
             .body(maud! {
-                SidebarWrapper tournament=(&tournament) rounds=(&rounds) active_page=(None) selected_seq=(None) {
+                SidebarWrapper tournament=(&tournament) rounds=(&rounds) active_page=(Some(SidebarPage::TeamStandings)) selected_seq=(None) {
+                    a href=(format!("/tournaments/{}/standings/history", tournament.id)) class="btn btn-sm btn-outline-secondary mb-3" {
+                        "Standings after earlier rounds"
+                    }
+                    a href=(format!("/tournaments/{}/standings/adjustments", tournament.id)) class="btn btn-sm btn-outline-secondary mb-3 ms-2" {
+                        "Adjustments"
+                    }
+                    p class="text-muted small" {
+                        "Hover over a rank to see what separates a team from the team above it."
+                    }
                     table class="table" {
                         thead {
                             tr {
@@ -55,7 +69,7 @@ pub async fn admin_view_team_standings(
                             @for (i, rank) in standings.teams_in_rank_order.iter().enumerate() {
                                 @for team in rank {
                                     tr {
-                                        th scope="col" {
+                                        th scope="col" title=(standings.explain_tie_break(&team.id, &participants, TeamNameKind::Full).unwrap_or_default()) {
                                             @if rank.len() > 1 {
                                                  "="
                                             }
@@ -0,0 +1,424 @@
+//! Standings as they stood after earlier rounds. These are useful for checking
+//! the inputs to an earlier draw (e.g. why a team was pulled up).
+
+use axum::extract::Path;
+use hypertext::{Raw, Renderable, maud, prelude::*};
+use itertools::Itertools;
+
+use crate::{
+    auth::User,
+    state::Conn,
+    template::Page,
+    tournaments::{
+        Tournament,
+        manage::sidebar::SidebarWrapper,
+        rounds::{Round, TournamentRounds},
+        standings::compute::{TeamStandings, speakers::SpeakerStandings},
+        teams::Team,
+    },
+    util_resp::{StandardResponse, err_not_found, success},
+};
+
+/// The sequence numbers after which standings can be viewed, in order, each
+/// with the preliminary rounds that share it. Concurrent rounds only count
+/// once all of them have been completed.
+fn completed_prelim_seqs(rounds: &TournamentRounds) -> Vec<(i64, Vec<Round>)> {
+    rounds
+        .prelims_grouped_by_seq()
+        .into_iter()
+        .filter(|group| {
+            group.iter().all(|round| {
+                round.completed && round.draw_status == "released_full"
+            })
+        })
+        .map(|group| (group[0].seq, group))
+        .collect()
+}
+
+/// The name of a set of concurrent rounds, e.g. "Round 1A / Round 1B".
+fn name_of_seq(rounds: &[Round]) -> String {
+    rounds
+        .iter()
+        .map(|round| round.name.as_str())
+        .sorted()
+        .join(" / ")
+}
+
+/// Computes the team standings after each of the given sequence numbers.
+fn team_standings_after_each_seq(
+    tid: &str,
+    seqs: &[(i64, Vec<Round>)],
+    conn: &mut impl diesel::connection::LoadConnection<
+        Backend = diesel::sqlite::Sqlite,
+    >,
+) -> Vec<TeamStandings> {
+    seqs.iter()
+        .map(|(seq, _)| TeamStandings::recompute_up_to(tid, *seq, conn))
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Draws a line chart of a team's rank after each round. Rank 1 is at the top.
+fn rank_chart_svg(ranks: &[(&str, i64)], n_teams: usize) -> String {
+    const WIDTH: f64 = 640.0;
+    const HEIGHT: f64 = 320.0;
+    const PAD: f64 = 40.0;
+
+    let x = |i: usize| {
+        PAD + i as f64 * (WIDTH - 2.0 * PAD)
+            / (ranks.len().saturating_sub(1).max(1)) as f64
+    };
+    let y = |rank: i64| {
+        PAD + (rank - 1) as f64 * (HEIGHT - 2.0 * PAD)
+            / (n_teams.saturating_sub(1).max(1)) as f64
+    };
+
+    let mut svg = format!(
+        r#"<svg viewBox="0 0 {WIDTH} {HEIGHT}" class="w-100" role="img" aria-label="Rank after each round">"#
+    );
+    svg.push_str(&format!(
+        r##"<line x1="{PAD}" y1="{top}" x2="{PAD}" y2="{bottom}" stroke="#adb5bd"/><text x="{label_x}" y="{top}" font-size="12" text-anchor="end" dominant-baseline="middle">1</text><text x="{label_x}" y="{bottom}" font-size="12" text-anchor="end" dominant-baseline="middle">{n_teams}</text>"##,
+        top = y(1),
+        bottom = y(n_teams as i64),
+        label_x = PAD - 8.0,
+    ));
+
+    let points = ranks
+        .iter()
+        .enumerate()
+        .map(|(i, (_, rank))| format!("{:.1},{:.1}", x(i), y(*rank)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    svg.push_str(&format!(
+        r##"<polyline points="{points}" fill="none" stroke="#0d6efd" stroke-width="2"/>"##
+    ));
+
+    for (i, (round_name, rank)) in ranks.iter().enumerate() {
+        let round_name = escape(round_name);
+        svg.push_str(&format!(
+            r##"<circle cx="{cx:.1}" cy="{cy:.1}" r="4" fill="#0d6efd"><title>{round_name}: {rank}</title></circle><text x="{cx:.1}" y="{label_y}" font-size="12" text-anchor="middle">{round_name}</text>"##,
+            cx = x(i),
+            cy = y(*rank),
+            label_y = HEIGHT - PAD / 2.0,
+        ));
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Shows the rank of each team after every completed round.
+pub async fn admin_standings_history_page(
+    Path(tournament_id): Path<String>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let rounds = TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+
+    let completed = completed_prelim_seqs(&rounds);
+    let standings =
+        team_standings_after_each_seq(&tournament.id, &completed, &mut *conn);
+    // teams are listed in the order of the latest standings
+    let teams: Vec<&Team> = standings
+        .last()
+        .map(|latest| latest.teams_in_rank_order.iter().flatten().collect())
+        .unwrap_or_default();
+
+    let current_rounds = Round::current_rounds(&tournament_id, &mut *conn);
+
+    success(
+        Page::new()
+            .user(user)
+            .tournament(tournament.clone())
+            .current_rounds(current_rounds)
+            .body(maud! {
+                SidebarWrapper tournament=(&tournament) rounds=(&rounds) active_page=(None) selected_seq=(None) {
+                    h1 { "Standings history" }
+                    @if completed.is_empty() {
+                        p class="text-muted" { "No rounds have been completed yet." }
+                    } @else {
+                        p {
+                            "View the full standings after: "
+                            @for (seq, group) in &completed {
+                                a href=(format!("/tournaments/{}/standings/history/rounds/{}", tournament.id, seq)) class="btn btn-sm btn-outline-primary me-2" {
+                                    (name_of_seq(group))
+                                }
+                            }
+                        }
+                        table class="table table-sm" {
+                            thead {
+                                tr {
+                                    th scope="col" { "Team" }
+                                    @for (_, group) in &completed {
+                                        th scope="col" { (name_of_seq(group)) }
+                                    }
+                                }
+                            }
+                            tbody {
+                                @for team in &teams {
+                                    tr {
+                                        td {
+                                            a href=(format!("/tournaments/{}/standings/history/teams/{}", tournament.id, team.id)) {
+                                                (team.name)
+                                            }
+                                        }
+                                        @for standings in &standings {
+                                            td { (standings.rank_of_team[&team.id]) }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+            .render(),
+    )
+}
+
+/// Shows the team and speaker standings as they stood after the round(s) with
+/// the given sequence number.
+pub async fn admin_standings_after_round_page(
+    Path((tournament_id, seq)): Path<(String, i64)>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let rounds = TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+
+    let Some((_, group)) = completed_prelim_seqs(&rounds)
+        .into_iter()
+        .find(|(completed_seq, _)| *completed_seq == seq)
+    else {
+        return err_not_found();
+    };
+
+    let team_standings =
+        TeamStandings::recompute_up_to(&tournament.id, seq, &mut *conn);
+    let speaker_standings =
+        SpeakerStandings::recompute_up_to(&tournament.id, seq, &mut *conn);
+
+    let current_rounds = Round::current_rounds(&tournament_id, &mut *conn);
+
+    success(
+        Page::new()
+            .user(user)
+            .tournament(tournament.clone())
+            .current_rounds(current_rounds)
+            .body(maud! {
+                SidebarWrapper tournament=(&tournament) rounds=(&rounds) active_page=(None) selected_seq=(Some(seq)) {
+                    a href=(format!("/tournaments/{}/standings/history", tournament.id)) class="btn btn-sm btn-outline-secondary mb-3" {
+                        "← Back to Standings history"
+                    }
+                    h1 { "Standings after " (name_of_seq(&group)) }
+
+                    h2 class="h4 mt-4" { "Teams" }
+                    table class="table" {
+                        thead {
+                            tr {
+                                th scope="col" { "Rank" }
+                                td { "Team name" }
+                                @for metric in &team_standings.metrics {
+                                    td { (metric.to_string()) }
+                                }
+                            }
+                        }
+                        tbody {
+                            @for teams in &team_standings.teams_in_rank_order {
+                                @for team in teams {
+                                    tr {
+                                        th scope="col" {
+                                            @if teams.len() > 1 {
+                                                "="
+                                            }
+                                            (team_standings.rank_of_team[&team.id])
+                                        }
+                                        td { (team.name) }
+                                        @for (_, value) in &team_standings.ranked_metrics_of_team[&team.id] {
+                                            td { (value.to_string()) }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    h2 class="h4 mt-4" { "Speakers" }
+                    table class="table" {
+                        thead {
+                            tr {
+                                th scope="col" { "Rank" }
+                                td { "Speaker name" }
+                                td { "Team" }
+                                @for metric in &speaker_standings.metrics {
+                                    td { (metric.to_string()) }
+                                }
+                            }
+                        }
+                        tbody {
+                            @for speakers in &speaker_standings.speakers_in_rank_order {
+                                @for speaker in speakers {
+                                    tr {
+                                        th scope="col" {
+                                            @if speakers.len() > 1 {
+                                                "="
+                                            }
+                                            (speaker_standings.rank_of_speaker[&speaker.id])
+                                        }
+                                        td { (speaker.name) }
+                                        td {
+                                            (speaker_standings.team_of_speaker.get(&speaker.id).map(|s| s.as_str()).unwrap_or("—"))
+                                        }
+                                        @for (_, value) in &speaker_standings.metrics_of_speaker[&speaker.id] {
+                                            td { (value.to_string()) }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+            .render(),
+    )
+}
+
+/// Shows how a single team's rank (and metrics) changed over the tournament.
+pub async fn admin_team_rank_history_page(
+    Path((tournament_id, team_id)): Path<(String, String)>,
+    user: User<true>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+    tournament.check_user_is_superuser(&user.id, &mut *conn)?;
+    let rounds = TournamentRounds::fetch(&tournament.id, &mut *conn).unwrap();
+    let team = Team::fetch(&team_id, &tournament.id, &mut *conn)?;
+
+    let completed = completed_prelim_seqs(&rounds);
+    let standings =
+        team_standings_after_each_seq(&tournament.id, &completed, &mut *conn);
+    let n_teams = standings
+        .first()
+        .map(|s| s.rank_of_team.len())
+        .unwrap_or_default();
+    let names: Vec<String> = completed
+        .iter()
+        .map(|(_, group)| name_of_seq(group))
+        .collect();
+    let ranks: Vec<(&str, i64)> = names
+        .iter()
+        .zip(&standings)
+        .map(|(name, standings)| {
+            (name.as_str(), standings.rank_of_team[&team.id])
+        })
+        .collect();
+
+    let current_rounds = Round::current_rounds(&tournament_id, &mut *conn);
+
+    success(
+        Page::new()
+            .user(user)
+            .tournament(tournament.clone())
+            .current_rounds(current_rounds)
+            .body(maud! {
+                SidebarWrapper tournament=(&tournament) rounds=(&rounds) active_page=(None) selected_seq=(None) {
+                    a href=(format!("/tournaments/{}/standings/history", tournament.id)) class="btn btn-sm btn-outline-secondary mb-3" {
+                        "← Back to Standings history"
+                    }
+                    h1 { (team.name) }
+                    @if ranks.is_empty() {
+                        p class="text-muted" { "No rounds have been completed yet." }
+                    } @else {
+                        div class="mb-4" {
+                            (Raw::dangerously_create(rank_chart_svg(&ranks, n_teams)))
+                        }
+                        table class="table" {
+                            thead {
+                                tr {
+                                    th scope="col" { "After" }
+                                    td { "Rank" }
+                                    @for metric in &standings[0].metrics {
+                                        td { (metric.to_string()) }
+                                    }
+                                }
+                            }
+                            tbody {
+                                @for (((seq, _), name), standings) in completed.iter().zip(&names).zip(&standings) {
+                                    tr {
+                                        th scope="row" {
+                                            a href=(format!("/tournaments/{}/standings/history/rounds/{}", tournament.id, seq)) {
+                                                (name)
+                                            }
+                                        }
+                                        td { (standings.rank_of_team[&team.id]) }
+                                        @for (_, value) in &standings.ranked_metrics_of_team[&team.id] {
+                                            td { (value.to_string()) }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+            .render(),
+    )
+}
+
+#[cfg(test)]
+#[test]
+fn test_concurrent_rounds_share_one_column() {
+    use diesel::{prelude::*, sqlite::SqliteConnection};
+    use diesel_migrations::MigrationHarness;
+
+    use crate::schema::rounds;
+
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+    conn.run_pending_migrations(crate::MIGRATIONS).unwrap();
+    crate::tournaments::create::insert_tournament(
+        "t", "Test", "T", "test", "user", &mut conn,
+    )
+    .unwrap();
+    for (id, seq, completed) in [
+        ("Round 1A", 1, true),
+        ("Round 1B", 1, true),
+        ("Round 2A", 2, true),
+        ("Round 2B", 2, false),
+    ] {
+        diesel::insert_into(rounds::table)
+            .values((
+                rounds::id.eq(id),
+                rounds::tournament_id.eq("t"),
+                rounds::seq.eq(seq),
+                rounds::name.eq(id),
+                rounds::kind.eq("P"),
+                rounds::completed.eq(completed),
+                rounds::draw_status.eq("released_full"),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+    }
+
+    let rounds = TournamentRounds::fetch("t", &mut conn).unwrap();
+    let seqs = completed_prelim_seqs(&rounds);
+    assert_eq!(seqs.len(), 1);
+    assert_eq!(seqs[0].0, 1);
+    assert_eq!(name_of_seq(&seqs[0].1), "Round 1A / Round 1B");
+}
+
+#[cfg(test)]
+#[test]
+fn test_rank_chart_puts_first_place_at_the_top() {
+    let svg = rank_chart_svg(&[("R1", 1), ("R<2>", 8)], 8);
+    assert!(svg.contains(r#"points="40.0,40.0 600.0,280.0""#));
+    assert!(svg.contains("R&lt;2&gt;"));
+}
@@ -1 +1,5 @@
+pub mod adjustments;
+pub mod admin_reply_standings;
+pub mod admin_speaker_standings;
 pub mod admin_team_standings;
+pub mod history;
@@ -8,8 +8,16 @@ use crate::{
     state::Conn,
     template::{ActiveNav, Page},
     tournaments::{
-        Tournament, config::RankableTeamMetric,
-        standings::compute::TeamStandings,
+        Tournament,
+        categories::{SpeakerCategory, TeamCategory},
+        config::RankableTeamMetric,
+        participants::TournamentParticipants,
+        rounds::side_names,
+        standings::compute::{
+            TeamStandings, breakdown::TeamTabBreakdown, rank_subset,
+            replies::ReplyStandings, speakers::SpeakerStandings,
+        },
+        teams::TeamNameKind,
     },
     util_resp::{StandardResponse, success, unauthorized},
 };
@@ -41,11 +49,21 @@ pub async fn public_team_tab_page(
         &mut *conn,
     );
     let standings = TeamStandings::recompute(&tournament_id, &mut *conn);
+    let participants = TournamentParticipants::load(&tournament_id, &mut *conn);
+    let name_kind = TeamNameKind::of_tab(&tournament);
 
     if show_full_tab {
+        let breakdown = TeamTabBreakdown::load(
+            &tournament_id,
+            !is_superuser,
+            &participants,
+            name_kind,
+            &mut *conn,
+        );
+
         success(Page::new()
             .active_nav(ActiveNav::Standings)
-            .tournament(tournament)
+            .tournament(tournament.clone())
             .user_opt(user)
             .current_rounds(current_rounds)
             .body(maud! {
@@ -62,26 +80,53 @@ pub async fn public_team_tab_page(
                                         (metric.to_string())
                                     }
                                 }
+                                @for round in &breakdown.rounds {
+                                    th scope = "col" {
+                                        (round.name)
+                                    }
+                                }
                             }
                         }
                         tbody {
                             @for (i, teams) in standings.teams_in_rank_order.iter().enumerate() {
                                 @for team in teams {
                                     tr {
-                                        th scope="row" {
+                                        th scope="row" title=(standings.explain_tie_break(&team.id, &participants, name_kind).unwrap_or_default()) {
                                             @if teams.len() > 1 {
                                                 "="
                                             }
                                             (i + 1)
                                         }
                                         td {
-                                            (team.name)
+                                            (participants.display_name_of_team(team, name_kind))
                                         }
                                         @for metric in standings.ranked_metrics_of_team.get(&team.id).unwrap() {
                                             td {
                                                 (metric.1.to_string())
                                             }
                                         }
+                                        @for round in &breakdown.rounds {
+                                            td class="small" {
+                                                @if let Some(result) = breakdown.results.get(&(team.id.clone(), round.id.clone())) {
+                                                    a href=(format!("/tournaments/{}/rounds/{}/results#debate-{}", tournament_id, round.seq, result.debate_id)) {
+                                                        @if let Some(points) = result.points {
+                                                            span class="fw-bold" { (points) }
+                                                        } @else {
+                                                            "—"
+                                                        }
+                                                    }
+                                                    " " (side_names::name_of_side(&tournament, result.side, result.seq, true))
+                                                    @if let Some(speaks) = result.speaks {
+                                                        " (" (speaks.to_string()) ")"
+                                                    }
+                                                    div class="text-muted" {
+                                                        "vs " (result.opponents.join(", "))
+                                                    }
+                                                } @else {
+                                                    span class="text-muted" { "—" }
+                                                }
+                                            }
+                                        }
                                     }
                                 }
                             }
@@ -115,8 +160,9 @@ pub async fn public_team_tab_page(
                 .map(|(points, group)| {
                     let mut teams: Vec<_> =
                         group.map(|(_, team)| team).collect();
-                    // todo: correctly add institutional prefix
-                    teams.sort_by(|a, b| a.name.cmp(&b.name));
+                    teams.sort_by_cached_key(|team| {
+                        participants.display_name_of_team(team, name_kind)
+                    });
                     (points, teams)
                 })
                 .collect();
@@ -164,7 +210,7 @@ pub async fn public_team_tab_page(
                                             (rank)
                                         }
                                         td {
-                                            (team.name)
+                                            (participants.display_name_of_team(team, name_kind))
                                         }
                                         td {
                                             (points)
@@ -179,3 +225,329 @@ pub async fn public_team_tab_page(
         )
     }
 }
+
+pub async fn public_speaker_tab_page(
+    Path(tournament_id): Path<String>,
+    user: Option<User<true>>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+
+    let is_superuser = if let Some(ref user) = user {
+        tournament
+            .check_user_is_superuser(&user.id, &mut *conn)
+            .is_ok()
+    } else {
+        false
+    };
+
+    if !is_superuser && !tournament.speaker_tab_public {
+        return unauthorized();
+    }
+
+    let current_rounds = crate::tournaments::rounds::Round::current_rounds(
+        &tournament_id,
+        &mut *conn,
+    );
+    // The saved standings are refreshed whenever a round is completed.
+    let standings = SpeakerStandings::fetch(&tournament_id, &mut *conn);
+
+    success(Page::new()
+        .active_nav(ActiveNav::Standings)
+        .tournament(tournament)
+        .user_opt(user)
+        .current_rounds(current_rounds)
+        .body(maud! {
+            div class="container py-5 px-4" {
+                table class = "table" {
+                    thead {
+                        tr {
+                            th scope = "col" { "#" }
+                            th scope = "col" { "Speaker name" }
+                            th scope = "col" { "Team" }
+                            @for metric in &standings.metrics {
+                                th scope = "col" {
+                                    (metric.to_string())
+                                }
+                            }
+                        }
+                    }
+                    tbody {
+                        @for speakers in &standings.speakers_in_rank_order {
+                            @for speaker in speakers {
+                                tr {
+                                    th scope="row" {
+                                        @if speakers.len() > 1 {
+                                            "="
+                                        }
+                                        (standings.rank_of_speaker[&speaker.id])
+                                    }
+                                    td {
+                                        (speaker.name)
+                                    }
+                                    td {
+                                        (standings.team_of_speaker.get(&speaker.id).map(|s| s.as_str()).unwrap_or("—"))
+                                    }
+                                    @for (_, value) in standings.metrics_of_speaker.get(&speaker.id).into_iter().flatten() {
+                                        td {
+                                            (value.to_string())
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        })
+        .render())
+}
+
+/// The speaker tab, restricted to the speakers in one speaker category (e.g.
+/// the ESL tab).
+pub async fn public_speaker_category_tab_page(
+    Path((tournament_id, category_id)): Path<(String, String)>,
+    user: Option<User<true>>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+
+    let is_superuser = if let Some(ref user) = user {
+        tournament
+            .check_user_is_superuser(&user.id, &mut *conn)
+            .is_ok()
+    } else {
+        false
+    };
+
+    if !is_superuser && !tournament.speaker_tab_public {
+        return unauthorized();
+    }
+
+    let category =
+        SpeakerCategory::fetch(&category_id, &tournament.id, &mut *conn)?;
+    let speaker_ids = category.speaker_ids(&mut *conn);
+
+    let current_rounds = crate::tournaments::rounds::Round::current_rounds(
+        &tournament_id,
+        &mut *conn,
+    );
+    let standings = SpeakerStandings::fetch(&tournament_id, &mut *conn);
+    let ranked = rank_subset(&standings.speakers_in_rank_order, |speaker| {
+        speaker_ids.contains(&speaker.id)
+    });
+
+    success(Page::new()
+        .active_nav(ActiveNav::Standings)
+        .tournament(tournament)
+        .user_opt(user)
+        .current_rounds(current_rounds)
+        .body(maud! {
+            div class="container py-5 px-4" {
+                h1 { (category.name) " speaker tab" }
+                table class = "table" {
+                    thead {
+                        tr {
+                            th scope = "col" { "#" }
+                            th scope = "col" { "Overall" }
+                            th scope = "col" { "Speaker name" }
+                            th scope = "col" { "Team" }
+                            @for metric in &standings.metrics {
+                                th scope = "col" {
+                                    (metric.to_string())
+                                }
+                            }
+                        }
+                    }
+                    tbody {
+                        @for (rank, speakers) in &ranked {
+                            @for speaker in speakers {
+                                tr {
+                                    th scope="row" {
+                                        @if speakers.len() > 1 {
+                                            "="
+                                        }
+                                        (rank)
+                                    }
+                                    td {
+                                        (standings.rank_of_speaker[&speaker.id])
+                                    }
+                                    td {
+                                        (speaker.name)
+                                    }
+                                    td {
+                                        (standings.team_of_speaker.get(&speaker.id).map(|s| s.as_str()).unwrap_or("—"))
+                                    }
+                                    @for (_, value) in standings.metrics_of_speaker.get(&speaker.id).into_iter().flatten() {
+                                        td {
+                                            (value.to_string())
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        })
+        .render())
+}
+
+/// The team tab, restricted to the teams in one team category.
+pub async fn public_team_category_tab_page(
+    Path((tournament_id, category_id)): Path<(String, String)>,
+    user: Option<User<true>>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+
+    let is_superuser = if let Some(ref user) = user {
+        tournament
+            .check_user_is_superuser(&user.id, &mut *conn)
+            .is_ok()
+    } else {
+        false
+    };
+
+    if !is_superuser && !tournament.team_tab_public {
+        return unauthorized();
+    }
+
+    let category =
+        TeamCategory::fetch(&category_id, &tournament.id, &mut *conn)?;
+    let team_ids = category.team_ids(&mut *conn);
+
+    let current_rounds = crate::tournaments::rounds::Round::current_rounds(
+        &tournament_id,
+        &mut *conn,
+    );
+    let standings = TeamStandings::recompute(&tournament_id, &mut *conn);
+    let participants = TournamentParticipants::load(&tournament_id, &mut *conn);
+    let name_kind = TeamNameKind::of_tab(&tournament);
+    let ranked = rank_subset(&standings.teams_in_rank_order, |team| {
+        team_ids.contains(&team.id)
+    });
+
+    success(Page::new()
+        .active_nav(ActiveNav::Standings)
+        .tournament(tournament)
+        .user_opt(user)
+        .current_rounds(current_rounds)
+        .body(maud! {
+            div class="container py-5 px-4" {
+                h1 { (category.name) " team tab" }
+                table class = "table" {
+                    thead {
+                        tr {
+                            th scope = "col" { "#" }
+                            th scope = "col" { "Team name" }
+                            @for metric in &standings.metrics {
+                                th scope = "col" {
+                                    (metric.to_string())
+                                }
+                            }
+                        }
+                    }
+                    tbody {
+                        @for (rank, teams) in &ranked {
+                            @for team in teams {
+                                tr {
+                                    th scope="row" {
+                                        @if teams.len() > 1 {
+                                            "="
+                                        }
+                                        (rank)
+                                    }
+                                    td {
+                                        (participants.display_name_of_team(team, name_kind))
+                                    }
+                                    @for metric in standings.ranked_metrics_of_team.get(&team.id).unwrap() {
+                                        td {
+                                            (metric.1.to_string())
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        })
+        .render())
+}
+
+pub async fn public_reply_tab_page(
+    Path(tournament_id): Path<String>,
+    user: Option<User<true>>,
+    mut conn: Conn<true>,
+) -> StandardResponse {
+    let tournament = Tournament::fetch(&tournament_id, &mut *conn)?;
+
+    let is_superuser = if let Some(ref user) = user {
+        tournament
+            .check_user_is_superuser(&user.id, &mut *conn)
+            .is_ok()
+    } else {
+        false
+    };
+
+    if !is_superuser && !tournament.reply_tab_public {
+        return unauthorized();
+    }
+
+    let current_rounds = crate::tournaments::rounds::Round::current_rounds(
+        &tournament_id,
+        &mut *conn,
+    );
+    let standings = ReplyStandings::compute(&tournament, &mut *conn);
+
+    success(Page::new()
+        .active_nav(ActiveNav::Standings)
+        .tournament(tournament)
+        .user_opt(user)
+        .current_rounds(current_rounds)
+        .body(maud! {
+            div class="container py-5 px-4" {
+                h1 { "Reply tab" }
+                table class = "table" {
+                    thead {
+                        tr {
+                            th scope = "col" { "#" }
+                            th scope = "col" { "Speaker name" }
+                            th scope = "col" { "Team" }
+                            th scope = "col" { "Replies" }
+                            th scope = "col" { "Average" }
+                        }
+                    }
+                    tbody {
+                        @for speakers in &standings.speakers_in_rank_order {
+                            @for speaker in speakers {
+                                @let (avg, n) = standings.replies_of_speaker[&speaker.id];
+                                tr {
+                                    th scope="row" {
+                                        @if speakers.len() > 1 {
+                                            "="
+                                        }
+                                        (standings.rank_of_speaker[&speaker.id])
+                                    }
+                                    td {
+                                        (speaker.name)
+                                    }
+                                    td {
+                                        (standings.team_of_speaker.get(&speaker.id).map(|s| s.as_str()).unwrap_or("—"))
+                                    }
+                                    td {
+                                        (n)
+                                    }
+                                    td {
+                                        (avg.to_string())
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        })
+        .render())
+}
@@ -1,7 +1,9 @@
 use diesel::{connection::LoadConnection, prelude::*, sqlite::Sqlite};
 use serde::{Deserialize, Serialize};
 
-use crate::{schema::teams, util_resp::FailureResponse};
+use crate::{
+    schema::teams, tournaments::Tournament, util_resp::FailureResponse,
+};
 
 #[derive(Serialize, Deserialize, Queryable, Clone, Debug, PartialEq)]
 pub struct Team {
@@ -10,9 +12,65 @@ pub struct Team {
     pub name: String,
     pub institution_id: Option<String>,
     pub number: i64,
+    /// Shown instead of the team name where the tournament hides team
+    /// identities (see [`TeamNameKind`]).
+    pub code_name: Option<String>,
+    pub emoji: Option<String>,
+    pub use_institution_prefix: bool,
+}
+
+/// Which of a team's names is shown on a public page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TeamNameKind {
+    /// The team name (with the institution prefix, if the team uses it).
+    Full,
+    /// The team's code name. Teams without one are shown by their number
+    /// (e.g. "Team 3"), so that their identity stays hidden.
+    Code,
+}
+
+impl TeamNameKind {
+    pub fn of_draw(tournament: &Tournament) -> Self {
+        Self::parse(&tournament.draw_team_names)
+    }
+
+    pub fn of_tab(tournament: &Tournament) -> Self {
+        Self::parse(&tournament.tab_team_names)
+    }
+
+    fn parse(setting: &str) -> Self {
+        match setting {
+            "full" => TeamNameKind::Full,
+            "code" => TeamNameKind::Code,
+            other => unreachable!("invalid team name setting: {other}"),
+        }
+    }
 }
 
 impl Team {
+    /// The team name, prefixed with the code of its institution (e.g.
+    /// "Oxford A") if the team uses the institution prefix.
+    pub fn full_name(&self, institution_code: Option<&str>) -> String {
+        match institution_code {
+            Some(code) if self.use_institution_prefix => {
+                format!("{code} {}", self.name)
+            }
+            _ => self.name.clone(),
+        }
+    }
+
+    pub fn display_name(
+        &self,
+        kind: TeamNameKind,
+        institution_code: Option<&str>,
+    ) -> String {
+        match (kind, &self.code_name) {
+            (TeamNameKind::Code, Some(code_name)) => code_name.clone(),
+            (TeamNameKind::Code, None) => format!("Team {}", self.number),
+            (TeamNameKind::Full, _) => self.full_name(institution_code),
+        }
+    }
+
     #[tracing::instrument(skip(conn))]
     pub fn fetch(
         team_id: &str,
@@ -35,3 +93,32 @@ impl Team {
         ret
     }
 }
+
+#[cfg(test)]
+#[test]
+fn test_display_names_respect_prefix_and_code_name() {
+    let mut team = Team {
+        id: "t".to_string(),
+        tournament_id: "tournament".to_string(),
+        name: "A".to_string(),
+        institution_id: Some("oxford".to_string()),
+        number: 1,
+        code_name: None,
+        emoji: None,
+        use_institution_prefix: true,
+    };
+    assert_eq!(team.full_name(Some("Oxford")), "Oxford A");
+    assert_eq!(
+        team.display_name(TeamNameKind::Code, Some("Oxford")),
+        "Team 1"
+    );
+
+    team.code_name = Some("Kingfisher".to_string());
+    assert_eq!(
+        team.display_name(TeamNameKind::Code, Some("Oxford")),
+        "Kingfisher"
+    );
+
+    team.use_institution_prefix = false;
+    assert_eq!(team.display_name(TeamNameKind::Full, Some("Oxford")), "A");
+}
@@ -55,6 +55,7 @@ const TABDA_DICTIONARY_STRINGS: [&str; 48] = [
     "ballots",
     "draw_strength_by_wins",
     "draw_strength_by_speaks",
+    "avg_draw_strength_by_speaks",
     "total_speaker_score",
     "avg_total_speaker_score",
     "n_times_achieved_0",
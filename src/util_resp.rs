@@ -1,5 +1,5 @@
 use axum::{
-    http::StatusCode,
+    http::{StatusCode, header},
     response::{Html, IntoResponse, Redirect, Response},
 };
 use hypertext::Rendered;
@@ -20,6 +20,20 @@ pub fn success(html: Rendered<String>) -> StandardResponse {
     Ok(SuccessResponse::Success(html))
 }
 
+/// Sends `body` as a file which the browser will download (rather than
+/// display).
+pub fn download(
+    content_type: &'static str,
+    filename: String,
+    body: Vec<u8>,
+) -> StandardResponse {
+    Ok(SuccessResponse::Download {
+        content_type,
+        filename,
+        body,
+    })
+}
+
 pub fn unauthorized() -> StandardResponse {
     Err(FailureResponse::Unauthorized(()))
 }
@@ -32,6 +46,11 @@ pub type StandardResponse = Result<SuccessResponse, FailureResponse>;
 pub enum SuccessResponse {
     Success(Rendered<String>),
     SeeOther(Box<Redirect>),
+    Download {
+        content_type: &'static str,
+        filename: String,
+        body: Vec<u8>,
+    },
 }
 
 impl IntoResponse for SuccessResponse {
@@ -41,6 +60,21 @@ impl IntoResponse for SuccessResponse {
                 Html(html.into_inner()).into_response()
             }
             SuccessResponse::SeeOther(redirect) => redirect.into_response(),
+            SuccessResponse::Download {
+                content_type,
+                filename,
+                body,
+            } => (
+                [
+                    (header::CONTENT_TYPE, content_type.to_string()),
+                    (
+                        header::CONTENT_DISPOSITION,
+                        format!("attachment; filename=\"{filename}\""),
+                    ),
+                ],
+                body,
+            )
+                .into_response(),
         }
     }
 }